@@ -0,0 +1,37 @@
+// Normalizes and ranks a generated league of 500k players. Run with `cargo bench --bench normalize`.
+use std::{collections::HashMap, time::Instant};
+use finalproject::cleaning::{normalize_metrics, Player, PlayerMap, Position};
+use finalproject::model::WeightedSigmoid;
+use finalproject::scoring::{metric_names, rank_players_with};
+
+const PLAYERS: usize = 500_000;
+const POSITIONS: [&[Position]; 4] = [&[Position::Center], &[Position::Wing], &[Position::Defense], &[Position::Center, Position::Wing]];
+
+fn main() {
+    let mut players = PlayerMap::default();
+    for i in 0..PLAYERS {
+        let positions = POSITIONS[i % POSITIONS.len()].to_vec();
+        let metrics: HashMap<Position, Vec<f64>> = positions
+            .iter()
+            .map(|position| (position.clone(), (0..metric_names(position).len()).map(|j| ((i * 7 + j * 13) % 97) as f64).collect()))
+            .collect();
+        let player = Player::new(format!("Player {}", i), positions, metrics);
+        players.insert(player.name.clone(), player);
+    }
+
+    let start = Instant::now();
+    normalize_metrics(&mut players);
+    let normalized = start.elapsed().as_secs_f64();
+
+    let start = Instant::now();
+    let rankings = rank_players_with(&players, &WeightedSigmoid::default());
+    let ranked = start.elapsed().as_secs_f64();
+
+    println!(
+        "normalized {} players in {:.3}s, ranked {} position entries in {:.3}s",
+        PLAYERS,
+        normalized,
+        rankings.values().map(Vec::len).sum::<usize>(),
+        ranked
+    );
+}
@@ -0,0 +1,42 @@
+// Parses a generated 1M-row export and reports throughput. Run with `cargo bench --bench parse`.
+use std::{fmt::Write, time::Instant};
+use finalproject::cleaning::{process_reader, ParseOptions, COLUMNS};
+
+// Every row is a different player, as in a real export, so each one is parsed into its own entry.
+const ROWS: usize = 1_000_000;
+const POSITIONS: [&str; 5] = ["C", "L", "R", "D", "C/R"];
+
+fn main() {
+    let mut input = COLUMNS.join(",");
+    input.push('\n');
+    for i in 0..ROWS {
+        writeln!(
+            input,
+            "{},Player {},{},82,1400.5,{},40,25,15,{},70.0,200,15.0,10.5,300,250,120,40,{},8,20,10,9,1,0,12,30,{},{},60,{},400,380,51.3",
+            i,
+            i,
+            POSITIONS[i % POSITIONS.len()],
+            i % 50,
+            i % 90,
+            i % 40,
+            i % 60,
+            i % 200,
+            i % 150,
+        )
+        .unwrap();
+    }
+
+    let start = Instant::now();
+    let players = process_reader(input.as_bytes(), &ParseOptions::default()).unwrap();
+    let elapsed = start.elapsed().as_secs_f64();
+
+    println!(
+        "parsed {} rows ({:.1} MB, {} players) in {:.2}s: {:.0} rows/s, {:.1} MB/s",
+        ROWS,
+        input.len() as f64 / 1e6,
+        players.len(),
+        elapsed,
+        ROWS as f64 / elapsed,
+        input.len() as f64 / 1e6 / elapsed
+    );
+}
@@ -0,0 +1,30 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "grpc")]
+    grpc_service();
+}
+
+// Generates the service from a description mirroring proto/nhl_scores.proto, so no protoc is needed.
+#[cfg(feature = "grpc")]
+fn grpc_service() {
+    use tonic_build::manual::{Builder, Method, Service};
+
+    let method = |name: &str, route: &str, message: &str| {
+        Method::builder()
+            .name(name)
+            .route_name(route)
+            .input_type(format!("crate::grpc::{}Request", message))
+            .output_type(format!("crate::grpc::{}Reply", message))
+            .codec_path("tonic_prost::ProstCodec")
+            .build()
+    };
+    let service = Service::builder()
+        .name("Scores")
+        .package("nhl_scores")
+        .method(method("rank", "Rank", "Rank"))
+        .method(method("lookup", "Lookup", "Lookup"))
+        .method(method("compare", "Compare", "Compare"))
+        .method(method("config", "Config", "Config"))
+        .build();
+    Builder::new().build_client(false).compile(&[service]);
+}
@@ -0,0 +1,195 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Deserialize;
+use crate::cleaning::{Player, PlayerMap, Position};
+use crate::model::{MetricMap, ScoringModel};
+use crate::stat_line::StatLine;
+
+pub const AGE_STAT: &str = "Age";
+pub const BIRTH_DATE_STAT: &str = "Birth Date";
+
+const DAYS_PER_YEAR: f64 = 365.2425;
+
+// Days since 1970-01-01 in the proleptic Gregorian calendar.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// The inverse of `days_from_civil`: the year, month and day of a day number.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// Accepts ISO dates (2001-01-12) and US-style dates (1/12/2001).
+pub fn parse_date(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let parts: Vec<&str> = value.split(['-', '/']).collect();
+    let [a, b, c] = parts.as_slice() else { return None };
+    let (year, month, day) = if value.contains('-') { (a, b, c) } else { (c, a, b) };
+
+    let (year, month, day) = (year.parse::<i64>().ok()?, month.parse::<u32>().ok()?, day.parse::<u32>().ok()?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day))
+}
+
+pub fn today() -> i64 {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    (seconds / 86_400) as i64
+}
+
+// Birth dates are kept as day numbers so a cached dataset doesn't freeze everyone's age.
+pub fn age_from_stats(stats: &StatLine, reference_day: i64) -> Option<f64> {
+    stats
+        .get(AGE_STAT)
+        .copied()
+        .or_else(|| stats.get(BIRTH_DATE_STAT).map(|&born| (reference_day as f64 - born) / DAYS_PER_YEAR))
+}
+
+pub fn player_age(player: &Player, reference_day: i64) -> Option<f64> {
+    age_from_stats(&player.stats, reference_day)
+}
+
+pub fn players_with_age(players: &PlayerMap) -> usize {
+    players.values().filter(|player| player.stats.contains_key(AGE_STAT) || player.stats.contains_key(BIRTH_DATE_STAT)).count()
+}
+
+pub fn parse_age_range(value: &str) -> Result<(f64, f64), String> {
+    let (low, high) = value.split_once('-').ok_or_else(|| format!("expected an age range like 18-23, got '{}'", value))?;
+    let parse = |bound: &str| bound.trim().parse::<f64>().map_err(|_| format!("invalid age '{}' in range", bound.trim()));
+    let (low, high) = (parse(low)?, parse(high)?);
+    if low > high {
+        return Err(format!("age range {} is empty", value));
+    }
+    Ok((low, high))
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AgeCurve {
+    pub peak_start: f64,
+    pub peak_end: f64,
+    pub growth: f64,
+    pub decline: f64,
+    pub reference_date: Option<String>,
+}
+
+impl Default for AgeCurve {
+    fn default() -> Self {
+        AgeCurve { peak_start: 24.0, peak_end: 27.0, growth: 0.02, decline: 0.03, reference_date: None }
+    }
+}
+
+impl AgeCurve {
+    // Players younger than the peak get a boost per year, older players a discount per year.
+    pub fn multiplier(&self, age: f64) -> f64 {
+        let adjustment = if age < self.peak_start {
+            self.growth * (self.peak_start - age)
+        } else if age > self.peak_end {
+            -self.decline * (age - self.peak_end)
+        } else {
+            0.0
+        };
+        (1.0 + adjustment).max(0.0)
+    }
+
+    pub fn reference_day(&self) -> Result<i64, String> {
+        match &self.reference_date {
+            Some(date) => parse_date(date).ok_or_else(|| format!("invalid age curve reference_date '{}'", date)),
+            None => Ok(today()),
+        }
+    }
+}
+
+pub struct AgeAdjusted {
+    pub base: Box<dyn ScoringModel>,
+    pub curve: AgeCurve,
+    pub reference_day: i64,
+}
+
+impl ScoringModel for AgeAdjusted {
+    fn name(&self) -> &str {
+        "age-adjusted"
+    }
+
+    fn fit(&mut self, players: &PlayerMap) {
+        self.base.fit(players);
+    }
+
+    fn league_relative(&self) -> bool {
+        self.base.league_relative()
+    }
+
+    fn score(&self, position: &Position, metrics: &MetricMap) -> f64 {
+        let score = self.base.score(position, metrics);
+        match age_from_stats(metrics.stats, self.reference_day) {
+            Some(age) => score * self.curve.multiplier(age),
+            None => score,
+        }
+    }
+
+    fn unit(&self, position: &Position) -> &'static str {
+        self.base.unit(position)
+    }
+
+    // Scaling the score leaves each metric's share of it unchanged.
+    fn weights(&self, position: &Position) -> Option<&[f64]> {
+        self.base.weights(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+        assert_eq!(parse_date("2000-03-01"), Some(11_017));
+        assert_eq!(parse_date("3/1/2000"), Some(11_017));
+        assert_eq!(parse_date("2000-13-01"), None);
+        assert_eq!(parse_date("March 1"), None);
+        for day in [0, 11_017, 19_782, -1] {
+            let (year, month, date) = civil_from_days(day);
+            assert_eq!(days_from_civil(year, month, date), day);
+        }
+        assert_eq!(civil_from_days(11_017), (2000, 3, 1));
+    }
+
+    #[test]
+    fn test_age_curve_multiplier() {
+        let curve = AgeCurve::default();
+        assert!((curve.multiplier(20.0) - 1.08).abs() < 1e-9);
+        assert_eq!(curve.multiplier(25.5), 1.0);
+        assert!((curve.multiplier(33.0) - 0.82).abs() < 1e-9);
+        assert_eq!(parse_age_range("18-23"), Ok((18.0, 23.0)));
+        assert!(parse_age_range("23-18").is_err());
+    }
+
+    #[test]
+    fn test_age_from_birth_date() {
+        let stats = StatLine::from_iter([(BIRTH_DATE_STAT, parse_date("2000-03-01").unwrap() as f64)]);
+        let age = age_from_stats(&stats, parse_date("2024-03-01").unwrap()).unwrap();
+        assert!((age - 24.0).abs() < 0.01);
+
+        let mut players = PlayerMap::from_iter(["A", "B"].map(|name| (name.into(), Player::new(name, vec![Position::Wing], Default::default()))));
+        assert_eq!(players_with_age(&players), 0);
+        players["B"].stats = stats;
+        assert_eq!(players_with_age(&players), 1);
+    }
+}
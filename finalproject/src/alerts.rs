@@ -0,0 +1,318 @@
+use std::{collections::HashMap, fmt, sync::Arc};
+use crate::cleaning::{Player, PlayerMap, Position};
+use crate::formula::Formula;
+use crate::model::ScoringModel;
+use crate::scoring::{overall_rankings, rank_players_with};
+use crate::stats::average_ranks;
+use crate::team::player_teams;
+
+// One side of a comparison: a player's score or within-position percentile (at a position, or
+// overall/best when none is named), a formula over their stats such as `gp` or `points_per60 * 2`,
+// or for `==` and `!=` only, their name, team or position against a quoted string like `'TOR'`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Score(Option<Position>),
+    Percentile(Option<Position>),
+    Stats(Formula),
+    Field(Field),
+    Text(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Name,
+    Team,
+    Position,
+}
+
+impl Operand {
+    fn parse(source: &str) -> Result<Operand, String> {
+        let source = source.trim();
+        if let Some(quote @ ('\'' | '"')) = source.chars().next() {
+            return match source[1..].strip_suffix(quote) {
+                Some(text) => Ok(Operand::Text(text.to_string())),
+                None => Err(format!("unclosed quote in {}", source)),
+            };
+        }
+
+        let words: Vec<String> = source.split_whitespace().map(str::to_lowercase).collect();
+        let Some((last, qualifier)) = words.split_last() else { return Err("missing value in rule".to_string()) };
+        match words.as_slice() {
+            [field] if field == "name" || field == "player" => return Ok(Operand::Field(Field::Name)),
+            [field] if field == "team" || field == "tm" => return Ok(Operand::Field(Field::Team)),
+            [field] if field == "position" || field == "pos" => return Ok(Operand::Field(Field::Position)),
+            _ => {}
+        }
+        let position = || match qualifier {
+            [] => Ok(None),
+            _ => qualifier.join(" ").parse::<Position>().map(Some).map_err(|e| format!("{}: {}", source.trim(), e)),
+        };
+        match last.as_str() {
+            "score" => Ok(Operand::Score(position()?)),
+            "percentile" | "pct" => Ok(Operand::Percentile(position()?)),
+            _ => Formula::parse(source).map(Operand::Stats).map_err(|e| format!("{}: {}", source.trim(), e)),
+        }
+    }
+
+    fn is_constant(&self) -> bool {
+        matches!(self, Operand::Text(_)) || matches!(self, Operand::Stats(formula) if formula.variables.is_empty())
+    }
+
+    fn is_text(&self) -> bool {
+        matches!(self, Operand::Field(_) | Operand::Text(_))
+    }
+
+    // Every spelling that counts as equal, e.g. `C` and `Center` for a center, or each team a traded
+    // player was on.
+    fn text(&self, player: &Player) -> Vec<String> {
+        match self {
+            Operand::Field(Field::Name) => vec![player.name.to_string()],
+            Operand::Field(Field::Team) => player_teams(player),
+            Operand::Field(Field::Position) => player.positions.iter().flat_map(|position| [position.abbreviation().to_string(), position.to_string()]).collect(),
+            Operand::Text(text) => vec![text.clone()],
+            _ => Vec::new(),
+        }
+    }
+
+    fn value(&self, player: &Player, scores: &ScoreIndex) -> Option<f64> {
+        match self {
+            Operand::Score(None) => scores.overall.get(&player.name).copied(),
+            Operand::Score(Some(position)) => scores.positions.get(position)?.get(&player.name).map(|&(score, _)| score),
+            // Unqualified, a multi-position player is judged at their best position.
+            Operand::Percentile(None) => player
+                .positions
+                .iter()
+                .filter_map(|position| Operand::Percentile(Some(position.clone())).value(player, scores))
+                .max_by(f64::total_cmp),
+            Operand::Percentile(Some(position)) => scores.positions.get(position)?.get(&player.name).map(|&(_, percentile)| percentile),
+            Operand::Stats(formula) => {
+                let values: Vec<f64> = formula.variables.iter().map(|variable| variable.value(&player.stats)).collect();
+                Some(formula.evaluate(&values))
+            }
+            Operand::Field(_) | Operand::Text(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    const SYMBOLS: [(&'static str, Comparison); 7] = [
+        (">=", Comparison::GreaterOrEqual),
+        ("<=", Comparison::LessOrEqual),
+        ("!=", Comparison::NotEqual),
+        ("==", Comparison::Equal),
+        (">", Comparison::Greater),
+        ("<", Comparison::Less),
+        ("=", Comparison::Equal),
+    ];
+
+    fn holds(self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            Comparison::Less => lhs < rhs,
+            Comparison::LessOrEqual => lhs <= rhs,
+            Comparison::Greater => lhs > rhs,
+            Comparison::GreaterOrEqual => lhs >= rhs,
+            Comparison::Equal => lhs == rhs,
+            Comparison::NotEqual => lhs != rhs,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub lhs: (String, Operand),
+    pub comparison: Comparison,
+    pub rhs: (String, Operand),
+}
+
+impl Condition {
+    fn parse(source: &str) -> Result<Condition, String> {
+        let (start, symbol, comparison) = source
+            .char_indices()
+            .find_map(|(i, _)| Comparison::SYMBOLS.iter().find(|(symbol, _)| source[i..].starts_with(symbol)).map(|&(symbol, comparison)| (i, symbol, comparison)))
+            .ok_or_else(|| format!("expected a comparison such as `>` or `<=` in '{}'", source.trim()))?;
+        let (lhs, rhs) = (source[..start].trim(), source[start + symbol.len()..].trim());
+        let condition = Condition { lhs: (lhs.to_string(), Operand::parse(lhs)?), comparison, rhs: (rhs.to_string(), Operand::parse(rhs)?) };
+        if condition.lhs.1.is_text() != condition.rhs.1.is_text() {
+            return Err(format!("cannot compare text with a number in '{}'", source.trim()));
+        }
+        if condition.lhs.1.is_text() && !matches!(comparison, Comparison::Equal | Comparison::NotEqual) {
+            return Err(format!("text can only be compared with == or != in '{}'", source.trim()));
+        }
+        Ok(condition)
+    }
+
+    // A player without a score at the named position never matches. Text matches ignoring case.
+    fn matches(&self, player: &Player, scores: &ScoreIndex) -> bool {
+        if self.lhs.1.is_text() {
+            let (lhs, rhs) = (self.lhs.1.text(player), self.rhs.1.text(player));
+            let equal = lhs.iter().any(|lhs| rhs.iter().any(|rhs| lhs.eq_ignore_ascii_case(rhs)));
+            return equal == (self.comparison == Comparison::Equal);
+        }
+        match (self.lhs.1.value(player, scores), self.rhs.1.value(player, scores)) {
+            (Some(lhs), Some(rhs)) => self.comparison.holds(lhs, rhs),
+            _ => false,
+        }
+    }
+}
+
+// Comparisons joined by AND and OR, with AND binding tighter, e.g.
+// `defense score > 85 AND gp < 40 OR center percentile >= 99`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub source: String,
+    any_of: Vec<Vec<Condition>>,
+}
+
+impl Rule {
+    pub fn parse(source: &str) -> Result<Rule, String> {
+        let mut any_of = vec![Vec::new()];
+        let mut clause: Vec<&str> = Vec::new();
+        let mut quote = None;
+        for word in source.split_whitespace().chain([""]) {
+            let joiner = word.to_lowercase();
+            // A joiner inside a quoted string, as in `name == 'Bo and Co'`, is part of the string.
+            if quote.is_some() || !matches!(joiner.as_str(), "and" | "or" | "&&" | "||" | "") {
+                quote = word.chars().fold(quote, |quote, c| match quote {
+                    Some(q) if c == q => None,
+                    None if c == '\'' || c == '"' => Some(c),
+                    _ => quote,
+                });
+                if !word.is_empty() {
+                    clause.push(word);
+                    continue;
+                }
+                return Err(format!("unclosed quote in rule '{}'", source.trim()));
+            }
+            if clause.is_empty() {
+                return Err(format!("expected a comparison before '{}' in rule '{}'", word, source.trim()));
+            }
+            any_of.last_mut().unwrap().push(Condition::parse(&clause.join(" "))?);
+            clause.clear();
+            if matches!(joiner.as_str(), "or" | "||") {
+                any_of.push(Vec::new());
+            }
+        }
+        Ok(Rule { source: source.trim().to_string(), any_of })
+    }
+
+    pub fn matches(&self, player: &Player, scores: &ScoreIndex) -> bool {
+        self.any_of.iter().any(|conditions| conditions.iter().all(|condition| condition.matches(player, scores)))
+    }
+
+    // The non-constant values the rule looks at, for showing why a player matched.
+    pub fn terms(&self) -> Vec<&(String, Operand)> {
+        let mut terms: Vec<&(String, Operand)> = Vec::new();
+        for condition in self.any_of.iter().flatten() {
+            for term in [&condition.lhs, &condition.rhs] {
+                if !term.1.is_constant() && !terms.iter().any(|seen| seen.0.eq_ignore_ascii_case(&term.0)) {
+                    terms.push(term);
+                }
+            }
+        }
+        terms
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+// Each position's scores and within-position percentiles by player, plus overall scores.
+pub struct ScoreIndex {
+    positions: HashMap<Position, HashMap<Arc<str>, (f64, f64)>>,
+    overall: HashMap<Arc<str>, f64>,
+}
+
+impl ScoreIndex {
+    pub fn new(players: &PlayerMap, model: &dyn ScoringModel, bonus: f64) -> Self {
+        let positions = rank_players_with(players, model)
+            .into_iter()
+            .map(|(position, scored)| {
+                let ranks = average_ranks(&scored.iter().map(|scored| scored.score).collect::<Vec<_>>());
+                let count = scored.len() as f64;
+                let scores = scored.into_iter().zip(ranks).map(|(scored, rank)| (scored.name, (scored.score, 100.0 * (rank - 0.5) / count))).collect();
+                (position, scores)
+            })
+            .collect();
+        let overall = overall_rankings(players, model, bonus).into_iter().map(|overall| (overall.name, overall.score)).collect();
+        ScoreIndex { positions, overall }
+    }
+
+    pub fn value(&self, player: &Player, operand: &Operand) -> Option<f64> {
+        operand.value(player, self)
+    }
+}
+
+// Players matching the rule, highest overall score first.
+pub fn matching_players<'a>(players: &'a PlayerMap, rule: &Rule, scores: &ScoreIndex) -> Vec<&'a Player> {
+    let mut matched: Vec<&Player> = players.values().filter(|player| rule.matches(player, scores)).collect();
+    matched.sort_by(|a, b| {
+        let overall = |player: &Player| scores.overall.get(&player.name).copied().unwrap_or(f64::NEG_INFINITY);
+        overall(b).total_cmp(&overall(a))
+    });
+    matched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::WeightedSigmoid;
+
+    #[test]
+    fn test_rules_combine_scores_and_stats() {
+        let player = |name: &str, position: Position, points: f64, gp: f64| {
+            let mut player = Player::new(name, vec![position.clone()], HashMap::from([(position, vec![points / 100.0; 5])]));
+            player.stats = [("GP", gp), ("Total Points", points)].into();
+            (player.name.clone(), player)
+        };
+        let players = PlayerMap::from_iter([
+            player("Breakout", Position::Defense, 90.0, 30.0),
+            player("Veteran", Position::Defense, 95.0, 82.0),
+            player("Depth", Position::Defense, 10.0, 20.0),
+            player("Forward", Position::Center, 90.0, 30.0),
+        ]);
+        let scores = ScoreIndex::new(&players, &WeightedSigmoid::default(), 0.0);
+        let names = |rule: &str| -> Vec<String> {
+            let rule = Rule::parse(rule).unwrap();
+            matching_players(&players, &rule, &scores).iter().map(|player| player.name.to_string()).collect()
+        };
+
+        assert_eq!(names("defense percentile >= 50 AND gp < 40"), ["Breakout"]);
+        assert_eq!(names("D pct >= 50 and gp<40 or points_per_game > 1 and gp > 80"), ["Veteran", "Breakout"]);
+        assert_eq!(names("center score > 0 && points = 90"), ["Forward"]);
+        assert_eq!(Rule::parse("defense score > 85 AND gp < 40").unwrap().terms().len(), 2);
+
+        for bad in ["", "gp", "gp < 40 and", "goalie score > 50", "wins > 3", "gp < 40 or or gp > 80"] {
+            assert!(Rule::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_rules_compare_text_fields() {
+        let mut player = Player::new("Auston Matthews", vec![Position::Center], HashMap::new());
+        player.team = Some("TOR".to_string());
+        player.stats = [("GP", 81.0), ("Hits", 52.0)].into();
+        let players = PlayerMap::from_iter([(player.name.clone(), player)]);
+        let scores = ScoreIndex::new(&players, &WeightedSigmoid::default(), 0.0);
+        let matches = |rule: &str| Rule::parse(rule).unwrap().matches(&players["Auston Matthews"], &scores);
+
+        assert!(matches("team == 'tor' && gp >= 20 && hits > 50"));
+        assert!(matches("position = \"Center\" and name != 'Bo and Co'"));
+        assert!(!matches("team != 'TOR' || pos == 'D'"));
+        for bad in ["team > 'TOR'", "team == 3", "name == 'Auston", "'TOR' == 'TOR' and"] {
+            assert!(Rule::parse(bad).is_err(), "{}", bad);
+        }
+    }
+}
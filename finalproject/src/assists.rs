@@ -0,0 +1,120 @@
+use std::{fs, io, sync::Arc};
+use serde::Serialize;
+use crate::cleaning::{metric_key, split_fields};
+use crate::graph::{sort_by_weight, Graph};
+
+const SCORER_COLUMNS: [&str; 4] = ["scorer", "goalscorer", "goal", "shooter"];
+const ASSIST_COLUMNS: [&str; 8] = ["assist1", "a1", "primaryassist", "firstassist", "assist2", "a2", "secondaryassist", "secondassist"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Connection {
+    pub name: Arc<str>,
+    pub assists: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Duo {
+    pub first: Arc<str>,
+    pub second: Arc<str>,
+    pub assists: f64,
+}
+
+// Who sets up whose goals: an edge from each assister to the scorer, weighted by the number of goals.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AssistNetwork {
+    pub graph: Graph,
+    pub goals: usize,
+}
+
+impl AssistNetwork {
+    pub fn read(path: &str) -> io::Result<Self> {
+        AssistNetwork::parse(&fs::read_to_string(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, e)))
+    }
+
+    // One goal per row, with a scorer column (`Scorer`, `Goal`) and up to two assist columns
+    // (`Assist 1`/`A1`, `Assist 2`/`A2`); unassisted goals leave them blank.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines();
+        let header = split_fields(lines.next().ok_or("empty event file")?, ',');
+        let keys: Vec<String> = header.iter().map(|name| metric_key(name)).collect();
+        let scorer = keys.iter().position(|key| SCORER_COLUMNS.contains(&key.as_str())).ok_or("missing a Scorer column")?;
+        let assists: Vec<usize> = (0..keys.len()).filter(|&i| ASSIST_COLUMNS.contains(&keys[i].as_str())).collect();
+
+        let mut network = AssistNetwork::default();
+        for line in lines.filter(|line| !line.trim().is_empty()) {
+            let fields = split_fields(line, ',');
+            let Some(name) = fields.get(scorer).filter(|name| !name.is_empty()) else {
+                log::debug!("Goal skipped: no scorer - {}", line);
+                continue;
+            };
+            let scorer = network.graph.node(name);
+            for name in assists.iter().filter_map(|&i| fields.get(i)).filter(|name| !name.is_empty()) {
+                let assister = network.graph.node(name);
+                network.graph.add_edge(assister, scorer, 1.0);
+            }
+            network.goals += 1;
+        }
+        Ok(network)
+    }
+
+    fn connections(&self, edges: impl Iterator<Item = (usize, f64)>) -> Vec<Connection> {
+        let mut edges: Vec<(usize, f64)> = edges.collect();
+        sort_by_weight(&self.graph, &mut edges);
+        edges.into_iter().map(|(id, assists)| Connection { name: self.graph.name(id).clone(), assists }).collect()
+    }
+
+    // The players whose goals this player set up most often.
+    pub fn finishers(&self, name: &str) -> Vec<Connection> {
+        self.graph.id(name).map_or_else(Vec::new, |id| self.connections(self.graph.out_edges(id)))
+    }
+
+    // The players who set up this player's goals most often.
+    pub fn setup_men(&self, name: &str) -> Vec<Connection> {
+        self.graph.id(name).map_or_else(Vec::new, |id| self.connections(self.graph.in_edges(id)))
+    }
+
+    // Pairs by goals either one set up for the other.
+    pub fn top_duos(&self) -> Vec<Duo> {
+        let mut duos: Vec<Duo> = self
+            .graph
+            .edges()
+            .filter(|&(from, to, _)| from < to || self.graph.weight(to, from).is_none())
+            .map(|(from, to, weight)| {
+                let (first, second) = if from < to { (from, to) } else { (to, from) };
+                let back = if from < to { self.graph.weight(to, from).unwrap_or(0.0) } else { 0.0 };
+                Duo { first: self.graph.name(first).clone(), second: self.graph.name(second).clone(), assists: weight + back }
+            })
+            .collect();
+        duos.sort_by(|a, b| {
+            b.assists.partial_cmp(&a.assists).unwrap_or(std::cmp::Ordering::Equal).then_with(|| (&a.first, &a.second).cmp(&(&b.first, &b.second)))
+        });
+        duos
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assist_network_queries() {
+        let text = "Scorer,Assist 1,Assist 2\n\
+                    Leon Draisaitl,Connor McDavid,Evan Bouchard\n\
+                    Leon Draisaitl,Connor McDavid,\n\
+                    Connor McDavid,Leon Draisaitl,Evan Bouchard\n\
+                    Zach Hyman,Connor McDavid,\n\
+                    Evan Bouchard,,\n";
+        let network = AssistNetwork::parse(text).unwrap();
+        assert_eq!((network.goals, network.graph.len()), (5, 4));
+
+        let finishers = network.finishers("connor mcdavid");
+        assert_eq!(finishers.iter().map(|c| (&*c.name, c.assists)).collect::<Vec<_>>(), [("Leon Draisaitl", 2.0), ("Zach Hyman", 1.0)]);
+        let setup = network.setup_men("Leon Draisaitl");
+        assert_eq!(setup.iter().map(|c| (&*c.name, c.assists)).collect::<Vec<_>>(), [("Connor McDavid", 2.0), ("Evan Bouchard", 1.0)]);
+
+        let duos = network.top_duos();
+        assert_eq!((&*duos[0].first, &*duos[0].second, duos[0].assists), ("Leon Draisaitl", "Connor McDavid", 3.0));
+        assert_eq!(duos.iter().map(|duo| duo.assists).sum::<f64>(), 6.0);
+        assert!(AssistNetwork::parse("Player,Team\n").is_err());
+    }
+}
@@ -0,0 +1,90 @@
+use std::sync::Arc;
+use serde::Serialize;
+use crate::cleaning::{Player, PlayerMap, Position};
+use crate::scoring::{metric_names};
+use crate::stats::summarize;
+use crate::positions;
+
+pub const DEFAULT_Z_THRESHOLD: f64 = 4.0;
+
+// Hard bounds for the percentage metrics; shooting over 40% only happens on a handful of shots.
+const LIMITS: [(&str, f64, f64); 3] = [("SH%", 0.0, 40.0), ("Faceoffs %", 0.0, 100.0), ("IPP", 0.0, 100.0)];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Flag {
+    pub name: Arc<str>,
+    pub position: Position,
+    pub metric: &'static str,
+    pub value: f64,
+    pub z: f64,
+    pub reason: String,
+}
+
+fn limit_reason(metric: &str, value: f64) -> Option<String> {
+    match LIMITS.iter().find(|(name, _, _)| *name == metric) {
+        Some(&(_, min, max)) if value < min || value > max => Some(format!("outside {}-{}", min, max)),
+        Some(_) => None,
+        None if value < 0.0 => Some("negative count".to_string()),
+        None => None,
+    }
+}
+
+// Flags raw (pre-normalization) metric values that are out of bounds or more than `z_threshold`
+// standard deviations from the position mean, largest deviations first within each position.
+pub fn audit(players: &PlayerMap, z_threshold: f64) -> Vec<Flag> {
+    let mut flags = Vec::new();
+
+    for position in positions::all() {
+        let rows: Vec<(&Player, &Vec<f64>)> =
+            players.values().filter_map(|p| Some((p, p.raw_metrics.get(position)?))).collect();
+        let mut position_flags = Vec::new();
+
+        for (i, &metric) in metric_names(position).iter().enumerate() {
+            let values: Vec<f64> = rows.iter().filter_map(|(_, raw)| raw.get(i).copied()).collect();
+            let summary = summarize(&values);
+
+            for &(player, raw) in &rows {
+                let Some(&value) = raw.get(i) else { continue };
+                let z = if summary.stddev > 0.0 { (value - summary.mean) / summary.stddev } else { 0.0 };
+                let reason = limit_reason(metric, value)
+                    .or_else(|| (z.abs() > z_threshold).then(|| format!("{:.1} standard deviations from the mean", z)));
+
+                if let Some(reason) = reason {
+                    position_flags.push(Flag { name: player.name.clone(), position: position.clone(), metric, value, z, reason });
+                }
+            }
+        }
+
+        position_flags.sort_by(|a, b| b.z.abs().partial_cmp(&a.z.abs()).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.name.cmp(&b.name)));
+        flags.extend(position_flags);
+    }
+
+    flags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_audit_flags_limits_and_outliers() {
+        let mut players: PlayerMap = (0..20)
+            .map(|i| {
+                let name = format!("Wing {}", i);
+                let metrics = vec![10.0 + (i % 3) as f64, 10.0, 5.0, 30.0, 50.0];
+                (name.clone().into(), Player::new(name, vec![Position::Wing], HashMap::from([(Position::Wing, metrics)])))
+            })
+            .collect();
+        let outlier = Player::new("Outlier", vec![Position::Wing], HashMap::from([(Position::Wing, vec![90.0, 50.0, 5.0, 30.0, 50.0])]));
+        players.insert("Outlier".into(), outlier);
+
+        let flags = audit(&players, DEFAULT_Z_THRESHOLD);
+        assert_eq!(flags.len(), 2);
+        assert!(flags.iter().all(|flag| &*flag.name == "Outlier"));
+        let goals = flags.iter().find(|flag| flag.metric == "Goals").unwrap();
+        assert!(goals.z > DEFAULT_Z_THRESHOLD);
+        let shooting = flags.iter().find(|flag| flag.metric == "SH%").unwrap();
+        assert_eq!(shooting.reason, "outside 0-40");
+    }
+}
@@ -0,0 +1,172 @@
+use std::{collections::HashMap, fs, io};
+use crate::age::{civil_from_days, parse_date, player_age, BIRTH_DATE_STAT};
+use crate::cleaning::{metric_key, split_fields, Player, PlayerMap};
+
+pub const PLAYER_ID_STAT: &str = "Player ID";
+pub const JERSEY_STAT: &str = "Jersey";
+// Inches and pounds, whatever units the roster file used.
+pub const HEIGHT_STAT: &str = "Height";
+pub const WEIGHT_STAT: &str = "Weight";
+
+const CM_PER_INCH: f64 = 2.54;
+const LB_PER_KG: f64 = 2.204_62;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bio {
+    pub jersey: Option<f64>,
+    pub height: Option<f64>,
+    pub weight: Option<f64>,
+    pub birth_date: Option<i64>,
+}
+
+// Roster rows keyed by lowercased name, and by player ID when the file has one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Roster {
+    pub by_name: HashMap<String, Bio>,
+    pub by_id: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BioJoin {
+    pub matched: usize,
+    // Roster names no player was found for, sorted.
+    pub unmatched: Vec<String>,
+}
+
+// `6'2"`, `6' 2`, `6-2`, `74` (inches) or `188 cm`; bare numbers over 100 are taken as centimeters.
+pub fn parse_height(value: &str) -> Option<f64> {
+    let value = value.trim().trim_end_matches(['"', '\'']).trim();
+    let inches = if let Some((feet, inches)) = value.split_once(['\'', '-']) {
+        let inches = inches.trim().trim_start_matches('\'').trim();
+        feet.trim().parse::<f64>().ok()? * 12.0 + if inches.is_empty() { 0.0 } else { inches.parse::<f64>().ok()? }
+    } else if let Some(cm) = value.strip_suffix("cm") {
+        cm.trim().parse::<f64>().ok()? / CM_PER_INCH
+    } else {
+        let number = value.strip_suffix("in").unwrap_or(value).trim().parse::<f64>().ok()?;
+        if number > 100.0 { number / CM_PER_INCH } else { number }
+    };
+    Some(inches).filter(|inches| inches.is_finite() && *inches > 0.0)
+}
+
+// `194`, `194 lbs` or `88 kg`.
+pub fn parse_weight(value: &str) -> Option<f64> {
+    let value = value.trim().to_lowercase();
+    let pounds = match value.strip_suffix("kg") {
+        Some(kg) => kg.trim().parse::<f64>().ok()? * LB_PER_KG,
+        None => value.trim_end_matches(['l', 'b', 's', '.']).trim().parse::<f64>().ok()?,
+    };
+    Some(pounds).filter(|pounds| pounds.is_finite() && *pounds > 0.0)
+}
+
+// A CSV with a header naming its columns, e.g. `Player,ID,Jersey,Height,Weight,Birth Date`. Only a
+// name column is required; blank or unreadable values are left out.
+pub fn parse_roster(text: &str) -> Result<Roster, String> {
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let header = lines.next().ok_or("empty roster file")?;
+    let columns: Vec<String> = split_fields(header, ',').into_iter().map(|name| if name == "#" { "jersey".to_string() } else { metric_key(name) }).collect();
+    let column = |names: &[&str]| columns.iter().position(|column| names.contains(&column.as_str()));
+    let name_column = column(&["player", "name", "playername", "fullname"]).ok_or("the roster needs a Player or Name column")?;
+    let id_column = column(&["id", "playerid", "nhlid"]);
+    let jersey_column = column(&["jersey", "number", "no", "num", "sweater", "jerseynumber"]);
+    let height_column = column(&["height", "ht"]);
+    let weight_column = column(&["weight", "wt"]);
+    let birth_column = column(&["birthdate", "dateofbirth", "dob", "birthday", "born"]);
+
+    let mut roster = Roster::default();
+    for line in lines {
+        let fields = split_fields(line, ',');
+        let field = |column: Option<usize>| column.and_then(|i| fields.get(i)).copied().filter(|field| !field.is_empty());
+        let Some(name) = field(Some(name_column)) else { continue };
+        let bio = Bio {
+            jersey: field(jersey_column).and_then(|jersey| jersey.trim_start_matches('#').parse().ok()),
+            height: field(height_column).and_then(parse_height),
+            weight: field(weight_column).and_then(parse_weight),
+            birth_date: field(birth_column).and_then(parse_date),
+        };
+        let name = name.to_lowercase();
+        if let Some(id) = field(id_column) {
+            roster.by_id.insert(id.to_string(), name.clone());
+        }
+        roster.by_name.insert(name, bio);
+    }
+    Ok(roster)
+}
+
+pub fn read_roster_bios(path: &str) -> io::Result<Roster> {
+    parse_roster(&fs::read_to_string(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, e)))
+}
+
+// Matches by player ID when both the export and the roster have one, else by name.
+pub fn join_bios(players: &mut PlayerMap, roster: &Roster) -> BioJoin {
+    let mut join = BioJoin::default();
+    let mut used: Vec<&str> = Vec::new();
+    for player in players.values_mut() {
+        let id = player.stats.get(PLAYER_ID_STAT).map(|id| id.to_string());
+        let name = id.and_then(|id| roster.by_id.get(&id)).cloned().unwrap_or_else(|| player.name.to_lowercase());
+        let Some((name, bio)) = roster.by_name.get_key_value(&name) else { continue };
+        let fields = [(JERSEY_STAT, bio.jersey), (HEIGHT_STAT, bio.height), (WEIGHT_STAT, bio.weight), (BIRTH_DATE_STAT, bio.birth_date.map(|day| day as f64))];
+        player.stats.extend(fields.into_iter().filter_map(|(stat, value)| Some((stat.to_string(), value?))));
+        used.push(name);
+        join.matched += 1;
+    }
+    join.unmatched = roster.by_name.keys().filter(|name| !used.contains(&name.as_str())).cloned().collect();
+    join.unmatched.sort();
+    join
+}
+
+// e.g. `#97, 6'1", 194 lb, born 1997-01-13 (age 27)`, or None without any bio stats.
+pub fn bio_line(player: &Player, reference_day: i64) -> Option<String> {
+    let stat = |name: &str| player.stats.get(name).copied();
+    let mut parts = Vec::new();
+    if let Some(jersey) = stat(JERSEY_STAT) {
+        parts.push(format!("#{}", jersey));
+    }
+    if let Some(height) = stat(HEIGHT_STAT) {
+        let inches = height.round() as i64;
+        parts.push(format!("{}'{}\"", inches / 12, inches % 12));
+    }
+    if let Some(weight) = stat(WEIGHT_STAT) {
+        parts.push(format!("{:.0} lb", weight));
+    }
+    if let Some(born) = stat(BIRTH_DATE_STAT) {
+        let (year, month, day) = civil_from_days(born as i64);
+        let age = player_age(player, reference_day).map_or(String::new(), |age| format!(" (age {:.0})", age.floor()));
+        parts.push(format!("born {:04}-{:02}-{:02}{}", year, month, day, age));
+    }
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::age::days_from_civil;
+    use crate::cleaning::Position;
+
+    #[test]
+    fn test_roster_bios_join_by_id_then_name() {
+        assert_eq!(parse_height("6'2\""), Some(74.0));
+        assert_eq!(parse_height("6-0"), Some(72.0));
+        assert_eq!(parse_height("73"), Some(73.0));
+        assert_eq!(parse_height("188 cm").map(f64::round), Some(74.0));
+        assert_eq!(parse_weight("194 lbs"), Some(194.0));
+        assert_eq!(parse_weight("88kg").map(f64::round), Some(194.0));
+
+        let roster = parse_roster("Player,ID,#,Ht,Wt,DOB\nConnor McDavid,8478402,97,6'1,194,1997-01-13\nCal Makar,8480069,8,72,187,1998-10-30\nRetired Guy,,1,,,\n").unwrap();
+        assert!(parse_roster("Jersey,Height\n97,73\n").is_err());
+
+        let player = |name: &str, id: Option<f64>| {
+            let mut player = Player::new(name, vec![Position::Center], HashMap::new());
+            player.stats.extend(id.map(|id| (PLAYER_ID_STAT.to_string(), id)));
+            (player.name.clone(), player)
+        };
+        // Cale Makar is matched by ID despite the roster's misspelling.
+        let mut players = PlayerMap::from_iter([player("Connor McDavid", None), player("Cale Makar", Some(8480069.0)), player("Unknown", None)]);
+        let join = join_bios(&mut players, &roster);
+        assert_eq!(join, BioJoin { matched: 2, unmatched: vec!["retired guy".to_string()] });
+        assert_eq!(players["Cale Makar"].stats.get(HEIGHT_STAT), Some(&72.0));
+
+        let reference_day = days_from_civil(2024, 10, 1);
+        assert_eq!(bio_line(&players["Connor McDavid"], reference_day).unwrap(), "#97, 6'1\", 194 lb, born 1997-01-13 (age 27)");
+        assert_eq!(bio_line(&players["Unknown"], reference_day), None);
+    }
+}
@@ -0,0 +1,158 @@
+use std::{collections::HashMap, fs, io, path::{Path, PathBuf}};
+use serde::Serialize;
+use crate::cleaning::{PlayerMap, Position};
+use crate::scoring::ScoredPlayer;
+
+pub const DEFAULT_CACHE_DIR: &str = ".nhl_scores_cache";
+
+// Bump whenever the cached Player layout, or what a `Position::Custom` id refers to, changes so stale
+// caches are ignored.
+const FORMAT_VERSION: u32 = 9;
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+// FNV-1a, so cache keys stay the same across Rust versions and platforms.
+pub fn hash_bytes(bytes: &[u8]) -> u64 {
+    extend_hash(FNV_OFFSET, bytes)
+}
+
+pub fn extend_hash(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+pub fn cache_path(cache_dir: &Path, hash: u64) -> PathBuf {
+    cache_dir.join(format!("{:016x}-v{}.json", hash, FORMAT_VERSION))
+}
+
+pub fn load(cache_dir: &Path, hash: u64) -> Option<PlayerMap> {
+    let contents = fs::read_to_string(cache_path(cache_dir, hash)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+pub fn store(cache_dir: &Path, hash: u64, players: &PlayerMap) -> io::Result<()> {
+    fs::create_dir_all(cache_dir)?;
+    let contents = serde_json::to_string(players).map_err(io::Error::other)?;
+    fs::write(cache_path(cache_dir, hash), contents)
+}
+
+const SCORES_PREFIX: &str = "scores-";
+
+// Rankings sit beside the datasets, keyed by everything they were scored from rather than just the input.
+pub fn scores_path(cache_dir: &Path, key: u64) -> PathBuf {
+    cache_dir.join(format!("{}{:016x}-v{}.json", SCORES_PREFIX, key, FORMAT_VERSION))
+}
+
+pub fn load_scores(cache_dir: &Path, key: u64) -> Option<HashMap<Position, Vec<ScoredPlayer>>> {
+    let contents = fs::read_to_string(scores_path(cache_dir, key)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+pub fn store_scores(cache_dir: &Path, key: u64, rankings: &HashMap<Position, Vec<ScoredPlayer>>) -> io::Result<()> {
+    fs::create_dir_all(cache_dir)?;
+    let contents = serde_json::to_string(rankings).map_err(io::Error::other)?;
+    fs::write(scores_path(cache_dir, key), contents)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CacheStatus {
+    pub datasets: usize,
+    pub scores: usize,
+    // Entries written by older versions, which are never read again.
+    pub stale: usize,
+    pub bytes: u64,
+}
+
+// Only the cache's own `.json` entries, so pointing the cache at the wrong directory can't touch anything else.
+fn entries(cache_dir: &Path) -> io::Result<Vec<(PathBuf, String, u64)>> {
+    let dir = match fs::read_dir(cache_dir) {
+        Ok(dir) => dir,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut entries = Vec::new();
+    for entry in dir {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.file_type()?.is_file() && name.ends_with(".json") {
+            entries.push((entry.path(), name, entry.metadata()?.len()));
+        }
+    }
+    Ok(entries)
+}
+
+pub fn status(cache_dir: &Path) -> io::Result<CacheStatus> {
+    let current = format!("-v{}.json", FORMAT_VERSION);
+    let mut status = CacheStatus::default();
+    for (_, name, bytes) in entries(cache_dir)? {
+        status.bytes += bytes;
+        match (name.ends_with(&current), name.starts_with(SCORES_PREFIX)) {
+            (false, _) => status.stale += 1,
+            (true, true) => status.scores += 1,
+            (true, false) => status.datasets += 1,
+        }
+    }
+    Ok(status)
+}
+
+// Returns how many entries were removed.
+pub fn clear(cache_dir: &Path) -> io::Result<usize> {
+    let entries = entries(cache_dir)?;
+    for (path, _, _) in &entries {
+        fs::remove_file(path)?;
+    }
+    Ok(entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::cleaning::{Player, Position};
+
+    #[test]
+    fn test_hash_bytes_is_stable() {
+        assert_eq!(hash_bytes(b""), 0xcbf29ce484222325);
+        assert_eq!(hash_bytes(b"a"), 0xaf63dc4c8601ec8c);
+        assert_ne!(hash_bytes(b"NHL.csv v1"), hash_bytes(b"NHL.csv v2"));
+    }
+
+    #[test]
+    fn test_store_and_load() {
+        let cache_dir = std::env::temp_dir().join("finalproject_cache_test");
+        let players = PlayerMap::from_iter([(
+            "Cale Makar".into(),
+            Player::new("Cale Makar", vec![Position::Defense], HashMap::from([(Position::Defense, vec![0.5; 5])])),
+        )]);
+
+        store(&cache_dir, 42, &players).unwrap();
+        let loaded = load(&cache_dir, 42).unwrap();
+        assert_eq!(loaded["Cale Makar"].metrics[&Position::Defense], vec![0.5; 5]);
+        assert!(load(&cache_dir, 43).is_none());
+
+        fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_scores_status_and_clear() {
+        let cache_dir = std::env::temp_dir().join("finalproject_score_cache_test");
+        let _ = fs::remove_dir_all(&cache_dir);
+        assert_eq!(status(&cache_dir).unwrap(), CacheStatus::default());
+
+        let rankings = HashMap::from([(Position::Wing, vec![ScoredPlayer { name: "David Pastrnak".into(), position: Position::Wing, score: 97.5 }])]);
+        store_scores(&cache_dir, 7, &rankings).unwrap();
+        store(&cache_dir, 7, &PlayerMap::default()).unwrap();
+        fs::write(cache_dir.join("0000000000000007-v1.json"), "{}").unwrap();
+        fs::write(cache_dir.join("notes.txt"), "keep me").unwrap();
+        assert_eq!(load_scores(&cache_dir, 7), Some(rankings));
+        assert!(load_scores(&cache_dir, 8).is_none());
+
+        let found = status(&cache_dir).unwrap();
+        assert_eq!((found.datasets, found.scores, found.stale), (1, 1, 1));
+        assert_eq!(clear(&cache_dir).unwrap(), 3);
+        assert_eq!(status(&cache_dir).unwrap(), CacheStatus::default());
+        assert!(cache_dir.join("notes.txt").exists());
+
+        fs::remove_dir_all(&cache_dir).unwrap();
+    }
+}
@@ -0,0 +1,146 @@
+use std::{collections::HashMap, fs, io};
+use serde::Serialize;
+use crate::cleaning::{Player, PlayerMap, Position};
+use crate::scoring::{metric_index, metric_names};
+use crate::stats::{first_principal_component, least_squares, Regression};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Calibration {
+    pub position: Position,
+    pub players: usize,
+    pub metrics: &'static [&'static str],
+    // The metric that is the target itself, left out of the fit with a zero weight.
+    pub excluded: Option<usize>,
+    pub regression: Regression,
+    pub weights: Vec<f64>,
+}
+
+// Negative coefficients are clamped to zero before rescaling, since the composite score only rewards metrics.
+pub fn relative_weights(coefficients: &[f64]) -> Vec<f64> {
+    let clamped: Vec<f64> = coefficients.iter().map(|c| c.max(0.0)).collect();
+    let total: f64 = clamped.iter().sum();
+    if total > 0.0 {
+        clamped.iter().map(|c| c / total).collect()
+    } else {
+        vec![0.0; coefficients.len()]
+    }
+}
+
+pub type Target<'a> = dyn Fn(&Player) -> Option<f64> + 'a;
+
+// `target_stat` names the stat column being fit against. When it's also one of the position's
+// metrics that metric is left out, or it would explain the target perfectly on its own.
+pub fn calibrate_position(players: &PlayerMap, position: &Position, target: &Target, target_stat: Option<&str>) -> Option<Calibration> {
+    let excluded = target_stat.and_then(|stat| metric_index(position, stat));
+    let mut rows = Vec::new();
+    let mut targets = Vec::new();
+
+    for player in players.values() {
+        let (Some(metrics), Some(value)) = (player.metrics.get(position), target(player)) else { continue };
+        let mut row = metrics.clone();
+        if let Some(i) = excluded.filter(|&i| i < row.len()) {
+            row.remove(i);
+        }
+        rows.push(row);
+        targets.push(value);
+    }
+
+    let mut regression = least_squares(&rows, &targets)?;
+    if let Some(i) = excluded.filter(|&i| i <= regression.coefficients.len()) {
+        regression.coefficients.insert(i, 0.0);
+    }
+    let weights = relative_weights(&regression.coefficients);
+    Some(Calibration { position: position.clone(), players: rows.len(), metrics: metric_names(position), excluded, regression, weights })
+}
+
+// Weights proportional to each metric's loading on the first principal component of the normalized metrics.
+pub fn pca_weights(players: &PlayerMap, position: &Position) -> Option<Vec<f64>> {
+    let rows: Vec<Vec<f64>> = players.values().filter_map(|p| p.metrics.get(position).cloned()).collect();
+    let weights = relative_weights(&first_principal_component(&rows)?);
+    weights.iter().any(|&w| w > 0.0).then_some(weights)
+}
+
+pub fn read_ratings(path: &str) -> io::Result<HashMap<String, f64>> {
+    parse_ratings(&fs::read_to_string(path)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, e)))
+}
+
+// Two columns, player name then rating. A header row is skipped when its rating doesn't parse.
+pub fn parse_ratings(text: &str) -> Result<HashMap<String, f64>, String> {
+    let mut ratings = HashMap::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (name, rating) = line.rsplit_once(',').ok_or_else(|| format!("line {}: expected `name,rating`", i + 1))?;
+        match rating.trim().trim_matches('"').parse::<f64>() {
+            Ok(value) if value.is_finite() => {
+                ratings.insert(name.trim().trim_matches('"').to_lowercase(), value);
+            }
+            _ if i == 0 => continue,
+            _ => return Err(format!("line {}: invalid rating '{}'", i + 1, rating.trim())),
+        }
+    }
+
+    Ok(ratings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calibrate_position_fits_target() {
+        let weights = [0.5, 0.2, 0.1, 0.1, 0.1];
+        let players: PlayerMap = (0..12)
+            .map(|i| {
+                let metrics: Vec<f64> = (0..5).map(|j| ((i * i * (j + 2) + 3 * j + i) % 11) as f64 / 11.0).collect();
+                let name = format!("Player {}", i);
+                (name.clone().into(), Player::new(name, vec![Position::Center], HashMap::from([(Position::Center, metrics)])))
+            })
+            .collect();
+        let target = |player: &Player| {
+            let metrics = player.metrics.get(&Position::Center)?;
+            Some(metrics.iter().zip(&weights).map(|(m, w)| m * w * 100.0).sum())
+        };
+
+        let calibration = calibrate_position(&players, &Position::Center, &target, None).unwrap();
+        assert_eq!(calibration.players, 12);
+        assert!((calibration.regression.r_squared - 1.0).abs() < 1e-9);
+        for (fitted, expected) in calibration.weights.iter().zip(weights) {
+            assert!((fitted - expected).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_calibrate_against_a_metric_leaves_it_out() {
+        // Total Points is the target and also the Center's second metric, which copies it.
+        let players: PlayerMap = (0..12)
+            .map(|i| {
+                let mut metrics: Vec<f64> = (0..5).map(|j| ((i * i * (j + 2) + 3 * j + i) % 11) as f64 / 11.0).collect();
+                metrics[1] = 0.5 * metrics[0] + 0.5 * metrics[2];
+                let name = format!("Player {}", i);
+                let mut player = Player::new(name.clone(), vec![Position::Center], HashMap::from([(Position::Center, metrics.clone())]));
+                player.stats.insert("Total Points".to_string(), 100.0 * metrics[1]);
+                (name.into(), player)
+            })
+            .collect();
+        let target = |player: &Player| player.stats.get("Total Points").copied();
+
+        let calibration = calibrate_position(&players, &Position::Center, &target, Some("Total Points")).unwrap();
+        assert_eq!(calibration.excluded, Some(1));
+        assert_eq!((calibration.weights.len(), calibration.weights[1]), (5, 0.0));
+        assert!((calibration.weights[0] - 0.5).abs() < 1e-6 && (calibration.weights[2] - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_parse_ratings() {
+        let ratings = parse_ratings("player,war\nCale Makar,4.5\n\"Mitch Marner\",3\n").unwrap();
+        assert_eq!(ratings["cale makar"], 4.5);
+        assert_eq!(ratings["mitch marner"], 3.0);
+        assert!(parse_ratings("Cale Makar,4.5\nConnor McDavid,lots").is_err());
+    }
+}
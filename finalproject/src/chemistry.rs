@@ -0,0 +1,159 @@
+use std::{collections::HashMap, fs, io, sync::Arc};
+use serde::Serialize;
+use crate::cleaning::{metric_key, split_fields};
+
+// Minutes together at which a duo's chemistry counts for half its on-ice share; shorter samples are
+// pulled toward zero so a 10-minute fluke doesn't top the list.
+pub const CHEMISTRY_PRIOR_TOI: f64 = 50.0;
+
+// On-ice results for two players, summed over every line in the export they were both on.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DuoStats {
+    pub toi: f64,
+    pub goals_for: f64,
+    pub goals_against: f64,
+    pub xg_for: f64,
+    pub xg_against: f64,
+}
+
+impl DuoStats {
+    // Expected-goals share (goal share when the export has no xG) above 50%, in points, shrunk by time together.
+    pub fn chemistry(&self) -> Option<f64> {
+        let share = [(self.xg_for, self.xg_against), (self.goals_for, self.goals_against)]
+            .into_iter()
+            .find(|(f, a)| f + a > 0.0)
+            .map(|(f, a)| f / (f + a))?;
+        Some((share - 0.5) * 100.0 * self.toi / (self.toi + CHEMISTRY_PRIOR_TOI))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Linemate {
+    pub name: Arc<str>,
+    pub chemistry: f64,
+    pub toi: f64,
+}
+
+// Duos keyed by their lowercased names in sorted order, so either player finds the pair.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Chemistry {
+    duos: HashMap<(String, String), DuoStats>,
+    names: HashMap<String, Arc<str>>,
+}
+
+fn duo_key(a: &str, b: &str) -> (String, String) {
+    let (a, b) = (a.to_lowercase(), b.to_lowercase());
+    if a <= b { (a, b) } else { (b, a) }
+}
+
+impl Chemistry {
+    pub fn read(path: &str) -> io::Result<Self> {
+        Chemistry::parse(&fs::read_to_string(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, e)))
+    }
+
+    // A line-combination export: players in `Player 1`..`Player 3` columns (or one `Line` column with
+    // names joined by " - "), and the line's `TOI`, `GF`, `GA`, `xGF` and `xGA`. A trio adds its
+    // results to each of its three pairs.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines();
+        let header = split_fields(lines.next().ok_or("empty line-combination file")?, ',');
+        let keys: Vec<String> = header.iter().map(|name| metric_key(name)).collect();
+        let column = |names: &[&str]| keys.iter().position(|key| names.contains(&key.as_str()));
+
+        let player_columns: Vec<usize> = (0..keys.len()).filter(|&i| keys[i].starts_with("player") && keys[i].len() > "player".len()).collect();
+        let line_column = column(&["line", "forwards", "defense", "players"]);
+        if player_columns.len() < 2 && line_column.is_none() {
+            return Err("expected `Player 1` and `Player 2` columns or a `Line` column".to_string());
+        }
+        let toi = column(&["toi"]).ok_or("missing TOI column")?;
+        let [gf, ga, xgf, xga] = [column(&["gf"]), column(&["ga"]), column(&["xgf"]), column(&["xga"])];
+
+        let mut chemistry = Chemistry::default();
+        for line in lines.filter(|line| !line.trim().is_empty()) {
+            let fields = split_fields(line, ',');
+            let value = |column: Option<usize>| column.and_then(|i| fields.get(i)?.parse::<f64>().ok()).filter(|v| v.is_finite()).unwrap_or(0.0);
+            let names: Vec<&str> = match line_column {
+                Some(i) if player_columns.len() < 2 => fields.get(i).map_or(Vec::new(), |line| line.split(" - ").map(str::trim).collect()),
+                _ => player_columns.iter().filter_map(|&i| fields.get(i).copied()).collect(),
+            };
+            let names: Vec<&str> = names.into_iter().filter(|name| !name.is_empty()).collect();
+            if names.len() < 2 {
+                log::debug!("Line skipped: fewer than two players - {}", line);
+                continue;
+            }
+
+            let stats = DuoStats { toi: value(Some(toi)), goals_for: value(gf), goals_against: value(ga), xg_for: value(xgf), xg_against: value(xga) };
+            for (i, a) in names.iter().enumerate() {
+                chemistry.names.entry(a.to_lowercase()).or_insert_with(|| Arc::from(*a));
+                for b in &names[i + 1..] {
+                    let duo = chemistry.duos.entry(duo_key(a, b)).or_default();
+                    duo.toi += stats.toi;
+                    duo.goals_for += stats.goals_for;
+                    duo.goals_against += stats.goals_against;
+                    duo.xg_for += stats.xg_for;
+                    duo.xg_against += stats.xg_against;
+                }
+            }
+        }
+        Ok(chemistry)
+    }
+
+    pub fn len(&self) -> usize {
+        self.duos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.duos.is_empty()
+    }
+
+    pub fn duo(&self, a: &str, b: &str) -> Option<&DuoStats> {
+        self.duos.get(&duo_key(a, b))
+    }
+
+    // The player's partners by chemistry, best first.
+    pub fn best_linemates(&self, name: &str) -> Vec<Linemate> {
+        let name = name.to_lowercase();
+        let mut linemates: Vec<Linemate> = self
+            .duos
+            .iter()
+            .filter_map(|((a, b), stats)| {
+                let partner = if *a == name { b } else if *b == name { a } else { return None };
+                Some(Linemate { name: self.names.get(partner)?.clone(), chemistry: stats.chemistry()?, toi: stats.toi })
+            })
+            .collect();
+        linemates.sort_by(|a, b| b.chemistry.partial_cmp(&a.chemistry).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.name.cmp(&b.name)));
+        linemates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trios_add_to_each_pair() {
+        let text = "Player 1,Player 2,Player 3,TOI,GF,GA,xGF,xGA\n\
+                    Brad Marchand,Patrice Bergeron,David Pastrnak,400,30,10,20,10\n\
+                    Brad Marchand,Charlie Coyle,,100,2,6,4,6\n\
+                    Patrice Bergeron,David Pastrnak,,50,3,1,3,1\n";
+        let chemistry = Chemistry::parse(text).unwrap();
+        assert_eq!(chemistry.len(), 4);
+
+        let duo = chemistry.duo("david pastrnak", "Patrice Bergeron").unwrap();
+        assert_eq!((duo.toi, duo.goals_for, duo.xg_for, duo.xg_against), (450.0, 33.0, 23.0, 11.0));
+
+        let linemates = chemistry.best_linemates("Brad Marchand");
+        let names: Vec<&str> = linemates.iter().map(|linemate| &*linemate.name).collect();
+        assert_eq!(names, ["David Pastrnak", "Patrice Bergeron", "Charlie Coyle"]);
+        assert!((linemates[0].chemistry - (20.0 / 30.0 - 0.5) * 100.0 * 400.0 / 450.0).abs() < 1e-9);
+        assert!(linemates[2].chemistry < 0.0);
+    }
+
+    #[test]
+    fn test_line_column_and_goal_share_fallback() {
+        let chemistry = Chemistry::parse("Line,TOI,GF,GA\n\"Cale Makar - Devon Toews\",950,40,20\n").unwrap();
+        let duo = chemistry.duo("Devon Toews", "Cale Makar").unwrap();
+        assert!((duo.chemistry().unwrap() - (40.0 / 60.0 - 0.5) * 100.0 * 950.0 / 1000.0).abs() < 1e-9);
+        assert!(Chemistry::parse("Name,TOI\n").is_err());
+    }
+}
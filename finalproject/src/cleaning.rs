@@ -1,10 +1,137 @@
-use std::{collections::HashMap, fs::File, io::{self, BufReader, BufRead}};
+use std::{collections::{BTreeMap, HashMap}, fmt, ops::{Deref, DerefMut}, sync::{Arc, OnceLock}, fs::File, io::{self, BufReader, BufRead, Cursor, Read, Seek}, str::FromStr};
+use flate2::read::MultiGzDecoder;
+use indexmap::IndexMap;
+use rustc_hash::FxBuildHasher;
+use zip::ZipArchive;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::age::{parse_date, AGE_STAT, BIRTH_DATE_STAT};
+use crate::bio::{HEIGHT_STAT, JERSEY_STAT, PLAYER_ID_STAT, WEIGHT_STAT};
+use crate::formula::Rate;
+use crate::inference::infer_position;
+use crate::mapping::ColumnMapping;
+use crate::matrix::{player_matrices, position_matrices, raw_matrices, MetricMatrix, PositionMatrix};
+use crate::metric::{Metric, Scale, Scaling, Unit, METRICS};
+use crate::model::MetricMap;
+use crate::onice::{onice_column, Report};
+use crate::positions;
+use crate::schema::SchemaReport;
+use crate::scoring::metric_names;
+use crate::clutch::{OT_POINTS_STAT, SHOOTOUT_ATTEMPTS_STAT, SHOOTOUT_GOALS_STAT};
+use crate::experience::{parse_flag, CAREER_GP_STAT, ROOKIE_STAT, SEASONS_STAT};
+use crate::splits::{parse_split, SPLIT_STAT};
+use crate::stat_line::StatLine;
 
-#[derive(Debug)]
+// Players by name, kept in the order they were read so output is the same from run to run. The
+// names are hashed on every parsed row, so this uses the faster non-DoS-resistant Fx hasher.
+//
+// Each position's metrics are also kept as a players x metrics matrix, built by normalization (or
+// the first ranking of a cached league) and scored down its columns from then on. Anything that
+// borrows the players mutably drops the matrices, so they're never stale; they're gathered again
+// the next time they're needed.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PlayerMap {
+    players: IndexMap<Arc<str>, Player, FxBuildHasher>,
+    #[serde(skip)]
+    matrices: OnceLock<HashMap<Position, PositionMatrix>>,
+}
+
+impl PlayerMap {
+    pub fn matrices(&self) -> &HashMap<Position, PositionMatrix> {
+        self.matrices.get_or_init(|| player_matrices(self))
+    }
+
+    // Each row of the position's matrix in order, as a model sees it.
+    pub fn for_each_row(&self, position: &Position, mut f: impl FnMut(&MetricMap)) {
+        let Some(matrix) = self.matrices().get(position) else { return };
+        let names = metric_names(position);
+        let (mut normalized, mut raw) = (Vec::new(), Vec::new());
+        for (row, &index) in matrix.players.iter().enumerate() {
+            matrix.normalized.read_row(row, &mut normalized);
+            matrix.raw.read_row(row, &mut raw);
+            f(&MetricMap { names, normalized: &normalized, raw: &raw, stats: &self.players[index].stats });
+        }
+    }
+}
+
+impl Deref for PlayerMap {
+    type Target = IndexMap<Arc<str>, Player, FxBuildHasher>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.players
+    }
+}
+
+impl DerefMut for PlayerMap {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.matrices.take();
+        &mut self.players
+    }
+}
+
+impl fmt::Debug for PlayerMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.players.fmt(f)
+    }
+}
+
+impl FromIterator<(Arc<str>, Player)> for PlayerMap {
+    fn from_iter<I: IntoIterator<Item = (Arc<str>, Player)>>(iter: I) -> Self {
+        PlayerMap { players: IndexMap::from_iter(iter), matrices: OnceLock::new() }
+    }
+}
+
+impl IntoIterator for PlayerMap {
+    type Item = (Arc<str>, Player);
+    type IntoIter = indexmap::map::IntoIter<Arc<str>, Player>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.players.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a PlayerMap {
+    type Item = (&'a Arc<str>, &'a Player);
+    type IntoIter = indexmap::map::Iter<'a, Arc<str>, Player>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.players.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut PlayerMap {
+    type Item = (&'a Arc<str>, &'a mut Player);
+    type IntoIter = indexmap::map::IterMut<'a, Arc<str>, Player>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
-    pub name: String,
+    pub name: Arc<str>,
     pub positions: Vec<Position>,
     pub metrics: HashMap<Position, Vec<f64>>,
+    pub raw_metrics: HashMap<Position, Vec<f64>>,
+    #[serde(default)]
+    pub stats: StatLine,
+    #[serde(default)]
+    pub team: Option<String>,
+    #[serde(default)]
+    pub sides: Vec<Side>,
+}
+
+impl Player {
+    pub fn new(name: impl Into<Arc<str>>, positions: Vec<Position>, metrics: HashMap<Position, Vec<f64>>) -> Self {
+        let raw_metrics = metrics.clone();
+        Player { name: name.into(), positions, metrics, raw_metrics, stats: StatLine::new(), team: None, sides: Vec::new() }
+    }
+}
+
+pub fn find_player<'a>(players: &'a PlayerMap, name: &str) -> Option<&'a Player> {
+    let name = name.trim().to_lowercase();
+    players.values().find(|player| player.name.to_lowercase() == name)
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
@@ -12,136 +139,781 @@ pub enum Position {
     Center,
     Wing,
     Defense,
+    // One of the config's `[positions]`, by its place in the registry.
+    Custom(u16),
+}
+
+impl Position {
+    pub fn abbreviation(&self) -> &'static str {
+        match self {
+            Position::Center => "C",
+            Position::Wing => "W",
+            Position::Defense => "D",
+            Position::Custom(id) => positions::definition(*id).map_or("?", |def| def.id.as_str()),
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Position::Center => "Center",
+            Position::Wing => "Wing",
+            Position::Defense => "Defense",
+            Position::Custom(id) => positions::definition(*id).map_or("Unregistered position", |def| def.label.as_str()),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Position {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "c" | "center" | "centre" => Ok(Position::Center),
+            "w" | "l" | "r" | "wing" | "winger" => Ok(Position::Wing),
+            "d" | "defense" | "defence" | "defenseman" => Ok(Position::Defense),
+            other => positions::find(other).ok_or_else(|| format!("unknown position '{}'", other)),
+        }
+    }
+}
+
+// Which wing a winger is listed at. Scoring treats both as `Position::Wing`; depth charts keep them apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    pub fn abbreviation(self) -> &'static str {
+        match self {
+            Side::Left => "LW",
+            Side::Right => "RW",
+        }
+    }
+}
+
+pub fn parse_sides(positions: &str) -> Vec<Side> {
+    let mut sides = Vec::new();
+    for side in positions.split(['/', ',']).filter_map(|pos| match pos.trim() {
+        "L" | "LW" => Some(Side::Left),
+        "R" | "RW" => Some(Side::Right),
+        _ => None,
+    }) {
+        if !sides.contains(&side) {
+            sides.push(side);
+        }
+    }
+    sides
+}
+
+impl Serialize for Position {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Position {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+pub const COLUMNS: [&str; 34] = [
+    "", "Player", "Position", "GP", "TOI", "Goals", "Total Assists", "First Assists", "Second Assists",
+    "Total Points", "IPP", "Shots", "SH%", "ixG", "iCF", "iFF", "iSCF", "iHDCF", "Rush Attempts",
+    "Rebounds Created", "PIM", "Total Penalties", "Minor", "Major", "Misconduct", "Penalties Drawn",
+    "Giveaways", "Takeaways", "Hits", "Hits Taken", "Shots Blocked", "Faceoffs Won", "Faceoffs Lost", "Faceoffs %",
+];
+
+const STAT_ALIASES: [(&str, &str); 28] = [
+    ("g", "Goals"),
+    ("a", "Total Assists"),
+    ("assists", "Total Assists"),
+    ("a1", "First Assists"),
+    ("a2", "Second Assists"),
+    ("p", "Total Points"),
+    ("pts", "Total Points"),
+    ("points", "Total Points"),
+    ("s", "Shots"),
+    ("sog", "Shots"),
+    ("hit", "Hits"),
+    ("blk", "Shots Blocked"),
+    ("blocks", "Shots Blocked"),
+    ("tk", "Takeaways"),
+    ("tka", "Takeaways"),
+    ("gv", "Giveaways"),
+    ("gva", "Giveaways"),
+    ("fow", "Faceoffs Won"),
+    ("fol", "Faceoffs Lost"),
+    ("pd", "Penalties Drawn"),
+    ("gp", "GP"),
+    ("finishing", "Finishing"),
+    ("gax", "Finishing"),
+    ("height", HEIGHT_STAT),
+    ("ht", HEIGHT_STAT),
+    ("weight", WEIGHT_STAT),
+    ("wt", WEIGHT_STAT),
+    ("jersey", JERSEY_STAT),
+];
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseOptions {
+    pub delimiter: char,
+    pub extra_columns: Vec<(usize, &'static str)>,
+    pub mapping: ColumnMapping,
+    pub report: Report,
+    pub columns: Option<Vec<Option<usize>>>,
+    pub schema: SchemaReport,
+    pub roster: BTreeMap<String, String>,
+    pub team_column: Option<usize>,
+    pub duplicates: DuplicatePolicy,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions { delimiter: ',', extra_columns: Vec::new(), mapping: ColumnMapping::default(), report: Report::Individual, columns: None, schema: SchemaReport::default(), roster: BTreeMap::new(), team_column: None, duplicates: DuplicatePolicy::default() }
+    }
+}
+
+impl ParseOptions {
+    pub fn with_delimiter(delimiter: char) -> Self {
+        ParseOptions { delimiter, ..ParseOptions::default() }
+    }
+
+    // Optional columns (age, birth date, experience) are found by name wherever the export puts them.
+    pub fn with_header(&self, header: &str) -> ParseOptions {
+        let names = split_fields(header, self.delimiter);
+        let extra_columns: Vec<(usize, &str)> = names
+            .iter()
+            .enumerate()
+            .filter_map(|(i, name)| match metric_key(name).as_str() {
+                "age" => Some((i, AGE_STAT)),
+                "dateofbirth" | "birthdate" | "birthday" | "dob" => Some((i, BIRTH_DATE_STAT)),
+                "rookie" | "isrookie" => Some((i, ROOKIE_STAT)),
+                "seasons" | "nhlseasons" => Some((i, SEASONS_STAT)),
+                "careergp" | "nhlgp" | "careergames" => Some((i, CAREER_GP_STAT)),
+                "shootoutgoals" | "sogoals" => Some((i, SHOOTOUT_GOALS_STAT)),
+                "shootoutattempts" | "soattempts" | "shootoutshots" | "soshots" => Some((i, SHOOTOUT_ATTEMPTS_STAT)),
+                "otpoints" | "otpts" | "overtimepoints" => Some((i, OT_POINTS_STAT)),
+                "split" | "venue" | "homeroad" | "homeaway" => Some((i, SPLIT_STAT)),
+                "playerid" | "nhlid" => Some((i, PLAYER_ID_STAT)),
+                _ if self.report == Report::OnIce => onice_column(name).map(|column| (i, column)),
+                _ => None,
+            })
+            .collect();
+
+        let team_column = names.iter().position(|name| matches!(metric_key(name).as_str(), "team" | "teams" | "tm"));
+        let resolved = self.mapping.resolve(&names);
+        let known: Vec<usize> = extra_columns.iter().map(|&(i, _)| i).chain(team_column).collect();
+        let schema = match self.report {
+            Report::Individual => SchemaReport::check(&names, &resolved, &known),
+            Report::OnIce => SchemaReport::default(),
+        };
+
+        // Individual reports are read by position unless their header shows the expected columns have moved.
+        let by_name = self.report == Report::OnIce
+            || !self.mapping.is_default()
+            || (schema.reordered && resolved[1].is_some() && resolved[2].is_some());
+        let columns = by_name.then_some(resolved);
+        ParseOptions { extra_columns, columns, schema, team_column, mapping: self.mapping.clone(), roster: self.roster.clone(), ..*self }
+    }
+}
+
+// What to do when a row's player name is already taken: the same player exported twice, a traded
+// player split across teams, or two different players who share a name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    Sum,
+    First,
+    #[default]
+    Last,
+    Team,
+}
+
+impl fmt::Display for DuplicatePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            DuplicatePolicy::Sum => "sum",
+            DuplicatePolicy::First => "first",
+            DuplicatePolicy::Last => "last",
+            DuplicatePolicy::Team => "team",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for DuplicatePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match metric_key(s).as_str() {
+            "sum" | "merge" => Ok(DuplicatePolicy::Sum),
+            "first" | "keepfirst" => Ok(DuplicatePolicy::First),
+            "last" | "keeplast" => Ok(DuplicatePolicy::Last),
+            "team" | "disambiguate" => Ok(DuplicatePolicy::Team),
+            _ => Err(format!("invalid duplicate policy '{}', expected sum, first, last or team", s)),
+        }
+    }
+}
+
+// Why a data row didn't produce a player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SkipReason {
+    InsufficientFields,
+    MissingNameOrPosition,
+    InvalidPosition,
+}
+
+impl SkipReason {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SkipReason::InsufficientFields => "insufficient_fields",
+            SkipReason::MissingNameOrPosition => "missing_name_or_position",
+            SkipReason::InvalidPosition => "invalid_position",
+        }
+    }
+}
+
+// Adds a parsed player, applying the policy if the name is taken. Returns whether it was a duplicate.
+pub fn insert_player(players: &mut PlayerMap, mut player: Player, policy: DuplicatePolicy) -> bool {
+    let Some(index) = players.get_index_of(&*player.name) else {
+        players.insert(player.name.clone(), player);
+        return false;
+    };
+
+    match policy {
+        DuplicatePolicy::Sum => {
+            log::debug!("Duplicate player '{}': adding the rows together", player.name);
+            merge_rows(&mut players[index], player);
+        }
+        DuplicatePolicy::First => log::debug!("Duplicate player '{}': keeping the first row", player.name),
+        DuplicatePolicy::Last => {
+            log::debug!("Duplicate player '{}': keeping the last row", player.name);
+            players[index] = player;
+        }
+        DuplicatePolicy::Team => {
+            // Later rows get the team as a suffix, or a number when that is missing or still taken.
+            let base = player.team.as_ref().map_or_else(|| player.name.to_string(), |team| format!("{} ({})", player.name, team));
+            let name: Arc<str> = std::iter::once(base.clone())
+                .chain((2..).map(|n| format!("{} ({})", base, n)))
+                .find(|name| !players.contains_key(name.as_str()))
+                .map(Arc::from)
+                .unwrap_or_else(|| player.name.clone());
+            log::debug!("Duplicate player '{}': keeping this row as '{}'", player.name, name);
+            player.name = name.clone();
+            players.insert(name, player);
+        }
+    }
+    true
+}
+
+// Counts are added; percentages are averaged weighted by games played.
+fn merge_rows(existing: &mut Player, other: Player) {
+    let games = |player: &Player| player.stats.get("GP").copied().unwrap_or(0.0);
+    let (weight, other_weight) = (games(existing), games(&other));
+    let combine = |current: &mut f64, value: f64, counting: bool| {
+        if counting {
+            *current += value;
+        } else if weight + other_weight > 0.0 {
+            *current = (*current * weight + value * other_weight) / (weight + other_weight);
+        }
+    };
+
+    for (stat, &value) in &other.stats {
+        let summable = COLUMNS[3..].contains(&stat);
+        match existing.stats.get_mut(stat) {
+            Some(current) if summable => combine(current, value, is_counting_metric(stat)),
+            Some(_) => {}
+            None => {
+                existing.stats.insert(stat, value);
+            }
+        }
+    }
+
+    for (position, values) in other.raw_metrics {
+        match existing.raw_metrics.get_mut(&position) {
+            Some(current) => {
+                for ((current, value), metric) in current.iter_mut().zip(values).zip(metric_names(&position)) {
+                    combine(current, value, is_counting_metric(metric));
+                }
+            }
+            None => {
+                existing.positions.push(position.clone());
+                existing.raw_metrics.insert(position, values);
+            }
+        }
+    }
+    existing.metrics = existing.raw_metrics.clone();
+    for side in other.sides {
+        if !existing.sides.contains(&side) {
+            existing.sides.push(side);
+        }
+    }
+    existing.team = match (existing.team.take(), other.team) {
+        (Some(team), Some(other)) if team != other => Some(format!("{}/{}", team, other)),
+        (team, other) => team.or(other),
+    };
+}
+
+pub fn parse_delimiter(s: &str) -> Result<char, String> {
+    match s {
+        "tab" | "\\t" | "\t" => Ok('\t'),
+        "comma" => Ok(','),
+        "semicolon" => Ok(';'),
+        "pipe" => Ok('|'),
+        _ => {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c != '"' => Ok(c),
+                _ => Err(format!("invalid delimiter '{}', expected a single character or tab/comma/semicolon/pipe", s)),
+            }
+        }
+    }
+}
+
+pub fn split_fields(line: &str, delimiter: char) -> Vec<&str> {
+    let mut fields = Vec::new();
+    split_fields_into(line, delimiter, &mut fields);
+    fields
+}
+
+// Splits on the delimiter outside double quotes, so a quoted "C, R" position stays one field. ASCII
+// delimiters are matched byte by byte, which is safe because a quote or ASCII byte never appears
+// inside a multi-byte character.
+pub fn split_fields_into<'a>(line: &'a str, delimiter: char, fields: &mut Vec<&'a str>) {
+    let clean = |field: &'a str| field.trim().trim_matches('"');
+    fields.clear();
+    let mut start = 0;
+    let mut quoted = false;
+
+    if delimiter.is_ascii() {
+        let delimiter = delimiter as u8;
+        for (i, &byte) in line.as_bytes().iter().enumerate() {
+            if byte == b'"' {
+                quoted = !quoted;
+            } else if byte == delimiter && !quoted {
+                fields.push(clean(&line[start..i]));
+                start = i + 1;
+            }
+        }
+    } else {
+        for (i, c) in line.char_indices() {
+            if c == '"' {
+                quoted = !quoted;
+            } else if c == delimiter && !quoted {
+                fields.push(clean(&line[start..i]));
+                start = i + c.len_utf8();
+            }
+        }
+    }
+    fields.push(clean(&line[start..]));
 }
 
-pub fn process_file(file_path: &str) -> io::Result<HashMap<String, Player>> {
-    let file = File::open(file_path)?;
-    let reader = BufReader::new(file);
-    let mut players: HashMap<String, Player> = HashMap::new();
+// Empties a list of fields so its allocation can be reused for the next row, whose fields borrow a different line.
+fn recycle<'b>(mut fields: Vec<&str>) -> Vec<&'b str> {
+    fields.clear();
+    fields.into_iter().map(|_| "").collect()
+}
 
-    let mut lines = reader.lines();
-    
-    lines.next();
+fn trim_line_ending(line: &str) -> &str {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    line.strip_suffix('\r').unwrap_or(line)
+}
 
-    for line in lines {
-        let line = line?;
+// Reads data rows one at a time, reusing the line buffer and field lists between rows, so splitting
+// a row allocates nothing and only the parsed player itself is new.
+pub struct RowReader<R> {
+    reader: R,
+    options: ParseOptions,
+    header: String,
+    line: String,
+    fields: Vec<&'static str>,
+    record: Vec<&'static str>,
+}
 
-        if let Some((player_name, positions, metrics)) = clean_fields(&line) {
-            players.insert(player_name.clone(), Player { name: player_name, positions, metrics });
+impl<R: BufRead> RowReader<R> {
+    // Reads the header line to find where the columns the rows are parsed from are.
+    pub fn new(mut reader: R, options: &ParseOptions) -> io::Result<Self> {
+        let mut header = String::new();
+        let options = match reader.read_line(&mut header)? {
+            0 => options.clone(),
+            _ => options.with_header(trim_line_ending(&header)),
+        };
+        Ok(RowReader { reader, options, header, line: String::new(), fields: Vec::new(), record: Vec::new() })
+    }
+
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    pub fn header(&self) -> &str {
+        trim_line_ending(&self.header)
+    }
+
+    // The row the last call to `next_row` or `next_checked` read, as it appears in the input.
+    pub fn line(&self) -> &str {
+        trim_line_ending(&self.line)
+    }
+
+    // The next row's player, `Some(None)` for a row that was skipped, or `None` at the end of the input.
+    pub fn next_row(&mut self) -> io::Result<Option<Option<Player>>> {
+        Ok(self.next_checked()?.map(Result::ok))
+    }
+
+    // Like `next_row`, but says why a skipped row was skipped.
+    pub fn next_checked(&mut self) -> io::Result<Option<Result<Player, SkipReason>>> {
+        self.line.clear();
+        if self.reader.read_line(&mut self.line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = trim_line_ending(&self.line);
+        let mut fields = recycle(std::mem::take(&mut self.fields));
+        let mut record = recycle(std::mem::take(&mut self.record));
+        split_fields_into(line, self.options.delimiter, &mut fields);
+        let player = parse_fields(line, &fields, &mut record, &self.options);
+        self.fields = recycle(fields);
+        self.record = recycle(record);
+        Ok(Some(player))
+    }
+}
+
+pub type ParsedRow = (Arc<str>, Vec<Position>, HashMap<Position, Vec<f64>>);
+
+pub fn process_file(file_path: &str) -> io::Result<PlayerMap> {
+    process_reader(open_input(file_path)?, &ParseOptions::default())
+}
+
+pub fn open_input(file_path: &str) -> io::Result<Box<dyn BufRead>> {
+    let mut reader = BufReader::new(File::open(file_path)?);
+    let magic = reader.fill_buf()?;
+
+    if magic.starts_with(GZIP_MAGIC) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else if magic.starts_with(ZIP_MAGIC) {
+        Ok(Box::new(Cursor::new(read_zipped_csv(reader)?)))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+pub fn decompress(contents: Vec<u8>) -> io::Result<Vec<u8>> {
+    if contents.starts_with(GZIP_MAGIC) {
+        let mut decompressed = Vec::new();
+        MultiGzDecoder::new(contents.as_slice()).read_to_end(&mut decompressed)?;
+        Ok(decompressed)
+    } else if contents.starts_with(ZIP_MAGIC) {
+        read_zipped_csv(Cursor::new(contents))
+    } else {
+        Ok(contents)
+    }
+}
+
+fn read_zipped_csv<R: Read + Seek>(reader: R) -> io::Result<Vec<u8>> {
+    let mut archive = ZipArchive::new(reader).map_err(io::Error::other)?;
+    let index = (0..archive.len())
+        .find(|&i| archive.name_for_index(i).is_some_and(|name| name.to_lowercase().ends_with(".csv")))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "zip archive does not contain a .csv file"))?;
+
+    let mut contents = Vec::new();
+    archive.by_index(index).map_err(io::Error::other)?.read_to_end(&mut contents)?;
+    Ok(contents)
+}
+
+pub fn process_reader<R: BufRead>(reader: R, options: &ParseOptions) -> io::Result<PlayerMap> {
+    let mut players = PlayerMap::default();
+    let mut rows = RowReader::new(reader, options)?;
+
+    while let Some(row) = rows.next_row()? {
+        if let Some(player) = row {
+            insert_player(&mut players, player, options.duplicates);
         }
     }
 
     Ok(players)
 }
 
-pub fn clean_fields(line: &str) -> Option<(String, Vec<Position>, HashMap<Position, Vec<f64>>)> {
-    let fields: Vec<&str> = line.split(',').map(|f| f.trim().trim_matches('"')).collect();
+pub fn process_text(text: &str) -> PlayerMap {
+    // Reading from memory can't fail.
+    process_reader(text.as_bytes(), &ParseOptions::default()).unwrap_or_default()
+}
+
+pub fn parse_line(line: &str, options: &ParseOptions) -> Option<Player> {
+    let fields = split_fields(line, options.delimiter);
+    parse_fields(line, &fields, &mut Vec::new(), options).ok()
+}
+
+// `record` is scratch space for the fields rearranged into the individual report's layout.
+fn parse_fields<'a>(line: &str, fields: &[&'a str], record: &mut Vec<&'a str>, options: &'a ParseOptions) -> Result<Player, SkipReason> {
+    arrange_fields(line, fields, record, options)?;
+    let mut player = read_record(record, line)?;
+
+    for &(index, stat) in &options.extra_columns {
+        let Some(field) = fields.get(index) else { continue };
+        let value = match stat {
+            BIRTH_DATE_STAT => parse_date(field).map(|day| day as f64),
+            ROOKIE_STAT => parse_flag(field),
+            SPLIT_STAT => parse_split(field),
+            _ => parse_number(field),
+        };
+        if let Some(value) = value.filter(|v| v.is_finite()) {
+            player.stats.insert(stat, value);
+        }
+    }
+    player.team = options.team_column.and_then(|i| fields.get(i)).filter(|team| !team.is_empty()).map(|team| team.to_string());
+
+    Ok(player)
+}
+
+fn arrange_fields<'a>(line: &str, fields: &[&'a str], record: &mut Vec<&'a str>, options: &'a ParseOptions) -> Result<(), SkipReason> {
+    record.clear();
+    match &options.columns {
+        Some(columns) => {
+            let needed = columns.iter().flatten().max().map_or(0, |&i| i + 1);
+            if fields.len() < needed {
+                log::debug!("Row skipped: Insufficient fields ({}/{}) - {}", fields.len(), needed, line);
+                return Err(SkipReason::InsufficientFields);
+            }
+            record.extend(columns.iter().map(|column| column.map_or("", |i| fields[i])));
+        }
+        None => record.extend_from_slice(fields),
+    }
+
+    if let Some((code, reason)) = infer_position(record, &options.roster).filter(|_| !record[1].is_empty()) {
+        log::debug!("Inferred position {} for {} ({})", code, record[1], reason);
+        record[2] = code;
+    }
+    Ok(())
+}
+
+// A row's fields in `COLUMNS` order with any inferred position filled in, as the parser reads them.
+pub fn canonical_fields<'a>(line: &'a str, options: &'a ParseOptions) -> Result<Vec<&'a str>, SkipReason> {
+    let fields = split_fields(line, options.delimiter);
+    let mut record = Vec::with_capacity(COLUMNS.len());
+    arrange_fields(line, &fields, &mut record, options)?;
+    record.resize(COLUMNS.len(), "");
+    Ok(record)
+}
+
+pub fn parse_record(fields: &[&str], line: &str) -> Option<Player> {
+    read_record(fields, line).ok()
+}
+
+fn read_record(fields: &[&str], line: &str) -> Result<Player, SkipReason> {
+    let (player_name, positions, metrics) = check_record(fields, line)?;
+    let mut player = Player::new(player_name, positions, metrics);
+    player.stats = parse_stats(fields);
+    player.sides = parse_sides(fields[2]);
+    Ok(player)
+}
 
-    if fields.len() < 34 {
-        eprintln!("Row skipped: Insufficient fields ({}/{}) - {}", fields.len(), 34, line);
-        return None;
+// Each of the report's stat fields is parsed straight into its slot, so a row's stats are read
+// without allocating.
+pub fn parse_stats(fields: &[&str]) -> StatLine {
+    let mut stats = StatLine::new();
+    for (&metric, field) in METRICS.iter().zip(fields.iter().skip(3)) {
+        if let Some(value) = parse_number(field) {
+            stats.set_metric(metric, value);
+        }
     }
+    stats
+}
+
+pub fn metric_key(name: &str) -> String {
+    name.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase()
+}
+
+pub fn column_index(name: &str) -> Option<usize> {
+    let key = metric_key(name);
+    COLUMNS.iter().position(|column| !column.is_empty() && metric_key(column) == key)
+}
+
+pub fn resolve_stat(name: &str) -> Option<&'static str> {
+    let key = metric_key(name);
+    STAT_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == key)
+        .map(|&(_, stat)| stat)
+        .or_else(|| column_index(name).filter(|&i| i >= 3).map(|i| COLUMNS[i]))
+        .or_else(|| onice_column(name))
+}
+
+pub fn clean_fields(line: &str) -> Option<ParsedRow> {
+    clean_fields_with(line, &ParseOptions::default())
+}
 
-    let player_name = fields[1].to_string(); 
-    let position_str = fields[2]; 
+pub fn clean_fields_with(line: &str, options: &ParseOptions) -> Option<ParsedRow> {
+    let fields = split_fields(line, options.delimiter);
+    clean_record(&fields, line)
+}
+
+pub fn clean_record(fields: &[&str], line: &str) -> Option<ParsedRow> {
+    check_record(fields, line).ok()
+}
+
+pub fn check_record(fields: &[&str], line: &str) -> Result<ParsedRow, SkipReason> {
+    if fields.len() < COLUMNS.len() {
+        log::debug!("Row skipped: Insufficient fields ({}/{}) - {}", fields.len(), COLUMNS.len(), line);
+        return Err(SkipReason::InsufficientFields);
+    }
+
+    let (player_name, position_str) = (fields[1], fields[2]);
 
     if player_name.is_empty() || position_str.is_empty() {
-        eprintln!("Row skipped: Missing player name or position - {}", line);
-        return None;
+        log::debug!("Row skipped: Missing player name or position - {}", line);
+        return Err(SkipReason::MissingNameOrPosition);
     }
 
     let mut positions = Vec::new();
     let mut metrics = HashMap::new();
 
-    let mut has_left_wing = false;
-    let mut has_right_wing = false;
-
-    for pos in position_str.split('/') {
-        match pos {
-            "C" => {
-                positions.push(Position::Center);
-                metrics.insert(
-                    Position::Center,
-                    vec![ 
-                        fields[33].parse::<f64>().unwrap_or_else(|_| default_metric("Faceoffs %", &player_name)),
-                        fields[9].parse::<f64>().unwrap_or_else(|_| default_metric("Total Points", &player_name)),
-                        fields[27].parse::<f64>().unwrap_or_else(|_| default_metric("Takeaways", &player_name)),
-                        fields[7].parse::<f64>().unwrap_or_else(|_| default_metric("First Assists", &player_name)),
-                        fields[10].parse::<f64>().unwrap_or_else(|_| default_metric("IPP", &player_name))
-                    ]
-                );
-            },
-            "L" | "R" => {
-                if pos == "L" {
-                    has_left_wing = true;
-                }
-                if pos == "R" {
-                    has_right_wing = true;
-                }
+    for pos in position_str.split(['/', ',']).map(str::trim) {
+        if matches!(pos, "L" | "R") && positions.contains(&Position::Wing) {
+            continue;
+        }
 
-                positions.push(Position::Wing);
-                metrics.entry(Position::Wing).or_insert_with(Vec::new).extend(
-                    vec![ 
-                        fields[5].parse::<f64>().unwrap_or_else(|_| default_metric("Goals", &player_name)),
-                        fields[12].parse::<f64>().unwrap_or_else(|_| default_metric("SH%", &player_name)),
-                        fields[18].parse::<f64>().unwrap_or_else(|_| default_metric("Rush Attempts", &player_name)),
-                        fields[9].parse::<f64>().unwrap_or_else(|_| default_metric("Total Points", &player_name)),
-                        fields[28].parse::<f64>().unwrap_or_else(|_| default_metric("Hits", &player_name))
-                    ]
-                );
-            },
-            "D" => {
-                positions.push(Position::Defense);
-                metrics.insert(
-                    Position::Defense,
-                    vec![ 
-                        fields[28].parse::<f64>().unwrap_or_else(|_| default_metric("Hits", &player_name)),
-                        fields[30].parse::<f64>().unwrap_or_else(|_| default_metric("Shots Blocked", &player_name)),
-                        fields[27].parse::<f64>().unwrap_or_else(|_| default_metric("Takeaways", &player_name)),
-                        fields[9].parse::<f64>().unwrap_or_else(|_| default_metric("Total Points", &player_name)),
-                        fields[18].parse::<f64>().unwrap_or_else(|_| default_metric("Rush Attempts", &player_name))
-                    ]
-                );
+        let position = match pos {
+            "C" => Position::Center,
+            "L" | "R" => Position::Wing,
+            "D" => Position::Defense,
+            other => match positions::find(other) {
+                Some(position) => position,
+                None => {
+                    log::debug!("Row skipped: Invalid position '{}' for player '{}'", pos, player_name);
+                    return Err(SkipReason::InvalidPosition);
+                }
             },
-            _ => {
-                eprintln!("Row skipped: Invalid position '{}' for player '{}'", pos, player_name);
-                return None;
-            }
-        }
+        };
+        let values: Vec<f64> = Metric::for_position(&position)
+            .iter()
+            .map(|metric| parse_metric(fields[metric.column()], metric.name(), player_name))
+            .collect();
+        positions.push(position.clone());
+        metrics.insert(position, values);
     }
 
-    Some((player_name, positions, metrics))
+    Ok((Arc::from(player_name), positions, metrics))
+}
+
+fn parse_metric(field: &str, metric_name: &str, player_name: &str) -> f64 {
+    parse_number(field).unwrap_or_else(|| default_metric(metric_name, player_name))
 }
 
-pub fn default_metric(metric_name: &str, player_name: &str) -> f64 {
+// Exports that aren't comma-separated often use a decimal comma (e.g. "12,5").
+pub fn parse_number(field: &str) -> Option<f64> {
+    let parsed = if field.contains(',') { field.replace(',', ".").parse::<f64>() } else { field.parse::<f64>() };
+    parsed.ok().filter(|value| value.is_finite())
+}
+
+pub fn default_metric(_metric_name: &str, _player_name: &str) -> f64 {
     0.0 
 }
 
-pub fn normalize_metrics(players: &mut HashMap<String, Player>) {
-    let mut max_metrics: HashMap<Position, Vec<f64>> = HashMap::new();
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PositionStats {
+    pub max: Vec<f64>,
+    pub mean: Vec<f64>,
+}
+
+pub fn position_stats<'a, I>(metric_vectors: I) -> HashMap<Position, PositionStats>
+where
+    I: Iterator<Item = (&'a Position, &'a Vec<f64>)>,
+{
+    position_matrices(metric_vectors).iter().map(|(position, matrix)| (position.clone(), matrix.stats())).collect()
+}
+
+pub fn raw_position_stats(players: &PlayerMap) -> HashMap<Position, PositionStats> {
+    players.matrices().iter().map(|(position, matrix)| (position.clone(), matrix.raw.stats())).collect()
+}
 
-    for player in players.values() {
-        for (position, metrics) in &player.metrics {
-            max_metrics.entry(position.clone()).or_insert_with(|| vec![0.0; metrics.len()]);
-            for (i, &metric) in metrics.iter().enumerate() {
-                if metric.is_finite() && metric > max_metrics[&position][i] {
-                    max_metrics.get_mut(&position).unwrap()[i] = metric;
+pub fn is_counting_metric(metric: &str) -> bool {
+    Metric::from_name(metric).map_or(!metric.contains('%'), |metric| metric.unit() == Unit::Count)
+}
+
+// Prorates the counting metrics from the raw values and normalizes again, so it can run on cached players.
+// Players without the stat the rate divides by are left out rather than ranked on their totals.
+pub fn apply_rates(players: &mut PlayerMap, rate: Rate, scaling: &Scaling) -> HashMap<Position, PositionStats> {
+    if let Some((stat, _)) = rate.per() {
+        let before = players.len();
+        players.retain(|_, player| rate.divisor(&player.stats).is_some());
+        if players.len() < before {
+            log::warn!("Left out {} players without {} for {} rates", before - players.len(), stat, rate);
+        }
+    }
+
+    for player in players.values_mut() {
+        let Some(divisor) = rate.divisor(&player.stats) else { continue };
+        for (position, raw) in &mut player.raw_metrics {
+            for (value, metric) in raw.iter_mut().zip(metric_names(position)) {
+                if is_counting_metric(metric) {
+                    *value /= divisor;
                 }
             }
         }
+        player.metrics = player.raw_metrics.clone();
     }
 
-    for player in players.values_mut() {
+    normalize_metrics_with(players, scaling)
+}
+
+pub fn normalize_metrics(players: &mut PlayerMap) -> HashMap<Position, PositionStats> {
+    normalize_metrics_with(players, &Scaling::default())
+}
+
+// Each metric is rescaled with its configured strategy, or its default: counts by the league leader,
+// and percentages by their 0-100 scale so one player going 3-for-3 on faceoffs doesn't set the bar
+// for everyone else. Lower-is-better metrics are then flipped, so a higher value always scores better.
+//
+// Normalization always starts over from the raw metrics, so running it twice changes nothing. The
+// scaling is done on each position's matrix a column at a time, and the matrices are kept on the
+// players for scoring once the normalized rows are copied back.
+pub fn normalize_metrics_with(players: &mut PlayerMap, scaling: &Scaling) -> HashMap<Position, PositionStats> {
+    let mut matrices = raw_matrices(players);
+    let mut stats = HashMap::new();
+    for (position, matrix) in &mut matrices {
+        stats.insert(position.clone(), matrix.normalized.stats());
+        for (i, scale) in matrix_scales(position, &matrix.normalized, scaling).into_iter().enumerate() {
+            matrix.normalized.column_mut(i).iter_mut().for_each(|value| *value = scale.apply(*value));
+        }
+    }
+
+    // The matrices' rows are in map order, so a running row count per position finds each one.
+    let mut rows: Vec<(&Position, &PositionMatrix, usize)> = matrices.iter().map(|(position, matrix)| (position, matrix, 0)).collect();
+    for player in players.players.values_mut() {
         for (position, metrics) in &mut player.metrics {
-            if let Some(max_vals) = max_metrics.get(position) {
-                for (i, metric) in metrics.iter_mut().enumerate() {
-                    if max_vals[i] > 0.0 && metric.is_finite() {
-                        *metric /= max_vals[i];
-                    } else {
-                        *metric = 0.0; 
-                    }
-                }
+            if let Some((_, matrix, row)) = rows.iter_mut().find(|(grouped, _, _)| *grouped == position) {
+                matrix.normalized.write_row(*row, metrics);
+                *row += 1;
             }
         }
     }
+    players.matrices = OnceLock::from(matrices);
+
+    stats
+}
+
+// Fits each of the position's metrics to the league's unnormalized values for it.
+pub fn position_scales<'a>(position: &Position, values: impl Iterator<Item = &'a Vec<f64>>, scaling: &Scaling) -> Vec<Scale> {
+    matrix_scales(position, &MetricMatrix::from_rows(metric_names(position).len(), values), scaling)
+}
+
+pub fn matrix_scales(position: &Position, matrix: &MetricMatrix, scaling: &Scaling) -> Vec<Scale> {
+    Metric::for_position(position)
+        .iter()
+        .enumerate()
+        .map(|(i, &metric)| scaling.fit(metric, matrix.column(i)))
+        .collect()
+}
+
+pub fn apply_scales(metrics: &mut [f64], scales: &[Scale]) {
+    for (value, scale) in metrics.iter_mut().zip(scales) {
+        *value = scale.apply(*value);
+    }
 }
@@ -0,0 +1,612 @@
+use std::{io, str::FromStr};
+use clap::{Args, Parser, Subcommand};
+use finalproject::alerts::Rule;
+use finalproject::age::{parse_age_range, today};
+use finalproject::audit::DEFAULT_Z_THRESHOLD;
+use finalproject::cleaning::{parse_delimiter, DuplicatePolicy, Position};
+use finalproject::scoring::Transform;
+use finalproject::onice::Report;
+use finalproject::model::Adjustment;
+use finalproject::formula::Rate;
+use finalproject::metric::Normalization;
+use finalproject::fantasy::{parse_point_values, PointValue, DEFAULT_POINT_VALUES};
+use finalproject::similarity::DEFAULT_NEIGHBORS;
+use finalproject::positions;
+use crate::lookup::{OutputFormat, SIMILAR_PLAYERS};
+
+// Players `rank` prints per position when neither `--top` nor the config's `top` says otherwise.
+pub const DEFAULT_TOP: usize = 10;
+
+#[derive(Parser)]
+#[command(about = "Rates NHL skaters out of 100 based on their position")]
+pub struct Cli {
+    /// CSV file to rate, or `-` to read it from stdin; repeat it or quote a glob like `data/*.csv` to merge several files
+    #[arg(short, long, global = true, env = "NHL_SCORES_INPUT", default_value = "NHL.csv")]
+    pub input: Vec<String>,
+
+    /// Field delimiter of the input file, e.g. `;` or `tab`
+    #[arg(short, long, global = true, default_value = ",", value_parser = parse_delimiter)]
+    pub delimiter: char,
+
+    /// Write parsed players, metrics and scores to a SQLite database
+    #[arg(long, global = true, value_name = "PATH")]
+    pub save_db: Option<String>,
+
+    /// Load players from a SQLite database instead of parsing the CSV
+    #[arg(long, global = true, value_name = "PATH", conflicts_with = "input")]
+    pub from_db: Option<String>,
+
+    /// Re-parse the CSV even if a cached copy of the dataset exists
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+
+    /// Scoring model: sigmoid, linear, percentile, ordinal, fantasy, possession, or formula (from the config)
+    #[arg(long, global = true, env = "NHL_SCORES_MODEL", value_name = "NAME")]
+    pub model: Option<String>,
+
+    /// CSV of `name,cap_hit` pairs to join onto the players for value rankings
+    #[arg(long, global = true, value_name = "FILE")]
+    pub salaries: Option<String>,
+
+    /// File of rookie names (one per line); everyone else is treated as a veteran
+    #[arg(long, global = true, value_name = "FILE")]
+    pub rookies: Option<String>,
+
+    /// CSV of `name,position,games` rows; overall ratings weight each position by its share of the games
+    #[arg(long, global = true, value_name = "FILE")]
+    pub usage: Option<String>,
+
+    /// Layout of the input: `individual` (default) or Natural Stat Trick's `onice` report
+    #[arg(long, global = true, default_value = "individual", value_parser = Report::from_str)]
+    pub report: Report,
+
+    /// Column mapping profile from the config's [profiles] tables
+    #[arg(long, global = true, env = "NHL_SCORES_PROFILE", value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Power-play export to merge per player for power-play scores
+    #[arg(long, global = true, value_name = "FILE")]
+    pub pp: Option<String>,
+
+    /// Penalty-kill export to merge per player for penalty-kill scores
+    #[arg(long, global = true, value_name = "FILE")]
+    pub pk: Option<String>,
+
+    /// 5v5 export of the same season, scored alongside the all-situations input and shown next to it in lookups
+    #[arg(long, global = true, value_name = "FILE")]
+    pub ev: Option<String>,
+
+    /// Roster CSV with a Player column and any of ID, Jersey, Height, Weight and Birth Date, joined onto the players
+    #[arg(long, global = true, value_name = "FILE")]
+    pub bio: Option<String>,
+
+    /// CSV of `name,position` pairs used for rows with a blank or generic `F` position
+    #[arg(long, global = true, value_name = "FILE")]
+    pub roster: Option<String>,
+
+    /// Line-combination export (player columns plus TOI, GF, GA, xGF, xGA) for linemate chemistry in lookups
+    #[arg(long, global = true, value_name = "FILE")]
+    pub lines: Option<String>,
+
+    /// Prorate counting metrics before normalizing: total, pergame, or per60
+    #[arg(long, global = true, env = "NHL_SCORES_RATES", default_value = "total", value_parser = Rate::from_str)]
+    pub rates: Rate,
+
+    /// Resolve rows that share a player name: sum, first, last, or team (suffix later rows with their team)
+    #[arg(long, global = true, default_value = "last", value_parser = DuplicatePolicy::from_str)]
+    pub duplicates: DuplicatePolicy,
+
+    /// Earlier season's export to build each player's SH% and IPP baseline from, to flag regression candidates (repeatable)
+    #[arg(long = "baseline", global = true, value_name = "FILE")]
+    pub baselines: Vec<String>,
+
+    /// Leave out players with fewer games played before normalizing (default: the config's `min_gp`)
+    #[arg(long, global = true, env = "NHL_SCORES_MIN_GP", value_name = "GAMES")]
+    pub min_gp: Option<f64>,
+
+    /// Normalize every metric with one strategy: max, min-max, z-score, percent, or none (default: the config's [normalization] table)
+    #[arg(long, global = true, env = "NHL_SCORES_NORMALIZATION", value_name = "STRATEGY", value_parser = Normalization::from_str)]
+    pub normalization: Option<Normalization>,
+
+    /// Map the weighted sum to 0-100 for the sigmoid model: linear, logistic, tanh, or percentile
+    #[arg(long, global = true, default_value = "logistic", value_parser = Transform::from_str)]
+    pub transform: Transform,
+
+    /// Express scores as value over a replacement-level player at the position
+    #[arg(long, global = true)]
+    pub vor: bool,
+
+    /// Boost or discount scores by age using the config's age curve (peak 24-27 by default)
+    #[arg(long, global = true)]
+    pub age_adjust: bool,
+
+    /// Score adjustments to apply: pdo, to shrink players riding unsustainable on-ice percentages
+    #[arg(long, global = true, value_delimiter = ',', value_parser = Adjustment::from_str)]
+    pub adjust: Vec<Adjustment>,
+
+    /// Derive each position's weights from the first principal component of its metrics and print them
+    #[arg(long, global = true)]
+    pub pca_weights: bool,
+
+    /// Bonus added to the overall rating of players eligible at several positions (default: from the config, else 0)
+    #[arg(long, global = true, value_name = "POINTS")]
+    pub versatility_bonus: Option<f64>,
+
+    /// Exit with an error when more than this percentage of rows are skipped
+    #[arg(long, global = true, value_name = "PCT")]
+    pub max_skip_pct: Option<f64>,
+
+    /// Locale for decimal separators, e.g. `fr-CA` for `87,50` (default: the config's [locale] table, else `.`)
+    #[arg(long, global = true, env = "NHL_SCORES_LOCALE", value_name = "NAME")]
+    pub locale: Option<String>,
+
+    /// Append every player's scores to this CSV file on each run, for the `history` command
+    #[arg(long, global = true, env = "NHL_SCORES_HISTORY", value_name = "FILE")]
+    pub history: Option<String>,
+
+    /// Append machine-readable events (rows skipped and why, normalization stats, timing) to this JSON-lines file
+    #[arg(long, global = true, value_name = "FILE")]
+    pub log_json: Option<String>,
+
+    /// Write the rows that were read to this CSV file, in the standard column order with the team last
+    #[arg(long, global = true, value_name = "FILE")]
+    pub emit_cleaned: Option<String>,
+
+    /// Write the rows that were skipped to this file as they appeared, with a reason column added
+    #[arg(long, global = true, value_name = "FILE")]
+    pub emit_rejected: Option<String>,
+
+    /// Only print warnings and errors, not the row counts and other progress messages
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Also print every skipped row and inferred position
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    /// TOML config file with custom scoring formulas (default: nhl_scores.toml if present)
+    #[arg(long, global = true, env = "NHL_SCORES_CONFIG", value_name = "PATH")]
+    pub config: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Print the top players at each position
+    Rank(RankArgs),
+    /// Print one leaderboard across all positions, averaging multi-position players' scores
+    Overall {
+        /// Number of players to print
+        #[arg(long, default_value_t = 25)]
+        top: usize,
+
+        /// Rank by standard deviations above each position's mean instead of raw score
+        #[arg(long)]
+        scarcity: bool,
+    },
+    /// Look up players by name interactively
+    Lookup {
+        /// Look up this player and exit instead of prompting
+        #[arg(conflicts_with = "batch")]
+        name: Option<String>,
+
+        /// Only show the breakdown and score at this position
+        #[arg(long)]
+        position: Option<String>,
+
+        /// Look up every name in this file (one per line) instead of prompting
+        #[arg(long, value_name = "FILE")]
+        batch: Option<String>,
+
+        /// Output format for --batch
+        #[arg(long, value_enum, default_value_t = OutputFormat::Csv, requires = "batch")]
+        format: OutputFormat,
+    },
+    /// Print the league leaders in a single stat across all positions
+    Leaders {
+        /// Stat to rank by, e.g. `hits` or `shots blocked`
+        #[arg(long)]
+        metric: String,
+
+        /// Number of players to print
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Print the mean, median and standard deviation of every metric and score by position
+    Averages {
+        /// Comma-separated positions to summarize (default: all)
+        #[arg(long, value_delimiter = ',')]
+        positions: Vec<String>,
+    },
+    /// Count the players at each position whose score falls in each tenth of the 0-100 scale
+    Distribution {
+        /// Comma-separated positions to summarize (default: all)
+        #[arg(long, value_delimiter = ',')]
+        positions: Vec<String>,
+    },
+    /// Project next-season scores by regressing metrics to the position mean and aging each player a year
+    Project {
+        /// Number of players to print per position
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+
+        /// Previous season's export to blend in, weighted 2/3 as much as the current one
+        #[arg(long, value_name = "FILE")]
+        previous: Option<String>,
+
+        /// Write every projection to stdout in this format instead of printing the top players
+        #[arg(long, value_enum)]
+        format: Option<OutputFormat>,
+    },
+    /// Rank players by fantasy points using custom point values per category
+    Fantasy {
+        /// Point value per category, e.g. `G=3,A=2,HIT=0.2,BLK=0.3`
+        #[arg(long, default_value = DEFAULT_POINT_VALUES, value_parser = parse_point_values)]
+        points: std::vec::Vec<PointValue>,
+
+        /// Number of players to print
+        #[arg(long, default_value_t = 25)]
+        top: usize,
+
+        /// Games in the season used for projections
+        #[arg(long, default_value_t = 82.0)]
+        games: f64,
+
+        /// Show the per-category breakdown and projection for a single player
+        #[arg(long)]
+        player: Option<String>,
+    },
+    /// Fit the per-position metric weights against a stat column or an external ratings file
+    Calibrate {
+        /// Stat column to fit against, e.g. `total points`
+        #[arg(long, default_value = "Total Points", conflicts_with = "ratings")]
+        target: String,
+
+        /// CSV of `name,rating` pairs (e.g. GAR or WAR) to fit against instead of a stat column
+        #[arg(long, value_name = "FILE")]
+        ratings: Option<String>,
+
+        /// Comma-separated positions to calibrate (default: all)
+        #[arg(long, value_delimiter = ',')]
+        positions: Vec<String>,
+    },
+    /// Report how much rankings move when each positional weight changes
+    Sensitivity {
+        /// Relative weight change to apply in each direction, in percent
+        #[arg(long, default_value_t = 20.0)]
+        delta: f64,
+
+        /// Number of most sensitive players to print per position
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+
+        /// Comma-separated positions to analyse (default: all)
+        #[arg(long, value_delimiter = ',')]
+        positions: Vec<String>,
+    },
+    /// Compare scores with an external ratings file using Spearman rank correlation
+    Validate {
+        /// CSV of `name,rating` pairs, e.g. GAR or WAR values
+        #[arg(long, value_name = "FILE")]
+        ratings: String,
+
+        /// Number of largest disagreements to print per position
+        #[arg(long, default_value_t = 5)]
+        top: usize,
+    },
+    /// Rank players by score per million dollars of cap hit (needs --salaries)
+    Value {
+        /// Number of players to print per position
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+
+        /// Comma-separated positions to print (default: all)
+        #[arg(long, value_delimiter = ',')]
+        positions: Vec<String>,
+    },
+    /// Print the Pearson correlation matrix between metrics at each position
+    Correlations {
+        /// Comma-separated stats to correlate (default: each position's five metrics)
+        #[arg(long, value_delimiter = ',')]
+        metrics: Vec<String>,
+
+        /// Comma-separated positions to print (default: all)
+        #[arg(long, value_delimiter = ',')]
+        positions: Vec<String>,
+    },
+    /// Flag implausible raw metric values before they distort normalization
+    Audit {
+        /// Flag values more than this many standard deviations from the position mean
+        #[arg(long, default_value_t = DEFAULT_Z_THRESHOLD)]
+        z: f64,
+    },
+    /// Rank forwards by power-play score (needs --pp)
+    Pp {
+        /// Number of players to print
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Rank defensemen by penalty-kill score (needs --pk)
+    Pk {
+        /// Number of players to print
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Compare two snapshots of the export: score changes, new entrants and dropped players
+    Diff {
+        /// The earlier export
+        old: String,
+
+        /// The later export (replaces --input)
+        new: String,
+
+        /// Number of biggest score changes to print
+        #[arg(long, default_value_t = 25)]
+        top: usize,
+    },
+    /// Score home and road exports separately and list the players with the biggest home/road gaps
+    Splits {
+        /// The home-games export (replaces --input)
+        home: String,
+
+        /// The road-games export
+        road: String,
+
+        /// Number of biggest gaps to print
+        #[arg(long, default_value_t = 25)]
+        top: usize,
+    },
+    /// Write each position's full leaderboard to its own CSV (centers.csv, wings.csv, defense.csv)
+    Export {
+        /// Directory to write the files into
+        #[arg(long, default_value = ".")]
+        dir: String,
+
+        /// Instead write one CSV of every player and position with raw and normalized features and the score, for training other models
+        #[arg(long, value_name = "FILE")]
+        dataset: Option<String>,
+
+        /// Only export players matching this expression, e.g. "team == 'TOR' && gp >= 20" (see `alerts`)
+        #[arg(long, value_name = "EXPR", value_parser = Rule::parse)]
+        filter: Option<Rule>,
+    },
+    /// Arrange a team's players into four forward lines and three defense pairs by score
+    Depthchart {
+        /// Team abbreviation, as in the input's Team column
+        team: String,
+    },
+    /// Compare two teams' lineups position by position
+    CompareTeams {
+        /// First team's abbreviation
+        first: String,
+        /// Second team's abbreviation
+        second: String,
+    },
+    /// Build the assist network from a goal event file and print who sets up whom
+    Assists {
+        /// CSV with one goal per row: a Scorer column and Assist 1 / Assist 2 columns
+        events: String,
+
+        /// Print this player's most frequent finishers and setup men instead of the top duos
+        #[arg(long)]
+        player: Option<String>,
+
+        /// Number of duos or connections to print
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Find the shortest chain of teammates connecting two players across seasons
+    Path {
+        /// Player to start from
+        from: String,
+        /// Player to reach
+        to: String,
+
+        /// Another season's export with a Team column (repeatable); the main input is included too
+        #[arg(long = "season", value_name = "FILE")]
+        seasons: Vec<String>,
+    },
+    /// List the players most similar to one player at each of their positions
+    Similar {
+        /// Player to compare against
+        name: String,
+
+        /// Number of similar players to print per position
+        #[arg(long, default_value_t = SIMILAR_PLAYERS)]
+        top: usize,
+    },
+    /// Group similar players into communities by label propagation over a nearest-neighbor graph
+    Communities {
+        /// Comma-separated positions to group (default: all)
+        #[arg(long, value_delimiter = ',')]
+        positions: Vec<String>,
+
+        /// Closest peers each player is linked to in the similarity graph
+        #[arg(long, default_value_t = DEFAULT_NEIGHBORS)]
+        neighbors: usize,
+
+        /// Representative members to print per community
+        #[arg(long, default_value_t = 3)]
+        members: usize,
+    },
+    /// Serve Rank, Lookup and Compare over gRPC (see proto/nhl_scores.proto)
+    #[cfg(feature = "grpc")]
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = finalproject::grpc::DEFAULT_ADDR)]
+        addr: std::net::SocketAddr,
+    },
+    /// Print a fantasy draft board across positions, adjusted for positional scarcity, and mark picks as they happen
+    Draftboard {
+        /// Number of available players to print after each pick
+        #[arg(long, default_value_t = 25)]
+        top: usize,
+
+        /// Teams in the league (default: from the config's [draft] table, else 12)
+        #[arg(long)]
+        teams: Option<usize>,
+
+        /// File of players already drafted (one name per line) to take off the board first
+        #[arg(long, value_name = "FILE")]
+        taken: Option<String>,
+
+        /// Print the board and exit instead of prompting for picks
+        #[arg(long)]
+        no_prompt: bool,
+    },
+    /// Rank keeper-league value: projected fantasy points over the next few seasons, discounted
+    Keepers {
+        /// Number of players to print
+        #[arg(long, default_value_t = 25)]
+        top: usize,
+
+        /// Seasons to value each player over (default: from the config's [keeper] table, else 3)
+        #[arg(long)]
+        seasons: Option<usize>,
+
+        /// Discount for each later season, e.g. 0.15 (default: from the config, else 0.15)
+        #[arg(long)]
+        discount: Option<f64>,
+
+        /// Point value per category, e.g. `G=3,A=2` (default: from the config, else the fantasy defaults)
+        #[arg(long)]
+        points: Option<String>,
+
+        /// Also write the full keeper ranking to this CSV file
+        #[arg(long, value_name = "FILE")]
+        out: Option<String>,
+    },
+    /// List buy-low and sell-high candidates whose SH% or IPP is far from their --baseline seasons
+    Regression {
+        /// Number of players to print
+        #[arg(long, default_value_t = 25)]
+        top: usize,
+    },
+    /// Rank shootout and overtime performers from the input's shootout goals/attempts and OT points columns
+    Clutch {
+        /// Number of players to print
+        #[arg(long, default_value_t = 25)]
+        top: usize,
+    },
+    /// Print players matching threshold rules, e.g. "defense score > 85 AND gp < 40"
+    Alerts {
+        /// Rules comparing `[position] score`, `[position] percentile` or stat formulas, joined by AND/OR (default: the config's [alerts] table)
+        rules: Vec<String>,
+    },
+    /// Print a player's scores from each run recorded in the --history file
+    History {
+        /// Player name, as recorded
+        player: String,
+    },
+    /// Show or clear the cache of parsed datasets and computed scores
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Explore players and leaderboards in a full-screen dashboard
+    Tui,
+}
+
+#[derive(Subcommand)]
+pub enum CacheAction {
+    /// Count the cached datasets and scores and the space they take
+    Status,
+    /// Delete every cached dataset and score
+    Clear,
+}
+
+#[derive(Args)]
+pub struct RankArgs {
+    /// Number of players to print per position (default: the config's `top`, else 10)
+    #[arg(long, env = "NHL_SCORES_TOP", value_name = "N")]
+    pub top: Option<usize>,
+
+    /// Comma-separated positions to print, e.g. `defense,center` (default: all)
+    #[arg(long, value_delimiter = ',')]
+    pub positions: Vec<String>,
+
+    /// Order by a single metric (e.g. `takeaways`) instead of the overall score
+    #[arg(long, value_name = "METRIC")]
+    pub by: Option<String>,
+
+    /// With --by, order by the raw stat instead of the normalized value
+    #[arg(long, requires = "by")]
+    pub raw: bool,
+
+    /// Only rank players in this age range, e.g. `18-23` (needs an Age or birth date column)
+    #[arg(long, value_name = "MIN-MAX", value_parser = parse_age_range)]
+    pub age_range: Option<(f64, f64)>,
+
+    /// Only rank players in their first N seasons, e.g. `1` for a Calder race
+    #[arg(long, value_name = "N")]
+    pub max_seasons: Option<f64>,
+
+    /// Only rank players this old or younger (needs an Age or birth date column, or --bio)
+    #[arg(long, value_name = "YEARS")]
+    pub max_age: Option<f64>,
+
+    /// Only rank players at least this tall, in inches (needs --bio)
+    #[arg(long, value_name = "INCHES")]
+    pub min_height: Option<f64>,
+
+    /// Only rank players at least this heavy, in pounds (needs --bio)
+    #[arg(long, value_name = "POUNDS")]
+    pub min_weight: Option<f64>,
+
+    /// Only print players matching this expression, e.g. "team == 'TOR' && gp >= 20 && hits > 100" (see `alerts`)
+    #[arg(long, value_name = "EXPR", value_parser = Rule::parse)]
+    pub filter: Option<Rule>,
+
+    /// Re-rank whenever the input file changes
+    #[arg(long)]
+    pub watch: bool,
+
+    #[arg(skip)]
+    pub reference_day: i64,
+
+    // `positions` resolved once the config's `[positions]` are registered.
+    #[arg(skip)]
+    pub selected: Vec<Position>,
+}
+
+impl Default for RankArgs {
+    fn default() -> Self {
+        RankArgs {
+            top: None,
+            positions: Vec::new(),
+            by: None,
+            raw: false,
+            age_range: None,
+            max_seasons: None,
+            max_age: None,
+            min_height: None,
+            min_weight: None,
+            filter: None,
+            watch: false,
+            reference_day: today(),
+            selected: Vec::new(),
+        }
+    }
+}
+
+impl RankArgs {
+    pub fn top(&self) -> usize {
+        self.top.unwrap_or(DEFAULT_TOP)
+    }
+
+    pub fn selected_positions(&self) -> &[Position] {
+        if self.selected.is_empty() {
+            positions::all()
+        } else {
+            &self.selected
+        }
+    }
+}
+
+// Position arguments are kept as names by the command line parser, since positions from the
+// config's `[positions]` table can't be recognized until the config is loaded.
+pub fn parse_positions(names: &[String]) -> io::Result<Vec<Position>> {
+    names.iter().map(|name| name.parse().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))).collect()
+}
@@ -0,0 +1,75 @@
+use std::{collections::HashMap, fs, io, path::Path};
+use finalproject::cache;
+use finalproject::cleaning::{PlayerMap, Position};
+use finalproject::scoring::{rank_players_with, ScoredPlayer};
+use finalproject::model::{Adjustment, ScoringModel};
+use finalproject::positions;
+use crate::cli::args::{CacheAction, Cli};
+use crate::cli::config::config_file;
+
+pub fn run_cache(action: &CacheAction) -> io::Result<()> {
+    let cache_dir = Path::new(cache::DEFAULT_CACHE_DIR);
+    match action {
+        CacheAction::Status => {
+            let status = cache::status(cache_dir)?;
+            println!("Cache: {}", cache_dir.display());
+            println!("Datasets: {}", status.datasets);
+            println!("Scores: {}", status.scores);
+            if status.stale > 0 {
+                println!("Stale entries from older versions: {}", status.stale);
+            }
+            println!("Size: {:.1} KB", status.bytes as f64 / 1024.0);
+        }
+        CacheAction::Clear => println!("Removed {} cached entries from {}", cache::clear(cache_dir)?, cache_dir.display()),
+    }
+    Ok(())
+}
+
+// Everything the rankings depend on: the input and every file joined onto it, the config and the
+// positions it registered, and the scoring options. Stdin and database input aren't cached.
+pub fn score_cache_key(cli: &Cli, inputs: &[String], adjustments: &[Adjustment], reference_day: i64) -> io::Result<Option<u64>> {
+    if cli.from_db.is_some() || inputs.iter().any(|input| input == "-") {
+        return Ok(None);
+    }
+    let mut hash = cache::hash_bytes(&[]);
+    let files = inputs.iter().chain([&cli.salaries, &cli.rookies, &cli.usage, &cli.pp, &cli.pk, &cli.roster, &cli.bio, &cli.lines].into_iter().flatten()).chain(&cli.baselines);
+    for path in files.chain(config_file(cli.config.as_deref()).map(String::from).as_ref()) {
+        hash = cache::extend_hash(hash, path.as_bytes());
+        hash = cache::extend_hash(hash, &fs::read(path)?);
+    }
+    let settings = format!(
+        "{}{:?}{:?}{:?}{:?}{:?}{:?}{}{:?}{}{:?}{:?}{:?}{}{:?}",
+        cli.delimiter,
+        cli.report,
+        cli.profile,
+        cli.duplicates,
+        cli.rates,
+        cli.min_gp,
+        cli.normalization,
+        cli.model.as_deref().unwrap_or_default(),
+        cli.transform,
+        cli.vor,
+        adjustments,
+        cli.pca_weights,
+        cli.versatility_bonus,
+        reference_day,
+        positions::registered()
+    );
+    Ok(Some(cache::extend_hash(hash, settings.as_bytes())))
+}
+
+// The position rankings from the score cache when everything they depend on is unchanged.
+pub fn cached_rankings(players: &PlayerMap, model: &dyn ScoringModel, key: Option<u64>) -> HashMap<Position, Vec<ScoredPlayer>> {
+    let cache_dir = Path::new(cache::DEFAULT_CACHE_DIR);
+    if let Some(rankings) = key.and_then(|key| cache::load_scores(cache_dir, key)) {
+        log::info!("Loaded scores from cache");
+        return rankings;
+    }
+    let rankings = rank_players_with(players, model);
+    if let Some(key) = key {
+        if let Err(e) = cache::store_scores(cache_dir, key, &rankings) {
+            log::warn!("Could not write score cache: {}", e);
+        }
+    }
+    rankings
+}
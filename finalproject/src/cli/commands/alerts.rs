@@ -0,0 +1,37 @@
+use std::io;
+use finalproject::alerts::{matching_players, Rule, ScoreIndex};
+use finalproject::cleaning::{PlayerMap, Position};
+use finalproject::model::ScoringModel;
+use finalproject::locale::Locale;
+use crate::lookup::format_value;
+
+pub fn print_alerts(players: &PlayerMap, model: &dyn ScoringModel, bonus: f64, rules: &[(Option<&str>, &str)], locale: &Locale) -> io::Result<()> {
+    if rules.is_empty() {
+        println!("No alert rules: pass one, e.g. \"defense score > 85 AND gp < 40\", or add an [alerts] table to the config");
+        return Ok(());
+    }
+    let parsed: Vec<(Option<&str>, Rule)> = rules
+        .iter()
+        .map(|&(name, source)| {
+            let rule = Rule::parse(source).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, name.map_or(e.clone(), |name| format!("alerts.{}: {}", name, e))))?;
+            Ok((name, rule))
+        })
+        .collect::<io::Result<_>>()?;
+
+    let scores = ScoreIndex::new(players, model, bonus);
+    for (name, rule) in &parsed {
+        let matched = matching_players(players, rule, &scores);
+        let title = name.map_or_else(|| rule.to_string(), |name| format!("{} ({})", name, rule));
+        println!("\nAlert: {}: {} player{}", title, matched.len(), if matched.len() == 1 { "" } else { "s" });
+        for player in matched {
+            let positions: Vec<&str> = player.positions.iter().map(Position::abbreviation).collect();
+            let values: Vec<String> = rule
+                .terms()
+                .iter()
+                .filter_map(|(source, operand)| Some(format!("{} {}", source, format_value(scores.value(player, operand)?, locale))))
+                .collect();
+            println!("{} ({}): {}", player.name, positions.join("/"), values.join(", "));
+        }
+    }
+    Ok(())
+}
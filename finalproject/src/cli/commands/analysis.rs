@@ -0,0 +1,255 @@
+use std::io::{self, Write};
+use finalproject::audit::audit;
+use finalproject::clutch::{clutch_line, clutch_scores, has_clutch_stats};
+use finalproject::calibrate::{calibrate_position, read_ratings, Target};
+use finalproject::community::position_communities;
+use finalproject::cleaning::{resolve_stat, Player, PlayerMap, Position, COLUMNS};
+use finalproject::export::write_csv_row;
+use finalproject::scoring::{metric_names, position_weights, rank_players_with};
+use finalproject::metric::Metric;
+use finalproject::model::ScoringModel;
+use finalproject::locale::Locale;
+use finalproject::sensitivity::weight_sensitivity;
+use finalproject::stats::{average_ranks, correlation_matrix, spearman};
+use finalproject::projection::Projection;
+use finalproject::regression::{regression_candidates, MIN_BASELINE_GAMES};
+use finalproject::positions;
+use crate::lookup::{format_value, OutputFormat};
+use serde_json::json;
+
+pub fn print_projections(projections: &[Projection], model: &dyn ScoringModel, top: usize, format: Option<OutputFormat>, locale: &Locale) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    match format {
+        Some(OutputFormat::Json) => {
+            let rows: Vec<_> = projections
+                .iter()
+                .map(|p| json!({ "name": p.name, "position": p.position, "age": p.age, "current_score": p.current, "projected_score": p.projected }))
+                .collect();
+            serde_json::to_writer_pretty(&mut out, &rows).map_err(io::Error::other)?;
+            writeln!(out)
+        }
+        Some(OutputFormat::Csv) => {
+            write_csv_row(&mut out, &["player", "position", "age", "current_score", "projected_score"])?;
+            for p in projections {
+                let age = p.age.map(|age| format!("{:.1}", locale.num(age))).unwrap_or_default();
+                let row = [p.name.to_string(), p.position.to_string(), age, format!("{:.2}", locale.num(p.current)), format!("{:.2}", locale.num(p.projected))];
+                write_csv_row(&mut out, &row)?;
+            }
+            Ok(())
+        }
+        None => {
+            for position in positions::all() {
+                let unit = model.unit(position);
+                println!("\nProjected Next-Season Scores in {} Position:", position);
+                for p in projections.iter().filter(|p| p.position == *position).take(top) {
+                    println!("{}: {:.2}{} projected ({:.2}{} this season)", p.name, locale.num(p.projected), unit, locale.num(p.current), unit);
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+pub fn print_calibration(players: &PlayerMap, target: &str, ratings_path: Option<&str>, positions: &[Position], locale: &Locale) -> io::Result<()> {
+    let positions = if positions.is_empty() { positions::all() } else { positions };
+    let (label, stat, target): (String, Option<&str>, Box<Target>) = match ratings_path {
+        Some(path) => {
+            let ratings = read_ratings(path)?;
+            (path.to_string(), None, Box::new(move |player: &Player| ratings.get(&player.name.to_lowercase()).copied()))
+        }
+        None => {
+            let Some(stat) = resolve_stat(target) else {
+                eprintln!("Unknown stat '{}'. Available stats: {}", target, COLUMNS[3..].join(", "));
+                return Ok(());
+            };
+            (stat.to_string(), Some(stat), Box::new(move |player: &Player| player.stats.get(stat).copied()))
+        }
+    };
+
+    for position in positions {
+        let Some(calibration) = calibrate_position(players, position, &target, stat) else {
+            println!("\nNot enough {} players with a {} value to fit weights.", position, label);
+            continue;
+        };
+
+        println!(
+            "\n{} calibration against {} ({} players, R\u{b2} = {:.3}):",
+            position, label, calibration.players, locale.num(calibration.regression.r_squared)
+        );
+        println!("{:<16} {:>8} {:>8} {:>12}", "Metric", "Current", "Fitted", "Coefficient");
+        let (current, _) = position_weights(position);
+        for (i, metric) in calibration.metrics.iter().enumerate() {
+            if calibration.excluded == Some(i) {
+                println!("{:<16} {:>8.2} {:>21}", locale.label(metric), locale.num(current[i]), "(target)");
+                continue;
+            }
+            println!(
+                "{:<16} {:>8.2} {:>8.2} {:>12.3}",
+                locale.label(metric), locale.num(current[i]), locale.num(calibration.weights[i]), locale.num(calibration.regression.coefficients[i])
+            );
+        }
+    }
+
+    Ok(())
+}
+
+pub fn print_sensitivity(players: &PlayerMap, delta: f64, top: usize, positions: &[Position], locale: &Locale) {
+    let positions = if positions.is_empty() { positions::all() } else { positions };
+
+    for position in positions {
+        let report = weight_sensitivity(players, position, delta / 100.0);
+        println!("\n{} weight sensitivity (\u{b1}{}%):", position, delta);
+        println!("{:<16} {:>12} {:>12}", "Metric", "Avg move +", "Avg move -");
+        for metric in &report.metrics {
+            println!("{:<16} {:>12.2} {:>12.2}", locale.label(metric.metric), locale.num(metric.mean_shift_up), locale.num(metric.mean_shift_down));
+        }
+
+        println!("\nMost weight-dependent players:");
+        for player in report.players.iter().take(top).filter(|p| p.max_shift != 0) {
+            println!(
+                "{} (rank {}): moves {} {} places with {} weight {:+.0}%",
+                player.name,
+                player.rank,
+                if player.max_shift > 0 { "up" } else { "down" },
+                player.max_shift.abs(),
+                player.metric,
+                locale.num(player.weight_change * 100.0)
+            );
+        }
+    }
+}
+
+pub fn print_validation(players: &PlayerMap, model: &dyn ScoringModel, ratings_path: &str, top: usize, locale: &Locale) -> io::Result<()> {
+    let ratings = read_ratings(ratings_path)?;
+    let rankings = rank_players_with(players, model);
+
+    for position in positions::all() {
+        let Some(ranked) = rankings.get(position) else { continue };
+        let matched: Vec<(&str, f64, f64)> = ranked
+            .iter()
+            .filter_map(|scored| Some((&*scored.name, scored.score, *ratings.get(&scored.name.to_lowercase())?)))
+            .collect();
+        let scores: Vec<f64> = matched.iter().map(|m| m.1).collect();
+        let external: Vec<f64> = matched.iter().map(|m| m.2).collect();
+
+        println!("\n{}: {} of {} players matched in {}", position, matched.len(), ranked.len(), ratings_path);
+        let Some(rho) = spearman(&scores, &external) else {
+            println!("Not enough matched players with varying ratings to correlate.");
+            continue;
+        };
+        println!("Spearman rank correlation: {:.3}", locale.num(rho));
+
+        let (score_ranks, rating_ranks) = (average_ranks(&scores), average_ranks(&external));
+        let mut disagreements: Vec<(usize, f64)> =
+            score_ranks.iter().zip(&rating_ranks).map(|(a, b)| (a - b).abs()).enumerate().collect();
+        disagreements.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        for &(i, gap) in disagreements.iter().take(top).filter(|(_, gap)| *gap > 0.0) {
+            let (name, score, rating) = matched[i];
+            println!("  {}: score {:.2}, rating {} ({:.0} ranks apart)", name, locale.num(score), rating, locale.num(gap));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn print_correlations(players: &PlayerMap, metrics: &[String], positions: &[Position], locale: &Locale) -> io::Result<()> {
+    let positions = if positions.is_empty() { positions::all() } else { positions };
+    let selected = metrics
+        .iter()
+        .map(|metric| resolve_stat(metric).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("unknown metric '{}'", metric))))
+        .collect::<io::Result<Vec<&str>>>()?;
+
+    for position in positions {
+        let stats = if selected.is_empty() { metric_names(position).to_vec() } else { selected.clone() };
+        let matrix = correlation_matrix(players, position, &stats);
+
+        println!("\n{} Correlations:", position);
+        print!("{:<16}", "");
+        for stat in &stats {
+            print!(" {:>8.8}", locale.label(stat));
+        }
+        println!();
+        for (stat, row) in stats.iter().zip(&matrix) {
+            print!("{:<16.16}", locale.label(stat));
+            for r in row {
+                match r {
+                    Some(r) => print!(" {:>8.2}", locale.num(*r)),
+                    None => print!(" {:>8}", "-"),
+                }
+            }
+            println!();
+        }
+
+        for (i, row) in matrix.iter().enumerate() {
+            for (j, r) in row.iter().enumerate().skip(i + 1) {
+                if let Some(r) = r.filter(|r| r.abs() >= 0.8) {
+                    println!("{} and {} are strongly correlated ({:.2})", locale.label(stats[i]), locale.label(stats[j]), locale.num(r));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn print_audit(players: &PlayerMap, z: f64, locale: &Locale) {
+    let flags = audit(players, z);
+    if flags.is_empty() {
+        println!("No implausible values found");
+        return;
+    }
+
+    let mut position = None;
+    for flag in &flags {
+        if position != Some(&flag.position) {
+            println!("\n{} Flags:", flag.position);
+            position = Some(&flag.position);
+        }
+        let value = Metric::from_name(flag.metric).map_or_else(|| format_value(flag.value, locale), |metric| metric.unit().format(flag.value, locale));
+        println!("{}: {} = {} ({})", flag.name, locale.label(flag.metric), value, flag.reason);
+    }
+}
+
+pub fn print_communities(players: &PlayerMap, positions: &[Position], neighbors: usize, members: usize, locale: &Locale) {
+    let positions = if positions.is_empty() { positions::all() } else { positions };
+
+    for position in positions {
+        let communities = position_communities(players, position, neighbors, members);
+        println!("\n{} Communities ({}):", position, communities.len());
+        let metrics = Metric::for_position(position);
+        for (i, community) in communities.iter().enumerate() {
+            let profile: Vec<String> =
+                metrics.iter().zip(&community.profile).map(|(metric, &mean)| format!("{} {}", locale.label(metric.name()), metric.unit().format(mean, locale))).collect();
+            println!("{}. {} players, e.g. {}", i + 1, community.members.len(), community.representatives.join(", "));
+            println!("   avg {}", profile.join(", "));
+        }
+    }
+}
+
+pub fn print_regression_candidates(players: &PlayerMap, top: usize, has_baselines: bool, locale: &Locale) {
+    if !has_baselines {
+        println!("No baselines: pass earlier seasons with --baseline FILE (at least {} games in total per player)", MIN_BASELINE_GAMES);
+        return;
+    }
+    let candidates = regression_candidates(players);
+    if candidates.is_empty() {
+        println!("No player's SH% or IPP is far enough from their baseline to flag.");
+        return;
+    }
+    println!("\nRegression Candidates:");
+    for (player, flag) in candidates.iter().take(top) {
+        println!("{}: {} ({:+.1})", player.name, flag, locale.num(flag.current - flag.baseline));
+    }
+}
+
+pub fn print_clutch(players: &PlayerMap, top: usize, locale: &Locale) {
+    if !has_clutch_stats(players) {
+        println!("No clutch stats: the input needs Shootout Goals and Shootout Attempts or OT Points columns");
+        return;
+    }
+    println!("\nClutch Leaderboard (shootout conversion and OT points per game):");
+    for (rank, clutch) in clutch_scores(players).iter().take(top).enumerate() {
+        println!("{}. {}: {:.1} ({})", rank + 1, clutch.name, locale.num(clutch.score), clutch_line(clutch));
+    }
+}
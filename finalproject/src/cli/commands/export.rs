@@ -0,0 +1,33 @@
+use std::{fs, io::{self, Write}, path::Path};
+use finalproject::cleaning::PlayerMap;
+use finalproject::export::{position_file_name, write_dataset_csv, write_position_csv};
+use finalproject::scoring::rank_players_with;
+use finalproject::model::ScoringModel;
+use finalproject::locale::Locale;
+use finalproject::tiers::{Grades, Tiers};
+use finalproject::positions;
+
+// Grades are taken against every player, not just the `selected` ones written out.
+pub fn export_positions(players: &PlayerMap, selected: &PlayerMap, model: &dyn ScoringModel, tiers: &Tiers, dir: &str, locale: &Locale) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let position_groups = rank_players_with(selected, model);
+    let grades = Grades::new(players, model, tiers);
+
+    for position in positions::all() {
+        let ranked = position_groups.get(position).map_or(&[][..], Vec::as_slice);
+        let path = Path::new(dir).join(position_file_name(position));
+        let mut file = io::BufWriter::new(fs::File::create(&path)?);
+        write_position_csv(&mut file, players, position, ranked, &grades, model, locale)?;
+        file.flush()?;
+        log::info!("Wrote {} {} players to {}", ranked.len(), position.to_string().to_lowercase(), path.display());
+    }
+    Ok(())
+}
+
+pub fn export_dataset(players: &PlayerMap, model: &dyn ScoringModel, path: &str, locale: &Locale) -> io::Result<()> {
+    let mut file = io::BufWriter::new(fs::File::create(path)?);
+    let rows = write_dataset_csv(&mut file, players, model, locale)?;
+    file.flush()?;
+    log::info!("Wrote {} player-position rows to {}", rows, path);
+    Ok(())
+}
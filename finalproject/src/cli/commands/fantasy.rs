@@ -0,0 +1,66 @@
+use std::{fs, io::{self, Write}};
+use finalproject::cleaning::{find_player, PlayerMap};
+use finalproject::export::write_csv_row;
+use finalproject::locale::Locale;
+use finalproject::keeper::KeeperValue;
+use finalproject::fantasy::{fantasy_leaderboard, fantasy_line, PointValue};
+
+pub fn print_fantasy(players: &PlayerMap, values: &[PointValue], top: usize, games: f64, player: Option<&str>, locale: &Locale) {
+    if let Some(name) = player {
+        let Some(player) = find_player(players, name) else {
+            println!("Player '{}' not found.", name);
+            return;
+        };
+
+        let line = fantasy_line(player, values, games);
+        println!("\nFantasy breakdown for {} ({} GP):", line.name, line.games);
+        for (stat, points) in &line.breakdown {
+            println!("{}: {:.1}", stat, locale.num(*points));
+        }
+        println!("Total: {:.1} ({:.2} per game, {:.1} projected over {} games)", locale.num(line.total), locale.num(line.per_game), locale.num(line.projection), games);
+        return;
+    }
+
+    println!("\nFantasy Leaderboard:");
+    println!("{:<4} {:<28} {:>5} {:>9} {:>7} {:>10}", "Rank", "Player", "GP", "Points", "Per GP", "Projected");
+    for (rank, line) in fantasy_leaderboard(players, values, games).iter().take(top).enumerate() {
+        println!(
+            "{:<4} {:<28} {:>5} {:>9.1} {:>7.2} {:>10.1}",
+            rank + 1,
+            line.name,
+            line.games,
+            locale.num(line.total),
+            locale.num(line.per_game),
+            locale.num(line.projection)
+        );
+    }
+}
+
+pub fn print_keepers(keepers: &[KeeperValue], top: usize, discount: f64, locale: &Locale) {
+    println!("\nKeeper Value (fantasy points by season, discounted {:.0}% a season):", locale.num(discount * 100.0));
+    for (rank, keeper) in keepers.iter().take(top).enumerate() {
+        let age = keeper.age.map(|age| format!(", age {:.1}", locale.num(age))).unwrap_or_default();
+        let seasons: Vec<String> = keeper.seasons.iter().map(|points| format!("{:.1}", locale.num(*points))).collect();
+        println!("{}. {}{}: {:.1} ({})", rank + 1, keeper.name, age, locale.num(keeper.value), seasons.join(", "));
+    }
+}
+
+pub fn write_keepers(keepers: &[KeeperValue], path: &str, locale: &Locale) -> io::Result<()> {
+    let mut file = io::BufWriter::new(fs::File::create(path)?);
+    let seasons = keepers.first().map_or(0, |keeper| keeper.seasons.len());
+    let mut header = vec!["rank".to_string(), "player".to_string(), "age".to_string(), "points_per_game".to_string()];
+    header.extend((1..=seasons).map(|season| format!("season_{}", season)));
+    header.push("keeper_value".to_string());
+    write_csv_row(&mut file, &header)?;
+
+    for (rank, keeper) in keepers.iter().enumerate() {
+        let age = keeper.age.map(|age| format!("{:.1}", locale.num(age))).unwrap_or_default();
+        let mut row = vec![(rank + 1).to_string(), keeper.name.to_string(), age, format!("{:.3}", locale.num(keeper.per_game))];
+        row.extend(keeper.seasons.iter().map(|points| format!("{:.2}", locale.num(*points))));
+        row.push(format!("{:.2}", locale.num(keeper.value)));
+        write_csv_row(&mut file, &row)?;
+    }
+    file.flush()?;
+    log::info!("Wrote {} keepers to {}", keepers.len(), path);
+    Ok(())
+}
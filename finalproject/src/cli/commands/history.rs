@@ -0,0 +1,71 @@
+use std::{collections::HashMap, io};
+use finalproject::diff::SnapshotDiff;
+use finalproject::splits::VenueGap;
+use finalproject::model::ScoringModel;
+use finalproject::history::{player_history, HistoryEntry};
+use finalproject::locale::Locale;
+
+pub fn print_history(entries: &[HistoryEntry], path: &str, player: &str, locale: &Locale) -> io::Result<()> {
+    let runs = player_history(entries, player);
+    let Some((_, first)) = runs.first() else {
+        println!("No history for {} in {}", player, path);
+        return Ok(());
+    };
+
+    println!("\nScore history for {}:", first[0].player);
+    let mut previous: HashMap<&str, f64> = HashMap::new();
+    for (recorded_at, run) in &runs {
+        let scores: Vec<String> = run
+            .iter()
+            .map(|entry| {
+                let change = previous.insert(&entry.position, entry.score).map(|old| format!(" ({:+.2})", locale.num(entry.score - old))).unwrap_or_default();
+                format!("{} {:.2}{}", entry.position, locale.num(entry.score), change)
+            })
+            .collect();
+        println!("{}: {}", recorded_at.replacen('T', " ", 1).trim_end_matches('Z'), scores.join(", "));
+    }
+    Ok(())
+}
+
+pub fn print_diff(diff: &SnapshotDiff, model: &dyn ScoringModel, top: usize, locale: &Locale) {
+    println!("\nBiggest Score Changes:");
+    for change in diff.changes.iter().take(top) {
+        let unit = model.unit(&change.position);
+        println!(
+            "{} ({}): {:.2}{} -> {:.2}{} ({:+.2})",
+            change.name,
+            change.position.abbreviation(),
+            locale.num(change.old),
+            unit,
+            locale.num(change.new),
+            unit,
+            locale.num(change.change())
+        );
+    }
+
+    for (title, players) in [("New Entrants", &diff.entrants), ("Dropped Players", &diff.dropped)] {
+        println!("\n{} ({}):", title, players.len());
+        for scored in players {
+            println!("{} ({}): {:.2}{}", scored.name, scored.position.abbreviation(), locale.num(scored.score), model.unit(&scored.position));
+        }
+    }
+}
+
+pub fn print_venue_gaps(gaps: &[VenueGap], model: &dyn ScoringModel, top: usize, locale: &Locale) {
+    println!("\nBiggest Home/Road Gaps:");
+    for gap in gaps.iter().take(top) {
+        let unit = model.unit(&gap.position);
+        println!(
+            "{} ({}): {:.2}{} home, {:.2}{} road ({:+.2})",
+            gap.name,
+            gap.position.abbreviation(),
+            locale.num(gap.home),
+            unit,
+            locale.num(gap.road),
+            unit,
+            locale.num(gap.gap())
+        );
+    }
+    let better_at_home = gaps.iter().filter(|gap| gap.gap() > 0.0).count();
+    println!("\n{} of {} players scored better at home", better_at_home, gaps.len());
+}
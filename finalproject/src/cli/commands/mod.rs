@@ -0,0 +1,9 @@
+pub mod alerts;
+pub mod analysis;
+pub mod export;
+pub mod fantasy;
+pub mod history;
+pub mod rankings;
+#[cfg(feature = "grpc")]
+pub mod serve;
+pub mod teams;
@@ -0,0 +1,184 @@
+use std::{borrow::Cow, collections::HashMap};
+use finalproject::alerts::{Rule, ScoreIndex};
+use finalproject::age::player_age;
+use finalproject::cleaning::{resolve_stat, PlayerMap, Position, COLUMNS};
+use finalproject::bio::{HEIGHT_STAT, WEIGHT_STAT};
+use finalproject::salary::value_leaderboard;
+use finalproject::scoring::{metric_index, metric_names, overall_rankings, rank_by_metric, rank_players_with, scarcity_rankings, stat_leaders, ScoredPlayer};
+use finalproject::metric::Unit;
+use finalproject::model::ScoringModel;
+use finalproject::experience::within_seasons;
+use finalproject::locale::Locale;
+use finalproject::stats::{decile_counts, summarize_position};
+use finalproject::tiers::{Grades, Tiers};
+use finalproject::xg::FINISHING_STAT;
+use finalproject::regression::regression_flags;
+use finalproject::positions;
+use crate::cli::args::RankArgs;
+
+pub fn print_rankings(players: &PlayerMap, args: &RankArgs, model: &dyn ScoringModel, tiers: &Tiers, position_groups: &HashMap<Position, Vec<ScoredPlayer>>, locale: &Locale) {
+    if let Some(metric) = &args.by {
+        print_metric_rankings(players, args, metric, locale);
+        return;
+    }
+
+    let grades = Grades::from_rankings(position_groups, tiers);
+    let selected = |name: &str| {
+        let Some(player) = players.get(name) else { return false };
+        let in_age_range = args.age_range.is_none_or(|(low, high)| {
+            player_age(player, args.reference_day).is_some_and(|age| (low..=high).contains(&age.floor()))
+        });
+        let young_enough = args.max_age.is_none_or(|max| player_age(player, args.reference_day).is_some_and(|age| age.floor() <= max));
+        let at_least = |stat: &str, min: Option<f64>| min.is_none_or(|min| player.stats.get(stat).is_some_and(|&value| value >= min));
+        in_age_range
+            && young_enough
+            && at_least(HEIGHT_STAT, args.min_height)
+            && at_least(WEIGHT_STAT, args.min_weight)
+            && args.max_seasons.is_none_or(|max| within_seasons(player, max))
+    };
+
+    for position in args.selected_positions() {
+        if let Some(players_in_position) = position_groups.get(position) {
+            println!("\nTop Players in {} Position:", position);
+            let unit = model.unit(position);
+            for scored in players_in_position.iter().filter(|scored| selected(&scored.name)).take(args.top()) {
+                let flags: Vec<String> = players.get(&scored.name).map(regression_flags).unwrap_or_default().iter().map(ToString::to_string).collect();
+                let flags = if flags.is_empty() { String::new() } else { format!(" [{}]", flags.join("; ")) };
+                println!("{}: {:.2}{} ({}){}", scored.name, locale.num(scored.score), unit, grades.grade(position, scored.score), flags);
+            }
+        }
+    }
+}
+
+pub fn print_overall(players: &PlayerMap, model: &dyn ScoringModel, top: usize, bonus: f64, locale: &Locale) {
+    println!("\nOverall Leaderboard:");
+    for (rank, overall) in overall_rankings(players, model, bonus).iter().take(top).enumerate() {
+        let positions: Vec<&str> = overall.positions.iter().map(Position::abbreviation).collect();
+        let unit = overall.positions.first().map(|position| model.unit(position)).unwrap_or_default();
+        let bonus = if overall.bonus != 0.0 { format!(" (incl. {:+.2} versatility)", locale.num(overall.bonus)) } else { String::new() };
+        println!("{}. {} ({}): {:.2}{}{}", rank + 1, overall.name, positions.join("/"), locale.num(overall.score), unit, bonus);
+    }
+}
+
+pub fn print_scarcity_overall(players: &PlayerMap, model: &dyn ScoringModel, top: usize, locale: &Locale) {
+    println!("\nOverall Leaderboard (standard deviations above the position mean):");
+    for (rank, overall) in scarcity_rankings(players, model).iter().take(top).enumerate() {
+        let positions: Vec<&str> = overall.positions.iter().map(Position::abbreviation).collect();
+        println!("{}. {} ({}): {:+.2}", rank + 1, overall.name, positions.join("/"), locale.num(overall.score));
+    }
+}
+
+pub fn print_metric_rankings(players: &PlayerMap, args: &RankArgs, metric: &str, locale: &Locale) {
+    let mut printed_any = false;
+
+    for position in args.selected_positions() {
+        let Some(index) = metric_index(position, metric) else { continue };
+        let metric_name = metric_names(position)[index];
+        let kind = if args.raw { "raw" } else { "normalized" };
+
+        println!("\nTop Players in {} Position by {} ({}):", position, locale.label(metric_name), kind);
+        for (name, value) in rank_by_metric(players, position, index, args.raw).iter().take(args.top()) {
+            println!("{}: {:.2}", name, locale.num(*value));
+        }
+        printed_any = true;
+    }
+
+    if !printed_any {
+        eprintln!("No selected position uses the metric '{}'. Available metrics:", metric);
+        for position in args.selected_positions() {
+            eprintln!("  {}: {}", position, metric_names(position).join(", "));
+        }
+    }
+}
+
+pub fn print_leaders(players: &PlayerMap, metric: &str, top: usize, locale: &Locale) {
+    let Some(stat) = resolve_stat(metric) else {
+        eprintln!("Unknown stat '{}'. Available stats: {}, {}", metric, COLUMNS[3..].join(", "), FINISHING_STAT);
+        return;
+    };
+
+    println!("\nLeague Leaders in {}:", locale.label(stat));
+    for (rank, (player, value)) in stat_leaders(players, stat).iter().take(top).enumerate() {
+        let positions: Vec<String> = player.positions.iter().map(|p| p.to_string()).collect();
+        println!("{}. {} ({}): {}", rank + 1, player.name, positions.join("/"), Unit::Count.format(*value, locale));
+    }
+}
+
+pub fn print_averages(players: &PlayerMap, positions: &[Position], locale: &Locale) {
+    let positions = if positions.is_empty() { positions::all() } else { positions };
+
+    for position in positions {
+        let summary = summarize_position(players, position);
+        println!("\n{} Averages ({} players):", position, summary.score.count);
+        println!(
+            "{:<16} {:>10} {:>10} {:>10}   {:>8} {:>8} {:>8}",
+            "Metric", "Raw Mean", "Median", "Std Dev", "Norm", "Median", "Std Dev"
+        );
+
+        for metric in &summary.metrics {
+            println!(
+                "{:<16} {:>10.2} {:>10.2} {:>10.2}   {:>8.3} {:>8.3} {:>8.3}",
+                locale.label(metric.metric),
+                locale.num(metric.raw.mean),
+                locale.num(metric.raw.median),
+                locale.num(metric.raw.stddev),
+                locale.num(metric.normalized.mean),
+                locale.num(metric.normalized.median),
+                locale.num(metric.normalized.stddev)
+            );
+        }
+        println!(
+            "{:<16} {:>10.2} {:>10.2} {:>10.2}",
+            "Score", locale.num(summary.score.mean), locale.num(summary.score.median), locale.num(summary.score.stddev)
+        );
+    }
+}
+
+pub fn print_distribution(players: &PlayerMap, model: &dyn ScoringModel, positions: &[Position]) {
+    let positions = if positions.is_empty() { positions::all() } else { positions };
+    let position_groups = rank_players_with(players, model);
+
+    println!("\nScore distribution by decile:");
+    let header: Vec<String> = (0..10).map(|decile| format!("{}-{}", decile * 10, decile * 10 + 10)).collect();
+    println!("{:<10} {}", "Position", header.iter().map(|label| format!("{:>7}", label)).collect::<String>());
+    for position in positions {
+        let scores: Vec<f64> = position_groups.get(position).into_iter().flatten().map(|scored| scored.score).collect();
+        let counts = decile_counts(&scores);
+        println!("{:<10} {}", position.to_string(), counts.iter().map(|count| format!("{:>7}", count)).collect::<String>());
+    }
+}
+
+pub fn print_value(players: &PlayerMap, model: &dyn ScoringModel, top: usize, positions: &[Position], locale: &Locale) {
+    let positions = if positions.is_empty() { positions::all() } else { positions };
+    let leaderboard = value_leaderboard(players, model);
+
+    if leaderboard.values().all(Vec::is_empty) {
+        eprintln!("No players have a cap hit; pass a `name,cap_hit` file with --salaries");
+        return;
+    }
+
+    for position in positions {
+        let Some(lines) = leaderboard.get(position) else { continue };
+        println!("\nBest Value in {} Position:", position);
+        for line in lines.iter().take(top) {
+            println!(
+                "{}: {:.2} per $1M ({:.2}{} at ${:.2}M)",
+                line.name,
+                locale.num(line.score_per_million),
+                locale.num(line.score),
+                model.unit(position),
+                locale.num(line.cap_hit / 1_000_000.0)
+            );
+        }
+    }
+}
+
+// The players `--filter` keeps, judged on scores against the whole league, which the model was
+// already fit to, so a slice's scores and grades match the unfiltered output's.
+pub fn filter_players<'a>(players: &'a PlayerMap, filter: Option<&Rule>, model: &dyn ScoringModel, bonus: f64) -> Cow<'a, PlayerMap> {
+    let Some(rule) = filter else { return Cow::Borrowed(players) };
+    let scores = ScoreIndex::new(players, model, bonus);
+    let selected: PlayerMap = players.values().filter(|player| rule.matches(player, &scores)).map(|player| (player.name.clone(), player.clone())).collect();
+    log::info!("--filter kept {} of {} players", selected.len(), players.len());
+    Cow::Owned(selected)
+}
@@ -0,0 +1,50 @@
+use std::{io, net::SocketAddr};
+use finalproject::cleaning::PlayerMap;
+use finalproject::config::Config;
+use finalproject::engine::ScoringEngine;
+use finalproject::grpc::{self, ScoresService};
+use finalproject::model::ScoringModel;
+use crate::watch;
+
+// Rebuilds the model from the config file for the players being served.
+pub type ReloadModel<'a> = dyn Fn(&PlayerMap) -> io::Result<(Config, Box<dyn ScoringModel>)> + Sync + 'a;
+
+// Reads the input files again, the way they were loaded at startup.
+pub type ReloadPlayers<'a> = dyn Fn() -> io::Result<PlayerMap> + Sync + 'a;
+
+// Serves the players over gRPC. When the config file changes they're rescored with its model, and
+// when an input file changes the engine rescores only the players that changed in it.
+pub fn serve(addr: SocketAddr, mut engine: ScoringEngine, inputs: &[&str], config_file: Option<&str>, reload_model: &ReloadModel, reload_players: &ReloadPlayers) -> io::Result<()> {
+    let service = ScoresService::new(engine.players().clone(), engine.model(), config_file);
+    let reloader = service.reloader();
+    let mut files = inputs.to_vec();
+    files.extend(config_file);
+    std::thread::scope(|scope| {
+        if !files.is_empty() {
+            scope.spawn(move || {
+                let watched = watch::watch_files(&files, |changed| {
+                    let reloaded = if config_file.is_some_and(|config_file| watch::is_file(config_file, changed)) {
+                        reload_model(engine.players()).map(|(_, model)| engine.set_model(model))
+                    } else {
+                        reload_players().map(|players| log::debug!("Rescored {} players", engine.sync(players)))
+                    };
+                    match reloaded {
+                        Ok(()) => {
+                            reloader.reload(&engine);
+                            log::info!("Reloaded {} and rescored with the {} model", changed.display(), engine.model().name());
+                            Ok(())
+                        }
+                        Err(e) => {
+                            reloader.failed(&e.to_string());
+                            Err(e)
+                        }
+                    }
+                });
+                if let Err(e) = watched {
+                    log::warn!("Stopped watching {}: {}", files.join(" and "), e);
+                }
+            });
+        }
+        grpc::serve(addr, service)
+    })
+}
@@ -0,0 +1,133 @@
+use std::path::Path;
+use finalproject::assists::AssistNetwork;
+use finalproject::cleaning::PlayerMap;
+use finalproject::model::ScoringModel;
+use finalproject::locale::Locale;
+use finalproject::special_teams::{special_teams_leaderboard, Situation};
+use finalproject::teammates::TeammateGraph;
+use finalproject::team::{depth_chart, Slot, TeamIndex, TeamStrength};
+
+pub fn print_special_teams(players: &PlayerMap, situation: Situation, top: usize, locale: &Locale) {
+    let leaderboard = special_teams_leaderboard(players, situation);
+    if leaderboard.is_empty() {
+        let flag = if situation == Situation::PowerPlay { "--pp" } else { "--pk" };
+        eprintln!("No {} stats were loaded; pass the {} export with {}", situation, situation.prefix(), flag);
+        return;
+    }
+
+    println!("\nTop {} Players:", situation);
+    for line in leaderboard.iter().take(top) {
+        println!("{}: {:.2}", line.name, locale.num(line.score));
+    }
+}
+
+pub fn print_depth_chart(players: &PlayerMap, model: &dyn ScoringModel, team: &str, locale: &Locale) {
+    let index = TeamIndex::new(players);
+    if index.is_empty() {
+        eprintln!("No team data: the input has no Team column");
+        return;
+    }
+    if index.players(players, team).is_empty() {
+        eprintln!("No players on team '{}'. Teams: {}", team, index.teams().collect::<Vec<_>>().join(", "));
+        return;
+    }
+
+    let chart = depth_chart(players, &index, team, model);
+    let cell = |slot, depth| chart.get(slot, depth).map_or("-- hole --".to_string(), |s| format!("{} ({:.1})", s.name, locale.num(s.score)));
+    println!("\nDepth Chart for {}:", team.to_uppercase());
+    println!("{:<5} {:<30} {:<30} RW", "Line", "LW", "C");
+    for depth in 0..Slot::Center.depth() {
+        println!("{:<5} {:<30} {:<30} {}", depth + 1, cell(Slot::LeftWing, depth), cell(Slot::Center, depth), cell(Slot::RightWing, depth));
+    }
+    println!("{:<5} {:<30} D", "Pair", "D");
+    for pair in 0..Slot::Defense.depth() / 2 {
+        println!("{:<5} {:<30} {}", pair + 1, cell(Slot::Defense, 2 * pair), cell(Slot::Defense, 2 * pair + 1));
+    }
+
+    let holes = chart.holes();
+    if !holes.is_empty() {
+        println!("Holes: {}", holes.join(", "));
+    }
+}
+
+pub fn print_team_comparison(players: &PlayerMap, model: &dyn ScoringModel, first: &str, second: &str, locale: &Locale) {
+    let index = TeamIndex::new(players);
+    if index.is_empty() {
+        eprintln!("No team data: the input has no Team column");
+        return;
+    }
+    for team in [first, second] {
+        if index.players(players, team).is_empty() {
+            eprintln!("No players on team '{}'. Teams: {}", team, index.teams().collect::<Vec<_>>().join(", "));
+            return;
+        }
+    }
+
+    let (first, second) = (first.to_uppercase(), second.to_uppercase());
+    let [a, b] = [&first, &second].map(|team| depth_chart(players, &index, team, model).strength());
+    let edge = |a: f64, b: f64| match a - b {
+        diff if diff.abs() < 0.05 => "even".to_string(),
+        diff if diff > 0.0 => format!("{} {:+.1}", first, locale.num(diff)),
+        diff => format!("{} {:+.1}", second, locale.num(-diff)),
+    };
+
+    println!("\n{} vs {} (mean score of each dressed lineup):", first, second);
+    println!("{:<10} {:>8} {:>8}  Edge", "Position", first, second);
+    for ((position, a), (_, b)) in a.positions.iter().zip(&b.positions) {
+        println!("{:<10} {:>8.1} {:>8.1}  {}", position.to_string(), locale.num(*a), locale.num(*b), edge(*a, *b));
+    }
+    println!("{:<10} {:>8.1} {:>8.1}  {}", "Overall", locale.num(a.overall), locale.num(b.overall), edge(a.overall, b.overall));
+
+    let wins = |a: &TeamStrength, b: &TeamStrength| a.positions.iter().zip(&b.positions).filter(|((_, a), (_, b))| a > b).count();
+    let (leader, positions_won) = if a.overall >= b.overall { (&first, wins(&a, &b)) } else { (&second, wins(&b, &a)) };
+    println!("Verdict: {} has the stronger lineup ({:+.1} overall), with the edge at {} of {} positions", leader, locale.num((a.overall - b.overall).abs()), positions_won, a.positions.len());
+}
+
+pub fn print_assist_network(network: &AssistNetwork, player: Option<&str>, top: usize) {
+    log::info!("Loaded {} goals between {} players", network.goals, network.graph.len());
+    let Some(player) = player else {
+        println!("\nMost Connected Duos:");
+        for duo in network.top_duos().iter().take(top) {
+            println!("{} & {}: {} assisted goals", duo.first, duo.second, duo.assists);
+        }
+        return;
+    };
+
+    let Some(id) = network.graph.id(player) else {
+        eprintln!("Player '{}' is not in the event file", player);
+        return;
+    };
+    let name = network.graph.name(id);
+    for (title, connections) in [("Finishers", network.finishers(player)), ("Setup Men", network.setup_men(player))] {
+        println!("\n{} for {}:", title, name);
+        if connections.is_empty() {
+            println!("(none)");
+        }
+        for connection in connections.iter().take(top) {
+            println!("{}: {}", connection.name, connection.assists);
+        }
+    }
+}
+
+// Seasons are named after their file, e.g. `2022-23` for `exports/2022-23.csv`.
+pub fn season_label(path: &str) -> String {
+    Path::new(path).file_stem().map_or_else(|| path.to_string(), |stem| stem.to_string_lossy().into_owned())
+}
+
+pub fn print_teammate_path(teammates: &TeammateGraph, from: &str, to: &str) {
+    if teammates.graph.id(from).is_none() || teammates.graph.id(to).is_none() {
+        let missing = if teammates.graph.id(from).is_none() { from } else { to };
+        eprintln!("Player '{}' is not on any team in the loaded seasons (the exports need a Team column)", missing);
+        return;
+    }
+    let Some(path) = teammates.path(from, to) else {
+        println!("No chain of teammates connects {} and {}", from, to);
+        return;
+    };
+
+    println!("\n{} and {} are {} teammate steps apart:", path[0].name, path[path.len() - 1].name, path.len() - 1);
+    println!("{}", path[0].name);
+    for step in &path[1..] {
+        println!("  -> {} ({})", step.name, step.via.as_deref().unwrap_or("?"));
+    }
+}
@@ -0,0 +1,90 @@
+use std::{io, path::Path};
+use finalproject::age::AgeAdjusted;
+use finalproject::calibrate::pca_weights;
+use finalproject::config::{load_config, Config, DEFAULT_CONFIG_FILE};
+use finalproject::cleaning::{PlayerMap, Position};
+use finalproject::scoring::{Transform, WeightTable};
+use finalproject::onice::Report;
+use finalproject::model::{Adjustment, Linear, ModelRegistry, Ordinal, ScoringModel, ValueOverReplacement, WeightedSigmoid, DEFAULT_MODEL};
+use finalproject::luck::LuckAdjusted;
+use finalproject::xg::ExpectedGoals;
+use finalproject::positions;
+use crate::cli::args::{Cli, Command};
+
+pub fn load_scoring_config(path: Option<&str>) -> io::Result<Config> {
+    match config_file(path) {
+        Some(path) => load_config(Path::new(path)),
+        None => Ok(Config::default()),
+    }
+}
+
+// The config file's settings only fill in what neither a flag nor its NHL_SCORES_* variable set, so
+// the command line wins over the environment and both win over the file.
+pub fn layer_settings(cli: &mut Cli, config: &Config) {
+    cli.min_gp = cli.min_gp.or(config.min_gp);
+    if let Some(Command::Rank(args)) = &mut cli.command {
+        args.top = args.top.or(config.top);
+    }
+}
+
+// The config file in use, if any.
+pub fn config_file(path: Option<&str>) -> Option<&str> {
+    path.or_else(|| Path::new(DEFAULT_CONFIG_FILE).exists().then_some(DEFAULT_CONFIG_FILE))
+}
+
+pub fn with_pca_weights(mut weights: WeightTable, players: &PlayerMap) -> WeightTable {
+    for position in positions::all() {
+        if let Some(fitted) = pca_weights(players, position) {
+            weights.overrides.insert(position.clone(), fitted);
+        }
+    }
+    weights
+}
+
+pub fn weight_table_toml(weights: &WeightTable) -> String {
+    let mut toml = String::from("[weights]");
+    // The config's own positions take their weights in their `[positions]` table instead.
+    for position in positions::all().iter().filter(|position| !matches!(position, Position::Custom(_))) {
+        let values: Vec<String> = weights.get(position).iter().map(|w| format!("{:.3}", w)).collect();
+        toml.push_str(&format!("\n{} = [{}]", position.to_string().to_lowercase(), values.join(", ")));
+    }
+    toml
+}
+
+pub fn scoring_model(config: &Config, weights: &WeightTable, name: Option<&str>, report: Report, transform: Transform, vor: bool, adjustments: &[Adjustment]) -> io::Result<Box<dyn ScoringModel>> {
+    let formulas = config.formulas().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let default_model = match report {
+        _ if !formulas.is_empty() => "formula",
+        Report::OnIce => "possession",
+        Report::Individual => DEFAULT_MODEL,
+    };
+
+    let mut registry = ModelRegistry::default();
+    registry.register(Box::new(WeightedSigmoid::new(weights.clone(), config.sigmoid.overrides(), transform)));
+    registry.register(Box::new(Linear::new(weights.clone())));
+    registry.register(Box::new(Ordinal::new(weights.clone())));
+    registry.register(Box::new(formulas));
+    let mut model = registry
+        .take(name.or(config.model.as_deref()).unwrap_or(default_model))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    if !config.xg.is_empty() {
+        let weights = config.xg.weights().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        model = Box::new(ExpectedGoals::new(model, weights));
+    }
+
+    if adjustments.contains(&Adjustment::Age) {
+        let reference_day = config.age_curve.reference_day().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        model = Box::new(AgeAdjusted { base: model, curve: config.age_curve.clone(), reference_day });
+    }
+
+    if adjustments.contains(&Adjustment::Pdo) {
+        model = Box::new(LuckAdjusted { base: model });
+    }
+
+    if vor {
+        Ok(Box::new(ValueOverReplacement::new(model, config.replacement.clone())))
+    } else {
+        Ok(model)
+    }
+}
@@ -0,0 +1,271 @@
+use std::{collections::{BTreeMap, HashMap, HashSet}, fs, io::{self, BufRead, Read, stdin}, path::Path};
+use finalproject::cache;
+use finalproject::cleaning::{apply_rates, decompress, insert_player, normalize_metrics, ParseOptions, PlayerMap, Position, PositionStats, RowReader};
+use finalproject::splits::{tag_venue, Venue};
+use finalproject::emit::RowFiles;
+use finalproject::bio::{join_bios, Roster};
+use finalproject::salary::join_cap_hits;
+use finalproject::scoring::metric_names;
+use finalproject::metric::Scaling;
+use finalproject::usage::join_usage;
+use finalproject::experience::mark_rookies;
+use finalproject::formula::Rate;
+use finalproject::input::merge_datasets;
+use finalproject::special_teams::{merge_situation, Situation, SituationStats};
+use finalproject::xg::add_finishing;
+use finalproject::regression::join_baselines;
+use finalproject::runlog::RunLog;
+use finalproject::positions;
+use serde_json::json;
+
+pub fn load_players<R: BufRead>(reader: R, options: &ParseOptions, max_skip_pct: Option<f64>, run_log: &RunLog, row_files: &RowFiles) -> io::Result<PlayerMap> {
+    let mut players = PlayerMap::default();
+    let mut skipped_rows = 0;
+    let mut processed_rows = 0;
+    let mut duplicate_rows = 0;
+    let mut skip_reasons: BTreeMap<&str, usize> = BTreeMap::new();
+
+    let mut rows = RowReader::new(reader, options)?;
+    // Header checks are progress output, so `--quiet` leaves them to `--log-json`'s schema_warnings.
+    for message in rows.options().schema.lines() {
+        log::info!("{}", message);
+    }
+
+    while let Some(row) = rows.next_checked()? {
+        match row {
+            Ok(player) => {
+                row_files.accepted(rows.line(), rows.options())?;
+                duplicate_rows += insert_player(&mut players, player, options.duplicates) as usize;
+                processed_rows += 1;
+            }
+            Err(reason) => {
+                row_files.rejected(rows.header(), rows.line(), options.delimiter, reason)?;
+                *skip_reasons.entry(reason.name()).or_insert(0) += 1;
+                skipped_rows += 1;
+            }
+        }
+    }
+
+    log::info!("Processed rows: {}", processed_rows);
+    log::info!("Skipped rows: {}", skipped_rows);
+    if duplicate_rows > 0 {
+        log::warn!("Duplicate player names: {} (resolved by --duplicates {})", duplicate_rows, options.duplicates);
+    }
+    let warnings = rows.options().schema.lines();
+    run_log.event(
+        "rows",
+        json!({ "processed": processed_rows, "skipped": skipped_rows, "duplicates": duplicate_rows, "skip_reasons": skip_reasons, "schema_warnings": warnings }),
+    );
+    check_skip_rate(processed_rows, skipped_rows, max_skip_pct)?;
+
+    let stats = normalize_metrics(&mut players);
+    run_log.event("normalized", json!({ "positions": normalization_summary(&stats) }));
+
+    Ok(players)
+}
+
+// Each position's pre-normalization mean and max by metric name.
+pub fn normalization_summary(stats: &HashMap<Position, PositionStats>) -> serde_json::Value {
+    let positions: BTreeMap<String, serde_json::Value> = positions::all()
+        .iter()
+        .filter_map(|position| {
+            let stats = stats.get(position)?;
+            let metrics: BTreeMap<&str, serde_json::Value> = metric_names(position)
+                .iter()
+                .enumerate()
+                .map(|(i, &name)| (name, json!({ "mean": stats.mean.get(i), "max": stats.max.get(i) })))
+                .collect();
+            Some((position.to_string(), json!(metrics)))
+        })
+        .collect();
+    json!(positions)
+}
+
+pub fn position_counts(players: &PlayerMap) -> BTreeMap<String, usize> {
+    positions::all()
+        .iter()
+        .map(|position| (position.to_string(), players.values().filter(|player| player.positions.contains(position)).count()))
+        .collect()
+}
+
+pub fn check_skip_rate(processed_rows: usize, skipped_rows: usize, max_skip_pct: Option<f64>) -> io::Result<()> {
+    let total = processed_rows + skipped_rows;
+    let Some(max) = max_skip_pct.filter(|_| total > 0) else { return Ok(()) };
+    let skipped_pct = 100.0 * skipped_rows as f64 / total as f64;
+    if skipped_pct > max {
+        let message = format!("skipped {:.1}% of rows ({} of {}), more than --max-skip-pct {}", skipped_pct, skipped_rows, total, max);
+        return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+    }
+    Ok(())
+}
+
+pub fn read_input(file_path: &str) -> io::Result<Vec<u8>> {
+    if file_path == "-" {
+        let mut contents = Vec::new();
+        stdin().lock().read_to_end(&mut contents)?;
+        Ok(contents)
+    } else {
+        fs::read(file_path)
+    }
+}
+
+#[cfg(feature = "parquet")]
+pub fn load_parquet(file_path: &str) -> io::Result<PlayerMap> {
+    let mut players = finalproject::parquet_input::process_parquet(file_path)?;
+    log::info!("Loaded {} players from {}", players.len(), file_path);
+    normalize_metrics(&mut players);
+    Ok(players)
+}
+
+#[cfg(not(feature = "parquet"))]
+pub fn load_parquet(file_path: &str) -> io::Result<PlayerMap> {
+    let message = format!("cannot read {}: rebuild with `--features parquet` to enable Parquet input", file_path);
+    Err(io::Error::new(io::ErrorKind::Unsupported, message))
+}
+
+pub fn load_dataset(file_path: &str, options: &ParseOptions, use_cache: bool, max_skip_pct: Option<f64>, run_log: &RunLog) -> io::Result<PlayerMap> {
+    let players = read_dataset(file_path, options, use_cache, max_skip_pct, run_log, &RowFiles::default())?;
+    run_log.event("players", json!({ "path": file_path, "players": players.len(), "positions": position_counts(&players) }));
+    Ok(players)
+}
+
+// Several input files are parsed (and cached) one by one, then merged as if they were one export.
+// Rows written out with `--emit-cleaned`/`--emit-rejected` are collected across all of them.
+pub fn load_inputs(file_paths: &[String], options: &ParseOptions, use_cache: bool, max_skip_pct: Option<f64>, run_log: &RunLog, row_files: &RowFiles) -> io::Result<PlayerMap> {
+    if let [file_path] = file_paths {
+        let players = read_dataset(file_path, options, use_cache, max_skip_pct, run_log, row_files)?;
+        run_log.event("players", json!({ "path": file_path, "players": players.len(), "positions": position_counts(&players) }));
+        return Ok(players);
+    }
+    let datasets = file_paths.iter().map(|path| read_dataset(path, options, use_cache, max_skip_pct, run_log, row_files)).collect::<io::Result<Vec<_>>>()?;
+    let (players, duplicates) = merge_datasets(datasets, options.duplicates);
+    log::info!("Merged {} files into {} players", file_paths.len(), players.len());
+    if duplicates > 0 {
+        log::warn!("Players in more than one file: {} rows (resolved by --duplicates {})", duplicates, options.duplicates);
+    }
+    run_log.event("players", json!({ "paths": file_paths, "players": players.len(), "duplicates": duplicates, "positions": position_counts(&players) }));
+    Ok(players)
+}
+
+pub fn read_dataset(file_path: &str, options: &ParseOptions, use_cache: bool, max_skip_pct: Option<f64>, run_log: &RunLog, row_files: &RowFiles) -> io::Result<PlayerMap> {
+    if file_path.to_lowercase().ends_with(".parquet") {
+        run_log.event("file_loaded", json!({ "path": file_path, "format": "parquet" }));
+        return load_parquet(file_path);
+    }
+
+    let contents = decompress(read_input(file_path)?)?;
+    run_log.event("file_loaded", json!({ "path": file_path, "format": "csv", "bytes": contents.len() }));
+    let cache_dir = Path::new(cache::DEFAULT_CACHE_DIR);
+    let settings = format!("{}{:?}{:?}{:?}{}{:?}", options.delimiter, options.report, options.mapping, options.roster, options.duplicates, positions::registered());
+    let hash = cache::extend_hash(cache::hash_bytes(&contents), settings.as_bytes());
+
+    // The cache doesn't keep row counts or the rows themselves, so a skip threshold or row files
+    // always re-parse the file.
+    if use_cache && max_skip_pct.is_none() && !row_files.is_active() {
+        if let Some(players) = cache::load(cache_dir, hash) {
+            log::info!("Loaded {} players from cache", players.len());
+            run_log.event("cache_hit", json!({ "path": file_path }));
+            return Ok(players);
+        }
+    }
+
+    let players = load_players(contents.as_slice(), options, max_skip_pct, run_log, row_files)?;
+
+    if let Err(e) = cache::store(cache_dir, hash, &players) {
+        log::warn!("Could not write dataset cache: {}", e);
+    }
+
+    Ok(players)
+}
+
+pub struct Supplements {
+    pub cap_hits: Option<HashMap<String, f64>>,
+    pub rookies: Option<HashSet<String>>,
+    pub usage: Option<HashMap<String, HashMap<Position, f64>>>,
+    pub bios: Option<Roster>,
+    pub situations: Vec<(Situation, SituationStats)>,
+    pub baselines: Vec<PlayerMap>,
+    pub rates: Rate,
+    pub min_gp: Option<f64>,
+    pub scaling: Scaling,
+}
+
+impl Supplements {
+    pub fn apply(&self, players: &mut PlayerMap, report: bool) {
+        if let Some(cap_hits) = &self.cap_hits {
+            let matched = join_cap_hits(players, cap_hits);
+            if report {
+                log::info!("Matched cap hits for {} of {} players", matched, players.len());
+            }
+        }
+
+        if let Some(rookies) = &self.rookies {
+            let matched = mark_rookies(players, rookies);
+            if report {
+                log::info!("Matched {} of {} listed rookies", matched, rookies.len());
+            }
+        }
+
+        if let Some(usage) = &self.usage {
+            let matched = join_usage(players, usage);
+            if report {
+                log::info!("Matched position usage for {} of {} players", matched, players.len());
+            }
+        }
+
+        if let Some(bios) = &self.bios {
+            let join = join_bios(players, bios);
+            if report {
+                log::info!("Matched roster bios for {} of {} players", join.matched, players.len());
+                if !join.unmatched.is_empty() {
+                    let shown: Vec<&str> = join.unmatched.iter().take(5).map(String::as_str).collect();
+                    let more = join.unmatched.len() - shown.len();
+                    let more = if more > 0 { format!(" and {} more", more) } else { String::new() };
+                    log::warn!("{} roster rows matched no player: {}{}", join.unmatched.len(), shown.join(", "), more);
+                }
+            }
+        }
+
+        for (situation, stats) in &self.situations {
+            let matched = merge_situation(players, *situation, stats);
+            if report {
+                log::info!("Matched {} stats for {} of {} players", situation, matched, players.len());
+            }
+        }
+
+        let finishing = add_finishing(players);
+        if report && finishing > 0 {
+            log::debug!("Computed finishing (goals - ixG) for {} players", finishing);
+        }
+
+        if !self.baselines.is_empty() {
+            let matched = join_baselines(players, &self.baselines);
+            if report {
+                log::info!("Matched SH% and IPP baselines for {} of {} players", matched, players.len());
+            }
+        }
+
+        if let Some(min_gp) = self.min_gp {
+            let before = players.len();
+            players.retain(|_, player| player.stats.get("GP").is_some_and(|&games| games >= min_gp));
+            if report {
+                log::info!("Left out {} players with fewer than {} GP", before - players.len(), min_gp);
+            }
+        }
+
+        // Re-normalizing from the raw metrics also rescales to the players that are left.
+        if self.rates != Rate::Total || self.min_gp.is_some() || !self.scaling.is_empty() {
+            apply_rates(players, self.rates, &self.scaling);
+        }
+    }
+}
+
+// A split's export, tagged with its venue.
+pub fn load_split(path: &str, venue: Venue, options: &ParseOptions, use_cache: bool, max_skip_pct: Option<f64>, run_log: &RunLog) -> io::Result<PlayerMap> {
+    let mut players = load_dataset(path, options, use_cache, max_skip_pct, run_log)?;
+    let conflicts = tag_venue(&mut players, venue);
+    if conflicts > 0 {
+        log::warn!("{}: {} rows have a split column that doesn't say {}; were the home and road exports swapped?", path, conflicts, venue);
+    }
+    Ok(players)
+}
@@ -0,0 +1,6 @@
+pub mod args;
+pub mod cache;
+pub mod commands;
+pub mod config;
+pub mod load;
+pub mod run;
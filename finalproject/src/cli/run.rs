@@ -0,0 +1,329 @@
+use std::{io, time::{SystemTime, UNIX_EPOCH}};
+use finalproject::age::players_with_age;
+use finalproject::assists::AssistNetwork;
+use finalproject::chemistry::Chemistry;
+use finalproject::config::Config;
+use finalproject::cleaning::{ParseOptions, PlayerMap};
+use finalproject::db;
+use finalproject::engine::ScoringEngine;
+use finalproject::diff::diff_rankings;
+use finalproject::splits::{venue_gaps, Venue};
+use finalproject::draft::DraftBoard;
+use finalproject::emit::RowFiles;
+use finalproject::bio::read_roster_bios;
+use finalproject::even_strength::join_ev_scores;
+use finalproject::salary::read_cap_hits;
+use finalproject::scoring::{rank_players_with, WeightTable};
+use finalproject::model::{Adjustment, ScoringModel};
+use finalproject::usage::read_usage;
+use finalproject::experience::read_rookie_list;
+use finalproject::history::{append_history, current_entries, read_history, timestamp};
+use finalproject::inference::read_roster;
+use finalproject::input::expand_inputs;
+use finalproject::keeper::keeper_values;
+use finalproject::luck::players_with_pdo;
+use finalproject::similarity::similarity_indexes;
+use finalproject::special_teams::{read_situation, Situation};
+use finalproject::teammates::TeammateGraph;
+use finalproject::projection::project;
+use finalproject::runlog::RunLog;
+use finalproject::positions;
+use crate::lookup::{lookup_batch, lookup_loop, lookup_player, print_similar, LookupOptions, Tuning};
+use crate::{draftboard, tui, watch};
+use crate::cli::args::{parse_positions, Cli, Command, RankArgs};
+use crate::cli::load::{load_dataset, load_inputs, load_split, Supplements};
+use crate::cli::config::{config_file, layer_settings, load_scoring_config, scoring_model, weight_table_toml, with_pca_weights};
+use crate::cli::cache::{cached_rankings, run_cache, score_cache_key};
+#[cfg(feature = "grpc")]
+use crate::cli::commands::serve::serve;
+use crate::cli::commands::rankings::{filter_players, print_averages, print_distribution, print_leaders, print_overall, print_rankings, print_scarcity_overall, print_value};
+use crate::cli::commands::export::{export_dataset, export_positions};
+use crate::cli::commands::analysis::{print_audit, print_calibration, print_clutch, print_communities, print_correlations, print_projections, print_regression_candidates, print_sensitivity, print_validation};
+use crate::cli::commands::fantasy::{print_fantasy, print_keepers, write_keepers};
+use crate::cli::commands::teams::{print_assist_network, print_depth_chart, print_special_teams, print_team_comparison, print_teammate_path, season_label};
+use crate::cli::commands::history::{print_diff, print_history, print_venue_gaps};
+use crate::cli::commands::alerts::print_alerts;
+
+pub fn run(mut cli: Cli, run_log: &RunLog) -> io::Result<()> {
+    if let Some(Command::Cache { action }) = &cli.command {
+        return run_cache(action);
+    }
+    let config = load_scoring_config(cli.config.as_deref())?;
+    layer_settings(&mut cli, &config);
+    positions::register(config.position_defs().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let locale = config.locale.resolve(cli.locale.as_deref()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    if let Some(Command::History { player }) = &cli.command {
+        let Some(path) = &cli.history else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "history needs --history FILE (or NHL_SCORES_HISTORY) to read from"));
+        };
+        return print_history(&read_history(path)?, path, player, &locale);
+    }
+    let mapping = config.mapping(cli.profile.as_deref()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let roster = cli.roster.as_deref().map(read_roster).transpose()?.unwrap_or_default();
+    let parse_options = ParseOptions { mapping, report: cli.report, roster, duplicates: cli.duplicates, ..ParseOptions::with_delimiter(cli.delimiter) };
+    let inputs = expand_inputs(&cli.input)?;
+    let bonus = cli.versatility_bonus.unwrap_or(config.overall.versatility_bonus);
+    let supplements = Supplements {
+        cap_hits: cli.salaries.as_deref().map(read_cap_hits).transpose()?,
+        rookies: cli.rookies.as_deref().map(read_rookie_list).transpose()?,
+        usage: cli.usage.as_deref().map(read_usage).transpose()?,
+        bios: cli.bio.as_deref().map(read_roster_bios).transpose()?,
+        situations: [(Situation::PowerPlay, &cli.pp), (Situation::PenaltyKill, &cli.pk)]
+            .into_iter()
+            .filter_map(|(situation, path)| Some((situation, path.as_deref()?)))
+            .map(|(situation, path)| Ok((situation, read_situation(path, &parse_options)?)))
+            .collect::<io::Result<_>>()?,
+        baselines: cli.baselines.iter().map(|path| load_dataset(path, &parse_options, !cli.no_cache, None, run_log)).collect::<io::Result<_>>()?,
+        rates: cli.rates,
+        min_gp: cli.min_gp,
+        scaling: config.scaling(cli.normalization).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?,
+    };
+    let mut players = match &cli.from_db {
+        Some(db_path) => db::load_players(db_path).map_err(io::Error::other)?,
+        None => match &cli.command {
+            Some(Command::Diff { new, .. }) => load_dataset(new, &parse_options, !cli.no_cache, cli.max_skip_pct, run_log)?,
+            Some(Command::Splits { home, .. }) => load_split(home, Venue::Home, &parse_options, !cli.no_cache, cli.max_skip_pct, run_log)?,
+            _ => {
+                let row_files = RowFiles::create(cli.emit_cleaned.as_deref(), cli.emit_rejected.as_deref())?;
+                let players = load_inputs(&inputs, &parse_options, !cli.no_cache, cli.max_skip_pct, run_log, &row_files)?;
+                let (cleaned, rejected) = row_files.finish()?;
+                for (path, rows, kind) in [(&cli.emit_cleaned, cleaned, "cleaned"), (&cli.emit_rejected, rejected, "rejected")] {
+                    if let Some(path) = path {
+                        log::info!("Wrote {} {} rows to {}", rows, kind, path);
+                    }
+                }
+                players
+            }
+        },
+    };
+
+    supplements.apply(&mut players, true);
+    let chemistry = cli.lines.as_deref().map(Chemistry::read).transpose()?.unwrap_or_default();
+    if cli.lines.is_some() {
+        log::info!("Loaded chemistry for {} duos", chemistry.len());
+    }
+
+    let mut weights = config.weight_table();
+    if cli.pca_weights {
+        weights = with_pca_weights(weights, &players);
+        eprintln!("PCA-derived weights (paste into the config to keep them):\n{}", weight_table_toml(&weights));
+    }
+
+    let mut adjustments = cli.adjust.clone();
+    if cli.age_adjust {
+        adjustments.push(Adjustment::Age);
+        if players_with_age(&players) == 0 {
+            log::warn!("--age-adjust: no player has an Age or Birth Date (add the column or join one with --bio FILE); scores are unadjusted");
+        }
+    }
+    if adjustments.contains(&Adjustment::Pdo) && players_with_pdo(&players) == 0 {
+        log::warn!("--adjust pdo: no player has on-ice SH% and SV% or PDO (rerun with --report onice on a Natural Stat Trick on-ice export); scores are unadjusted");
+    }
+    let mut model = scoring_model(&config, &weights, cli.model.as_deref(), cli.report, cli.transform, cli.vor, &adjustments)?;
+    // 5v5 scores are taken against the league's 5v5 numbers, before the model is fit to the input.
+    if let Some(path) = &cli.ev {
+        let mut even_strength = load_dataset(path, &parse_options, !cli.no_cache, cli.max_skip_pct, run_log)?;
+        supplements.apply(&mut even_strength, false);
+        model.fit(&even_strength);
+        let matched = join_ev_scores(&mut players, &even_strength, model.as_ref());
+        log::info!("Matched 5v5 scores for {} of {} players", matched, players.len());
+    }
+    model.fit(&players);
+    let rebuild = |weights: &WeightTable| -> io::Result<Box<dyn ScoringModel>> {
+        let mut model = scoring_model(&config, weights, cli.model.as_deref(), cli.report, cli.transform, cli.vor, &adjustments)?;
+        model.fit(&players);
+        Ok(model)
+    };
+    let lookup_options = LookupOptions { bonus, tiers: &config.tiers, chemistry: &chemistry, locale: &locale };
+    let tuning = || Tuning { weights: weights.clone(), scaling: &supplements.scaling, rebuild: &rebuild };
+    // Re-reads the config for the modes that pick up its changes. Players were normalized when they
+    // loaded, so a changed [normalization] or [direction] table needs a restart.
+    let config_file = config_file(cli.config.as_deref());
+    let startup_positions = config.positions.clone();
+    // Reads the inputs again for the modes that watch them. Stdin and a database can't be watched.
+    let watched_inputs: Vec<&str> = if cli.from_db.is_some() || inputs.iter().any(|input| input == "-") { Vec::new() } else { inputs.iter().map(String::as_str).collect() };
+    let reload_players = |run_log: &RunLog| -> io::Result<PlayerMap> {
+        let mut players = load_inputs(&inputs, &parse_options, !cli.no_cache, cli.max_skip_pct, run_log, &RowFiles::default())?;
+        supplements.apply(&mut players, false);
+        Ok(players)
+    };
+    let reload_model = |players: &PlayerMap| -> io::Result<(Config, Box<dyn ScoringModel>)> {
+        let config = load_scoring_config(cli.config.as_deref())?;
+        if config.scaling(cli.normalization).ok().as_ref() != Some(&supplements.scaling) {
+            log::warn!("[normalization] or [direction] changed; restart to renormalize the players with them");
+        }
+        if config.positions != startup_positions {
+            log::warn!("[positions] changed; restart to parse the players with them");
+        }
+        let weights = if cli.pca_weights { with_pca_weights(config.weight_table(), players) } else { config.weight_table() };
+        let mut model = scoring_model(&config, &weights, cli.model.as_deref(), cli.report, cli.transform, cli.vor, &adjustments)?;
+        model.fit(players);
+        Ok((config, model))
+    };
+
+    if let Some(db_path) = &cli.save_db {
+        db::save_players(db_path, &players).map_err(io::Error::other)?;
+    }
+    if let Some(path) = &cli.history {
+        let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+        let entries = current_entries(&players, model.as_ref(), &timestamp(seconds));
+        if append_history(path, &entries)? {
+            log::info!("Recorded {} scores in {}", entries.len(), path);
+        } else {
+            log::info!("Scores unchanged since the last run in {}", path);
+        }
+    }
+
+    let score_key = match &cli.command {
+        Some(Command::Rank(args)) if !cli.no_cache && args.by.is_none() && !args.watch => {
+            score_cache_key(&cli, &inputs, &adjustments, config.age_curve.reference_day().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?)?
+        }
+        _ => None,
+    };
+
+    match cli.command {
+        Some(Command::Rank(mut args)) => {
+            args.reference_day = config.age_curve.reference_day().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            args.selected = parse_positions(&args.positions)?;
+            let selected = filter_players(&players, args.filter.as_ref(), model.as_ref(), bonus);
+            print_rankings(&selected, &args, model.as_ref(), &config.tiers, &cached_rankings(&players, model.as_ref(), score_key), &locale);
+            if args.watch {
+                if watched_inputs.is_empty() {
+                    eprintln!("--watch needs a CSV file and cannot be combined with --from-db or stdin input");
+                } else {
+                    let mut files = watched_inputs.clone();
+                    files.extend(config_file);
+                    // Edits to the export only rescore the players they touched.
+                    let mut engine = ScoringEngine::new(players.clone(), model, supplements.scaling.clone());
+                    let mut tiers = config.tiers.clone();
+                    watch::watch_files(&files, |changed| {
+                        if config_file.is_some_and(|config_file| watch::is_file(config_file, changed)) {
+                            let (config, reloaded) = reload_model(engine.players())?;
+                            log::info!("Reloaded {} with the {} model", changed.display(), reloaded.name());
+                            engine.set_model(reloaded);
+                            tiers = config.tiers;
+                        } else {
+                            let rescored = engine.sync(reload_players(run_log)?);
+                            log::debug!("Rescored {} players", rescored);
+                        }
+                        let selected = filter_players(engine.players(), args.filter.as_ref(), engine.model(), bonus);
+                        print_rankings(&selected, &args, engine.model(), &tiers, &engine.rankings(), &locale);
+                        Ok(())
+                    })?;
+                }
+            }
+        }
+        Some(Command::Overall { top, scarcity: true }) => print_scarcity_overall(&players, model.as_ref(), top, &locale),
+        Some(Command::Overall { top, .. }) => print_overall(&players, model.as_ref(), top, bonus, &locale),
+        Some(Command::Lookup { batch: Some(names_path), format, .. }) => {
+            lookup_batch(&players, &names_path, format, model.as_ref(), &config.tiers, &locale)?
+        }
+        Some(Command::Lookup { name: Some(name), position, .. }) => {
+            lookup_player(&players, model.as_ref(), &lookup_options, &name, parse_positions(position.as_slice())?.first())
+        }
+        Some(Command::Lookup { position, .. }) => lookup_loop(&players, model.as_ref(), &lookup_options, parse_positions(position.as_slice())?.first(), tuning())?,
+        Some(Command::Leaders { metric, top }) => print_leaders(&players, &metric, top, &locale),
+        Some(Command::Averages { positions }) => print_averages(&players, &parse_positions(&positions)?, &locale),
+        Some(Command::Distribution { positions }) => print_distribution(&players, model.as_ref(), &parse_positions(&positions)?),
+        Some(Command::Project { top, previous, format }) => {
+            let previous = match previous {
+                Some(path) => {
+                    let mut previous = load_dataset(&path, &parse_options, !cli.no_cache, cli.max_skip_pct, run_log)?;
+                    supplements.apply(&mut previous, false);
+                    Some(previous)
+                }
+                None => None,
+            };
+            let reference_day = config.age_curve.reference_day().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            let projections = project(&players, model.as_ref(), &config.age_curve, reference_day, previous.as_ref());
+            print_projections(&projections, model.as_ref(), top, format, &locale)?
+        }
+        Some(Command::Fantasy { points, top, games, player }) => print_fantasy(&players, &points, top, games, player.as_deref(), &locale),
+        Some(Command::Calibrate { target, ratings, positions }) => {
+            print_calibration(&players, &target, ratings.as_deref(), &parse_positions(&positions)?, &locale)?
+        }
+        Some(Command::Sensitivity { delta, top, positions }) => print_sensitivity(&players, delta, top, &parse_positions(&positions)?, &locale),
+        Some(Command::Validate { ratings, top }) => print_validation(&players, model.as_ref(), &ratings, top, &locale)?,
+        Some(Command::Value { top, positions }) => print_value(&players, model.as_ref(), top, &parse_positions(&positions)?, &locale),
+        Some(Command::Correlations { metrics, positions }) => print_correlations(&players, &metrics, &parse_positions(&positions)?, &locale)?,
+        Some(Command::Audit { z }) => print_audit(&players, z, &locale),
+        Some(Command::Pp { top }) => print_special_teams(&players, Situation::PowerPlay, top, &locale),
+        Some(Command::Pk { top }) => print_special_teams(&players, Situation::PenaltyKill, top, &locale),
+        Some(Command::Diff { old, top, .. }) => {
+            let new_rankings = rank_players_with(&players, model.as_ref());
+            let mut old_players = load_dataset(&old, &parse_options, !cli.no_cache, cli.max_skip_pct, run_log)?;
+            supplements.apply(&mut old_players, false);
+            model.fit(&old_players);
+            let diff = diff_rankings(&rank_players_with(&old_players, model.as_ref()), &new_rankings);
+            print_diff(&diff, model.as_ref(), top, &locale);
+        }
+        Some(Command::Splits { road, top, .. }) => {
+            let home_rankings = rank_players_with(&players, model.as_ref());
+            let mut road_players = load_split(&road, Venue::Road, &parse_options, !cli.no_cache, cli.max_skip_pct, run_log)?;
+            supplements.apply(&mut road_players, false);
+            model.fit(&road_players);
+            print_venue_gaps(&venue_gaps(&home_rankings, &rank_players_with(&road_players, model.as_ref())), model.as_ref(), top, &locale);
+        }
+        Some(Command::Export { dir, dataset, filter }) => {
+            let selected = filter_players(&players, filter.as_ref(), model.as_ref(), bonus);
+            match dataset {
+                Some(path) => export_dataset(&selected, model.as_ref(), &path, &locale)?,
+                None => export_positions(&players, &selected, model.as_ref(), &config.tiers, &dir, &locale)?,
+            }
+        }
+        Some(Command::Depthchart { team }) => print_depth_chart(&players, model.as_ref(), &team, &locale),
+        Some(Command::CompareTeams { first, second }) => print_team_comparison(&players, model.as_ref(), &first, &second, &locale),
+        Some(Command::Assists { events, player, top }) => print_assist_network(&AssistNetwork::read(&events)?, player.as_deref(), top),
+        Some(Command::Path { from, to, seasons }) => {
+            let mut teammates = TeammateGraph::new();
+            teammates.add_season(&season_label(&inputs[0]), &players);
+            for path in &seasons {
+                teammates.add_season(&season_label(path), &load_dataset(path, &parse_options, !cli.no_cache, None, run_log)?);
+            }
+            print_teammate_path(&teammates, &from, &to)
+        }
+        Some(Command::Similar { name, top }) => print_similar(&players, model.as_ref(), &similarity_indexes(&players), &name, top, &locale),
+        Some(Command::Communities { positions, neighbors, members }) => print_communities(&players, &parse_positions(&positions)?, neighbors, members, &locale),
+        #[cfg(feature = "grpc")]
+        Some(Command::Serve { addr }) => {
+            let engine = ScoringEngine::new(players, model, supplements.scaling.clone());
+            serve(addr, engine, &watched_inputs, config_file, &reload_model, &|| reload_players(&RunLog::default()))?
+        }
+        Some(Command::Draftboard { top, teams, taken, no_prompt }) => {
+            let mut league = config.draft.clone();
+            league.teams = teams.unwrap_or(league.teams);
+            draftboard::run(DraftBoard::new(&players, model.as_ref(), &league, bonus), top, taken.as_deref(), !no_prompt, &locale)?
+        }
+        Some(Command::Keepers { top, seasons, discount, points, out }) => {
+            let mut league = config.keeper.clone();
+            league.seasons = seasons.unwrap_or(league.seasons);
+            league.discount = discount.unwrap_or(league.discount);
+            league.points = points.unwrap_or(league.points);
+            let values = league.validate().and_then(|_| league.point_values()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            let reference_day = config.age_curve.reference_day().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            let keepers = keeper_values(&players, &values, &league, &config.age_curve, reference_day);
+            if let Some(path) = &out {
+                write_keepers(&keepers, path, &locale)?;
+            }
+            print_keepers(&keepers, top, league.discount, &locale);
+        }
+        Some(Command::Regression { top }) => print_regression_candidates(&players, top, !cli.baselines.is_empty(), &locale),
+        Some(Command::Clutch { top }) => print_clutch(&players, top, &locale),
+        Some(Command::Alerts { rules }) => {
+            let rules: Vec<(Option<&str>, &str)> = if rules.is_empty() {
+                config.alerts.iter().map(|(name, rule)| (Some(name.as_str()), rule.as_str())).collect()
+            } else {
+                rules.iter().map(|rule| (None, rule.as_str())).collect()
+            };
+            print_alerts(&players, model.as_ref(), bonus, &rules, &locale)?
+        }
+        Some(Command::Tui) => tui::run(&players, model.as_ref(), &locale)?,
+        Some(Command::Cache { .. } | Command::History { .. }) => unreachable!("cache and history commands run before the input is loaded"),
+        None => {
+            print_rankings(&players, &RankArgs::default(), model.as_ref(), &config.tiers, &rank_players_with(&players, model.as_ref()), &locale);
+            lookup_loop(&players, model.as_ref(), &lookup_options, None, tuning())?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use std::{collections::HashMap, sync::Arc};
+use crate::cleaning::PlayerMap;
+use crate::stats::average_ranks;
+
+pub const SHOOTOUT_GOALS_STAT: &str = "Shootout Goals";
+pub const SHOOTOUT_ATTEMPTS_STAT: &str = "Shootout Attempts";
+pub const OT_POINTS_STAT: &str = "OT Points";
+
+// Shootout percentages are pulled toward the league's by this many attempts' worth, so a 2-for-2
+// night doesn't top the board, and OT scoring rates by this many games' worth. The default rate is
+// used when nobody has attempted a shootout.
+pub const SHOOTOUT_PRIOR_ATTEMPTS: f64 = 10.0;
+pub const OT_PRIOR_GAMES: f64 = 20.0;
+pub const DEFAULT_SHOOTOUT_PCT: f64 = 0.33;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clutch {
+    pub name: Arc<str>,
+    // 0-100: the mean of the player's percentiles in shootout conversion and OT points per game.
+    pub score: f64,
+    pub shootout_goals: Option<f64>,
+    pub shootout_attempts: Option<f64>,
+    pub shootout_pct: Option<f64>,
+    pub ot_points: Option<f64>,
+}
+
+pub fn has_clutch_stats(players: &PlayerMap) -> bool {
+    players.values().any(|player| [SHOOTOUT_ATTEMPTS_STAT, OT_POINTS_STAT].iter().any(|stat| player.stats.contains_key(stat)))
+}
+
+// Every player with shootout attempts or an OT points figure, best first. Each side is ranked only
+// among the players who have it, and a player missing one is scored on the other alone.
+pub fn clutch_scores(players: &PlayerMap) -> Vec<Clutch> {
+    let (goals, attempts): (f64, f64) = players
+        .values()
+        .filter_map(|player| Some((*player.stats.get(SHOOTOUT_GOALS_STAT)?, *player.stats.get(SHOOTOUT_ATTEMPTS_STAT)?)))
+        .fold((0.0, 0.0), |(goals, attempts), (g, a)| (goals + g, attempts + a));
+    let league_pct = if attempts > 0.0 { goals / attempts } else { DEFAULT_SHOOTOUT_PCT };
+
+    let shootout: Vec<(Arc<str>, f64)> = players
+        .values()
+        .filter_map(|player| {
+            let attempts = player.stats.get(SHOOTOUT_ATTEMPTS_STAT).copied().filter(|attempts| *attempts > 0.0)?;
+            let goals = player.stats.get(SHOOTOUT_GOALS_STAT).copied().unwrap_or(0.0);
+            Some((player.name.clone(), (goals + league_pct * SHOOTOUT_PRIOR_ATTEMPTS) / (attempts + SHOOTOUT_PRIOR_ATTEMPTS)))
+        })
+        .collect();
+    let ot_games: Vec<(&Arc<str>, f64, f64)> = players
+        .values()
+        .filter_map(|player| Some((&player.name, *player.stats.get(OT_POINTS_STAT)?, player.stats.get("GP").copied().filter(|games| *games > 0.0)?)))
+        .collect();
+    let (points, games) = ot_games.iter().fold((0.0, 0.0), |(points, games), (_, p, g)| (points + p, games + g));
+    let league_rate = if games > 0.0 { points / games } else { 0.0 };
+    let overtime: Vec<(Arc<str>, f64)> =
+        ot_games.into_iter().map(|(name, points, games)| (name.clone(), (points + league_rate * OT_PRIOR_GAMES) / (games + OT_PRIOR_GAMES))).collect();
+
+    let mut percentiles: HashMap<Arc<str>, Vec<f64>> = HashMap::new();
+    for values in [&shootout, &overtime] {
+        let ranks = average_ranks(&values.iter().map(|(_, value)| *value).collect::<Vec<_>>());
+        for ((name, _), rank) in values.iter().zip(ranks) {
+            percentiles.entry(name.clone()).or_default().push(100.0 * (rank - 0.5) / values.len() as f64);
+        }
+    }
+
+    let mut clutch: Vec<Clutch> = players
+        .values()
+        .filter_map(|player| {
+            let percentiles = percentiles.get(&player.name)?;
+            let stat = |name: &str| player.stats.get(name).copied();
+            Some(Clutch {
+                name: player.name.clone(),
+                score: percentiles.iter().sum::<f64>() / percentiles.len() as f64,
+                shootout_goals: stat(SHOOTOUT_GOALS_STAT),
+                shootout_attempts: stat(SHOOTOUT_ATTEMPTS_STAT),
+                shootout_pct: shootout.iter().find(|(name, _)| *name == player.name).map(|(_, pct)| *pct),
+                ot_points: stat(OT_POINTS_STAT),
+            })
+        })
+        .collect();
+    clutch.sort_by(|a, b| b.score.total_cmp(&a.score));
+    clutch
+}
+
+// e.g. `7/15 in shootouts, 4 OT points`, leaving out whichever the player has no figure for.
+pub fn clutch_line(clutch: &Clutch) -> String {
+    let mut parts = Vec::new();
+    if let Some(attempts) = clutch.shootout_attempts {
+        parts.push(format!("{}/{} in shootouts", clutch.shootout_goals.unwrap_or(0.0), attempts));
+    }
+    if let Some(points) = clutch.ot_points {
+        parts.push(format!("{} OT points", points));
+    }
+    parts.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cleaning::{Player, Position};
+
+    #[test]
+    fn test_clutch_scores_shrink_small_shootout_samples() {
+        let player = |name: &str, stats: &[(&str, f64)]| {
+            let mut player = Player::new(name, vec![Position::Wing], HashMap::new());
+            player.stats = stats.iter().map(|&(stat, value)| (stat.to_string(), value)).collect();
+            (player.name.clone(), player)
+        };
+        let players = PlayerMap::from_iter([
+            player("Lucky", &[(SHOOTOUT_GOALS_STAT, 2.0), (SHOOTOUT_ATTEMPTS_STAT, 2.0), ("GP", 82.0)]),
+            player("Sniper", &[(SHOOTOUT_GOALS_STAT, 15.0), (SHOOTOUT_ATTEMPTS_STAT, 20.0), (OT_POINTS_STAT, 8.0), ("GP", 82.0)]),
+            player("Grinder", &[(SHOOTOUT_GOALS_STAT, 1.0), (SHOOTOUT_ATTEMPTS_STAT, 10.0), (OT_POINTS_STAT, 1.0), ("GP", 82.0)]),
+            player("Nobody", &[("GP", 82.0)]),
+        ]);
+        assert!(has_clutch_stats(&players));
+
+        let clutch = clutch_scores(&players);
+        let names: Vec<&str> = clutch.iter().map(|clutch| &*clutch.name).collect();
+        assert_eq!(names, ["Sniper", "Lucky", "Grinder"]);
+        // 2-for-2 is shrunk below 15-for-20, and Lucky is scored on the shootout alone.
+        assert!(clutch[1].shootout_pct < clutch[0].shootout_pct);
+        assert_eq!((clutch[1].score, clutch[1].ot_points), (50.0, None));
+    }
+}
@@ -0,0 +1,104 @@
+use std::{collections::BTreeMap, sync::Arc};
+use serde::Serialize;
+use crate::cleaning::{PlayerMap, Position};
+use crate::graph::Graph;
+use crate::similarity::similarity_graph;
+
+const MAX_ROUNDS: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Community {
+    pub position: Position,
+    pub members: Vec<Arc<str>>,
+    // The members most strongly linked to the rest of the community.
+    pub representatives: Vec<Arc<str>>,
+    // Mean raw value of each of the position's metrics.
+    pub profile: Vec<f64>,
+}
+
+// Label propagation: every node starts in its own community and repeatedly joins the one its
+// neighbors' links weigh most toward, until nothing changes. Nodes are visited in id order and ties
+// go to the lowest label, so the result is the same every run. Largest communities first.
+pub fn label_propagation(graph: &Graph) -> Vec<Vec<usize>> {
+    let mut labels: Vec<usize> = (0..graph.len()).collect();
+    for _ in 0..MAX_ROUNDS {
+        let mut changed = false;
+        for id in 0..graph.len() {
+            let mut weights: BTreeMap<usize, f64> = BTreeMap::new();
+            for (neighbor, weight) in graph.out_edges(id) {
+                *weights.entry(labels[neighbor]).or_default() += weight;
+            }
+            let best = weights.into_iter().fold(None, |best: Option<(usize, f64)>, (label, weight)| match best {
+                Some((_, best_weight)) if best_weight >= weight => best,
+                _ => Some((label, weight)),
+            });
+            if let Some((label, _)) = best.filter(|&(label, _)| label != labels[id]) {
+                labels[id] = label;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (id, label) in labels.into_iter().enumerate() {
+        groups.entry(label).or_default().push(id);
+    }
+    let mut communities: Vec<Vec<usize>> = groups.into_values().collect();
+    communities.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(&b[0])));
+    communities
+}
+
+pub fn position_communities(players: &PlayerMap, position: &Position, neighbors: usize, representatives: usize) -> Vec<Community> {
+    let graph = similarity_graph(players, position, neighbors);
+    label_propagation(&graph)
+        .into_iter()
+        .map(|members| {
+            let strength = |id: usize| graph.out_edges(id).filter(|(neighbor, _)| members.contains(neighbor)).map(|(_, w)| w).sum::<f64>();
+            let mut central = members.clone();
+            central.sort_by(|&a, &b| strength(b).partial_cmp(&strength(a)).unwrap_or(std::cmp::Ordering::Equal));
+
+            let raw: Vec<&Vec<f64>> = members.iter().filter_map(|&id| players.get(graph.name(id))?.raw_metrics.get(position)).collect();
+            let width = raw.first().map_or(0, |metrics| metrics.len());
+            let profile = (0..width).map(|i| raw.iter().map(|metrics| metrics[i]).sum::<f64>() / raw.len() as f64).collect();
+
+            Community {
+                position: position.clone(),
+                members: members.iter().map(|&id| graph.name(id).clone()).collect(),
+                representatives: central.into_iter().take(representatives).map(|id| graph.name(id).clone()).collect(),
+                profile,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::cleaning::Player;
+
+    #[test]
+    fn test_label_propagation_separates_clusters() {
+        let mut graph = Graph::new();
+        let ids: Vec<usize> = ["A", "B", "C", "X", "Y", "Z"].iter().map(|name| graph.node(name)).collect();
+        for (a, b, weight) in [(0, 1, 1.0), (1, 2, 1.0), (0, 2, 1.0), (3, 4, 1.0), (4, 5, 1.0), (3, 5, 1.0), (2, 3, 0.1)] {
+            graph.add_undirected(ids[a], ids[b], weight);
+        }
+        assert_eq!(label_propagation(&graph), vec![vec![0, 1, 2], vec![3, 4, 5]]);
+    }
+
+    #[test]
+    fn test_position_communities_profile_raw_metrics() {
+        let wing = |name: &str, value: f64| (name.into(), Player::new(name, vec![Position::Wing], HashMap::from([(Position::Wing, vec![value; 5])])));
+        let players = PlayerMap::from_iter([wing("A", 0.1), wing("B", 0.12), wing("C", 0.9), wing("D", 0.92)]);
+
+        let communities = position_communities(&players, &Position::Wing, 1, 1);
+        assert_eq!(communities.len(), 2);
+        assert_eq!(communities[0].members.iter().map(|name| &**name).collect::<Vec<_>>(), ["A", "B"]);
+        assert_eq!(communities[0].representatives.len(), 1);
+        assert!((communities[1].profile[0] - 0.91).abs() < 1e-9);
+    }
+}
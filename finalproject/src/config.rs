@@ -0,0 +1,404 @@
+use std::{collections::{BTreeMap, HashMap}, fs, io, path::Path};
+use serde::Deserialize;
+use crate::age::AgeCurve;
+use crate::cleaning::Position;
+use crate::draft::DraftConfig;
+use crate::formula::{Formula, FormulaSet};
+use crate::keeper::KeeperConfig;
+use crate::locale::LocaleConfig;
+use crate::mapping::ColumnMapping;
+use crate::metric::{Direction, Metric, Normalization, Scaling, METRICS};
+use crate::model::ReplacementLevel;
+use crate::scoring::{metric_names, Sigmoid, WeightTable};
+use crate::tiers::Tiers;
+use crate::xg::XgConfig;
+use crate::positions::{self, PositionDef};
+
+pub const DEFAULT_CONFIG_FILE: &str = "nhl_scores.toml";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub model: Option<String>,
+    pub profile: Option<String>,
+    pub min_gp: Option<f64>,
+    pub top: Option<usize>,
+    pub formula: FormulaConfig,
+    pub replacement: ReplacementLevel,
+    pub draft: DraftConfig,
+    pub keeper: KeeperConfig,
+    pub age_curve: AgeCurve,
+    pub overall: OverallConfig,
+    pub columns: ColumnMapping,
+    pub profiles: BTreeMap<String, ColumnMapping>,
+    pub weights: WeightConfig,
+    pub sigmoid: SigmoidConfig,
+    pub tiers: Tiers,
+    pub normalization: BTreeMap<String, Normalization>,
+    pub direction: BTreeMap<String, Direction>,
+    pub xg: XgConfig,
+    pub positions: BTreeMap<String, PositionConfig>,
+    // Named rules for the `alerts` command, e.g. `breakout = "defense score > 85 AND gp < 40"`. They
+    // can name the config's own positions, so they're parsed when the command runs.
+    pub alerts: BTreeMap<String, String>,
+    pub locale: LocaleConfig,
+}
+
+// A position group beyond center, wing and defense, keyed by the code the input's Position column
+// uses for it, e.g. `[positions.F]`. It's scored on any of the report's stats, one weight for each.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PositionConfig {
+    pub label: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub metrics: Vec<String>,
+    pub weights: Option<Vec<f64>>,
+    #[serde(default = "default_slots")]
+    pub slots: usize,
+}
+
+fn default_slots() -> usize {
+    6
+}
+
+impl PositionConfig {
+    fn metrics(&self) -> Result<Vec<Metric>, String> {
+        self.metrics
+            .iter()
+            .map(|name| Metric::from_name(name).ok_or_else(|| format!("unknown metric '{}'", name)))
+            .collect()
+    }
+
+    // Equal weights when none are given.
+    fn weights(&self) -> Result<Vec<f64>, String> {
+        let Some(weights) = self.weights.clone() else { return Ok(vec![1.0 / self.metrics.len().max(1) as f64; self.metrics.len()]) };
+        if weights.len() != self.metrics.len() {
+            return Err(format!("has {} weights for {} metrics", weights.len(), self.metrics.len()));
+        }
+        if weights.iter().any(|weight| !weight.is_finite() || *weight < 0.0) {
+            return Err("weights must be non-negative".to_string());
+        }
+        let total: f64 = weights.iter().sum();
+        if (total - 1.0).abs() > WEIGHT_SUM_TOLERANCE {
+            return Err(format!("weights sum to {}, expected 1.0", total));
+        }
+        Ok(weights)
+    }
+
+    fn definition(&self, id: &str) -> Result<PositionDef, String> {
+        PositionDef::new(id, &self.label, &self.aliases, self.metrics()?, self.weights()?, self.slots)
+    }
+
+    fn validate(&self, id: &str) -> Result<(), String> {
+        for name in [id, &self.label].into_iter().chain(self.aliases.iter().map(String::as_str)) {
+            if let Ok(position @ (Position::Center | Position::Wing | Position::Defense)) = name.parse::<Position>() {
+                return Err(format!("'{}' already names the built-in {} position", name, position));
+            }
+        }
+        self.definition(id).map(|_| ())
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SigmoidConfig {
+    pub center: Option<Sigmoid>,
+    pub wing: Option<Sigmoid>,
+    pub defense: Option<Sigmoid>,
+}
+
+impl SigmoidConfig {
+    pub fn overrides(&self) -> HashMap<Position, Sigmoid> {
+        [(Position::Center, self.center), (Position::Wing, self.wing), (Position::Defense, self.defense)]
+            .into_iter()
+            .filter_map(|(position, sigmoid)| Some((position, sigmoid?)))
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (position, sigmoid) in self.overrides() {
+            if !(sigmoid.scale.is_finite() && sigmoid.scale > 0.0 && sigmoid.midpoint.is_finite()) {
+                let name = position.to_string().to_lowercase();
+                return Err(format!("sigmoid.{}: scale must be positive and midpoint finite, got {:?}", name, sigmoid));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WeightConfig {
+    pub center: Option<Vec<f64>>,
+    pub wing: Option<Vec<f64>>,
+    pub defense: Option<Vec<f64>>,
+    pub allow_negative: bool,
+}
+
+// Loose enough for weights written to three decimals, like the ones --pca-weights prints.
+const WEIGHT_SUM_TOLERANCE: f64 = 0.01;
+
+impl WeightConfig {
+    fn positions(&self) -> [(Position, &Option<Vec<f64>>); 3] {
+        [(Position::Center, &self.center), (Position::Wing, &self.wing), (Position::Defense, &self.defense)]
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (position, weights) in self.positions() {
+            let Some(weights) = weights else { continue };
+            let name = position.to_string().to_lowercase();
+            let metrics = metric_names(&position);
+
+            if weights.len() != metrics.len() {
+                return Err(format!(
+                    "weights.{} has {} values but {} is scored on {} metrics ({})",
+                    name,
+                    weights.len(),
+                    position,
+                    metrics.len(),
+                    metrics.join(", ")
+                ));
+            }
+            if let Some((i, weight)) = weights.iter().enumerate().find(|(_, w)| !w.is_finite() || (**w < 0.0 && !self.allow_negative)) {
+                return Err(format!(
+                    "weights.{} gives {} a weight of {}; set allow_negative = true in [weights] to allow negative weights",
+                    name, metrics[i], weight
+                ));
+            }
+            let total: f64 = weights.iter().sum();
+            if (total - 1.0).abs() > WEIGHT_SUM_TOLERANCE {
+                return Err(format!("weights.{} sums to {}, expected 1.0", name, total));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OverallConfig {
+    pub versatility_bonus: f64,
+}
+
+// `score` applies to every position that doesn't have its own formula.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FormulaConfig {
+    pub score: Option<String>,
+    pub center: Option<String>,
+    pub wing: Option<String>,
+    pub defense: Option<String>,
+}
+
+impl FormulaConfig {
+    fn for_position(&self, position: &Position) -> Option<&String> {
+        let specific = match position {
+            Position::Center => &self.center,
+            Position::Wing => &self.wing,
+            Position::Defense => &self.defense,
+            Position::Custom(_) => &None,
+        };
+        specific.as_ref().or(self.score.as_ref())
+    }
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let config: Config = toml::from_str(text).map_err(|e| e.message().to_string())?;
+        config.columns.validate()?;
+        config.weights.validate()?;
+        config.sigmoid.validate()?;
+        config.keeper.validate()?;
+        config.xg.weights()?;
+        config.scaling(None)?;
+        config.locale.resolve(None)?;
+        for (id, position) in &config.positions {
+            position.validate(id).map_err(|e| format!("positions.{}: {}", id, e))?;
+        }
+        for (name, profile) in &config.profiles {
+            profile.validate().map_err(|e| format!("profile '{}': {}", name, e))?;
+        }
+        Ok(config)
+    }
+
+    pub fn weight_table(&self) -> WeightTable {
+        WeightTable {
+            overrides: self.weights.positions().into_iter().filter_map(|(position, weights)| Some((position, weights.clone()?))).collect(),
+        }
+    }
+
+    // `[normalization]` and `[direction]` are keyed by metric name, e.g. `"SH%" = "zscore"` and
+    // `giveaways = "lower"`.
+    // A `strategy` from `--normalization` applies to every metric, over the `[normalization]` table.
+    pub fn scaling(&self, strategy: Option<Normalization>) -> Result<Scaling, String> {
+        let normalization = match strategy {
+            Some(strategy) => METRICS.iter().map(|&metric| (metric, strategy)).collect(),
+            None => by_metric("normalization", &self.normalization)?,
+        };
+        Ok(Scaling { normalization, direction: by_metric("direction", &self.direction)? })
+    }
+
+    // A selected profile replaces the `[columns]` table.
+    pub fn mapping(&self, profile: Option<&str>) -> Result<ColumnMapping, String> {
+        let Some(name) = profile.or(self.profile.as_deref()) else { return Ok(self.columns.clone()) };
+        self.profiles.get(name).cloned().ok_or_else(|| {
+            let names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            format!("unknown column profile '{}', expected one of: {}", name, names.join(", "))
+        })
+    }
+
+    // The `[positions]` to register before parsing, in a stable order.
+    pub fn position_defs(&self) -> Result<Vec<PositionDef>, String> {
+        self.positions
+            .iter()
+            .map(|(id, position)| position.definition(id).map_err(|e| format!("positions.{}: {}", id, e)))
+            .collect()
+    }
+
+    pub fn formulas(&self) -> Result<FormulaSet, String> {
+        let mut formulas = HashMap::new();
+        for position in positions::all() {
+            if let Some(source) = self.formula.for_position(position) {
+                let formula = Formula::parse(source).map_err(|e| format!("{} formula: {}", position, e))?;
+                formulas.insert(position.clone(), formula);
+            }
+        }
+        Ok(FormulaSet::new(formulas))
+    }
+}
+
+// A table keyed by metric name, with the table named in the error for a name that isn't one.
+fn by_metric<T: Copy>(table: &str, values: &BTreeMap<String, T>) -> Result<HashMap<Metric, T>, String> {
+    values
+        .iter()
+        .map(|(name, &value)| match Metric::from_name(name) {
+            Some(metric) => Ok((metric, value)),
+            None => Err(format!("{}: unknown metric '{}'", table, name)),
+        })
+        .collect()
+}
+
+pub fn load_config(path: &Path) -> io::Result<Config> {
+    let text = fs::read_to_string(path)?;
+    Config::parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formula_config_falls_back_to_score() {
+        let config = Config::parse(
+            r#"
+            [formula]
+            score = "0.4*goals + 0.3*points_per60 + 0.3*takeaways"
+            defense = "0.5*blocks + 0.5*hits"
+            "#,
+        )
+        .unwrap();
+
+        let formulas = config.formulas().unwrap();
+        assert_eq!(formulas.formulas[&Position::Center].variables.len(), 3);
+        assert_eq!(formulas.formulas[&Position::Defense].source, "0.5*blocks + 0.5*hits");
+
+        assert!(Config::parse("[formula]\nforward = \"goals\"").is_err());
+        assert!(Config::parse("[formula]\nwing = \"goals +\"").unwrap().formulas().is_err());
+    }
+
+    #[test]
+    fn test_profiles_replace_columns() {
+        let config = Config::parse(
+            r#"
+            [columns]
+            score_adjusted = true
+
+            [profiles.nst2024.map]
+            "TkA" = "Takeaways"
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.mapping(None).unwrap().score_adjusted);
+        let profile = config.mapping(Some("nst2024")).unwrap();
+        assert!(!profile.score_adjusted);
+        assert_eq!(profile.map["TkA"], "Takeaways");
+        assert!(config.mapping(Some("moneypuck")).is_err());
+
+        assert!(Config::parse("[profiles.bad.map]\nW = \"Wins\"").is_err());
+    }
+
+    #[test]
+    fn test_weight_table_overrides_configured_positions() {
+        let config = Config::parse("[weights]\nwing = [0.2, 0.2, 0.2, 0.2, 0.2]").unwrap();
+        let weights = config.weight_table();
+        assert_eq!(weights.get(&Position::Wing), &[0.2; 5]);
+        assert_eq!(weights.get(&Position::Center), crate::scoring::position_weights(&Position::Center).0);
+    }
+
+    #[test]
+    fn test_positions_table_declares_extra_positions() {
+        let config = Config::parse(
+            r#"
+            [positions.F]
+            label = "Forward"
+            aliases = ["fwd"]
+            metrics = ["Goals", "Total Points", "IPP", "Takeaways", "Hits"]
+            weights = [0.3, 0.3, 0.15, 0.15, 0.1]
+            "#,
+        )
+        .unwrap();
+        let defs = config.position_defs().unwrap();
+        assert_eq!((defs[0].id.as_str(), defs[0].label.as_str(), defs[0].slots), ("F", "Forward", 6));
+        assert_eq!(defs[0].metrics[2], Metric::Ipp);
+
+        let metrics = r#"metrics = ["Goals", "Total Points", "IPP", "Takeaways", "Hits"]"#;
+        assert!(Config::parse(&format!("[positions.G]\nlabel = \"Goalie\"\n{}", metrics)).is_ok());
+        assert!(Config::parse(&format!("[positions.L]\nlabel = \"Left\"\n{}", metrics)).is_err());
+        assert!(Config::parse(&format!("[positions.X]\nlabel = \"Rover\"\nweights = [0.5, 0.5]\n{}", metrics)).is_err());
+        assert!(Config::parse("[positions.X]\nlabel = \"Rover\"\nmetrics = [\"Goals\", \"Saves\"]").is_err());
+        assert!(Config::parse("[positions.X]\nlabel = \"Rover\"\nmetrics = []").is_err());
+
+        // Any of the report's stats, as many as the position needs.
+        let config = Config::parse("[positions.G]\nlabel = \"Goalie\"\nmetrics = [\"Shots Blocked\", \"Giveaways\", \"TOI\"]").unwrap();
+        let goalie = &config.position_defs().unwrap()[0];
+        assert_eq!((goalie.metrics.len(), goalie.weights.iter().sum::<f64>()), (3, 1.0));
+    }
+
+    #[test]
+    fn test_sigmoid_overrides() {
+        let config = Config::parse("[sigmoid.wing]\nscale = 8.0\nmidpoint = 0.4").unwrap();
+        let sigmoids = config.sigmoid.overrides();
+        assert_eq!(sigmoids[&Position::Wing], Sigmoid { scale: 8.0, midpoint: 0.4 });
+        assert!(!sigmoids.contains_key(&Position::Center));
+        assert_eq!(sigmoids[&Position::Wing].apply(0.4), 50.0);
+
+        assert_eq!(Config::parse("[sigmoid.center]\nmidpoint = 0.5").unwrap().sigmoid.center.unwrap().scale, 5.0);
+        assert!(Config::parse("[sigmoid.center]\nscale = 0").is_err());
+    }
+
+    #[test]
+    fn test_weights_are_validated() {
+        assert!(Config::parse("[weights]\nwing = [0.5, 0.5]").unwrap_err().contains("2 values"));
+        assert!(Config::parse("[weights]\nwing = [0.3, 0.3, 0.3, 0.3, 0.3]").unwrap_err().contains("sums to"));
+        assert!(Config::parse("[weights]\ndefense = [0.6, -0.2, 0.2, 0.2, 0.2]").unwrap_err().contains("allow_negative"));
+        assert!(Config::parse("[weights]\nallow_negative = true\ndefense = [0.6, -0.2, 0.2, 0.2, 0.2]").is_ok());
+        assert!(Config::parse("[weights]\ncenter = [0.333, 0.333, 0.111, 0.111, 0.111]").is_ok());
+    }
+
+    #[test]
+    fn test_scaling_overrides() {
+        let config = Config::parse("[normalization]\n\"SH%\" = \"zscore\"\nhits = \"min-max\"\n[direction]\nhits = \"lower\"\npim = \"higher\"").unwrap();
+        let scaling = config.scaling(None).unwrap();
+        assert_eq!(scaling.normalization[&Metric::ShootingPct], Normalization::ZScore);
+        assert_eq!(scaling.normalization[&Metric::Hits], Normalization::MinMax);
+        assert_eq!((scaling.direction(Metric::Hits), scaling.direction(Metric::Pim)), (Direction::LowerIsBetter, Direction::HigherIsBetter));
+        assert_eq!(scaling.direction(Metric::Giveaways), Direction::LowerIsBetter);
+
+        assert!(Config::parse("[normalization]\nsaves = \"zscore\"").unwrap_err().contains("unknown metric"));
+        assert!(Config::parse("[normalization]\nhits = \"log\"").is_err());
+        assert!(Config::parse("[direction]\nsaves = \"lower\"").unwrap_err().contains("direction: unknown metric"));
+        assert!(Config::parse("[direction]\nhits = \"down\"").is_err());
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use rusqlite::{params, types::Type, Connection, Error, Result};
+use crate::cleaning::{Player, PlayerMap, Position};
+use crate::scoring::{calculate_score, metric_names};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS players (
+        name TEXT PRIMARY KEY,
+        positions TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS metrics (
+        player TEXT NOT NULL REFERENCES players(name),
+        position TEXT NOT NULL,
+        metric_index INTEGER NOT NULL,
+        metric TEXT NOT NULL,
+        raw REAL NOT NULL,
+        normalized REAL NOT NULL,
+        PRIMARY KEY (player, position, metric_index)
+    );
+    CREATE TABLE IF NOT EXISTS stats (
+        player TEXT NOT NULL REFERENCES players(name),
+        stat TEXT NOT NULL,
+        value REAL NOT NULL,
+        PRIMARY KEY (player, stat)
+    );
+    CREATE TABLE IF NOT EXISTS scores (
+        player TEXT NOT NULL REFERENCES players(name),
+        position TEXT NOT NULL,
+        score REAL NOT NULL,
+        PRIMARY KEY (player, position)
+    );
+";
+
+pub fn save_players(db_path: &str, players: &PlayerMap) -> Result<()> {
+    let mut conn = Connection::open(db_path)?;
+    conn.execute_batch(SCHEMA)?;
+
+    let tx = conn.transaction()?;
+    {
+        let mut insert_player = tx.prepare("INSERT OR REPLACE INTO players (name, positions) VALUES (?1, ?2)")?;
+        let mut clear_metrics = tx.prepare("DELETE FROM metrics WHERE player = ?1")?;
+        let mut clear_scores = tx.prepare("DELETE FROM scores WHERE player = ?1")?;
+        let mut clear_stats = tx.prepare("DELETE FROM stats WHERE player = ?1")?;
+        let mut insert_stat = tx.prepare("INSERT INTO stats (player, stat, value) VALUES (?1, ?2, ?3)")?;
+        let mut insert_metric = tx.prepare(
+            "INSERT INTO metrics (player, position, metric_index, metric, raw, normalized) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        let mut insert_score = tx.prepare("INSERT INTO scores (player, position, score) VALUES (?1, ?2, ?3)")?;
+
+        for player in players.values() {
+            let positions: Vec<String> = player.positions.iter().map(|p| p.to_string()).collect();
+            insert_player.execute(params![player.name, positions.join("/")])?;
+            clear_metrics.execute(params![player.name])?;
+            clear_scores.execute(params![player.name])?;
+            clear_stats.execute(params![player.name])?;
+
+            for (stat, value) in &player.stats {
+                insert_stat.execute(params![player.name, stat, value])?;
+            }
+
+            for (position, normalized) in &player.metrics {
+                let raw = player.raw_metrics.get(position).unwrap_or(normalized);
+                let names = metric_names(position);
+
+                for (i, (raw_value, normalized_value)) in raw.iter().zip(normalized).enumerate() {
+                    let name = names.get(i).copied().unwrap_or("");
+                    insert_metric.execute(params![player.name, position.to_string(), i, name, raw_value, normalized_value])?;
+                }
+
+                insert_score.execute(params![player.name, position.to_string(), calculate_score(position, normalized)])?;
+            }
+        }
+    }
+    tx.commit()
+}
+
+pub fn load_players(db_path: &str) -> Result<PlayerMap> {
+    let conn = Connection::open(db_path)?;
+    let mut players = PlayerMap::default();
+
+    let mut player_rows = conn.prepare("SELECT name, positions FROM players")?;
+    let mut rows = player_rows.query([])?;
+    while let Some(row) = rows.next()? {
+        let name: String = row.get(0)?;
+        let positions_str: String = row.get(1)?;
+        let positions = positions_str
+            .split('/')
+            .map(|p| parse_position(p, 1))
+            .collect::<Result<Vec<Position>>>()?;
+
+        let player = Player::new(name, positions, HashMap::new());
+        players.insert(player.name.clone(), player);
+    }
+
+    let mut metric_rows =
+        conn.prepare("SELECT player, position, raw, normalized FROM metrics ORDER BY player, position, metric_index")?;
+    let mut rows = metric_rows.query([])?;
+    while let Some(row) = rows.next()? {
+        let name: String = row.get(0)?;
+        let position = parse_position(&row.get::<_, String>(1)?, 1)?;
+        let raw: f64 = row.get(2)?;
+        let normalized: f64 = row.get(3)?;
+
+        if let Some(player) = players.get_mut(name.as_str()) {
+            player.raw_metrics.entry(position.clone()).or_default().push(raw);
+            player.metrics.entry(position).or_default().push(normalized);
+        }
+    }
+
+    let mut stat_rows = conn.prepare("SELECT player, stat, value FROM stats")?;
+    let mut rows = stat_rows.query([])?;
+    while let Some(row) = rows.next()? {
+        let name: String = row.get(0)?;
+        if let Some(player) = players.get_mut(name.as_str()) {
+            player.stats.insert(row.get::<_, String>(1)?, row.get(2)?);
+        }
+    }
+
+    Ok(players)
+}
+
+fn parse_position(value: &str, column: usize) -> Result<Position> {
+    value
+        .parse()
+        .map_err(|e: String| Error::FromSqlConversionFailure(column, Type::Text, e.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let db_path = std::env::temp_dir().join("finalproject_db_round_trip.sqlite");
+        let db_path = db_path.to_str().unwrap();
+        let _ = std::fs::remove_file(db_path);
+
+        let mut player = Player::new(
+            "Cale Makar".to_string(),
+            vec![Position::Defense],
+            HashMap::from([(Position::Defense, vec![80.0, 190.0, 60.0, 210.0, 40.0])]),
+        );
+        player.metrics.insert(Position::Defense, vec![0.4, 1.0, 0.5, 1.0, 0.8]);
+        player.stats.insert("GP".to_string(), 200.0);
+        let players = PlayerMap::from_iter([(player.name.clone(), player)]);
+
+        save_players(db_path, &players).unwrap();
+        save_players(db_path, &players).unwrap();
+        let loaded = load_players(db_path).unwrap();
+
+        let makar = &loaded["Cale Makar"];
+        assert_eq!(makar.positions, vec![Position::Defense]);
+        assert_eq!(makar.raw_metrics[&Position::Defense], vec![80.0, 190.0, 60.0, 210.0, 40.0]);
+        assert_eq!(makar.metrics[&Position::Defense], vec![0.4, 1.0, 0.5, 1.0, 0.8]);
+        assert_eq!(makar.stats["GP"], 200.0);
+
+        std::fs::remove_file(db_path).unwrap();
+    }
+}
@@ -0,0 +1,83 @@
+use std::{collections::HashMap, sync::Arc};
+use serde::Serialize;
+use crate::cleaning::Position;
+use crate::scoring::ScoredPlayer;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScoreChange {
+    pub name: Arc<str>,
+    pub position: Position,
+    pub old: f64,
+    pub new: f64,
+}
+
+impl ScoreChange {
+    pub fn change(&self) -> f64 {
+        self.new - self.old
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SnapshotDiff {
+    pub changes: Vec<ScoreChange>,
+    pub entrants: Vec<ScoredPlayer>,
+    pub dropped: Vec<ScoredPlayer>,
+}
+
+// Matches players by name and position between two snapshots' rankings. Changes are sorted by
+// the size of the move, entrants and dropped players by score.
+pub fn diff_rankings(old: &HashMap<Position, Vec<ScoredPlayer>>, new: &HashMap<Position, Vec<ScoredPlayer>>) -> SnapshotDiff {
+    let index = |groups: &HashMap<Position, Vec<ScoredPlayer>>| -> HashMap<(Arc<str>, Position), ScoredPlayer> {
+        groups.values().flatten().map(|scored| ((scored.name.clone(), scored.position.clone()), scored.clone())).collect()
+    };
+    let (old, new) = (index(old), index(new));
+    let mut diff = SnapshotDiff::default();
+
+    for (key, scored) in &new {
+        match old.get(key) {
+            Some(before) => diff.changes.push(ScoreChange {
+                name: scored.name.clone(),
+                position: scored.position.clone(),
+                old: before.score,
+                new: scored.score,
+            }),
+            None => diff.entrants.push(scored.clone()),
+        }
+    }
+    diff.dropped = old.iter().filter(|(key, _)| !new.contains_key(*key)).map(|(_, scored)| scored.clone()).collect();
+
+    let by_score = |a: &ScoredPlayer, b: &ScoredPlayer| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.name.cmp(&b.name));
+    diff.changes.sort_by(|a, b| {
+        b.change().abs().partial_cmp(&a.change().abs()).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.name.cmp(&b.name))
+    });
+    diff.entrants.sort_by(by_score);
+    diff.dropped.sort_by(by_score);
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scored(name: &str, position: Position, score: f64) -> ScoredPlayer {
+        ScoredPlayer { name: name.into(), position, score }
+    }
+
+    #[test]
+    fn test_diff_rankings() {
+        let old = HashMap::from([
+            (Position::Center, vec![scored("Steady", Position::Center, 80.0), scored("Faller", Position::Center, 75.0)]),
+            (Position::Defense, vec![scored("Traded", Position::Defense, 60.0)]),
+        ]);
+        let new = HashMap::from([(
+            Position::Center,
+            vec![scored("Steady", Position::Center, 81.0), scored("Faller", Position::Center, 65.0), scored("Rookie", Position::Center, 70.0)],
+        )]);
+
+        let diff = diff_rankings(&old, &new);
+        assert_eq!(diff.changes.iter().map(|c| &*c.name).collect::<Vec<_>>(), vec!["Faller", "Steady"]);
+        assert_eq!(diff.changes[0].change(), -10.0);
+        assert_eq!(diff.entrants, vec![scored("Rookie", Position::Center, 70.0)]);
+        assert_eq!(diff.dropped, vec![scored("Traded", Position::Defense, 60.0)]);
+    }
+}
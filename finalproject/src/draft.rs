@@ -0,0 +1,156 @@
+use std::{collections::HashMap, sync::Arc};
+use serde::{Deserialize, Serialize};
+use crate::cleaning::{PlayerMap, Position};
+use crate::model::{ReplacementLevel, ScoringModel};
+use crate::scoring::{overall_rankings, rank_players_with};
+
+// A fantasy league's size and starting roster, 12 teams dressing 2 C, 4 W and 4 D by default.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DraftConfig {
+    pub teams: usize,
+    pub center: usize,
+    pub wing: usize,
+    pub defense: usize,
+}
+
+impl Default for DraftConfig {
+    fn default() -> Self {
+        DraftConfig { teams: 12, center: 2, wing: 4, defense: 4 }
+    }
+}
+
+impl DraftConfig {
+    pub fn league(&self) -> ReplacementLevel {
+        ReplacementLevel { teams: self.teams, center: self.center, wing: self.wing, defense: self.defense }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DraftEntry {
+    pub name: Arc<str>,
+    pub positions: Vec<Position>,
+    pub score: f64,
+    pub scarcity: f64,
+    pub value: f64,
+}
+
+// The score of the first player left over once every team has filled its starters at each position.
+// Positions with fewer players than starting spots use their weakest player.
+pub fn replacement_levels(players: &PlayerMap, model: &dyn ScoringModel, league: &DraftConfig) -> HashMap<Position, f64> {
+    let league = league.league();
+    rank_players_with(players, model)
+        .into_iter()
+        .filter_map(|(position, ranked)| {
+            let index = league.rank(&position).min(ranked.len()).checked_sub(1)?;
+            Some((position, ranked[index].score))
+        })
+        .collect()
+}
+
+// Every player ordered by overall rating plus a scarcity adjustment: how far their position's
+// replacement level sits below the average position's, so a defenseman who is hard to replace
+// goes ahead of a winger with the same rating. Multi-position players get their scarcest position.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DraftBoard {
+    entries: Vec<DraftEntry>,
+    picks: Vec<usize>,
+}
+
+impl DraftBoard {
+    pub fn new(players: &PlayerMap, model: &dyn ScoringModel, league: &DraftConfig, bonus: f64) -> Self {
+        let replacement = replacement_levels(players, model, league);
+        let average = replacement.values().sum::<f64>() / replacement.len().max(1) as f64;
+
+        let mut entries: Vec<DraftEntry> = overall_rankings(players, model, bonus)
+            .into_iter()
+            .map(|overall| {
+                let scarcity = overall
+                    .positions
+                    .iter()
+                    .filter_map(|position| Some(average - replacement.get(position)?))
+                    .reduce(f64::max)
+                    .unwrap_or(0.0);
+                DraftEntry { value: overall.score + scarcity, name: overall.name, positions: overall.positions, score: overall.score, scarcity }
+            })
+            .collect();
+        entries.sort_by(|a, b| b.value.partial_cmp(&a.value).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.name.cmp(&b.name)));
+        DraftBoard { entries, picks: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn picks(&self) -> usize {
+        self.picks.len()
+    }
+
+    // The untaken players as (board rank from 0, entry), best first, optionally at one position.
+    pub fn available<'a>(&'a self, position: Option<&'a Position>) -> impl Iterator<Item = (usize, &'a DraftEntry)> + 'a {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !self.picks.contains(i))
+            .filter(move |(_, entry)| position.is_none_or(|position| entry.positions.contains(position)))
+    }
+
+    // Marks a player as drafted, matching the name case-insensitively.
+    pub fn take(&mut self, name: &str) -> Result<&DraftEntry, String> {
+        let name = name.trim();
+        let index = self.entries.iter().position(|entry| entry.name.eq_ignore_ascii_case(name)).ok_or_else(|| format!("'{}' is not on the board", name))?;
+        if let Some(pick) = self.picks.iter().position(|&taken| taken == index) {
+            return Err(format!("{} was already taken (pick {})", self.entries[index].name, pick + 1));
+        }
+        self.picks.push(index);
+        Ok(&self.entries[index])
+    }
+
+    // Puts the most recent pick back on the board.
+    pub fn undo(&mut self) -> Option<&DraftEntry> {
+        self.picks.pop().map(|index| &self.entries[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cleaning::Player;
+    use crate::model::Linear;
+
+    fn player(name: &str, position: Position, value: f64) -> (Arc<str>, Player) {
+        (name.into(), Player::new(name, vec![position.clone()], HashMap::from([(position, vec![value; 5])])))
+    }
+
+    #[test]
+    fn test_scarce_positions_move_up_the_board() {
+        // Three wings deep but only one defenseman worth starting in a one-team league.
+        let players = PlayerMap::from_iter([
+            player("Wing A", Position::Wing, 0.9),
+            player("Wing B", Position::Wing, 0.8),
+            player("Wing C", Position::Wing, 0.7),
+            player("Defense A", Position::Defense, 0.85),
+            player("Defense B", Position::Defense, 0.2),
+        ]);
+        let league = DraftConfig { teams: 1, center: 0, wing: 1, defense: 1 };
+        let mut board = DraftBoard::new(&players, &Linear::default(), &league, 0.0);
+
+        // Each position's replacement player lands at the average replacement level, so they tie.
+        let names: Vec<&str> = board.available(None).map(|(_, entry)| &*entry.name).collect();
+        assert_eq!(names[..2], ["Defense A", "Wing A"]);
+        assert_eq!(names[4], "Wing C");
+        assert!(board.entries[0].scarcity > 0.0 && board.entries[1].scarcity < 0.0);
+
+        assert_eq!(&*board.take("defense a").unwrap().name, "Defense A");
+        assert!(board.take("Defense A").unwrap_err().contains("pick 1"));
+        assert!(board.take("Nobody").is_err());
+        let defense: Vec<&str> = board.available(Some(&Position::Defense)).map(|(_, entry)| &*entry.name).collect();
+        assert_eq!(defense, ["Defense B"]);
+        assert_eq!(board.undo().map(|entry| &*entry.name), Some("Defense A"));
+        assert_eq!(board.picks(), 0);
+    }
+}
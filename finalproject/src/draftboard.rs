@@ -0,0 +1,73 @@
+use std::{fs, io};
+use finalproject::cleaning::Position;
+use finalproject::draft::{DraftBoard, DraftEntry};
+use finalproject::locale::Locale;
+use rustyline::{error::ReadlineError, DefaultEditor};
+
+fn print_entry(rank: usize, entry: &DraftEntry, locale: &Locale) {
+    let positions: Vec<&str> = entry.positions.iter().map(Position::abbreviation).collect();
+    println!("{}. {} ({}): {:.2} (overall {:.2}, scarcity {:+.2})", rank + 1, entry.name, positions.join("/"), locale.num(entry.value), locale.num(entry.score), locale.num(entry.scarcity));
+}
+
+fn print_available(board: &DraftBoard, position: Option<&Position>, top: usize, locale: &Locale) {
+    let label = position.map_or("Best available".to_string(), |position| format!("Best available {} players", position));
+    println!("\n{} ({} taken):", label, board.picks());
+    for (rank, entry) in board.available(position).take(top) {
+        print_entry(rank, entry, locale);
+    }
+}
+
+// Marks the names in `taken` (one per line) as drafted, then prompts for picks until an empty line:
+// a name takes that player, `undo` puts the last pick back and `best <C|W|D>` lists one position.
+pub fn run(mut board: DraftBoard, top: usize, taken: Option<&str>, prompt: bool, locale: &Locale) -> io::Result<()> {
+    if let Some(path) = taken {
+        for name in fs::read_to_string(path)?.lines().map(|line| line.trim().trim_matches('"')).filter(|name| !name.is_empty()) {
+            if let Err(e) = board.take(name) {
+                log::warn!("{}: {}", path, e);
+            }
+        }
+    }
+    print_available(&board, None, top, locale);
+    if !prompt {
+        return Ok(());
+    }
+
+    let mut editor = DefaultEditor::new().map_err(io::Error::other)?;
+    loop {
+        println!("\nEnter a drafted player's name, `undo`, or `best <C|W|D>` (press Enter to exit):");
+        let input = match editor.readline("> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => String::new(),
+            Err(e) => return Err(io::Error::other(e)),
+        };
+        let input = input.trim();
+        if input.is_empty() {
+            println!("Exiting...");
+            break;
+        }
+        let _ = editor.add_history_entry(input);
+
+        if input.eq_ignore_ascii_case("undo") {
+            match board.undo() {
+                Some(entry) => println!("Back on the board: {}", entry.name),
+                None => println!("No picks to undo."),
+            }
+        } else if let Some(position) = input.strip_prefix("best ") {
+            match position.parse::<Position>() {
+                Ok(position) => print_available(&board, Some(&position), top, locale),
+                Err(e) => println!("{}", e),
+            }
+            continue;
+        } else {
+            match board.take(input).map(|entry| entry.name.clone()) {
+                Ok(name) => println!("Pick {}: {}", board.picks(), name),
+                Err(e) => {
+                    println!("{}", e);
+                    continue;
+                }
+            }
+        }
+        print_available(&board, None, top, locale);
+    }
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use std::{cell::RefCell, fs::File, io::{self, BufWriter, Write}};
+use crate::cleaning::{canonical_fields, split_fields, ParseOptions, SkipReason, COLUMNS};
+use crate::export::write_csv_row;
+
+// Copies of the input rows the parser accepted and rejected, for fixing an export by hand and feeding
+// it back in. Accepted rows are written in `COLUMNS` order with the team last, whatever layout the
+// input had; rejected rows are written as they appeared with a `reason` column added. Files are
+// created up front but a header is only written before the first row. Disabled by default.
+#[derive(Default)]
+pub struct RowFiles {
+    cleaned: RefCell<Option<Output>>,
+    rejected: RefCell<Option<Output>>,
+}
+
+struct Output {
+    writer: BufWriter<File>,
+    rows: usize,
+}
+
+impl RowFiles {
+    pub fn create(cleaned: Option<&str>, rejected: Option<&str>) -> io::Result<Self> {
+        let open = |path: Option<&str>| path.map(|path| File::create(path).map(|file| Output { writer: BufWriter::new(file), rows: 0 })).transpose();
+        Ok(RowFiles { cleaned: RefCell::new(open(cleaned)?), rejected: RefCell::new(open(rejected)?) })
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.cleaned.borrow().is_some() || self.rejected.borrow().is_some()
+    }
+
+    pub fn accepted(&self, line: &str, options: &ParseOptions) -> io::Result<()> {
+        let mut cleaned = self.cleaned.borrow_mut();
+        let Some(out) = cleaned.as_mut() else { return Ok(()) };
+        // The row parsed, so it has the fields its layout needs.
+        let Ok(mut fields) = canonical_fields(line, options) else { return Ok(()) };
+
+        if out.rows == 0 {
+            write_csv_row(&mut out.writer, &COLUMNS.iter().chain(&["Team"]).collect::<Vec<_>>())?;
+        }
+        let split = split_fields(line, options.delimiter);
+        fields.push(options.team_column.and_then(|i| split.get(i).copied()).unwrap_or(""));
+        write_csv_row(&mut out.writer, &fields)?;
+        out.rows += 1;
+        Ok(())
+    }
+
+    // Short rows are padded out to the header's width so the reason always lands in the last column.
+    pub fn rejected(&self, header: &str, line: &str, delimiter: char, reason: SkipReason) -> io::Result<()> {
+        let mut rejected = self.rejected.borrow_mut();
+        let Some(out) = rejected.as_mut() else { return Ok(()) };
+
+        if out.rows == 0 {
+            writeln!(out.writer, "{}{}reason", header, delimiter)?;
+        }
+        let padding = split_fields(header, delimiter).len().saturating_sub(split_fields(line, delimiter).len());
+        let padding: String = std::iter::repeat_n(delimiter, padding).collect();
+        writeln!(out.writer, "{}{}{}{}", line, padding, delimiter, reason.name())?;
+        out.rows += 1;
+        Ok(())
+    }
+
+    // How many accepted and rejected rows were written.
+    pub fn finish(&self) -> io::Result<(usize, usize)> {
+        let mut counts = [0; 2];
+        for (count, file) in counts.iter_mut().zip([&self.cleaned, &self.rejected]) {
+            if let Some(out) = file.borrow_mut().as_mut() {
+                out.writer.flush()?;
+                *count = out.rows;
+            }
+        }
+        Ok((counts[0], counts[1]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use super::*;
+    use crate::cleaning::RowReader;
+
+    #[test]
+    fn test_rows_are_split_into_cleaned_and_rejected_files() {
+        let dir = std::env::temp_dir().join(format!("nhl_scores_emit_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (cleaned, rejected) = (dir.join("cleaned.csv"), dir.join("rejected.csv"));
+        let files = RowFiles::create(cleaned.to_str(), rejected.to_str()).unwrap();
+        assert!(files.is_active());
+
+        let input = ",Player,Position,GP,Goals\n1,Sam Reinhart,C,82,57\n2,,C,80,10\n3,Zach Hyman,G,80,54\n";
+        let mut rows = RowReader::new(input.as_bytes(), &ParseOptions::default()).unwrap();
+        while let Some(row) = rows.next_checked().unwrap() {
+            match row {
+                Ok(_) => files.accepted(rows.line(), rows.options()).unwrap(),
+                Err(reason) => files.rejected(rows.header(), rows.line(), ',', reason).unwrap(),
+            }
+        }
+        assert_eq!(files.finish().unwrap(), (1, 2));
+
+        let cleaned = fs::read_to_string(cleaned).unwrap();
+        let lines: Vec<&str> = cleaned.lines().collect();
+        assert_eq!(split_fields(lines[0], ',').len(), COLUMNS.len() + 1);
+        // Goals is matched to its column by name, leaving TOI blank.
+        assert!(lines[1].starts_with("1,Sam Reinhart,C,82,,57,"));
+        assert_eq!(split_fields(lines[1], ',').len(), COLUMNS.len() + 1);
+        assert_eq!(
+            fs::read_to_string(rejected).unwrap(),
+            ",Player,Position,GP,Goals,reason\n2,,C,80,10,missing_name_or_position\n3,Zach Hyman,G,80,54,invalid_position\n"
+        );
+        assert_eq!(RowFiles::default().finish().unwrap(), (0, 0));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
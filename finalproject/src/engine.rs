@@ -0,0 +1,412 @@
+use std::{collections::HashMap, sync::Arc};
+use crate::cleaning::{apply_scales, matrix_scales, Player, PlayerMap, Position};
+use crate::matrix::MetricMatrix;
+use crate::metric::{Metric, Normalization, Scale, Scaling};
+use crate::model::{MetricMap, ScoringModel};
+use crate::scoring::{metric_names, ScoredPlayer};
+use crate::positions;
+
+// Keeps a league normalized and scored so single-player corrections don't re-run the whole pipeline.
+// A change only rescores everyone at a position when it moves that position's normalization (a new
+// league leader, say); otherwise just the changed player is rescored. Models that score against the
+// rest of the league (percentile, ordinal, value over replacement) are refitted after every change
+// and rescore everyone, so the rankings always match a full run.
+pub struct ScoringEngine {
+    players: PlayerMap,
+    model: Box<dyn ScoringModel>,
+    scaling: Scaling,
+    scales: HashMap<Position, Vec<Scale>>,
+    extremes: HashMap<Position, Vec<Extremes>>,
+    scores: HashMap<Position, HashMap<Arc<str>, f64>>,
+}
+
+// A column's largest and smallest raw values and how many players share each, so a change is
+// compared against them instead of the whole column. Only losing the last player at an extreme
+// means the column has to be read again.
+#[derive(Debug, Clone, Copy)]
+struct Extremes {
+    max: f64,
+    max_count: usize,
+    min: f64,
+    min_count: usize,
+}
+
+impl Default for Extremes {
+    fn default() -> Self {
+        Extremes { max: f64::NEG_INFINITY, max_count: 0, min: f64::INFINITY, min_count: 0 }
+    }
+}
+
+impl Extremes {
+    fn of(column: &[f64]) -> Self {
+        let mut extremes = Extremes::default();
+        column.iter().for_each(|&value| extremes.add(value));
+        extremes
+    }
+
+    fn add(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        if value > self.max {
+            (self.max, self.max_count) = (value, 1);
+        } else if value == self.max {
+            self.max_count += 1;
+        }
+        if value < self.min {
+            (self.min, self.min_count) = (value, 1);
+        } else if value == self.min {
+            self.min_count += 1;
+        }
+    }
+
+    // False once the value was the last one at either extreme.
+    fn remove(&mut self, value: f64) -> bool {
+        if !value.is_finite() {
+            return true;
+        }
+        if value == self.max {
+            self.max_count -= 1;
+        }
+        if value == self.min {
+            self.min_count -= 1;
+        }
+        self.max_count > 0 && self.min_count > 0
+    }
+
+    // Matches `Normalization::fit` over the whole column for the strategies that only need its ends.
+    fn fit(self, normalization: Normalization) -> Option<(f64, f64)> {
+        match normalization {
+            Normalization::Max => Some((0.0, self.max.max(0.0))),
+            Normalization::MinMax => Some((self.min, self.max - self.min)),
+            Normalization::Percent | Normalization::None => Some(normalization.fit(&[])),
+            Normalization::ZScore => None,
+        }
+    }
+}
+
+impl ScoringEngine {
+    // Normalizes from each player's raw metrics, so players can come straight from the parser.
+    pub fn new(players: PlayerMap, model: Box<dyn ScoringModel>, scaling: Scaling) -> Self {
+        let mut engine = ScoringEngine { players, model, scaling, scales: HashMap::new(), extremes: HashMap::new(), scores: HashMap::new() };
+        for position in positions::all() {
+            let scales = engine.refit(position);
+            engine.renormalize(position, scales);
+        }
+        engine.model.fit(&engine.players);
+        for position in positions::all() {
+            engine.rescore_position(position);
+        }
+        engine
+    }
+
+    pub fn players(&self) -> &PlayerMap {
+        &self.players
+    }
+
+    pub fn model(&self) -> &dyn ScoringModel {
+        self.model.as_ref()
+    }
+
+    // A new model scores everyone, but the normalized metrics stay as they are.
+    pub fn set_model(&mut self, model: Box<dyn ScoringModel>) {
+        self.model = model;
+        self.model.fit(&self.players);
+        for position in positions::all() {
+            self.rescore_position(position);
+        }
+    }
+
+    // Brings the league in line with a fresh load of it: players that changed or are new are upserted
+    // and players no longer there are removed, one at a time, and the input's order is kept. Returns
+    // how many scores were recomputed.
+    pub fn sync(&mut self, players: PlayerMap) -> usize {
+        let gone: Vec<Arc<str>> = self.players.keys().filter(|name| !players.contains_key(&***name)).cloned().collect();
+        let mut rescored: usize = gone.iter().filter_map(|name| Some(self.remove(name)?.1)).sum();
+        for (name, player) in &players {
+            if self.players.get(&**name).is_none_or(|current| !same_input(current, player)) {
+                rescored += self.upsert_player(player.clone());
+            }
+        }
+        if !self.players.keys().eq(players.keys()) {
+            self.players.sort_by_cached_key(|name, _| players.get_index_of(&**name));
+        }
+        rescored
+    }
+
+    pub fn score(&self, name: &str, position: &Position) -> Option<f64> {
+        self.scores.get(position)?.get(name).copied()
+    }
+
+    // Sorted best first, like `rank_players_with`.
+    pub fn rankings(&self) -> HashMap<Position, Vec<ScoredPlayer>> {
+        self.scores
+            .iter()
+            .map(|(position, scores)| {
+                let mut ranked: Vec<ScoredPlayer> = self
+                    .players
+                    .keys()
+                    .filter_map(|name| Some(ScoredPlayer { name: name.clone(), position: position.clone(), score: *scores.get(name)? }))
+                    .collect();
+                ranked.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+                (position.clone(), ranked)
+            })
+            .collect()
+    }
+
+    // Adds or replaces a player by name and returns how many scores were recomputed.
+    pub fn upsert_player(&mut self, mut player: Player) -> usize {
+        player.metrics = player.raw_metrics.clone();
+        let mut positions = player.positions.clone();
+        if let Some(old) = self.players.get(&*player.name) {
+            positions.extend(old.positions.iter().filter(|p| !player.positions.contains(p)).cloned());
+        }
+        let name = player.name.clone();
+        let old = self.players.insert(name.clone(), player);
+        self.refresh(&positions, &name, old.as_ref())
+    }
+
+    // Returns the removed player with its normalized metrics as they were.
+    pub fn remove_player(&mut self, name: &str) -> Option<Player> {
+        self.remove(name).map(|(player, _)| player)
+    }
+
+    fn remove(&mut self, name: &str) -> Option<(Player, usize)> {
+        let player = self.players.shift_remove(name)?;
+        for scores in self.scores.values_mut() {
+            scores.remove(name);
+        }
+        let rescored = self.refresh(&player.positions, &player.name, Some(&player));
+        Some((player, rescored))
+    }
+
+    // `old` is the player as they were before the change, if they were in the league.
+    fn refresh(&mut self, positions: &[Position], name: &Arc<str>, old: Option<&Player>) -> usize {
+        let mut rescaled = Vec::new();
+        for position in positions {
+            let new = self.players.get(&**name).and_then(|p| p.raw_metrics.get(position)).cloned();
+            let scales = self.update_scales(position, old.and_then(|p| p.raw_metrics.get(position)), new.as_deref());
+            if self.renormalize(position, scales) {
+                rescaled.push(position);
+                continue;
+            }
+            let (Some(player), Some(scales), Some(mut normalized)) = (self.players.get_mut(&**name), self.scales.get(position), new) else { continue };
+            apply_scales(&mut normalized, scales);
+            player.metrics.insert(position.clone(), normalized);
+        }
+
+        let league_relative = self.model.league_relative();
+        if league_relative || !rescaled.is_empty() {
+            self.model.fit(&self.players);
+        }
+        if league_relative {
+            return positions::all().iter().map(|position| self.rescore_position(position)).sum();
+        }
+
+        let mut rescored = 0;
+        for position in positions {
+            if rescaled.contains(&position) {
+                rescored += self.rescore_position(position);
+                continue;
+            }
+            let scores = self.scores.entry(position.clone()).or_default();
+            scores.remove(name);
+            if let Some(metrics) = self.players.get(&**name).and_then(|player| MetricMap::for_player(player, position)) {
+                scores.insert(name.clone(), self.model.score(position, &metrics));
+                rescored += 1;
+            }
+        }
+        rescored
+    }
+
+    // Fits the position's scales to every player's raw metrics, as a full run does.
+    fn refit(&mut self, position: &Position) -> Vec<Scale> {
+        let matrix = MetricMatrix::from_rows(metric_names(position).len(), self.players.values().filter_map(|p| p.raw_metrics.get(position)));
+        self.extremes.insert(position.clone(), (0..matrix.width()).map(|i| Extremes::of(matrix.column(i))).collect());
+        matrix_scales(position, &matrix, &self.scaling)
+    }
+
+    // Swaps one player's row in the running extremes and fits the scales from those, falling back to
+    // the whole column when the change took away an extreme or a metric is z-scored.
+    fn update_scales(&mut self, position: &Position, old: Option<&Vec<f64>>, new: Option<&[f64]>) -> Vec<Scale> {
+        let Some(extremes) = self.extremes.get_mut(position) else { return self.refit(position) };
+        let kept = old.into_iter().flatten().zip(extremes.iter_mut()).all(|(&value, extremes)| extremes.remove(value));
+        if !kept {
+            return self.refit(position);
+        }
+        if let Some(new) = new {
+            if new.len() > extremes.len() {
+                extremes.resize(new.len(), Extremes::default());
+            }
+            new.iter().zip(extremes.iter_mut()).for_each(|(&value, extremes)| extremes.add(value));
+        }
+
+        let scales: Option<Vec<Scale>> = Metric::for_position(position)
+            .iter()
+            .enumerate()
+            .map(|(i, &metric)| {
+                let normalization = self.scaling.normalization(metric);
+                let (offset, divisor) = extremes.get(i).copied().unwrap_or_default().fit(normalization)?;
+                Some(Scale { metric, normalization, direction: self.scaling.direction(metric), offset, divisor })
+            })
+            .collect();
+        scales.unwrap_or_else(|| self.refit(position))
+    }
+
+    // Renormalizes the position's players if its scales moved.
+    fn renormalize(&mut self, position: &Position, scales: Vec<Scale>) -> bool {
+        if self.scales.get(position) == Some(&scales) {
+            return false;
+        }
+
+        for player in self.players.values_mut() {
+            if let Some(raw) = player.raw_metrics.get(position) {
+                let mut normalized = raw.clone();
+                apply_scales(&mut normalized, &scales);
+                player.metrics.insert(position.clone(), normalized);
+            }
+        }
+        self.scales.insert(position.clone(), scales);
+        true
+    }
+
+    fn rescore_position(&mut self, position: &Position) -> usize {
+        let scores: HashMap<Arc<str>, f64> = self
+            .model
+            .score_position(position, &self.players)
+            .into_iter()
+            .zip(self.players.matrices().get(position).map_or(&[][..], |matrix| &matrix.players))
+            .filter_map(|(score, &index)| Some((self.players.get_index(index)?.0.clone(), score)))
+            .collect();
+        let count = scores.len();
+        self.scores.insert(position.clone(), scores);
+        count
+    }
+}
+
+// Whether a reloaded player is the one already in the league. Normalized metrics are left out, since
+// they move with the rest of the league.
+fn same_input(current: &Player, loaded: &Player) -> bool {
+    current.positions == loaded.positions
+        && current.raw_metrics == loaded.raw_metrics
+        && current.stats == loaded.stats
+        && current.team == loaded.team
+        && current.sides == loaded.sides
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cleaning::normalize_metrics_with;
+    use crate::model::{Ordinal, PercentileSum, ReplacementLevel, ValueOverReplacement, WeightedSigmoid};
+    use crate::scoring::{rank_players_with, WeightTable};
+
+    fn wing(name: &str, value: f64) -> Player {
+        Player::new(name, vec![Position::Wing], HashMap::from([(Position::Wing, vec![value; 5])]))
+    }
+
+    fn league(values: &[(&str, f64)]) -> PlayerMap {
+        values.iter().map(|&(name, value)| (name.into(), wing(name, value))).collect()
+    }
+
+    fn models() -> Vec<fn() -> Box<dyn ScoringModel>> {
+        vec![
+            || Box::new(WeightedSigmoid::default()),
+            || Box::new(PercentileSum::default()),
+            || Box::new(Ordinal::new(WeightTable::default())),
+            || Box::new(ValueOverReplacement::new(Box::new(WeightedSigmoid::default()), ReplacementLevel { teams: 1, center: 1, wing: 2, defense: 1 })),
+        ]
+    }
+
+    fn assert_matches_full_run(engine: &ScoringEngine, mut model: Box<dyn ScoringModel>) {
+        let mut players = engine.players().clone();
+        for player in players.values_mut() {
+            player.metrics = player.raw_metrics.clone();
+        }
+        normalize_metrics_with(&mut players, &engine.scaling);
+        model.fit(&players);
+        let expected = rank_players_with(&players, model.as_ref());
+        assert_eq!(engine.rankings()[&Position::Wing], expected[&Position::Wing], "{}", model.name());
+    }
+
+    #[test]
+    fn test_upsert_rescores_only_what_changed() {
+        let mut engine = ScoringEngine::new(league(&[("A", 10.0), ("B", 20.0), ("C", 40.0)]), Box::new(WeightedSigmoid::default()), Scaling::default());
+        assert_matches_full_run(&engine, Box::new(WeightedSigmoid::default()));
+        assert!(engine.score("C", &Position::Wing).unwrap() > engine.score("A", &Position::Wing).unwrap());
+
+        assert_eq!(engine.upsert_player(wing("B", 30.0)), 1);
+        assert_matches_full_run(&engine, Box::new(WeightedSigmoid::default()));
+
+        // A new league leader moves everyone's normalized metrics.
+        assert_eq!(engine.upsert_player(wing("D", 80.0)), 4);
+        assert_matches_full_run(&engine, Box::new(WeightedSigmoid::default()));
+        assert_eq!(engine.rankings()[&Position::Wing][0].name, "D".into());
+    }
+
+    #[test]
+    fn test_league_fitted_models_match_a_full_run() {
+        for model in models() {
+            let mut engine = ScoringEngine::new(league(&[("A", 10.0), ("B", 20.0), ("C", 40.0), ("D", 5.0)]), model(), Scaling::default());
+            // Every score moves with the league for these, so everyone is rescored.
+            let rescored = engine.upsert_player(wing("B", 30.0));
+            assert_eq!(rescored, if engine.model.league_relative() { 4 } else { 1 });
+            assert_matches_full_run(&engine, model());
+            engine.upsert_player(wing("E", 25.0));
+            assert_matches_full_run(&engine, model());
+            engine.remove_player("C");
+            assert_matches_full_run(&engine, model());
+        }
+    }
+
+    #[test]
+    fn test_scales_follow_running_extremes() {
+        let scaling = Scaling { normalization: HashMap::from([(Metric::Goals, Normalization::MinMax)]), ..Scaling::default() };
+        let mut engine = ScoringEngine::new(league(&[("A", 10.0), ("B", 10.0), ("C", 20.0), ("D", 40.0)]), Box::new(WeightedSigmoid::default()), scaling);
+        let scales = engine.scales[&Position::Wing].clone();
+
+        // B shared the minimum with A, so moving B leaves the scales alone.
+        assert_eq!(engine.upsert_player(wing("B", 12.0)), 1);
+        assert_eq!(engine.scales[&Position::Wing], scales);
+        assert_matches_full_run(&engine, Box::new(WeightedSigmoid::default()));
+
+        // Taking away the last player at the top reads the column again.
+        assert_eq!(engine.upsert_player(wing("D", 15.0)), 4);
+        assert_eq!((engine.scales[&Position::Wing][0].offset, engine.scales[&Position::Wing][0].divisor), (10.0, 10.0));
+        assert_matches_full_run(&engine, Box::new(WeightedSigmoid::default()));
+
+        // A z-score moves with every change, so the whole position is renormalized.
+        let mut engine = ScoringEngine::new(league(&[("A", 10.0), ("B", 20.0), ("C", 40.0)]), Box::new(WeightedSigmoid::default()), Scaling { normalization: HashMap::from([(Metric::Hits, Normalization::ZScore)]), ..Scaling::default() });
+        assert_eq!(engine.upsert_player(wing("B", 25.0)), 3);
+        assert_matches_full_run(&engine, Box::new(WeightedSigmoid::default()));
+    }
+
+    #[test]
+    fn test_remove_player() {
+        let mut engine = ScoringEngine::new(league(&[("A", 10.0), ("B", 20.0), ("C", 40.0)]), Box::new(WeightedSigmoid::default()), Scaling::default());
+
+        assert_eq!(&*engine.remove_player("A").unwrap().name, "A");
+        assert_eq!(engine.score("A", &Position::Wing), None);
+        assert_matches_full_run(&engine, Box::new(WeightedSigmoid::default()));
+
+        engine.remove_player("C");
+        assert_eq!(engine.rankings()[&Position::Wing].len(), 1);
+        assert_matches_full_run(&engine, Box::new(WeightedSigmoid::default()));
+        assert!(engine.remove_player("Nobody").is_none());
+    }
+
+    #[test]
+    fn test_sync_rescores_only_the_players_a_reload_changed() {
+        let mut engine = ScoringEngine::new(league(&[("A", 10.0), ("B", 20.0), ("C", 40.0)]), Box::new(WeightedSigmoid::default()), Scaling::default());
+
+        // B is gone and D is new; A and C load as they were, so only D is scored.
+        assert_eq!(engine.sync(league(&[("D", 25.0), ("A", 10.0), ("C", 40.0)])), 1);
+        assert_eq!(engine.players().keys().map(|name| &**name).collect::<Vec<_>>(), ["D", "A", "C"]);
+        assert_matches_full_run(&engine, Box::new(WeightedSigmoid::default()));
+        assert_eq!(engine.sync(league(&[("D", 25.0), ("A", 10.0), ("C", 40.0)])), 0);
+
+        engine.set_model(Box::new(PercentileSum::default()));
+        assert_eq!(engine.model().name(), PercentileSum::default().name());
+        assert_matches_full_run(&engine, Box::new(PercentileSum::default()));
+    }
+}
@@ -0,0 +1,51 @@
+use crate::cleaning::{Player, PlayerMap, Position};
+use crate::model::{MetricMap, ScoringModel};
+
+// Where a player's score from the 5v5 export is kept, one stat per position, e.g. `EV Score C`.
+pub fn ev_score_stat(position: &Position) -> String {
+    format!("EV Score {}", position.abbreviation())
+}
+
+// Scores each player on their 5v5 numbers with a model already fit to `even_strength`, and stores
+// the scores on the all-situations players. Returns how many players were in both exports.
+pub fn join_ev_scores(players: &mut PlayerMap, even_strength: &PlayerMap, model: &dyn ScoringModel) -> usize {
+    let mut matched = 0;
+    for player in players.values_mut() {
+        let Some(ev) = even_strength.get(&player.name) else { continue };
+        for position in player.positions.iter().filter(|position| ev.positions.contains(position)) {
+            let Some(metrics) = MetricMap::for_player(ev, position) else { continue };
+            player.stats.insert(ev_score_stat(position), model.score(position, &metrics));
+        }
+        matched += 1;
+    }
+    matched
+}
+
+pub fn ev_score(player: &Player, position: &Position) -> Option<f64> {
+    player.stats.get(&ev_score_stat(position)).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::WeightedSigmoid;
+
+    #[test]
+    fn test_ev_scores_join_by_name_and_position() {
+        let player = |name: &str, positions: Vec<Position>, value: f64| {
+            let metrics = positions.iter().map(|position| (position.clone(), vec![value; 5])).collect();
+            let player = Player::new(name, positions, metrics);
+            (player.name.clone(), player)
+        };
+        let mut players = PlayerMap::from_iter([player("Power Play", vec![Position::Center, Position::Wing], 0.9), player("No 5v5", vec![Position::Defense], 0.5)]);
+        let even_strength = PlayerMap::from_iter([player("Power Play", vec![Position::Center], 0.4)]);
+        let model = WeightedSigmoid::default();
+
+        assert_eq!(join_ev_scores(&mut players, &even_strength, &model), 1);
+        let power_play = &players["Power Play"];
+        let all_situations = model.score(&Position::Center, &MetricMap::for_player(power_play, &Position::Center).unwrap());
+        assert!(ev_score(power_play, &Position::Center).unwrap() < all_situations);
+        assert_eq!(ev_score(power_play, &Position::Wing), None);
+        assert_eq!(ev_score(&players["No 5v5"], &Position::Defense), None);
+    }
+}
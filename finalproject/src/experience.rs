@@ -0,0 +1,93 @@
+use std::{collections::HashSet, fs, io};
+use crate::cleaning::{Player, PlayerMap};
+
+pub const ROOKIE_STAT: &str = "Rookie";
+pub const SEASONS_STAT: &str = "Seasons";
+pub const CAREER_GP_STAT: &str = "Career GP";
+
+// The Calder Trophy rule: a player stays a rookie until they have played more than 25 NHL games before the season.
+const ROOKIE_PRIOR_GAMES: f64 = 25.0;
+
+pub fn parse_flag(value: &str) -> Option<f64> {
+    match value.trim().to_lowercase().as_str() {
+        "1" | "y" | "yes" | "true" | "r" | "rookie" => Some(1.0),
+        "0" | "n" | "no" | "false" | "" => Some(0.0),
+        _ => None,
+    }
+}
+
+// Only the seasons column says how many years a veteran has played; a rookie flag or
+// career games column can only tell first-year players apart from everyone else.
+pub fn seasons_played(player: &Player) -> Option<f64> {
+    if let Some(&seasons) = player.stats.get(SEASONS_STAT) {
+        return Some(seasons);
+    }
+    if let Some(&rookie) = player.stats.get(ROOKIE_STAT) {
+        return (rookie > 0.0).then_some(1.0);
+    }
+
+    let career = *player.stats.get(CAREER_GP_STAT)?;
+    let prior = career - player.stats.get("GP").copied().unwrap_or(0.0);
+    (prior <= ROOKIE_PRIOR_GAMES).then_some(1.0)
+}
+
+pub fn within_seasons(player: &Player, max_seasons: f64) -> bool {
+    seasons_played(player).is_some_and(|seasons| seasons <= max_seasons)
+}
+
+pub fn read_rookie_list(path: &str) -> io::Result<HashSet<String>> {
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .map(|line| line.trim().trim_matches('"').to_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect())
+}
+
+// Everyone missing from the list is marked as a veteran, so the file is the whole rookie class.
+pub fn mark_rookies(players: &mut PlayerMap, rookies: &HashSet<String>) -> usize {
+    let mut matched = 0;
+    for player in players.values_mut() {
+        let is_rookie = rookies.contains(&player.name.to_lowercase());
+        player.stats.insert(ROOKIE_STAT.to_string(), if is_rookie { 1.0 } else { 0.0 });
+        matched += is_rookie as usize;
+    }
+    matched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::cleaning::Position;
+
+    fn player(stats: &[(&str, f64)]) -> Player {
+        let mut player = Player::new("P", vec![Position::Center], HashMap::new());
+        player.stats = stats.iter().map(|&(k, v)| (k.to_string(), v)).collect();
+        player
+    }
+
+    #[test]
+    fn test_seasons_played() {
+        assert_eq!(seasons_played(&player(&[(SEASONS_STAT, 2.0), (ROOKIE_STAT, 1.0)])), Some(2.0));
+        assert_eq!(seasons_played(&player(&[(ROOKIE_STAT, 1.0)])), Some(1.0));
+        assert_eq!(seasons_played(&player(&[(ROOKIE_STAT, 0.0)])), None);
+        assert_eq!(seasons_played(&player(&[(CAREER_GP_STAT, 90.0), ("GP", 70.0)])), Some(1.0));
+        assert_eq!(seasons_played(&player(&[(CAREER_GP_STAT, 150.0), ("GP", 82.0)])), None);
+        assert_eq!(seasons_played(&player(&[])), None);
+
+        assert!(within_seasons(&player(&[(SEASONS_STAT, 2.0)]), 2.0));
+        assert!(!within_seasons(&player(&[(SEASONS_STAT, 3.0)]), 2.0));
+    }
+
+    #[test]
+    fn test_mark_rookies() {
+        let mut players = PlayerMap::from_iter([
+            ("Connor Bedard".into(), Player::new("Connor Bedard", vec![Position::Center], HashMap::new())),
+            ("Sidney Crosby".into(), Player::new("Sidney Crosby", vec![Position::Center], HashMap::new())),
+        ]);
+
+        assert_eq!(mark_rookies(&mut players, &HashSet::from(["connor bedard".to_string()])), 1);
+        assert_eq!(players["Connor Bedard"].stats[ROOKIE_STAT], 1.0);
+        assert_eq!(players["Sidney Crosby"].stats[ROOKIE_STAT], 0.0);
+    }
+}
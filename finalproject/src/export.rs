@@ -0,0 +1,169 @@
+use std::{borrow::Cow, collections::BTreeSet, io::{self, Write}};
+use crate::cleaning::{PlayerMap, Position};
+use crate::locale::Locale;
+use crate::metric::Metric;
+use crate::model::{MetricMap, ScoringModel};
+use crate::scoring::{metric_names, score_contributions, ScoredPlayer};
+use crate::tiers::Grades;
+use crate::positions;
+
+pub fn csv_escape(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+pub fn write_csv_row<W: Write, S: AsRef<str>>(writer: &mut W, fields: &[S]) -> io::Result<()> {
+    let row: Vec<Cow<str>> = fields.iter().map(|field| csv_escape(field.as_ref())).collect();
+    writeln!(writer, "{}", row.join(","))
+}
+
+pub fn position_file_name(position: &Position) -> &'static str {
+    match position {
+        Position::Center => "centers.csv",
+        Position::Wing => "wings.csv",
+        Position::Defense => "defense.csv",
+        Position::Custom(id) => positions::definition(*id).map_or("unregistered.csv", |def| def.file_name.as_str()),
+    }
+}
+
+// One position's full leaderboard, best first, with the raw value of each of its metrics and, for
+// weighted models, the points each metric contributed to the score.
+pub fn write_position_csv<W: Write>(
+    writer: &mut W,
+    players: &PlayerMap,
+    position: &Position,
+    ranked: &[ScoredPlayer],
+    grades: &Grades,
+    model: &dyn ScoringModel,
+    locale: &Locale,
+) -> io::Result<()> {
+    let explained = model.weights(position).is_some();
+    let mut header = vec!["rank".to_string(), "player".to_string(), "score".to_string(), "grade".to_string()];
+    header.extend(metric_names(position).iter().map(|metric| locale.label(metric)));
+    if explained {
+        header.extend(metric_names(position).iter().map(|metric| format!("{} pts", locale.label(metric))));
+    }
+    write_csv_row(writer, &header)?;
+
+    for (rank, scored) in ranked.iter().enumerate() {
+        let mut row = vec![(rank + 1).to_string(), scored.name.to_string(), format!("{:.2}", locale.num(scored.score)), grades.grade(position, scored.score).to_string()];
+        let player = players.get(&scored.name);
+        let raw = player.and_then(|player| player.raw_metrics.get(position).or(player.metrics.get(position)));
+        row.extend(raw.into_iter().flatten().map(|value| locale.num(*value).to_string()));
+        if explained {
+            let metrics = player.and_then(|player| player.metrics.get(position));
+            let contributions = metrics.and_then(|metrics| score_contributions(model, position, metrics, scored.score)).unwrap_or_default();
+            row.extend(contributions.iter().map(|contribution| format!("{:.2}", locale.num(contribution.points))));
+        }
+        write_csv_row(writer, &row)?;
+    }
+    Ok(())
+}
+
+// One row per player and position for training models elsewhere: the model's score as the label,
+// then the raw and normalized value of every metric the position is scored on (the others are left
+// blank so the columns line up across positions), then every stat parsed or joined for the player.
+// Returns how many rows were written.
+pub fn write_dataset_csv<W: Write>(writer: &mut W, players: &PlayerMap, model: &dyn ScoringModel, locale: &Locale) -> io::Result<usize> {
+    let stats: BTreeSet<&str> = players.values().flat_map(|player| player.stats.keys()).collect();
+    let mut header = vec!["player".to_string(), "position".to_string(), "team".to_string(), "score".to_string()];
+    let scored_metrics = positions::scored_metrics();
+    for &metric in &scored_metrics {
+        header.push(format!("{} raw", locale.label(metric.name())));
+        header.push(format!("{} norm", locale.label(metric.name())));
+    }
+    header.extend(stats.iter().map(|stat| locale.label(stat)));
+    write_csv_row(writer, &header)?;
+
+    let mut rows = 0;
+    for player in players.values() {
+        for position in &player.positions {
+            let Some(metrics) = MetricMap::for_player(player, position) else { continue };
+            let score = model.score(position, &metrics);
+            let scored = Metric::for_position(position);
+            let mut row = vec![player.name.to_string(), position.to_string(), player.team.clone().unwrap_or_default(), locale.num(score).to_string()];
+            for &metric in &scored_metrics {
+                match scored.iter().position(|&m| m == metric) {
+                    Some(i) => row.extend([locale.num(metrics.raw[i]).to_string(), locale.num(metrics.normalized[i]).to_string()]),
+                    None => row.extend([String::new(), String::new()]),
+                }
+            }
+            row.extend(stats.iter().map(|&stat| player.stats.get(stat).map(|&value| locale.num(value).to_string()).unwrap_or_default()));
+            write_csv_row(writer, &row)?;
+            rows += 1;
+        }
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::cleaning::Player;
+    use crate::model::WeightedSigmoid;
+    use crate::scoring::rank_players_with;
+    use crate::tiers::Tiers;
+
+    #[test]
+    fn test_write_csv_row_quotes_when_needed() {
+        let mut out = Vec::new();
+        write_csv_row(&mut out, &["Cale Makar", "Makar, Cale", "the \"Cale\" show"]).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "Cale Makar,\"Makar, Cale\",\"the \"\"Cale\"\" show\"\n");
+    }
+
+    #[test]
+    fn test_write_dataset_csv_has_a_row_per_position() {
+        let mut dual = Player::new("Dual", vec![Position::Center, Position::Wing], HashMap::from([(Position::Center, vec![0.5; 5]), (Position::Wing, vec![0.25; 5])]));
+        dual.raw_metrics = HashMap::from([(Position::Center, vec![50.0; 5]), (Position::Wing, vec![25.0; 5])]);
+        dual.stats = [("GP", 82.0)].into();
+        dual.team = Some("TOR".to_string());
+        let players = PlayerMap::from_iter([(dual.name.clone(), dual)]);
+
+        let mut out = Vec::new();
+        assert_eq!(write_dataset_csv(&mut out, &players, &WeightedSigmoid::default(), &Locale::default()).unwrap(), 2);
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<Vec<&str>> = csv.lines().map(|line| line.split(',').collect()).collect();
+        assert_eq!(lines[0][..6], ["player", "position", "team", "score", "Goals raw", "Goals norm"]);
+        assert_eq!(lines[0].last(), Some(&"GP"));
+        assert!(lines.iter().all(|line| line.len() == lines[0].len()));
+
+        let column = |name: &str| lines[0].iter().position(|&column| column == name).unwrap();
+        let center = lines.iter().find(|line| line[1] == "Center").unwrap();
+        assert_eq!((center[2], center[column("Faceoffs % raw")], center[column("Faceoffs % norm")]), ("TOR", "50", "0.5"));
+        assert_eq!(center[column("Goals norm")], "");
+        let wing = lines.iter().find(|line| line[1] == "Wing").unwrap();
+        assert_eq!((wing[column("Goals norm")], wing[column("GP")]), ("0.25", "82"));
+    }
+
+    #[test]
+    fn test_write_position_csv_is_sorted_with_raw_metrics() {
+        let players: PlayerMap = [("Low", 0.2), ("High", 0.8)]
+            .iter()
+            .map(|&(name, value)| {
+                (name.into(), Player::new(name, vec![Position::Defense], HashMap::from([(Position::Defense, vec![value; 5])])))
+            })
+            .collect();
+        let model = WeightedSigmoid::default();
+        let tiers = Tiers::default();
+        let ranked = &rank_players_with(&players, &model)[&Position::Defense];
+
+        let mut out = Vec::new();
+        write_position_csv(&mut out, &players, &Position::Defense, ranked, &Grades::new(&players, &model, &tiers), &model, &Locale::default()).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert!(lines[0].starts_with("rank,player,score,grade,Hits,Shots Blocked,Takeaways,Total Points,Rush Attempts,Hits pts,"));
+        assert!(lines[1].starts_with("1,High,"));
+        assert!(lines[1].contains(",A+,0.8,0.8,0.8,0.8,0.8,"));
+        assert!(lines[2].starts_with("2,Low,"));
+
+        // The metric points add back up to the score.
+        let fields: Vec<f64> = lines[1].split(',').skip(9).map(|field| field.parse().unwrap()).collect();
+        let score: f64 = lines[1].split(',').nth(2).unwrap().parse().unwrap();
+        assert_eq!(fields.len(), 5);
+        assert!((fields.iter().sum::<f64>() - score).abs() < 0.05);
+    }
+}
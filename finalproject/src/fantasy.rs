@@ -0,0 +1,87 @@
+use std::sync::Arc;
+use serde::Serialize;
+use crate::cleaning::{resolve_stat, Player, PlayerMap};
+
+pub const DEFAULT_POINT_VALUES: &str = "G=3,A=2,SOG=0.4,HIT=0.2,BLK=0.3";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointValue {
+    pub stat: &'static str,
+    pub points: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FantasyLine {
+    pub name: Arc<str>,
+    pub games: f64,
+    pub total: f64,
+    pub per_game: f64,
+    pub projection: f64,
+    pub breakdown: Vec<(&'static str, f64)>,
+}
+
+pub fn parse_point_values(spec: &str) -> Result<Vec<PointValue>, String> {
+    spec.split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .map(|entry| {
+            let (category, points) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("expected CATEGORY=POINTS, got '{}'", entry.trim()))?;
+            let stat = resolve_stat(category).ok_or_else(|| format!("unknown fantasy category '{}'", category.trim()))?;
+            let points = points
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("invalid point value '{}' for {}", points.trim(), category.trim()))?;
+            Ok(PointValue { stat, points })
+        })
+        .collect()
+}
+
+pub fn fantasy_line(player: &Player, values: &[PointValue], season_games: f64) -> FantasyLine {
+    let breakdown: Vec<(&'static str, f64)> = values
+        .iter()
+        .map(|value| (value.stat, player.stats.get(value.stat).copied().unwrap_or(0.0) * value.points))
+        .collect();
+    let total: f64 = breakdown.iter().map(|(_, points)| points).sum();
+    let games = player.stats.get("GP").copied().unwrap_or(0.0);
+    let per_game = if games > 0.0 { total / games } else { 0.0 };
+
+    FantasyLine { name: player.name.clone(), games, total, per_game, projection: per_game * season_games, breakdown }
+}
+
+pub fn fantasy_leaderboard(players: &PlayerMap, values: &[PointValue], season_games: f64) -> Vec<FantasyLine> {
+    let mut lines: Vec<FantasyLine> = players.values().map(|player| fantasy_line(player, values, season_games)).collect();
+    lines.sort_by(|a, b| b.total.partial_cmp(&a.total).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.name.cmp(&b.name)));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::cleaning::Position;
+
+    #[test]
+    fn test_parse_point_values() {
+        let values = parse_point_values("G=3, A=2,Hit=0.2,shots blocked=0.3").unwrap();
+        assert_eq!(values[0], PointValue { stat: "Goals", points: 3.0 });
+        assert_eq!(values[1].stat, "Total Assists");
+        assert_eq!(values[2].stat, "Hits");
+        assert_eq!(values[3].stat, "Shots Blocked");
+
+        assert!(parse_point_values("G3").is_err());
+        assert!(parse_point_values("XYZ=1").is_err());
+        assert!(parse_point_values("G=lots").is_err());
+    }
+
+    #[test]
+    fn test_fantasy_line_projects_per_game() {
+        let mut player = Player::new("Cale Makar", vec![Position::Defense], HashMap::new());
+        player.stats = [("GP", 41.0), ("Goals", 10.0), ("Hits", 50.0)].into();
+
+        let line = fantasy_line(&player, &parse_point_values("G=3,HIT=0.2").unwrap(), 82.0);
+        assert_eq!(line.total, 40.0);
+        assert_eq!(line.projection, 80.0);
+        assert_eq!(line.breakdown, vec![("Goals", 30.0), ("Hits", 10.0)]);
+    }
+}
@@ -0,0 +1,384 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+use crate::cleaning::{metric_key, resolve_stat, PlayerMap, Position};
+use crate::model::{MetricMap, ScoringModel};
+use crate::scoring::calculate_score;
+use crate::stat_line::StatLine;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rate {
+    #[default]
+    Total,
+    Per60,
+    PerGame,
+}
+
+impl Rate {
+    // The stat a rate divides by, and how many of its units make one: TOI is in minutes.
+    pub fn per(&self) -> Option<(&'static str, f64)> {
+        match self {
+            Rate::Total => None,
+            Rate::Per60 => Some(("TOI", 60.0)),
+            Rate::PerGame => Some(("GP", 1.0)),
+        }
+    }
+
+    // None when the stat a rate divides by is missing or zero, as a total isn't comparable to a rate.
+    pub fn divisor(&self, stats: &StatLine) -> Option<f64> {
+        match self.per() {
+            None => Some(1.0),
+            Some((stat, units)) => stats.get(stat).map(|value| value / units).filter(|divisor| *divisor > 0.0),
+        }
+    }
+
+    pub fn apply(&self, value: f64, stats: &StatLine) -> Option<f64> {
+        self.divisor(stats).map(|divisor| value / divisor)
+    }
+}
+
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Rate::Total => "total",
+            Rate::Per60 => "per-60",
+            Rate::PerGame => "per-game",
+        })
+    }
+}
+
+impl FromStr for Rate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match metric_key(s).as_str() {
+            "total" | "totals" => Ok(Rate::Total),
+            "pergame" | "pg" => Ok(Rate::PerGame),
+            "per60" | "p60" => Ok(Rate::Per60),
+            _ => Err(format!("invalid rate '{}', expected total, pergame or per60", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variable {
+    pub name: String,
+    pub stat: &'static str,
+    pub rate: Rate,
+}
+
+impl Variable {
+    fn parse(name: &str) -> Result<Variable, String> {
+        let key = metric_key(name);
+        let (base, rate) = if let Some(base) = key.strip_suffix("per60") {
+            (base, Rate::Per60)
+        } else if let Some(base) = key.strip_suffix("pergame") {
+            (base, Rate::PerGame)
+        } else {
+            (key.as_str(), Rate::Total)
+        };
+
+        // Keep the percent sign so on-ice shares like `xgf%` aren't read as their raw counts.
+        let base = if name.contains('%') { format!("{}%", base) } else { base.to_string() };
+        let stat = resolve_stat(&base).ok_or_else(|| format!("unknown metric '{}' in scoring formula", name))?;
+        Ok(Variable { name: name.to_string(), stat, rate })
+    }
+
+    pub fn value(&self, stats: &StatLine) -> f64 {
+        self.rate.apply(stats.get(self.stat).copied().unwrap_or(0.0), stats).unwrap_or(0.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Variable(usize),
+    Neg(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn evaluate(&self, values: &[f64]) -> f64 {
+        match self {
+            Expr::Number(n) => *n,
+            Expr::Variable(i) => values[*i],
+            Expr::Neg(inner) => -inner.evaluate(values),
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.evaluate(values), rhs.evaluate(values));
+                match op {
+                    '+' => lhs + rhs,
+                    '-' => lhs - rhs,
+                    '*' => lhs * rhs,
+                    '/' => lhs / rhs,
+                    _ => lhs.powf(rhs),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+    Open,
+    Close,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+            while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit() || **d == '.') {
+                number.push(d);
+                chars.next();
+            }
+            tokens.push(Token::Number(number.parse().map_err(|_| format!("invalid number '{}'", number))?));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut ident = String::new();
+            while let Some(&d) = chars.peek().filter(|d| d.is_alphanumeric() || **d == '_' || **d == '%') {
+                ident.push(d);
+                chars.next();
+            }
+            tokens.push(Token::Ident(ident));
+        } else {
+            tokens.push(match c {
+                '+' | '-' | '*' | '/' | '^' => Token::Op(c),
+                '(' => Token::Open,
+                ')' => Token::Close,
+                _ => return Err(format!("unexpected character '{}' in scoring formula", c)),
+            });
+            chars.next();
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    variables: Vec<Variable>,
+}
+
+impl Parser {
+    fn peek_op(&self) -> Option<char> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) => Some(*op),
+            _ => None,
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expr, String> {
+        let mut expr = self.term()?;
+        while let Some(op @ ('+' | '-')) = self.peek_op() {
+            self.pos += 1;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.term()?));
+        }
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut expr = self.factor()?;
+        while let Some(op @ ('*' | '/')) = self.peek_op() {
+            self.pos += 1;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.factor()?));
+        }
+        Ok(expr)
+    }
+
+    fn factor(&mut self) -> Result<Expr, String> {
+        let base = self.unary()?;
+        if self.peek_op() == Some('^') {
+            self.pos += 1;
+            return Ok(Expr::Binary('^', Box::new(base), Box::new(self.factor()?)));
+        }
+        Ok(base)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.peek_op() == Some('-') {
+            self.pos += 1;
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        let token = self.tokens.get(self.pos).cloned().ok_or("scoring formula ends unexpectedly")?;
+        self.pos += 1;
+
+        match token {
+            Token::Number(n) => Ok(Expr::Number(n)),
+            Token::Ident(name) => {
+                let variable = Variable::parse(&name)?;
+                let index = match self.variables.iter().position(|v| v.stat == variable.stat && v.rate == variable.rate) {
+                    Some(index) => index,
+                    None => {
+                        self.variables.push(variable);
+                        self.variables.len() - 1
+                    }
+                };
+                Ok(Expr::Variable(index))
+            }
+            Token::Open => {
+                let expr = self.expression()?;
+                match self.tokens.get(self.pos) {
+                    Some(Token::Close) => {
+                        self.pos += 1;
+                        Ok(expr)
+                    }
+                    _ => Err("missing ')' in scoring formula".to_string()),
+                }
+            }
+            Token::Op(op) => Err(format!("unexpected '{}' in scoring formula", op)),
+            Token::Close => Err("unexpected ')' in scoring formula".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    pub source: String,
+    pub variables: Vec<Variable>,
+    expr: Expr,
+}
+
+impl Formula {
+    pub fn parse(source: &str) -> Result<Formula, String> {
+        // Accept the `score = ...` spelling used in config examples as well as a bare expression.
+        let body = match source.split_once('=') {
+            Some((lhs, rhs)) if lhs.trim().eq_ignore_ascii_case("score") => rhs,
+            _ => source,
+        };
+
+        let mut parser = Parser { tokens: tokenize(body)?, pos: 0, variables: Vec::new() };
+        if parser.tokens.is_empty() {
+            return Err("scoring formula is empty".to_string());
+        }
+
+        let expr = parser.expression()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(format!("unexpected trailing input in scoring formula '{}'", source.trim()));
+        }
+
+        Ok(Formula { source: source.trim().to_string(), variables: parser.variables, expr })
+    }
+
+    pub fn evaluate(&self, values: &[f64]) -> f64 {
+        self.expr.evaluate(values)
+    }
+}
+
+// Positions without a formula keep the built-in weighted score.
+#[derive(Debug, Clone, Default)]
+pub struct FormulaSet {
+    pub formulas: HashMap<Position, Formula>,
+    maxes: HashMap<Position, Vec<f64>>,
+}
+
+impl FormulaSet {
+    pub fn new(formulas: HashMap<Position, Formula>) -> Self {
+        FormulaSet { formulas, maxes: HashMap::new() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.formulas.is_empty()
+    }
+}
+
+impl ScoringModel for FormulaSet {
+    fn name(&self) -> &str {
+        "formula"
+    }
+
+    // Variables are normalized against the best value at the position, like the built-in metrics.
+    fn fit(&mut self, players: &PlayerMap) {
+        self.maxes.clear();
+        for (position, formula) in &self.formulas {
+            let mut max = vec![0.0_f64; formula.variables.len()];
+            for player in players.values().filter(|p| p.positions.contains(position)) {
+                for (m, variable) in max.iter_mut().zip(&formula.variables) {
+                    *m = m.max(variable.value(&player.stats));
+                }
+            }
+            self.maxes.insert(position.clone(), max);
+        }
+    }
+
+    fn league_relative(&self) -> bool {
+        !self.formulas.is_empty()
+    }
+
+    fn score(&self, position: &Position, metrics: &MetricMap) -> f64 {
+        let Some(formula) = self.formulas.get(position) else {
+            return calculate_score(position, metrics.normalized);
+        };
+        let empty = Vec::new();
+        let max = self.maxes.get(position).unwrap_or(&empty);
+
+        let values: Vec<f64> = formula
+            .variables
+            .iter()
+            .enumerate()
+            .map(|(i, variable)| {
+                let m = max.get(i).copied().unwrap_or(0.0);
+                if m > 0.0 { variable.value(metrics.stats) / m } else { 0.0 }
+            })
+            .collect();
+        formula.evaluate(&values)
+    }
+
+    fn unit(&self, position: &Position) -> &'static str {
+        if self.formulas.contains_key(position) { "" } else { "%" }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cleaning::Player;
+    use crate::scoring::rank_players_with;
+
+    #[test]
+    fn test_parse_respects_precedence() {
+        let formula = Formula::parse("score = 2 + 3 * goals ^ 2 - (assists - 1) / 2").unwrap();
+        assert_eq!(formula.variables.len(), 2);
+        assert_eq!(formula.variables[0].stat, "Goals");
+        assert_eq!(formula.variables[1].stat, "Total Assists");
+        assert_eq!(formula.evaluate(&[2.0, 5.0]), 12.0);
+        assert_eq!(Formula::parse("-goals * -2").unwrap().evaluate(&[3.0]), 6.0);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Formula::parse("").is_err());
+        assert!(Formula::parse("0.5 * goals +").is_err());
+        assert!(Formula::parse("(goals").is_err());
+        assert!(Formula::parse("goals goals").is_err());
+        assert!(Formula::parse("0.5 * wins").is_err());
+        assert!(Formula::parse("goals # 2").is_err());
+        assert_eq!(Formula::parse("xgf% + hdcf%_per60").unwrap().variables[0].stat, "xGF%");
+    }
+
+    #[test]
+    fn test_rank_players_normalizes_rate_variables() {
+        let metrics = HashMap::from([(Position::Wing, vec![0.0; 5])]);
+        let mut a = Player::new("A", vec![Position::Wing], metrics.clone());
+        a.stats = [("Total Points", 60.0), ("TOI", 1200.0)].into();
+        let mut b = Player::new("B", vec![Position::Wing], metrics);
+        b.stats = [("Total Points", 40.0), ("TOI", 600.0)].into();
+        let players = PlayerMap::from_iter([("A".into(), a), ("B".into(), b)]);
+
+        let mut formulas = FormulaSet::new(HashMap::from([(Position::Wing, Formula::parse("points_per60").unwrap())]));
+        formulas.fit(&players);
+        let ranked = &rank_players_with(&players, &formulas)[&Position::Wing];
+        assert_eq!(&*ranked[0].name, "B");
+        assert_eq!(ranked[0].score, 1.0);
+        assert_eq!(ranked[1].score, 0.75);
+    }
+}
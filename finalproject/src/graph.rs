@@ -0,0 +1,142 @@
+use std::{collections::{BTreeMap, HashMap, VecDeque}, sync::Arc};
+
+// A weighted directed graph over named nodes. Names match case-insensitively and keep the spelling
+// they were first added with; adjacency is kept both ways and ordered by node id, so walks and
+// ties come out the same from run to run. Undirected graphs store each edge in both directions.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Graph {
+    names: Vec<Arc<str>>,
+    ids: HashMap<String, usize>,
+    outgoing: Vec<BTreeMap<usize, f64>>,
+    incoming: Vec<BTreeMap<usize, f64>>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Graph::default()
+    }
+
+    // The node's id, adding it if it's new.
+    pub fn node(&mut self, name: &str) -> usize {
+        if let Some(&id) = self.ids.get(&name.to_lowercase()) {
+            return id;
+        }
+        let id = self.names.len();
+        self.names.push(Arc::from(name));
+        self.ids.insert(name.to_lowercase(), id);
+        self.outgoing.push(BTreeMap::new());
+        self.incoming.push(BTreeMap::new());
+        id
+    }
+
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(&name.trim().to_lowercase()).copied()
+    }
+
+    pub fn name(&self, id: usize) -> &Arc<str> {
+        &self.names[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    // Adds to the edge's weight if it's already there.
+    pub fn add_edge(&mut self, from: usize, to: usize, weight: f64) {
+        *self.outgoing[from].entry(to).or_default() += weight;
+        *self.incoming[to].entry(from).or_default() += weight;
+    }
+
+    pub fn add_undirected(&mut self, a: usize, b: usize, weight: f64) {
+        self.add_edge(a, b, weight);
+        if a != b {
+            self.add_edge(b, a, weight);
+        }
+    }
+
+    pub fn weight(&self, from: usize, to: usize) -> Option<f64> {
+        self.outgoing[from].get(&to).copied()
+    }
+
+    pub fn out_edges(&self, id: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+        self.outgoing[id].iter().map(|(&to, &weight)| (to, weight))
+    }
+
+    pub fn in_edges(&self, id: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+        self.incoming[id].iter().map(|(&from, &weight)| (from, weight))
+    }
+
+    // Breadth-first over outgoing edges, so the fewest hops regardless of weight. Both ends are included.
+    pub fn shortest_path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        let mut previous: Vec<Option<usize>> = vec![None; self.len()];
+        let mut queue = VecDeque::from([from]);
+        previous[from] = Some(from);
+
+        while let Some(id) = queue.pop_front() {
+            if id == to {
+                let mut path = vec![to];
+                while let Some(&last) = path.last().filter(|&&last| last != from) {
+                    path.push(previous[last]?);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for (next, _) in self.out_edges(id) {
+                if previous[next].is_none() {
+                    previous[next] = Some(id);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize, f64)> + '_ {
+        self.outgoing.iter().enumerate().flat_map(|(from, edges)| edges.iter().map(move |(&to, &weight)| (from, to, weight)))
+    }
+}
+
+// Heaviest first, breaking ties by name.
+pub fn sort_by_weight(graph: &Graph, edges: &mut [(usize, f64)]) {
+    edges.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal).then_with(|| graph.name(a.0).cmp(graph.name(b.0))));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edges_accumulate_and_names_match_case_insensitively() {
+        let mut graph = Graph::new();
+        let (a, b) = (graph.node("Connor McDavid"), graph.node("Leon Draisaitl"));
+        assert_eq!(graph.node("connor mcdavid"), a);
+        assert_eq!(graph.id(" LEON DRAISAITL "), Some(b));
+
+        graph.add_edge(a, b, 1.0);
+        graph.add_edge(a, b, 2.0);
+        graph.add_undirected(b, a, 0.5);
+        assert_eq!(graph.weight(a, b), Some(3.5));
+        assert_eq!(graph.weight(b, a), Some(0.5));
+        assert_eq!(graph.in_edges(b).collect::<Vec<_>>(), [(a, 3.5)]);
+        assert_eq!(graph.edges().count(), 2);
+        assert_eq!(&**graph.name(a), "Connor McDavid");
+    }
+
+    #[test]
+    fn test_shortest_path_takes_fewest_hops() {
+        let mut graph = Graph::new();
+        let ids: Vec<usize> = ["A", "B", "C", "D", "E"].iter().map(|name| graph.node(name)).collect();
+        for (a, b) in [(0, 1), (1, 2), (2, 3), (0, 4), (4, 3)] {
+            graph.add_undirected(ids[a], ids[b], 1.0);
+        }
+        let isolated = graph.node("F");
+
+        assert_eq!(graph.shortest_path(ids[0], ids[3]), Some(vec![ids[0], ids[4], ids[3]]));
+        assert_eq!(graph.shortest_path(ids[2], ids[2]), Some(vec![ids[2]]));
+        assert_eq!(graph.shortest_path(ids[0], isolated), None);
+    }
+}
@@ -0,0 +1,376 @@
+use std::{collections::HashMap, io, net::SocketAddr, sync::{Arc, RwLock}, time::{SystemTime, UNIX_EPOCH}};
+use tonic::{Request, Response, Status};
+use crate::cleaning::{find_player, Player as ParsedPlayer, PlayerMap, Position};
+use crate::engine::ScoringEngine;
+use crate::model::ScoringModel;
+use crate::scoring::{metric_names, rank_players_with, ScoredPlayer};
+use crate::positions;
+
+#[allow(clippy::all)]
+mod service {
+    include!(concat!(env!("OUT_DIR"), "/nhl_scores.Scores.rs"));
+}
+
+pub use service::scores_server::{Scores, ScoresServer};
+
+pub const DEFAULT_ADDR: &str = "127.0.0.1:50051";
+const DEFAULT_TOP: usize = 25;
+
+// The messages of proto/nhl_scores.proto.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Score {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub position: String,
+    #[prost(double, tag = "3")]
+    pub score: f64,
+    #[prost(uint32, tag = "4")]
+    pub rank: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PositionMetrics {
+    #[prost(string, tag = "1")]
+    pub position: String,
+    #[prost(string, repeated, tag = "2")]
+    pub metrics: Vec<String>,
+    #[prost(double, repeated, tag = "3")]
+    pub raw: Vec<f64>,
+    #[prost(double, repeated, tag = "4")]
+    pub normalized: Vec<f64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Player {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, repeated, tag = "2")]
+    pub positions: Vec<String>,
+    #[prost(string, optional, tag = "3")]
+    pub team: Option<String>,
+    #[prost(message, repeated, tag = "4")]
+    pub metrics: Vec<PositionMetrics>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct RankRequest {
+    #[prost(string, tag = "1")]
+    pub position: String,
+    #[prost(uint32, tag = "2")]
+    pub top: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct RankReply {
+    #[prost(message, repeated, tag = "1")]
+    pub scores: Vec<Score>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct LookupRequest {
+    #[prost(string, tag = "1")]
+    pub name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct LookupReply {
+    #[prost(message, optional, tag = "1")]
+    pub player: Option<Player>,
+    #[prost(message, repeated, tag = "2")]
+    pub scores: Vec<Score>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CompareRequest {
+    #[prost(string, tag = "1")]
+    pub first: String,
+    #[prost(string, tag = "2")]
+    pub second: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Comparison {
+    #[prost(string, tag = "1")]
+    pub position: String,
+    #[prost(double, tag = "2")]
+    pub first: f64,
+    #[prost(double, tag = "3")]
+    pub second: f64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CompareReply {
+    #[prost(message, optional, tag = "1")]
+    pub first: Option<Player>,
+    #[prost(message, optional, tag = "2")]
+    pub second: Option<Player>,
+    #[prost(message, repeated, tag = "3")]
+    pub positions: Vec<Comparison>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ConfigRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PositionWeights {
+    #[prost(string, tag = "1")]
+    pub position: String,
+    #[prost(string, repeated, tag = "2")]
+    pub metrics: Vec<String>,
+    #[prost(double, repeated, tag = "3")]
+    pub weights: Vec<f64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ConfigReply {
+    #[prost(string, tag = "1")]
+    pub path: String,
+    #[prost(string, tag = "2")]
+    pub model: String,
+    #[prost(uint64, tag = "3")]
+    pub loaded_at: u64,
+    #[prost(uint32, tag = "4")]
+    pub reloads: u32,
+    #[prost(string, tag = "5")]
+    pub error: String,
+    #[prost(message, repeated, tag = "6")]
+    pub weights: Vec<PositionWeights>,
+}
+
+// Everything requests read that a reload can change. A reload builds a new one and swaps it in
+// whole, so no request sees one load's players or config with another's rankings.
+struct Scored {
+    players: Arc<PlayerMap>,
+    rankings: HashMap<Position, Vec<ScoredPlayer>>,
+    config: ConfigReply,
+}
+
+impl Scored {
+    fn new(players: Arc<PlayerMap>, rankings: HashMap<Position, Vec<ScoredPlayer>>, model: &dyn ScoringModel, path: String, reloads: u32) -> Self {
+        let weights = positions::all()
+            .iter()
+            .filter_map(|position| {
+                Some(PositionWeights {
+                    position: position.to_string(),
+                    metrics: metric_names(position).iter().map(|name| name.to_string()).collect(),
+                    weights: model.weights(position)?.to_vec(),
+                })
+            })
+            .collect();
+        let loaded_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+        let config = ConfigReply { path, model: model.name().to_string(), loaded_at, reloads, error: String::new(), weights };
+        Scored { players, rankings, config }
+    }
+}
+
+type SharedScores = Arc<RwLock<Arc<Scored>>>;
+
+// Scores everyone up front, so requests only read and the model needn't be shared across threads.
+pub struct ScoresService {
+    scored: SharedScores,
+}
+
+impl ScoresService {
+    // `config_path` is the config file the scores came from, if any, as reported by the Config call.
+    pub fn new(players: PlayerMap, model: &dyn ScoringModel, config_path: Option<&str>) -> Self {
+        let rankings = rank_players_with(&players, model);
+        let scored = Scored::new(Arc::new(players), rankings, model, config_path.unwrap_or_default().to_string(), 0);
+        ScoresService { scored: Arc::new(RwLock::new(Arc::new(scored))) }
+    }
+
+    // A handle for swapping in new scores from another thread while requests keep being answered.
+    pub fn reloader(&self) -> Reloader {
+        Reloader { scored: self.scored.clone() }
+    }
+
+    fn snapshot(&self) -> Arc<Scored> {
+        self.scored.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn scores(&self, scored: &Scored, player: &ParsedPlayer) -> Vec<Score> {
+        player
+            .positions
+            .iter()
+            .filter_map(|position| {
+                let ranked = scored.rankings.get(position)?;
+                let rank = ranked.iter().position(|scored| scored.name == player.name)?;
+                Some(score_message(&ranked[rank], rank))
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone)]
+pub struct Reloader {
+    scored: SharedScores,
+}
+
+impl Reloader {
+    // Serves the engine's players and scores once it has applied a reload. The engine only rescored
+    // what the reload changed, so this is a copy rather than a new run.
+    pub fn reload(&self, engine: &ScoringEngine) {
+        let current = self.current();
+        let scored = Scored::new(Arc::new(engine.players().clone()), engine.rankings(), engine.model(), current.config.path.clone(), current.config.reloads + 1);
+        *self.scored.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(scored);
+    }
+
+    // Keeps serving the last good scores and reports why the reload failed.
+    pub fn failed(&self, error: &str) {
+        let current = self.current();
+        let config = ConfigReply { error: error.to_string(), ..current.config.clone() };
+        let scored = Scored { players: current.players.clone(), rankings: current.rankings.clone(), config };
+        *self.scored.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(scored);
+    }
+
+    fn current(&self) -> Arc<Scored> {
+        self.scored.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+fn find<'a>(scored: &'a Scored, name: &str) -> Result<&'a ParsedPlayer, Status> {
+    find_player(&scored.players, name).ok_or_else(|| Status::not_found(format!("player '{}' not found", name.trim())))
+}
+
+fn score_message(scored: &ScoredPlayer, rank: usize) -> Score {
+    Score { name: scored.name.to_string(), position: scored.position.to_string(), score: scored.score, rank: rank as u32 + 1 }
+}
+
+fn player_message(player: &ParsedPlayer) -> Player {
+    Player {
+        name: player.name.to_string(),
+        positions: player.positions.iter().map(Position::to_string).collect(),
+        team: player.team.clone(),
+        metrics: player
+            .positions
+            .iter()
+            .filter_map(|position| {
+                Some(PositionMetrics {
+                    position: position.to_string(),
+                    metrics: metric_names(position).iter().map(|name| name.to_string()).collect(),
+                    raw: player.raw_metrics.get(position)?.clone(),
+                    normalized: player.metrics.get(position)?.clone(),
+                })
+            })
+            .collect(),
+    }
+}
+
+#[tonic::async_trait]
+impl Scores for ScoresService {
+    async fn rank(&self, request: Request<RankRequest>) -> Result<Response<RankReply>, Status> {
+        let request = request.into_inner();
+        let positions = match request.position.trim() {
+            "" => positions::all().to_vec(),
+            position => vec![position.parse::<Position>().map_err(Status::invalid_argument)?],
+        };
+        let top = if request.top == 0 { DEFAULT_TOP } else { request.top as usize };
+        let scored = self.snapshot();
+        let scores = positions
+            .iter()
+            .filter_map(|position| scored.rankings.get(position))
+            .flat_map(|ranked| ranked.iter().take(top).enumerate().map(|(rank, scored)| score_message(scored, rank)))
+            .collect();
+        Ok(Response::new(RankReply { scores }))
+    }
+
+    async fn lookup(&self, request: Request<LookupRequest>) -> Result<Response<LookupReply>, Status> {
+        let scored = self.snapshot();
+        let player = find(&scored, &request.get_ref().name)?;
+        Ok(Response::new(LookupReply { player: Some(player_message(player)), scores: self.scores(&scored, player) }))
+    }
+
+    async fn compare(&self, request: Request<CompareRequest>) -> Result<Response<CompareReply>, Status> {
+        let scored = self.snapshot();
+        let (first, second) = (find(&scored, &request.get_ref().first)?, find(&scored, &request.get_ref().second)?);
+        let (first_scores, second_scores) = (self.scores(&scored, first), self.scores(&scored, second));
+        let positions = first_scores
+            .iter()
+            .filter_map(|a| {
+                let b = second_scores.iter().find(|b| b.position == a.position)?;
+                Some(Comparison { position: a.position.clone(), first: a.score, second: b.score })
+            })
+            .collect();
+        Ok(Response::new(CompareReply { first: Some(player_message(first)), second: Some(player_message(second)), positions }))
+    }
+
+    async fn config(&self, _request: Request<ConfigRequest>) -> Result<Response<ConfigReply>, Status> {
+        Ok(Response::new(self.snapshot().config.clone()))
+    }
+}
+
+// Blocks serving requests until the process is stopped.
+pub fn serve(addr: SocketAddr, service: ScoresService) -> io::Result<()> {
+    let runtime = tokio::runtime::Runtime::new()?;
+    log::info!("Serving gRPC on {}", addr);
+    runtime
+        .block_on(tonic::transport::Server::builder().add_service(ScoresServer::new(service)).serve(addr))
+        .map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::WeightedSigmoid;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(future)
+    }
+
+    #[test]
+    fn test_rank_lookup_and_compare() {
+        let wing = |name: &str, value: f64| {
+            (name.into(), ParsedPlayer::new(name, vec![Position::Wing], HashMap::from([(Position::Wing, vec![value; 5])])))
+        };
+        let service = ScoresService::new(PlayerMap::from_iter([wing("Brad Marchand", 0.6), wing("David Pastrnak", 0.9)]), &WeightedSigmoid::default(), None);
+
+        let ranked = block_on(service.rank(Request::new(RankRequest { position: "w".to_string(), top: 0 }))).unwrap().into_inner();
+        assert_eq!(ranked.scores.iter().map(|s| (s.name.as_str(), s.rank)).collect::<Vec<_>>(), [("David Pastrnak", 1), ("Brad Marchand", 2)]);
+        let invalid = block_on(service.rank(Request::new(RankRequest { position: "goalie".to_string(), top: 5 }))).unwrap_err();
+        assert_eq!(invalid.code(), tonic::Code::InvalidArgument);
+
+        let lookup = block_on(service.lookup(Request::new(LookupRequest { name: "brad marchand".to_string() }))).unwrap().into_inner();
+        assert_eq!(lookup.player.unwrap().metrics[0].raw, vec![0.6; 5]);
+        assert_eq!(lookup.scores[0].rank, 2);
+
+        let compare = CompareRequest { first: "David Pastrnak".to_string(), second: "Brad Marchand".to_string() };
+        let compared = block_on(service.compare(Request::new(compare))).unwrap().into_inner();
+        assert!(compared.positions[0].first > compared.positions[0].second);
+        let missing = CompareRequest { first: "Nobody".to_string(), second: "Brad Marchand".to_string() };
+        assert_eq!(block_on(service.compare(Request::new(missing))).unwrap_err().code(), tonic::Code::NotFound);
+    }
+
+    #[test]
+    fn test_reload_swaps_scores_and_reports_the_config() {
+        let wing = |name: &str, values: Vec<f64>| (name.into(), ParsedPlayer::new(name, vec![Position::Wing], HashMap::from([(Position::Wing, values)])));
+        let players = PlayerMap::from_iter([wing("Sniper", vec![1.0, 1.0, 0.0, 0.0, 0.0]), wing("Grinder", vec![0.0, 0.0, 0.0, 0.0, 1.0])]);
+        let service = ScoresService::new(players.clone(), &WeightedSigmoid::default(), Some("nhl_scores.toml"));
+        let top = || block_on(service.rank(Request::new(RankRequest { position: "w".to_string(), top: 1 }))).unwrap().into_inner().scores[0].name.clone();
+        let config = || block_on(service.config(Request::new(ConfigRequest {}))).unwrap().into_inner();
+        assert_eq!(top(), "Sniper");
+        assert_eq!((config().path.as_str(), config().reloads), ("nhl_scores.toml", 0));
+
+        let mut weights = crate::scoring::WeightTable::default();
+        weights.set_weight(&Position::Wing, "hits", 0.9).unwrap();
+        let reloader = service.reloader();
+        let mut engine = ScoringEngine::new(players, Box::new(WeightedSigmoid::default()), Default::default());
+        engine.set_model(Box::new(WeightedSigmoid::new(weights, HashMap::new(), Default::default())));
+        reloader.reload(&engine);
+        assert_eq!(top(), "Grinder");
+        let reloaded = config();
+        assert_eq!(reloaded.reloads, 1);
+        assert_eq!(reloaded.weights.iter().find(|w| w.position == "Wing").unwrap().weights[4], 0.9);
+
+        reloader.failed("weights.wing sums to 2, expected 1.0");
+        assert_eq!((top().as_str(), config().reloads), ("Grinder", 1));
+        assert!(config().error.contains("sums to 2"));
+
+        // A reloaded export swaps in the players along with their scores.
+        engine.sync(PlayerMap::from_iter([wing("Sniper", vec![1.0, 1.0, 0.0, 0.0, 0.0]), wing("Enforcer", vec![0.0, 0.0, 0.0, 0.0, 2.0])]));
+        reloader.reload(&engine);
+        assert_eq!((top().as_str(), config().reloads, config().error.as_str()), ("Enforcer", 2, ""));
+        let lookup = |name: &str| block_on(service.lookup(Request::new(LookupRequest { name: name.to_string() })));
+        assert!(lookup("Enforcer").is_ok());
+        assert_eq!(lookup("Grinder").unwrap_err().code(), tonic::Code::NotFound);
+    }
+}
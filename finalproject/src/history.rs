@@ -0,0 +1,131 @@
+use std::{collections::BTreeSet, fs::{self, OpenOptions}, io::{self, BufWriter, Write}};
+use crate::age::civil_from_days;
+use crate::cleaning::{split_fields, PlayerMap};
+use crate::export::write_csv_row;
+use crate::model::ScoringModel;
+use crate::scoring::{overall_score, rank_players_with};
+
+const HEADER: [&str; 4] = ["recorded_at", "player", "position", "score"];
+
+// The `position` recorded for a player's overall rating, next to their per-position scores.
+pub const OVERALL: &str = "Overall";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub recorded_at: String,
+    pub player: String,
+    pub position: String,
+    pub score: f64,
+}
+
+// e.g. `2024-03-01T18:30:00Z`, which sorts in time order as text.
+pub fn timestamp(seconds: u64) -> String {
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let time = seconds % 86_400;
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, time / 3600, time % 3600 / 60, time % 60)
+}
+
+// Every player's score at each of their positions plus their overall rating, as of `recorded_at`.
+pub fn current_entries(players: &PlayerMap, model: &dyn ScoringModel, recorded_at: &str) -> Vec<HistoryEntry> {
+    let entry = |player: &str, position: String, score: f64| HistoryEntry { recorded_at: recorded_at.to_string(), player: player.to_string(), position, score };
+    let mut entries: Vec<HistoryEntry> = rank_players_with(players, model)
+        .into_iter()
+        .flat_map(|(position, scored)| scored.into_iter().map(move |scored| (position.to_string(), scored)))
+        .map(|(position, scored)| entry(&scored.name, position, scored.score))
+        .chain(players.values().filter_map(|player| Some(entry(&player.name, OVERALL.to_string(), overall_score(player, model)?))))
+        .collect();
+    entries.sort_by(|a, b| (&a.player, &a.position).cmp(&(&b.player, &b.position)));
+    entries
+}
+
+// A missing file is an empty history.
+pub fn read_history(path: &str) -> io::Result<Vec<HistoryEntry>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut entries = Vec::new();
+    for (i, line) in text.lines().enumerate().skip(1).filter(|(_, line)| !line.trim().is_empty()) {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("{}: line {}: expected {}", path, i + 1, HEADER.join(",")));
+        let [recorded_at, player, position, score] = split_fields(line, ',')[..] else { return Err(invalid()) };
+        let score = score.parse::<f64>().map_err(|_| invalid())?;
+        entries.push(HistoryEntry { recorded_at: recorded_at.to_string(), player: player.to_string(), position: position.to_string(), score });
+    }
+    Ok(entries)
+}
+
+// Appends a run's scores, unless they're the same as the last run recorded so re-running on an
+// unchanged export doesn't pad the history. Returns whether anything was written.
+pub fn append_history(path: &str, entries: &[HistoryEntry]) -> io::Result<bool> {
+    let existing = read_history(path)?;
+    let scores = |entries: &mut dyn Iterator<Item = &HistoryEntry>| -> BTreeSet<(String, String, String)> {
+        entries.map(|entry| (entry.player.clone(), entry.position.clone(), format!("{:.4}", entry.score))).collect()
+    };
+    let last_run = existing.iter().map(|entry| &entry.recorded_at).max();
+    if last_run.is_some() && scores(&mut existing.iter().filter(|entry| Some(&entry.recorded_at) == last_run)) == scores(&mut entries.iter()) {
+        return Ok(false);
+    }
+
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let write_header = file.metadata()?.len() == 0;
+    let mut writer = BufWriter::new(file);
+    if write_header {
+        write_csv_row(&mut writer, &HEADER)?;
+    }
+    for entry in entries {
+        write_csv_row(&mut writer, &[entry.recorded_at.as_str(), &entry.player, &entry.position, &format!("{:.4}", entry.score)])?;
+    }
+    writer.flush()?;
+    Ok(true)
+}
+
+// One player's entries grouped by run, oldest first, matching the name ignoring case.
+pub fn player_history<'a>(entries: &'a [HistoryEntry], name: &str) -> Vec<(&'a str, Vec<&'a HistoryEntry>)> {
+    let name = name.trim();
+    let mut runs: Vec<(&str, Vec<&HistoryEntry>)> = Vec::new();
+    let mut matching: Vec<&HistoryEntry> = entries.iter().filter(|entry| entry.player.eq_ignore_ascii_case(name)).collect();
+    matching.sort_by(|a, b| a.recorded_at.cmp(&b.recorded_at));
+    for entry in matching {
+        match runs.last_mut() {
+            Some((recorded_at, run)) if *recorded_at == entry.recorded_at => run.push(entry),
+            _ => runs.push((&entry.recorded_at, vec![entry])),
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+    use crate::cleaning::{Player, Position};
+    use crate::model::WeightedSigmoid;
+
+    #[test]
+    fn test_history_appends_only_changed_runs() {
+        assert_eq!(timestamp(951_935_400), "2000-03-01T18:30:00Z");
+
+        let path = std::env::temp_dir().join(format!("nhl_scores_history_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = fs::remove_file(path);
+        let players = |value: f64| {
+            let player = Player::new("Smith, Jr.", vec![Position::Center, Position::Wing], HashMap::from([(Position::Center, vec![value; 5]), (Position::Wing, vec![value; 5])]));
+            PlayerMap::from_iter([(player.name.clone(), player)])
+        };
+        let model = WeightedSigmoid::default();
+
+        assert!(append_history(path, &current_entries(&players(0.5), &model, "2024-01-01T00:00:00Z")).unwrap());
+        assert!(!append_history(path, &current_entries(&players(0.5), &model, "2024-01-02T00:00:00Z")).unwrap());
+        assert!(append_history(path, &current_entries(&players(0.8), &model, "2024-01-03T00:00:00Z")).unwrap());
+
+        let entries = read_history(path).unwrap();
+        assert_eq!(entries.len(), 6);
+        let runs = player_history(&entries, "smith, jr.");
+        assert_eq!(runs.iter().map(|(recorded_at, run)| (*recorded_at, run.len())).collect::<Vec<_>>(), [("2024-01-01T00:00:00Z", 3), ("2024-01-03T00:00:00Z", 3)]);
+        assert!(runs[1].1.iter().zip(&runs[0].1).all(|(new, old)| new.position == old.position && new.score > old.score));
+        assert!(player_history(&entries, "Nobody").is_empty());
+        fs::remove_file(path).unwrap();
+    }
+}
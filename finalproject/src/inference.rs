@@ -0,0 +1,114 @@
+use std::{collections::BTreeMap, fs, io};
+use crate::cleaning::{column_index, Position};
+use crate::positions;
+
+const CENTER_FACEOFFS_PER_GAME: f64 = 4.0;
+const DEFENSE_BLOCKS_PER_GAME: f64 = 0.9;
+
+// The position codes the export uses, which is what the cleaning step matches on.
+pub fn position_code(position: &Position) -> &'static str {
+    match position {
+        Position::Center => "C",
+        Position::Wing => "L",
+        Position::Defense => "D",
+        Position::Custom(id) => positions::definition(*id).map_or("?", |def| def.id.as_str()),
+    }
+}
+
+// `name,position` lines, where position is e.g. `C`, `Wing` or `C/L`; a header row is skipped.
+pub fn parse_roster(text: &str) -> Result<BTreeMap<String, String>, String> {
+    let mut roster = BTreeMap::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (name, positions) = line.rsplit_once(',').ok_or_else(|| format!("line {}: expected `name,position`", i + 1))?;
+        let codes: Result<Vec<&str>, String> = positions
+            .trim()
+            .trim_matches('"')
+            .split(['/', ';'])
+            .map(|position| position.parse::<Position>().map(|p| position_code(&p)))
+            .collect();
+
+        match codes {
+            Ok(codes) => {
+                roster.insert(name.trim().trim_matches('"').to_lowercase(), codes.join("/"));
+            }
+            Err(_) if i == 0 => continue,
+            Err(e) => return Err(format!("line {}: {}", i + 1, e)),
+        }
+    }
+
+    Ok(roster)
+}
+
+pub fn read_roster(path: &str) -> io::Result<BTreeMap<String, String>> {
+    parse_roster(&fs::read_to_string(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, e)))
+}
+
+fn per_game(fields: &[&str], columns: &[&str]) -> f64 {
+    let field = |name: &str| column_index(name).and_then(|i| fields.get(i)).and_then(|f| f.parse::<f64>().ok()).unwrap_or(0.0);
+    let games = field("GP");
+    if games > 0.0 { columns.iter().map(|&name| field(name)).sum::<f64>() / games } else { 0.0 }
+}
+
+// Fills in a blank or generic "F" position from the roster, or failing that from the player's stats.
+// Returns the position code with the reason it was chosen, or None when the row has a real position.
+pub fn infer_position<'a>(fields: &[&str], roster: &'a BTreeMap<String, String>) -> Option<(&'a str, String)> {
+    let (name, position) = (fields.get(1)?, fields.get(2)?);
+    // A config that declares its own `F` position scores generic forwards as that instead.
+    if !position.is_empty() && (!position.eq_ignore_ascii_case("F") || positions::find(position).is_some()) {
+        return None;
+    }
+
+    if let Some(code) = roster.get(&name.to_lowercase()) {
+        return Some((code, "from the roster file".to_string()));
+    }
+
+    let faceoffs = per_game(fields, &["Faceoffs Won", "Faceoffs Lost"]);
+    let blocks = per_game(fields, &["Shots Blocked"]);
+    if faceoffs >= CENTER_FACEOFFS_PER_GAME {
+        Some(("C", format!("{:.1} faceoffs per game", faceoffs)))
+    } else if position.is_empty() && blocks >= DEFENSE_BLOCKS_PER_GAME {
+        Some(("D", format!("{:.1} blocked shots per game", blocks)))
+    } else {
+        Some(("L", format!("{:.1} faceoffs and {:.1} blocked shots per game", faceoffs, blocks)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cleaning::COLUMNS;
+
+    fn row(position: &'static str, gp: &'static str, blocks: &'static str, faceoffs: &'static str) -> Vec<&'static str> {
+        let mut fields = vec![""; COLUMNS.len()];
+        fields[1] = "Player One";
+        fields[2] = position;
+        fields[column_index("GP").unwrap()] = gp;
+        fields[column_index("Shots Blocked").unwrap()] = blocks;
+        fields[column_index("Faceoffs Won").unwrap()] = faceoffs;
+        fields
+    }
+
+    #[test]
+    fn test_infer_position_from_stats() {
+        let roster = BTreeMap::new();
+        assert_eq!(infer_position(&row("C", "80", "10", "900"), &roster), None);
+        assert_eq!(infer_position(&row("F", "80", "10", "900"), &roster).unwrap().0, "C");
+        assert_eq!(infer_position(&row("", "80", "120", "0"), &roster).unwrap().0, "D");
+        assert_eq!(infer_position(&row("F", "80", "120", "0"), &roster).unwrap().0, "L");
+        assert_eq!(infer_position(&row("", "0", "", ""), &roster).unwrap().0, "L");
+    }
+
+    #[test]
+    fn test_roster_overrides_stats() {
+        let roster = parse_roster("name,position\n\"Player One\",Center/Wing\nPlayer Two,D\n").unwrap();
+        assert_eq!(roster["player one"], "C/L");
+        assert_eq!(infer_position(&row("", "80", "120", "0"), &roster).unwrap().0, "C/L");
+        assert!(parse_roster("Player One,C\nPlayer Two,goalie").is_err());
+    }
+}
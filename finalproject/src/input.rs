@@ -0,0 +1,111 @@
+use std::{fs, io, path::Path};
+use crate::cleaning::{insert_player, normalize_metrics, DuplicatePolicy, PlayerMap};
+
+// Every file the `--input` arguments name, in order. An argument with `*` or `?` in its file name is
+// a pattern matched against its directory, sorted so e.g. monthly exports load oldest first; one that
+// matches nothing is an error rather than a silently empty dataset.
+pub fn expand_inputs(patterns: &[String]) -> io::Result<Vec<String>> {
+    let mut paths = Vec::new();
+    for pattern in patterns {
+        let path = Path::new(pattern);
+        let Some(name) = path.file_name().and_then(|name| name.to_str()).filter(|name| name.contains(['*', '?'])) else {
+            paths.push(pattern.clone());
+            continue;
+        };
+
+        let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+        let mut matches: Vec<String> = fs::read_dir(dir.unwrap_or(Path::new(".")))
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", pattern, e)))?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_file()))
+            .filter(|entry| entry.file_name().to_str().is_some_and(|file_name| wildcard_match(name, file_name)))
+            .map(|entry| dir.map_or_else(|| entry.file_name().to_string_lossy().into_owned(), |dir| dir.join(entry.file_name()).display().to_string()))
+            .collect();
+        if matches.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("no files match {}", pattern)));
+        }
+        matches.sort();
+        paths.extend(matches);
+    }
+    Ok(paths)
+}
+
+// `*` matches any run of characters and `?` any single one.
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let (pattern, name): (Vec<char>, Vec<char>) = (pattern.chars().collect(), name.chars().collect());
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                // Let the last `*` swallow one more character and retry from there.
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    (p, n) = (star_p + 1, star_n + 1);
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+// Combines datasets parsed from separate files as if their rows had come from one, so a player split
+// across per-team or per-month files is resolved by the duplicate policy. Metrics are normalized
+// again over the merged league. Returns the players and how many rows were duplicates.
+pub fn merge_datasets(datasets: impl IntoIterator<Item = PlayerMap>, policy: DuplicatePolicy) -> (PlayerMap, usize) {
+    let mut players = PlayerMap::default();
+    let mut duplicates = 0;
+    for dataset in datasets {
+        for (_, mut player) in dataset {
+            player.metrics = player.raw_metrics.clone();
+            duplicates += insert_player(&mut players, player, policy) as usize;
+        }
+    }
+    normalize_metrics(&mut players);
+    (players, duplicates)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+    use crate::cleaning::{Player, Position};
+
+    #[test]
+    fn test_glob_inputs_merge_through_the_duplicate_policy() {
+        assert!(wildcard_match("*.csv", "2023-10.csv"));
+        assert!(wildcard_match("20??-*.csv", "2023-10.csv"));
+        assert!(!wildcard_match("*.csv", "2023-10.csv.gz"));
+        assert!(!wildcard_match("team-?.csv", "team-10.csv"));
+
+        let dir = std::env::temp_dir().join(format!("nhl_scores_inputs_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["b.csv", "a.csv", "notes.txt"] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+        let pattern = dir.join("*.csv").display().to_string();
+        let paths = expand_inputs(&[pattern, "NHL.csv".to_string()]).unwrap();
+        assert_eq!(paths, [dir.join("a.csv").display().to_string(), dir.join("b.csv").display().to_string(), "NHL.csv".to_string()]);
+        assert!(expand_inputs(&[dir.join("*.parquet").display().to_string()]).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+
+        let month = |points: f64| {
+            let mut player = Player::new("Sam Reinhart", vec![Position::Center], HashMap::from([(Position::Center, vec![50.0, points, 0.0, 0.0, 0.0])]));
+            player.metrics = HashMap::from([(Position::Center, vec![0.5, 1.0, 0.0, 0.0, 0.0])]);
+            PlayerMap::from_iter([(player.name.clone(), player)])
+        };
+        let (players, duplicates) = merge_datasets([month(10.0), month(6.0)], DuplicatePolicy::Sum);
+        assert_eq!(duplicates, 1);
+        assert_eq!(players["Sam Reinhart"].raw_metrics[&Position::Center][1], 16.0);
+        assert_eq!(players["Sam Reinhart"].metrics[&Position::Center][..2], [0.5, 1.0]);
+    }
+}
@@ -0,0 +1,138 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+use crate::similarity::distance;
+
+// A k-d tree over fixed-length points, split on each axis in turn at the median, so a k-nearest
+// query only visits the branches that could still hold something closer than what it has found.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KdTree {
+    points: Vec<Vec<f64>>,
+    nodes: Vec<Node>,
+    root: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Node {
+    point: usize,
+    axis: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+// A found neighbor, ordered by distance and then index so equal distances resolve the same way a
+// sorted brute-force scan would.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Candidate(f64, usize);
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then(self.1.cmp(&other.1))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl KdTree {
+    pub fn new(points: Vec<Vec<f64>>) -> Self {
+        let mut tree = KdTree { points, nodes: Vec::new(), root: None };
+        let mut indices: Vec<usize> = (0..tree.points.len()).collect();
+        tree.root = tree.build(&mut indices, 0);
+        tree
+    }
+
+    fn build(&mut self, indices: &mut [usize], depth: usize) -> Option<usize> {
+        if indices.is_empty() {
+            return None;
+        }
+        let dimensions = self.points[indices[0]].len().max(1);
+        let axis = depth % dimensions;
+        let points = &self.points;
+        indices.sort_by(|&a, &b| {
+            let (a, b) = (points[a].get(axis).copied().unwrap_or(0.0), points[b].get(axis).copied().unwrap_or(0.0));
+            a.total_cmp(&b)
+        });
+
+        let median = indices.len() / 2;
+        let point = indices[median];
+        let (below, above) = indices.split_at_mut(median);
+        let left = self.build(below, depth + 1);
+        let right = self.build(&mut above[1..], depth + 1);
+        self.nodes.push(Node { point, axis, left, right });
+        Some(self.nodes.len() - 1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn point(&self, index: usize) -> &[f64] {
+        &self.points[index]
+    }
+
+    // The `k` points closest to the target as (index, distance), nearest first, leaving out `exclude`.
+    pub fn nearest(&self, target: &[f64], k: usize, exclude: Option<usize>) -> Vec<(usize, f64)> {
+        let mut best = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            self.search(self.root, target, k, exclude, &mut best);
+        }
+        best.into_sorted_vec().into_iter().map(|Candidate(distance, index)| (index, distance)).collect()
+    }
+
+    fn search(&self, node: Option<usize>, target: &[f64], k: usize, exclude: Option<usize>, best: &mut BinaryHeap<Candidate>) {
+        let Some(node) = node.map(|node| &self.nodes[node]) else { return };
+        let point = &self.points[node.point];
+
+        if Some(node.point) != exclude {
+            let candidate = Candidate(distance(target, point), node.point);
+            if best.len() < k {
+                best.push(candidate);
+            } else if best.peek().is_some_and(|worst| candidate < *worst) {
+                best.pop();
+                best.push(candidate);
+            }
+        }
+
+        let offset = target.get(node.axis).copied().unwrap_or(0.0) - point.get(node.axis).copied().unwrap_or(0.0);
+        let (near, far) = if offset < 0.0 { (node.left, node.right) } else { (node.right, node.left) };
+        self.search(near, target, k, exclude, best);
+        // The far side can only help if the splitting plane is closer than the worst neighbor kept.
+        if best.len() < k || best.peek().is_some_and(|worst| offset.abs() <= worst.0) {
+            self.search(far, target, k, exclude, best);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nearest_matches_brute_force() {
+        // A small linear congruential generator keeps the points the same on every run.
+        let mut state: u64 = 42;
+        let mut next = || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 33) as f64 / (1u64 << 31) as f64
+        };
+        let points: Vec<Vec<f64>> = (0..300).map(|_| (0..5).map(|_| (next() * 10.0).round() / 10.0).collect()).collect();
+        let tree = KdTree::new(points.clone());
+        assert_eq!(tree.len(), 300);
+
+        for (i, target) in points.iter().enumerate().step_by(17) {
+            let mut expected: Vec<Candidate> = points.iter().enumerate().filter(|&(j, _)| j != i).map(|(j, p)| Candidate(distance(target, p), j)).collect();
+            expected.sort();
+            let expected: Vec<(usize, f64)> = expected.into_iter().take(7).map(|Candidate(d, j)| (j, d)).collect();
+            assert_eq!(tree.nearest(target, 7, Some(i)), expected);
+        }
+        assert!(KdTree::new(Vec::new()).nearest(&[0.0; 5], 3, None).is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use crate::age::{age_from_stats, AgeCurve};
+use crate::cleaning::PlayerMap;
+use crate::fantasy::{fantasy_line, parse_point_values, PointValue, DEFAULT_POINT_VALUES};
+use crate::projection::{aging, regress};
+
+// A keeper league's settings: the seasons a kept player is valued over, how much each later season
+// is discounted (0.15 counts next year's points 15% less), and the league's fantasy scoring.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeeperConfig {
+    pub seasons: usize,
+    pub discount: f64,
+    pub points: String,
+    pub games: f64,
+}
+
+impl Default for KeeperConfig {
+    fn default() -> Self {
+        KeeperConfig { seasons: 3, discount: 0.15, points: DEFAULT_POINT_VALUES.to_string(), games: 82.0 }
+    }
+}
+
+impl KeeperConfig {
+    pub fn point_values(&self) -> Result<Vec<PointValue>, String> {
+        parse_point_values(&self.points).map_err(|e| format!("keeper.points: {}", e))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.seasons == 0 {
+            return Err("keeper.seasons must be at least 1".to_string());
+        }
+        if !(0.0..1.0).contains(&self.discount) {
+            return Err(format!("keeper.discount must be at least 0 and below 1, got {}", self.discount));
+        }
+        if !(self.games.is_finite() && self.games > 0.0) {
+            return Err(format!("keeper.games must be positive, got {}", self.games));
+        }
+        self.point_values().map(|_| ())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeeperValue {
+    pub name: Arc<str>,
+    pub age: Option<f64>,
+    pub per_game: f64,
+    pub seasons: Vec<f64>,
+    pub value: f64,
+}
+
+// Projects each player's fantasy points for every season of the keeper window: their points per game
+// regressed toward the league rate and aged along `curve` a year at a time, as `projection::project`
+// does a score, then discounted back to today. Players without games played are left out. Sorted most valuable first.
+pub fn keeper_values(players: &PlayerMap, values: &[PointValue], league: &KeeperConfig, curve: &AgeCurve, reference_day: i64) -> Vec<KeeperValue> {
+    let lines: Vec<_> = players.values().map(|player| (player, fantasy_line(player, values, league.games))).filter(|(_, line)| line.games > 0.0).collect();
+    let total_games: f64 = lines.iter().map(|(_, line)| line.games).sum();
+    let league_rate = if total_games > 0.0 { lines.iter().map(|(_, line)| line.total).sum::<f64>() / total_games } else { 0.0 };
+
+    let mut keepers: Vec<KeeperValue> = lines
+        .into_iter()
+        .map(|(player, line)| {
+            let per_game = regress(line.per_game, line.games, league_rate);
+            let age = age_from_stats(&player.stats, reference_day);
+            let seasons: Vec<f64> = (1..=league.seasons).map(|season| per_game * league.games * aging(curve, age, season as f64)).collect();
+            let value = seasons.iter().enumerate().map(|(i, points)| points * (1.0 - league.discount).powi(i as i32)).sum();
+            KeeperValue { name: player.name.clone(), age, per_game, seasons, value }
+        })
+        .collect();
+
+    keepers.sort_by(|a, b| b.value.partial_cmp(&a.value).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.name.cmp(&b.name)));
+    keepers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::cleaning::{Player, Position};
+
+    fn skater(name: &str, goals: f64, gp: f64, age: f64) -> (Arc<str>, Player) {
+        let mut player = Player::new(name, vec![Position::Wing], HashMap::new());
+        player.stats = [("GP", gp), ("Goals", goals), ("Age", age)].into();
+        (player.name.clone(), player)
+    }
+
+    #[test]
+    fn test_keeper_value_ages_and_discounts() {
+        let players = PlayerMap::from_iter([
+            skater("Prospect", 40.0, 82.0, 21.0),
+            skater("Veteran", 40.0, 82.0, 33.0),
+            skater("Call-up", 5.0, 5.0, 21.0),
+            skater("Scratched", 0.0, 0.0, 25.0),
+        ]);
+        let league = KeeperConfig { seasons: 3, discount: 0.1, points: "G=1".to_string(), games: 82.0 };
+        let keepers = keeper_values(&players, &league.point_values().unwrap(), &league, &AgeCurve::default(), 0);
+
+        assert_eq!(keepers.len(), 3);
+        let find = |name: &str| keepers.iter().find(|k| &*k.name == name).unwrap();
+        let prospect = find("Prospect");
+        assert!(prospect.value > find("Veteran").value);
+        assert!(find("Veteran").seasons[2] < prospect.seasons[2]);
+        let discounted = prospect.seasons[0] + prospect.seasons[1] * 0.9 + prospect.seasons[2] * 0.81;
+        assert!((prospect.value - discounted).abs() < 1e-9);
+        // One hot stretch is pulled most of the way back to the league rate.
+        assert!(find("Call-up").per_game < 0.6 && find("Call-up").per_game > 40.0 / 82.0);
+
+        assert!(KeeperConfig { seasons: 0, ..KeeperConfig::default() }.validate().is_err());
+        assert!(KeeperConfig { discount: 1.0, ..KeeperConfig::default() }.validate().is_err());
+        assert!(KeeperConfig { points: "XYZ=1".to_string(), ..KeeperConfig::default() }.validate().is_err());
+    }
+}
@@ -0,0 +1,65 @@
+pub mod age;
+pub mod alerts;
+pub mod assists;
+pub mod audit;
+pub mod bio;
+pub mod cache;
+pub mod calibrate;
+pub mod chemistry;
+pub mod cleaning;
+pub mod clutch;
+pub mod community;
+pub mod config;
+pub mod diff;
+pub mod draft;
+pub mod emit;
+pub mod engine;
+pub mod even_strength;
+pub mod experience;
+pub mod export;
+pub mod fantasy;
+pub mod formula;
+pub mod graph;
+pub mod history;
+pub mod inference;
+pub mod input;
+pub mod kdtree;
+pub mod keeper;
+pub mod locale;
+pub mod luck;
+pub mod mapping;
+pub mod matrix;
+pub mod metric;
+pub mod model;
+pub mod onice;
+pub mod positions;
+pub mod projection;
+pub mod regression;
+pub mod runlog;
+pub mod salary;
+pub mod schema;
+pub mod scoring;
+pub mod sensitivity;
+pub mod similarity;
+pub mod special_teams;
+pub mod splits;
+pub mod stat_line;
+pub mod stats;
+pub mod team;
+pub mod teammates;
+pub mod tiers;
+pub mod usage;
+pub mod whatif;
+pub mod xg;
+
+#[cfg(feature = "parquet")]
+pub mod parquet_input;
+
+#[cfg(feature = "sqlite")]
+pub mod db;
+
+#[cfg(feature = "grpc")]
+pub mod grpc;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
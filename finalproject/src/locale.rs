@@ -0,0 +1,119 @@
+use std::{collections::BTreeMap, fmt};
+use serde::Deserialize;
+
+// Languages whose numbers are written with a decimal comma, e.g. `87,50` in French.
+const DECIMAL_COMMA_LANGUAGES: [&str; 21] =
+    ["bg", "ca", "cs", "da", "de", "el", "es", "et", "fi", "fr", "hr", "hu", "id", "it", "lt", "lv", "nb", "nl", "pl", "pt", "ru"];
+
+// The config's `[locale]` table: a locale name such as `fr-CA` picks the decimal separator, which
+// `decimal_separator` can override, and `labels` renames metrics and stats wherever they're shown,
+// e.g. `"Faceoffs %" = "Mises en jeu %"`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LocaleConfig {
+    pub name: Option<String>,
+    pub decimal_separator: Option<char>,
+    pub labels: BTreeMap<String, String>,
+}
+
+impl LocaleConfig {
+    // `name` (from `--locale`) takes the place of the config's.
+    pub fn resolve(&self, name: Option<&str>) -> Result<Locale, String> {
+        let decimal = match (self.decimal_separator, name.or(self.name.as_deref())) {
+            (Some(separator), _) => separator,
+            (None, Some(name)) => decimal_separator(name)?,
+            (None, None) => '.',
+        };
+        if decimal.is_ascii_digit() || decimal == '-' || decimal == '+' {
+            return Err(format!("locale: '{}' can't be a decimal separator", decimal));
+        }
+        Ok(Locale { decimal, labels: self.labels.clone() })
+    }
+}
+
+// `fr`, `fr-CA`, `fr_CA.UTF-8` and the like.
+pub fn decimal_separator(name: &str) -> Result<char, String> {
+    let language = name.split(['-', '_', '.']).next().unwrap_or_default().to_lowercase();
+    if language.len() < 2 || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(format!("invalid locale '{}', expected e.g. en-US or fr-CA", name));
+    }
+    Ok(if DECIMAL_COMMA_LANGUAGES.contains(&language.as_str()) { ',' } else { '.' })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Locale {
+    pub decimal: char,
+    pub labels: BTreeMap<String, String>,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale { decimal: '.', labels: BTreeMap::new() }
+    }
+}
+
+impl Locale {
+    // The display label for a metric or stat name, renamed if the config's `[locale.labels]` says so.
+    pub fn label(&self, name: &str) -> String {
+        self.labels.get(name).cloned().unwrap_or_else(|| name.to_string())
+    }
+
+    // Swaps the separator into a number already formatted the Rust way.
+    pub fn localize(&self, number: String) -> String {
+        if self.decimal == '.' { number } else { number.replace('.', &self.decimal.to_string()) }
+    }
+
+    pub fn num(&self, value: f64) -> Num<'_> {
+        Num { value, locale: self }
+    }
+}
+
+// A number written with the locale's decimal separator. Every format flag works as it does for an
+// `f64`, so `format!("{:^+9.2}", locale.num(x))` only differs from `format!("{:^+9.2}", x)` in the separator.
+#[derive(Debug, Clone, Copy)]
+pub struct Num<'a> {
+    value: f64,
+    locale: &'a Locale,
+}
+
+impl fmt::Display for Num<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // NaN and infinity have no decimal point to swap.
+        if !self.value.is_finite() {
+            return fmt::Display::fmt(&self.value, f);
+        }
+        let digits = match f.precision() {
+            Some(precision) => format!("{:.*}", precision, self.value.abs()),
+            None => self.value.abs().to_string(),
+        };
+        // Leaves the sign, fill, alignment and zero padding to the formatter; `pad` would also cut
+        // the digits down to the precision.
+        f.pad_integral(self.value.is_sign_positive(), "", &self.locale.localize(digits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_sets_separator_and_labels() {
+        assert_eq!(decimal_separator("fr_CA.UTF-8"), Ok(','));
+        assert_eq!(decimal_separator("en-US"), Ok('.'));
+        assert!(decimal_separator("1").is_err());
+
+        let config = LocaleConfig { name: Some("en".to_string()), labels: BTreeMap::from([("Hits".to_string(), "Mises en échec".to_string())]), ..LocaleConfig::default() };
+        assert_eq!(config.resolve(None).unwrap().decimal, '.');
+        assert_eq!(config.resolve(Some("fr-CA")).unwrap().decimal, ',');
+        assert!(LocaleConfig { decimal_separator: Some('5'), ..config.clone() }.resolve(None).is_err());
+
+        let french = config.resolve(Some("fr")).unwrap();
+        assert_eq!(french.localize(format!("{:+.2}", 1.5)), "+1,50");
+        assert_eq!(format!("{:+.2}|{:>7.1}|{:<4}|{:*^9.1}|{:07.2}|", french.num(1.5), french.num(-2.75), french.num(3.0), french.num(0.25), french.num(-4.5)), "+1,50|   -2,8|3   |***0,2***|-004,50|");
+        let english = Locale::default();
+        for value in [1.5, -2.75, 0.0, -0.001, f64::NAN] {
+            assert_eq!(format!("{:_^+10.2}|{:>6}", english.num(value), english.num(value)), format!("{:_^+10.2}|{:>6}", value, value));
+        }
+        assert_eq!((french.label("Hits"), french.label("Goals")), ("Mises en échec".to_string(), "Goals".to_string()));
+    }
+}
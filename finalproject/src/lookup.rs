@@ -0,0 +1,644 @@
+use std::{collections::HashMap, env, fs, io::{self, Write}, path::{Path, PathBuf}, str::FromStr};
+use clap::ValueEnum;
+use finalproject::age::today;
+use finalproject::bio::bio_line;
+use finalproject::chemistry::Chemistry;
+use finalproject::clutch::{clutch_line, clutch_scores, Clutch};
+use finalproject::cleaning::{find_player, raw_position_stats, Player, PlayerMap, Position, PositionStats};
+use finalproject::even_strength::ev_score;
+use finalproject::export::write_csv_row;
+use finalproject::locale::Locale;
+use finalproject::luck::{luck_factor, pdo};
+use finalproject::metric::{Metric, Scaling};
+use finalproject::model::{MetricMap, ScoringModel};
+use finalproject::regression::regression_flags;
+use finalproject::salary::{median_value, value_leaderboard, CAP_HIT_STAT};
+use finalproject::scoring::{metric_names, metric_percentiles, overall_score, position_reports, rank_players_with, score_contributions, versatility_bonus, ScoredPlayer, WeightTable};
+use finalproject::similarity::{similarity_indexes, SimilarityIndex};
+use finalproject::special_teams::{special_teams_score, Situation};
+use finalproject::team::{team_ratings, TeamIndex};
+use finalproject::tiers::{Grades, Tiers};
+use finalproject::whatif::{parse_what_if, what_if};
+use finalproject::xg::FINISHING_STAT;
+use finalproject::positions;
+use rustyline::{error::ReadlineError, DefaultEditor};
+use serde_json::json;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    Csv,
+    Json,
+}
+
+pub fn format_value(value: f64, locale: &Locale) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value)
+    } else {
+        format!("{:.2}", locale.num(value))
+    }
+}
+
+pub const HISTORY_FILE: &str = ".nhl_scores_history";
+
+fn history_path() -> Option<PathBuf> {
+    let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?;
+    Some(Path::new(&home).join(HISTORY_FILE))
+}
+
+// What the lookup prompt needs to rescore: the weights `setweight` edits, the scaling `whatif`
+// rescales with, and how to rebuild and fit the model around new weights.
+// What every lookup is printed with besides the players and the model.
+pub struct LookupOptions<'a> {
+    pub bonus: f64,
+    pub tiers: &'a Tiers,
+    pub chemistry: &'a Chemistry,
+    pub locale: &'a Locale,
+}
+
+pub struct Tuning<'a> {
+    pub weights: WeightTable,
+    pub scaling: &'a Scaling,
+    pub rebuild: &'a dyn Fn(&WeightTable) -> io::Result<Box<dyn ScoringModel>>,
+}
+
+pub fn lookup_loop(
+    players: &PlayerMap,
+    model: &dyn ScoringModel,
+    options: &LookupOptions,
+    position: Option<&Position>,
+    mut tuning: Tuning,
+) -> io::Result<()> {
+    let mut editor = DefaultEditor::new().map_err(io::Error::other)?;
+    let history = history_path();
+    if let Some(path) = &history {
+        // A missing file just means this is the first session.
+        let _ = editor.load_history(path);
+    }
+
+    // Each `setweight` swaps in a rebuilt model, and everything the lookups compare against is recomputed for it.
+    let mut tuned: Option<Box<dyn ScoringModel>> = None;
+    loop {
+        let current = tuned.as_deref().unwrap_or(model);
+        let context = LookupContext::new(players, current, options);
+        let Some((reweighted, rebuilt)) = read_commands(&mut editor, &context, position, &mut tuning)? else { break };
+        let before = rank_players_with(players, current).remove(&reweighted).unwrap_or_default();
+        drop(context);
+        let rebuilt = tuned.insert(rebuilt);
+        print_reweighted(players, rebuilt.as_ref(), &reweighted, &before, tuning.weights.get(&reweighted), options.locale);
+    }
+
+    if let Some(path) = &history {
+        if let Err(e) = editor.save_history(path) {
+            log::warn!("Could not save lookup history to {}: {}", path.display(), e);
+        }
+    }
+    Ok(())
+}
+
+// Runs commands until the prompt is left (None) or a `setweight` needs the model rebuilt around new weights.
+fn read_commands(
+    editor: &mut DefaultEditor,
+    context: &LookupContext,
+    position: Option<&Position>,
+    tuning: &mut Tuning,
+) -> io::Result<Option<(Position, Box<dyn ScoringModel>)>> {
+    let (players, model, locale) = (context.players, context.model, context.locale);
+    loop {
+        println!(
+            "\nEnter a player name to get their score, `team <ABBR>` to list a team, `similar <name>`, `whatif <name> <metric> <value>`, `table <name>, <name>, ...`, or `setweight <position> <metric> <weight>` (press Enter to exit):"
+        );
+
+        let input = match editor.readline("> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => String::new(),
+            Err(e) => return Err(io::Error::other(e)),
+        };
+        let player_name = input.trim().to_lowercase();
+
+        if player_name.is_empty() {
+            println!("Exiting...");
+            return Ok(None);
+        }
+
+        let _ = editor.add_history_entry(input.trim());
+        if let Some(team) = player_name.strip_prefix("team ") {
+            context.print_team(team);
+        } else if let Some(name) = player_name.strip_prefix("similar ") {
+            print_similar(players, model, &context.similarity, name, SIMILAR_PLAYERS, locale);
+        } else if let Some(args) = player_name.strip_prefix("whatif ") {
+            print_what_if(players, model, args, tuning.scaling, locale);
+        } else if let Some(names) = player_name.strip_prefix("table ") {
+            print_table(players, model, names, locale);
+        } else if let Some(args) = player_name.strip_prefix("setweight ") {
+            match set_weight(model, tuning, args) {
+                Ok(rebuilt) => return Ok(Some(rebuilt)),
+                Err(e) => println!("{}", e),
+            }
+        } else {
+            context.print_player(&player_name, position);
+        }
+    }
+}
+
+// One row per comparison: the score at each player's first position, their overall rating, then the
+// raw value of every metric any of them is scored on, blank for players whose position isn't.
+fn comparison_rows(model: &dyn ScoringModel, chosen: &[&Player], locale: &Locale) -> Vec<(String, Vec<Option<f64>>)> {
+    let first_positions: Vec<Option<&Position>> = chosen.iter().map(|player| player.positions.first()).collect();
+    let mut rows = vec![
+        (
+            "Score".to_string(),
+            chosen.iter().zip(first_positions.iter().copied()).map(|(player, position)| Some(model.score(position?, &MetricMap::for_player(player, position?)?))).collect(),
+        ),
+        ("Overall".to_string(), chosen.iter().map(|player| overall_score(player, model)).collect()),
+    ];
+    let ev_scores: Vec<Option<f64>> = chosen.iter().zip(first_positions.iter().copied()).map(|(player, position)| ev_score(player, position?)).collect();
+    if ev_scores.iter().any(Option::is_some) {
+        rows.insert(1, ("EV Score".to_string(), ev_scores));
+    }
+    for metric in positions::scored_metrics() {
+        let values: Vec<Option<f64>> = chosen
+            .iter()
+            .zip(first_positions.iter().copied())
+            .map(|(player, position)| {
+                let i = Metric::for_position(position?).iter().position(|&m| m == metric)?;
+                player.raw_metrics.get(position?)?.get(i).copied()
+            })
+            .collect();
+        if values.iter().any(Option::is_some) {
+            rows.push((locale.label(metric.name()), values));
+        }
+    }
+    rows
+}
+
+// `table A, B, C`: the players side by side, with the best value in each row starred.
+fn print_table(players: &PlayerMap, model: &dyn ScoringModel, names: &str, locale: &Locale) {
+    let mut chosen: Vec<&Player> = Vec::new();
+    for name in names.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        match find_player(players, name) {
+            Some(player) if !chosen.iter().any(|chosen| chosen.name == player.name) => chosen.push(player),
+            Some(_) => {}
+            None => println!("Player '{}' not found.", name),
+        }
+    }
+    if chosen.len() < 2 {
+        println!("usage: table <name>, <name>[, <name>...] with at least two players found");
+        return;
+    }
+
+    let width = chosen.iter().map(|player| player.name.chars().count() + 1).max().unwrap_or(0).max(10);
+    let header: String = chosen.iter().map(|player| format!(" {:>width$}", player.name, width = width)).collect();
+    println!("\n{:<16}{}", "", header);
+    let positions: String = chosen
+        .iter()
+        .map(|player| format!(" {:>width$}", player.positions.first().map_or("-", Position::abbreviation), width = width))
+        .collect();
+    println!("{:<16}{}", "Position", positions);
+
+    for (label, values) in comparison_rows(model, &chosen, locale) {
+        let best = values.iter().flatten().copied().fold(f64::NEG_INFINITY, f64::max);
+        let starred = values.iter().flatten().count() > 1;
+        let cells: String = values
+            .iter()
+            .zip(&chosen)
+            .map(|(value, player)| {
+                let text = match value {
+                    Some(value) => match Metric::from_name(&label) {
+                        Some(metric) => metric.unit().format(*value, locale),
+                        None => format!("{:.2}{}", locale.num(*value), player.positions.first().map_or("", |position| model.unit(position))),
+                    },
+                    None => "-".to_string(),
+                };
+                let marker = if starred && *value == Some(best) { "*" } else { " " };
+                format!(" {:>width$}", format!("{}{}", text, marker), width = width)
+            })
+            .collect();
+        println!("{:<16}{}", label, cells);
+    }
+    println!("* best in the row");
+}
+
+// `<position> <metric> <weight>`, where the metric may contain spaces. The weights only change once
+// the model rebuilds around them.
+fn set_weight(model: &dyn ScoringModel, tuning: &mut Tuning, args: &str) -> Result<(Position, Box<dyn ScoringModel>), String> {
+    let usage = "usage: setweight <position> <metric> <weight>";
+    let words: Vec<&str> = args.split_whitespace().collect();
+    let [position, metric @ .., weight] = &words[..] else { return Err(usage.to_string()) };
+    if metric.is_empty() {
+        return Err(usage.to_string());
+    }
+    let position = Position::from_str(position)?;
+    let weight: f64 = weight.parse().map_err(|_| format!("'{}' is not a number; {}", weight, usage))?;
+    if model.weights(&position).is_none() {
+        return Err(format!("the {} model doesn't score {} on the position weights", model.name(), position.to_string().to_lowercase()));
+    }
+
+    let mut weights = tuning.weights.clone();
+    weights.set_weight(&position, &metric.join(" "), weight)?;
+    let rebuilt = (tuning.rebuild)(&weights).map_err(|e| e.to_string())?;
+    tuning.weights = weights;
+    Ok((position, rebuilt))
+}
+
+const REWEIGHTED_LEADERS: usize = 10;
+
+fn print_reweighted(players: &PlayerMap, model: &dyn ScoringModel, position: &Position, before: &[ScoredPlayer], weights: &[f64], locale: &Locale) {
+    let weights: Vec<String> = metric_names(position).iter().zip(weights).map(|(metric, weight)| format!("{} {:.3}", locale.label(metric), locale.num(*weight))).collect();
+    println!("\n{} weights: {}", position, weights.join(", "));
+
+    let after = rank_players_with(players, model).remove(position).unwrap_or_default();
+    println!("\nTop {} Players in {} Position:", REWEIGHTED_LEADERS.min(after.len()), position);
+    for (rank, scored) in after.iter().take(REWEIGHTED_LEADERS).enumerate() {
+        let change = match before.iter().position(|old| old.name == scored.name) {
+            Some(old) if old > rank => format!("(up {})", old - rank),
+            Some(old) if old < rank => format!("(down {})", rank - old),
+            Some(_) => "(=)".to_string(),
+            None => "(new)".to_string(),
+        };
+        println!("{:<4} {:<28} {:>8.2}{} {}", rank + 1, scored.name, locale.num(scored.score), model.unit(position), change);
+    }
+}
+
+// Nothing is kept: the override only lasts for this one rescore.
+fn print_what_if(players: &PlayerMap, model: &dyn ScoringModel, args: &str, scaling: &Scaling, locale: &Locale) {
+    let (player, metric, value) = match parse_what_if(players, args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    let results = what_if(players, model, player, metric, value, scaling);
+    if results.is_empty() {
+        let positions: Vec<String> = player.positions.iter().map(Position::to_string).collect();
+        println!("{} is not scored on {} at {}", player.name, metric, positions.join(", "));
+    }
+    for result in results {
+        let unit = model.unit(&result.position);
+        println!(
+            "\nWhat if {} had {} {} instead of {} at {}:",
+            player.name,
+            metric.unit().format(result.value, locale),
+            metric,
+            metric.unit().format(result.raw, locale),
+            result.position
+        );
+        println!("Score: {:.2}{} -> {:.2}{} ({:+.2})", locale.num(result.score), unit, locale.num(result.new_score), unit, locale.num(result.new_score - result.score));
+        println!("Rank: {} -> {} of {} ({:+})", result.rank, result.new_rank, result.players, result.rank as i64 - result.new_rank as i64);
+    }
+}
+
+pub const SIMILAR_PLAYERS: usize = 5;
+const COMPARABLES: usize = 3;
+
+fn position_score(players: &PlayerMap, model: &dyn ScoringModel, name: &str, position: &Position) -> Option<f64> {
+    Some(model.score(position, &MetricMap::for_player(players.get(name)?, position)?))
+}
+
+// The players whose normalized metrics are closest to this player's at each of their positions.
+pub fn print_similar(players: &PlayerMap, model: &dyn ScoringModel, indexes: &HashMap<Position, SimilarityIndex>, name: &str, top: usize, locale: &Locale) {
+    let Some(player) = find_player(players, name) else {
+        println!("Player '{}' not found. Please try again.", name.trim());
+        return;
+    };
+
+    for position in &player.positions {
+        let Some(index) = indexes.get(position) else { continue };
+        println!("\nMost similar {} players to {}:", position, player.name);
+        for neighbor in index.similar_to(&player.name, top) {
+            let score = position_score(players, model, &neighbor.name, position);
+            let score = score.map_or(String::new(), |score| format!(", score {:.2}{}", locale.num(score), model.unit(position)));
+            println!("{} (distance {:.3}{})", neighbor.name, locale.num(neighbor.distance), score);
+        }
+    }
+}
+
+pub fn lookup_player(
+    players: &PlayerMap,
+    model: &dyn ScoringModel,
+    options: &LookupOptions,
+    name: &str,
+    position: Option<&Position>,
+) {
+    LookupContext::new(players, model, options).print_player(&name.to_lowercase(), position);
+}
+
+// League-wide figures each lookup compares against, computed once.
+struct LookupContext<'a> {
+    players: &'a PlayerMap,
+    model: &'a dyn ScoringModel,
+    bonus: f64,
+    grades: Grades<'a>,
+    averages: HashMap<Position, PositionStats>,
+    value_medians: HashMap<Position, f64>,
+    teams: TeamIndex,
+    chemistry: &'a Chemistry,
+    locale: &'a Locale,
+    similarity: HashMap<Position, SimilarityIndex>,
+    clutch: Vec<Clutch>,
+}
+
+impl<'a> LookupContext<'a> {
+    fn new(players: &'a PlayerMap, model: &'a dyn ScoringModel, options: &LookupOptions<'a>) -> Self {
+        LookupContext {
+            players,
+            model,
+            bonus: options.bonus,
+            grades: Grades::new(players, model, options.tiers),
+            averages: raw_position_stats(players),
+            value_medians: median_value(&value_leaderboard(players, model)),
+            teams: TeamIndex::new(players),
+            chemistry: options.chemistry,
+            locale: options.locale,
+            similarity: similarity_indexes(players),
+            clutch: clutch_scores(players),
+        }
+    }
+
+    fn print_team(&self, team: &str) {
+        let (team, locale) = (team.trim().to_uppercase(), self.locale);
+        let ratings = team_ratings(self.players, &self.teams, &team, self.model, self.bonus);
+        if ratings.is_empty() {
+            if self.teams.is_empty() {
+                println!("No team data: the input has no Team column.");
+            } else {
+                println!("No players on team '{}'. Teams: {}", team, self.teams.teams().collect::<Vec<_>>().join(", "));
+            }
+            return;
+        }
+
+        println!("{} ({} players):", team, ratings.len());
+        println!("{:<4} {:<28} {:<10} {:>8}", "Rank", "Player", "Positions", "Rating");
+        for (rank, (player, rating)) in ratings.iter().enumerate() {
+            let positions: Vec<&str> = player.positions.iter().map(Position::abbreviation).collect();
+            println!("{:<4} {:<28} {:<10} {:>8.2}", rank + 1, player.name, positions.join("/"), locale.num(*rating));
+        }
+    }
+
+    // With `only`, prints just that position's breakdown and score instead of every position and the overall rating.
+    fn print_player(&self, player_name: &str, only: Option<&Position>) {
+        let (players, model, locale) = (self.players, self.model, self.locale);
+        let Some(player) = find_player(players, player_name) else {
+            println!("Player '{}' not found. Please try again.", player_name);
+            return;
+        };
+        println!("Player: {}", player.name);
+        if let Some(bio) = bio_line(player, today()) {
+            println!("Bio: {}", bio);
+        }
+        for flag in regression_flags(player) {
+            println!("Regression watch: {}", flag);
+        }
+        if let (Some(finishing), Some(goals), Some(expected)) = (player.stats.get(FINISHING_STAT), player.stats.get("Goals"), player.stats.get("ixG")) {
+            println!("Finishing: {:+.1} goals vs expected ({} goals on {:.1} ixG)", locale.num(*finishing), goals, locale.num(*expected));
+        }
+        if let Some(pdo) = pdo(&player.stats) {
+            println!("PDO: {:.1} (luck adjustment x{:.2})", locale.num(pdo), locale.num(luck_factor(pdo)));
+        }
+        if let Some(clutch) = self.clutch.iter().find(|clutch| clutch.name == player.name) {
+            println!("Clutch: {:.1} ({})", locale.num(clutch.score), clutch_line(clutch));
+        }
+
+        if let Some(position) = only.filter(|position| !player.positions.contains(position)) {
+            let positions: Vec<String> = player.positions.iter().map(Position::to_string).collect();
+            println!("{} does not play {}; eligible at {}", player.name, position, positions.join(", "));
+            return;
+        }
+
+        for position in player.positions.iter().filter(|position| only.is_none_or(|only| only == *position)) {
+            if let Some(metrics_for_position) = player.metrics.get(position) {
+                let Some(metric_map) = MetricMap::for_player(player, position) else { continue };
+                let score = model.score(position, &metric_map);
+
+                println!("\nStats for {} at {} (grade {}):", player.name, position, self.grades.grade(position, score));
+                let metrics = Metric::for_position(position);
+                let raw_metrics = player.raw_metrics.get(position).unwrap_or(metrics_for_position);
+
+                let percentiles = metric_percentiles(players, position, metrics_for_position);
+                let contributions = score_contributions(model, position, metrics_for_position, score).unwrap_or_default();
+
+                println!(
+                    "{:<16} {:>10} {:>10} {:>10} {:>12} {:>8} {:>7}",
+                    "Metric",
+                    "Raw",
+                    "Normalized",
+                    "Percentile",
+                    format!("vs avg {}", position.abbreviation()),
+                    "Points",
+                    "Share"
+                );
+                for (i, &raw) in raw_metrics.iter().enumerate() {
+                    let average = self.averages.get(position).and_then(|stats| stats.mean.get(i)).copied().unwrap_or(0.0);
+                    let (points, share) = match contributions.get(i) {
+                        Some(contribution) => (format!("{:.2}", locale.num(contribution.points)), format!("{:.1}%", locale.num(contribution.share))),
+                        None => ("-".to_string(), "-".to_string()),
+                    };
+                    println!(
+                        "{:<16} {:>10} {:>10.3} {:>10.1} {:>+12.1} {:>8} {:>7}",
+                        locale.label(metrics[i].name()),
+                        metrics[i].unit().format(raw, locale),
+                        locale.num(metrics_for_position.get(i).copied().unwrap_or_default()),
+                        locale.num(percentiles.get(i).copied().unwrap_or_default()),
+                        locale.num(raw - average),
+                        points,
+                        share
+                    );
+                }
+
+                // Well above the 5v5 score means the power play is carrying the numbers.
+                if let Some(ev) = ev_score(player, position) {
+                    let unit = model.unit(position);
+                    println!("All situations: {:.2}{} | 5v5: {:.2}{} ({:+.2} from special teams)", locale.num(score), unit, locale.num(ev), unit, locale.num(score - ev));
+                }
+
+                if let Some(&cap_hit) = player.stats.get(CAP_HIT_STAT) {
+                    let value = score / (cap_hit / 1_000_000.0);
+                    let median = self.value_medians.get(position).copied().unwrap_or(0.0);
+                    println!(
+                        "Contract: ${:.2}M, {:.2} per $1M ({} than the median {} at {:.2})",
+                        locale.num(cap_hit / 1_000_000.0),
+                        locale.num(value),
+                        if value >= median { "more efficient" } else { "less efficient" },
+                        position.abbreviation(),
+                        locale.num(median)
+                    );
+                }
+
+                let comparables: Vec<String> = self
+                    .similarity
+                    .get(position)
+                    .map(|index| index.similar_to(&player.name, COMPARABLES))
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|neighbor| {
+                        let score = position_score(players, model, &neighbor.name, position)?;
+                        Some(format!("{} ({:.2}{})", neighbor.name, locale.num(score), model.unit(position)))
+                    })
+                    .collect();
+                if !comparables.is_empty() {
+                    println!("Comparables: {}", comparables.join(", "));
+                }
+
+                if only.is_some() {
+                    println!("\nScore at {}: {:.2}{}", position, locale.num(score), model.unit(position));
+                }
+            }
+        }
+
+        if only.is_some() {
+            return;
+        }
+
+        let linemates: Vec<String> = self
+            .chemistry
+            .best_linemates(&player.name)
+            .iter()
+            .take(3)
+            .map(|linemate| format!("{} ({:+.1}, {:.0} min)", linemate.name, locale.num(linemate.chemistry), locale.num(linemate.toi)))
+            .collect();
+        if !linemates.is_empty() {
+            println!("\nBest linemates: {}", linemates.join(", "));
+        }
+
+        for situation in [Situation::PowerPlay, Situation::PenaltyKill] {
+            if let Some(score) = special_teams_score(players, player, situation) {
+                println!("\n{} score: {:.2}", situation, locale.num(score));
+            }
+        }
+
+        if let Some(overall) = overall_score(player, model) {
+            let position = player.positions.first().unwrap_or(&Position::Center);
+            let bonus = versatility_bonus(player, self.bonus);
+            if bonus != 0.0 {
+                println!("\nPositional average: {:.2}{}", locale.num(overall), model.unit(position));
+                println!("Versatility bonus: {:+.2}", locale.num(bonus));
+            }
+            println!("\nCurrent Rating: {:.2}{}", locale.num(overall + bonus), model.unit(position));
+        }
+    }
+}
+
+pub fn lookup_batch(
+    players: &PlayerMap,
+    names_path: &str,
+    format: OutputFormat,
+    model: &dyn ScoringModel,
+    tiers: &Tiers,
+    locale: &Locale,
+) -> io::Result<()> {
+    let names = fs::read_to_string(names_path)?;
+    let mut found = Vec::new();
+
+    for name in names.lines().map(str::trim).filter(|name| !name.is_empty()) {
+        match find_player(players, name) {
+            Some(player) => found.push(player),
+            None => eprintln!("Player '{}' not found, skipping", name),
+        }
+    }
+
+    let grades = Grades::new(players, model, tiers);
+    let mut out = io::stdout().lock();
+    match format {
+        OutputFormat::Json => {
+            let rows: Vec<_> = found
+                .iter()
+                .map(|player| json!({ "name": player.name, "positions": position_reports(players, player, model, &grades) }))
+                .collect();
+            serde_json::to_writer_pretty(&mut out, &rows).map_err(io::Error::other)?;
+            writeln!(out)
+        }
+        OutputFormat::Csv => write_batch_csv(&mut out, players, &found, model, &grades, locale),
+    }
+}
+
+fn write_batch_csv<W: Write>(
+    out: &mut W,
+    players: &PlayerMap,
+    found: &[&Player],
+    model: &dyn ScoringModel,
+    grades: &Grades,
+    locale: &Locale,
+) -> io::Result<()> {
+    let mut metric_columns: Vec<&str> = Vec::new();
+    for position in positions::all() {
+        for metric in metric_names(position) {
+            if !metric_columns.contains(metric) {
+                metric_columns.push(metric);
+            }
+        }
+    }
+
+    let mut header = vec!["player".to_string(), "position".to_string(), "score".to_string(), "grade".to_string()];
+    header.extend(metric_columns.iter().map(|metric| format!("{} pct", locale.label(metric))));
+    header.extend(metric_columns.iter().map(|metric| format!("{} pts", metric)));
+    write_csv_row(out, &header)?;
+
+    for player in found {
+        for report in position_reports(players, player, model, grades) {
+            let mut row = vec![player.name.to_string(), report.position.to_string(), format!("{:.2}", locale.num(report.score)), report.grade];
+            row.extend(metric_columns.iter().map(|metric| {
+                report
+                    .metrics
+                    .iter()
+                    .find(|m| m.metric == *metric)
+                    .map(|m| format!("{:.1}", locale.num(m.percentile)))
+                    .unwrap_or_default()
+            }));
+            row.extend(metric_columns.iter().map(|metric| {
+                report
+                    .metrics
+                    .iter()
+                    .find(|m| m.metric == *metric)
+                    .and_then(|m| m.contribution)
+                    .map(|contribution| format!("{:.2}", locale.num(contribution.points)))
+                    .unwrap_or_default()
+            }));
+            write_csv_row(out, &row)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use finalproject::model::WeightedSigmoid;
+
+    #[test]
+    fn test_comparison_rows_cover_every_players_metrics() {
+        let center = Player::new("Center", vec![Position::Center], HashMap::from([(Position::Center, vec![55.0, 80.0, 40.0, 30.0, 70.0])]));
+        let wing = Player::new("Wing", vec![Position::Wing, Position::Center], HashMap::from([(Position::Wing, vec![40.0, 15.0, 60.0, 70.0, 90.0])]));
+        let rows = comparison_rows(&WeightedSigmoid::default(), &[&center, &wing], &Locale::default());
+
+        let labels: Vec<&str> = rows.iter().map(|(label, _)| label.as_str()).collect();
+        assert_eq!(labels[..2], ["Score", "Overall"]);
+        assert_eq!(labels.len(), 2 + 9);
+        let row = |label: &str| &rows.iter().find(|(row, _)| row == label).unwrap().1;
+        assert_eq!(row("Faceoffs %"), &[Some(55.0), None]);
+        assert_eq!(row("Total Points"), &[Some(80.0), Some(70.0)]);
+        assert_eq!(row("Goals"), &[None, Some(40.0)]);
+    }
+
+    #[test]
+    fn test_write_batch_csv_leaves_other_positions_blank() {
+        let players = PlayerMap::from_iter([(
+            "Cale Makar".into(),
+            Player::new("Cale Makar", vec![Position::Defense], HashMap::from([(Position::Defense, vec![0.5; 5])])),
+        )]);
+        let found = vec![&players["Cale Makar"]];
+
+        let model = WeightedSigmoid::default();
+        let tiers = Tiers::default();
+        let mut out = Vec::new();
+        write_batch_csv(&mut out, &players, &found, &model, &Grades::new(&players, &model, &tiers), &Locale::default()).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert!(lines[0].starts_with("player,position,score,grade,Faceoffs % pct,"));
+        assert!(lines[1].starts_with("Cale Makar,Defense,"));
+        assert!(lines[1].contains(",A+,"));
+        assert_eq!(lines[1].split(',').count(), lines[0].split(',').count());
+        assert!(lines[1].contains(",100.0,"));
+    }
+}
@@ -0,0 +1,88 @@
+use crate::cleaning::{PlayerMap, Position};
+use crate::model::{MetricMap, ScoringModel};
+use crate::stat_line::StatLine;
+
+// Each point of PDO away from 100 moves the score 2% the other way, up to 10% either way.
+pub const PDO_SENSITIVITY: f64 = 0.02;
+pub const MAX_PDO_ADJUSTMENT: f64 = 0.1;
+
+// On-ice shooting plus save percentage on a 100 scale, from a PDO column if the export has one
+// (Natural Stat Trick writes it as 1.012) or else from the on-ice SH% and SV% columns.
+pub fn pdo(stats: &StatLine) -> Option<f64> {
+    let pdo = stats.get("PDO").copied().or_else(|| Some(stats.get("On-Ice SH%")? + stats.get("On-Ice SV%")?))?;
+    let pdo = if pdo < 10.0 { pdo * 100.0 } else { pdo };
+    (pdo.is_finite() && pdo > 0.0).then_some(pdo)
+}
+
+// The factor a score is multiplied by: below 1 for players riding high percentages, above 1 for
+// players whose percentages have run cold.
+pub fn luck_factor(pdo: f64) -> f64 {
+    1.0 - (PDO_SENSITIVITY * (pdo - 100.0)).clamp(-MAX_PDO_ADJUSTMENT, MAX_PDO_ADJUSTMENT)
+}
+
+pub fn players_with_pdo(players: &PlayerMap) -> usize {
+    players.values().filter(|player| pdo(&player.stats).is_some()).count()
+}
+
+// Shrinks scores built on unsustainable percentages. Players without on-ice percentages are left alone.
+pub struct LuckAdjusted {
+    pub base: Box<dyn ScoringModel>,
+}
+
+impl ScoringModel for LuckAdjusted {
+    fn name(&self) -> &str {
+        "pdo-adjusted"
+    }
+
+    fn fit(&mut self, players: &PlayerMap) {
+        self.base.fit(players);
+    }
+
+    fn league_relative(&self) -> bool {
+        self.base.league_relative()
+    }
+
+    fn score(&self, position: &Position, metrics: &MetricMap) -> f64 {
+        let score = self.base.score(position, metrics);
+        match pdo(metrics.stats) {
+            Some(pdo) => score * luck_factor(pdo),
+            None => score,
+        }
+    }
+
+    fn unit(&self, position: &Position) -> &'static str {
+        self.base.unit(position)
+    }
+
+    fn weights(&self, position: &Position) -> Option<&[f64]> {
+        self.base.weights(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::cleaning::Player;
+    use crate::model::Linear;
+
+    #[test]
+    fn test_pdo_shrinks_lucky_players() {
+        let stats = |pairs: &[(&str, f64)]| pairs.iter().map(|&(stat, value)| (stat.to_string(), value)).collect::<StatLine>();
+        assert_eq!(pdo(&stats(&[("PDO", 1.03)])).map(|pdo| pdo.round()), Some(103.0));
+        assert_eq!(pdo(&stats(&[("On-Ice SH%", 9.0), ("On-Ice SV%", 92.5)])), Some(101.5));
+        assert_eq!(pdo(&stats(&[("On-Ice SH%", 9.0)])), None);
+        assert!((luck_factor(103.0) - 0.94).abs() < 1e-9);
+        assert!((luck_factor(90.0) - 1.1).abs() < 1e-9);
+
+        let mut player = Player::new("Lucky", vec![Position::Wing], HashMap::from([(Position::Wing, vec![0.8; 5])]));
+        let metrics = MetricMap::for_player(&player, &Position::Wing).unwrap();
+        let base = Linear::default().score(&Position::Wing, &metrics);
+        let model = LuckAdjusted { base: Box::new(Linear::default()) };
+        assert_eq!(model.score(&Position::Wing, &metrics), base);
+
+        player.stats = stats(&[("PDO", 104.0)]);
+        let metrics = MetricMap::for_player(&player, &Position::Wing).unwrap();
+        assert!((model.score(&Position::Wing, &metrics) - base * 0.92).abs() < 1e-9);
+    }
+}
@@ -1,128 +1,58 @@
-mod cleaning;
-use std::{collections::HashMap, fs::File, io::{self, BufReader, BufRead, stdin}};
-use cleaning::{clean_fields, normalize_metrics, Player, Position};
-use crate::cleaning::process_file;
-
-fn calculate_score(position: &Position, metrics: &[f64]) -> f64 {
-    let (weights, scaling_factor) = match position {
-        Position::Center => (&[0.25, 0.3, 0.15, 0.2, 0.1], 5.0),
-        Position::Wing => (&[0.35, 0.25, 0.15, 0.2, 0.05], 5.0),
-        Position::Defense => (&[0.15, 0.3, 0.2, 0.2, 0.15], 5.0),
+mod cli;
+mod draftboard;
+mod lookup;
+mod tui;
+mod watch;
+
+use std::io::{self, Write};
+use clap::Parser;
+use finalproject::runlog::RunLog;
+use serde_json::json;
+use crate::cli::args::Cli;
+use crate::cli::run::run;
+
+fn init_logging(quiet: bool, verbose: bool) {
+    let level = if quiet {
+        log::LevelFilter::Warn
+    } else if verbose {
+        log::LevelFilter::Debug
+    } else {
+        log::LevelFilter::Info
     };
-
-    if metrics.len() != weights.len() || metrics.iter().any(|m| !m.is_finite()) {
-        eprintln!("Invalid metrics for scoring: {:?}", metrics);
-        return 0.0;
-    }
-
-    let weighted_sum: f64 = metrics
-        .iter()
-        .zip(weights.iter())
-        .map(|(metric, weight)| metric * weight)
-        .sum();
-
-    let scaled_score = scaling_factor * weighted_sum; 
-
-    (100.0 / (1.0 + (-scaled_score).exp())).clamp(0.0, 100.0)
+    env_logger::Builder::new()
+        .filter_level(level)
+        .format(|buf, record| writeln!(buf, "{}", record.args()))
+        .init();
 }
 
 fn main() -> io::Result<()> {
-    let file = File::open("NHL.csv")?; 
-    let reader = BufReader::new(file);
-
-    let mut players: HashMap<String, Player> = HashMap::new();
-    let mut skipped_rows = 0;
-    let mut processed_rows = 0;
-
-    for line in reader.lines() {
-        let line = line?; 
-
-        if let Some((player_name, positions, metrics)) = clean_fields(&line) {
-            players.insert(player_name.clone(), Player { name: player_name, positions, metrics });
-            processed_rows += 1;
-        } else {
-            skipped_rows += 1;
-        }
-    }
-
-    println!("Processed rows: {}", processed_rows);
-    println!("Skipped rows: {}", skipped_rows);
-
-    normalize_metrics(&mut players);
-
-    let mut position_groups: HashMap<Position, Vec<(String, f64)>> = HashMap::new();
-
-    for (name, player) in &players {
-        for position in &player.positions {
-            if let Some(metrics_for_position) = player.metrics.get(position) {
-                let score = calculate_score(position, metrics_for_position);
-                position_groups
-                    .entry(position.clone())
-                    .or_insert_with(Vec::new)
-                    .push((name.clone(), score));
-            }
-        }
-    }
-
-    for position in &[Position::Center, Position::Wing, Position::Defense] {
-        if let Some(players_in_position) = position_groups.get_mut(position) {
-            players_in_position.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-            println!("\nTop Players in {:?} Position:", position);
-            for (name, score) in players_in_position.iter().take(10) {
-                println!("{}: {:.2}%", name, score);
-            }
-        }
-    }
-
-    let mut input = String::new(); 
-    loop {
-        println!("\nEnter a player name to get their score (or press Enter to exit):");
-
-        input.clear();
-        stdin().read_line(&mut input)?; 
-        let player_name = input.trim().to_lowercase();
-
-        if player_name.is_empty() {
-            println!("Exiting...");
-            break;
-        }
-
-        match players.iter().find(|(name, _)| name.to_lowercase() == player_name) {
-            Some((player_name, player)) => {
-                let mut total_score = 0.0;
-                println!("Player: {}", player_name);
-
-                for position in &player.positions {
-                    if let Some(metrics_for_position) = player.metrics.get(position) {
-                        let score = calculate_score(position, metrics_for_position);
-                        total_score += score; 
-
-                        println!("\nStats for {} at {:?}:", player_name, position);
-                        let metric_names = match position {
-                            Position::Center => vec!["Faceoffs %", "Total Points", "Takeaways", "First Assists", "IPP"],
-                            Position::Wing => vec!["Goals", "SH%", "Rush Attempts", "Total Points", "Hits"],
-                            Position::Defense => vec!["Hits", "Shots Blocked", "Takeaways", "Rebounds Created", "Rush Attempts"],
-                        };
-
-                        for (i, &metric) in metrics_for_position.iter().enumerate() {
-                            println!("{}: {:.2}", metric_names[i], metric);
-                        }
-                    }
-                }
-
-                println!("\nCurrent Rating: {:.2}%", total_score);
-            }
-            None => println!("Player '{}' not found. Please try again.", player_name),
-        }
-    }
-
-    Ok(())
+    let cli = Cli::parse();
+    init_logging(cli.quiet, cli.verbose);
+    let run_log = cli.log_json.as_deref().map(RunLog::create).transpose()?.unwrap_or_default();
+    run_log.event("run_started", json!({ "version": env!("CARGO_PKG_VERSION"), "args": std::env::args().skip(1).collect::<Vec<_>>() }));
+
+    let result = run(cli, &run_log);
+    run_log.event("run_finished", json!({ "ok": result.is_ok(), "error": result.as_ref().err().map(|e| e.to_string()) }));
+    result
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::{collections::HashMap, io::Write, fs::File};
+    use finalproject::cleaning::{apply_rates, clean_fields, clean_fields_with, column_index, normalize_metrics, parse_delimiter, parse_line, process_file, process_reader, raw_position_stats, split_fields, DuplicatePolicy, ParseOptions, Player, PlayerMap, Position, RowReader, COLUMNS};
+    use finalproject::emit::RowFiles;
+    use finalproject::config::Config;
+    use finalproject::formula::Rate;
+    use finalproject::metric::{Metric, Normalization, Scaling};
+    use finalproject::model::Adjustment;
+    use finalproject::scoring::stat_leaders;
+    use finalproject::scoring::calculate_score;
+    use flate2::{write::GzEncoder, Compression};
+    use std::{collections::HashMap, fs::{self, File}, io::Write};
+    use crate::cli::args::Command;
+    use crate::cli::cache::score_cache_key;
+    use crate::cli::config::layer_settings;
+    use crate::cli::load::{load_players, Supplements};
 
     #[test]
     fn test_clean_fields_center() {
@@ -131,7 +61,7 @@ mod tests {
     assert!(result.is_some(), "Failed to clean fields for valid input");
     
     let (name, positions, metrics) = result.unwrap();
-    assert_eq!(name, "Player One");
+    assert_eq!(&*name, "Player One");
     assert_eq!(positions, vec![Position::Center]);
     assert!(metrics.contains_key(&Position::Center));
     }
@@ -139,28 +69,28 @@ mod tests {
 
     #[test]
     fn test_normalize_metrics() {
-        let mut players: HashMap<String, Player> = HashMap::new();
+        let mut players = PlayerMap::default();
         players.insert(
-            "Player A".to_string(),
-            Player {
-                name: "Player A".to_string(),
-                positions: vec![Position::Wing],
-                metrics: HashMap::from([(
+            "Player A".into(),
+            Player::new(
+                "Player A".to_string(),
+                vec![Position::Wing],
+                HashMap::from([(
                     Position::Wing,
                     vec![10.0, 20.0, 30.0],
                 )]),
-            },
+            ),
         );
         players.insert(
-            "Player B".to_string(),
-            Player {
-                name: "Player B".to_string(),
-                positions: vec![Position::Wing],
-                metrics: HashMap::from([(
+            "Player B".into(),
+            Player::new(
+                "Player B".to_string(),
+                vec![Position::Wing],
+                HashMap::from([(
                     Position::Wing,
                     vec![20.0, 10.0, 40.0],
                 )]),
-            },
+            ),
         );
 
         normalize_metrics(&mut players);
@@ -168,8 +98,8 @@ mod tests {
         let wing_metrics_a = &players["Player A"].metrics[&Position::Wing];
         let wing_metrics_b = &players["Player B"].metrics[&Position::Wing];
 
-        assert_eq!(wing_metrics_a, &[0.5, 1.0, 0.75]);
-        assert_eq!(wing_metrics_b, &[1.0, 0.5, 1.0]);
+        assert_eq!(wing_metrics_a, &[0.5, 0.2, 0.75]);
+        assert_eq!(wing_metrics_b, &[1.0, 0.1, 1.0]);
     }
 
     #[test]
@@ -193,4 +123,289 @@ mod tests {
     
     assert_eq!(players.len(), 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_process_reader_skips_header() {
+        let input = ",Player,Position\n1,Cale Makar,D,200,4000,60,150,90,60,210,60.5,500,12,40,900,700,400,100,40,80,50,25,25,0,0,60,150,120,80,60,190,0,0,0\n";
+
+        let players = process_reader(input.as_bytes(), &ParseOptions::default()).unwrap();
+
+        assert_eq!(players.len(), 1);
+        assert_eq!(players["Cale Makar"].raw_metrics[&Position::Defense], vec![80.0, 190.0, 120.0, 210.0, 40.0]);
+    }
+
+    #[test]
+    fn test_load_players_fails_over_max_skip_pct() {
+        let input = ",Player,Position\n1,Cale Makar,D,200,4000,60,150,90,60,210,60.5,500,12,40,900,700,400,100,40,80,50,25,25,0,0,60,150,120,80,60,190,0,0,0\n2,Bad Row,D,1\n";
+
+        assert!(load_players(input.as_bytes(), &ParseOptions::default(), Some(60.0), &RunLog::default(), &RowFiles::default()).is_ok());
+        let error = load_players(input.as_bytes(), &ParseOptions::default(), Some(5.0), &RunLog::default(), &RowFiles::default()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(load_players(input.as_bytes(), &ParseOptions::default(), None, &RunLog::default(), &RowFiles::default()).is_ok());
+    }
+
+    #[test]
+    fn test_run_log_records_skip_reasons() {
+        let stats = "200,4000,60,150,90,60,210,60.5,500,12,40,900,700,400,100,40,80,50,25,25,0,0,60,150,120,80,60,190,0,0,0";
+        let input = format!(",Player,Position\n1,Cale Makar,D,{0}\n2,Bad Row,D,1\n3,,D,{0}\n4,Goalie,G,{0}\n5,Short,C\n", stats);
+        let path = std::env::temp_dir().join("finalproject_skip_reasons_test.jsonl");
+        let _ = fs::remove_file(&path);
+
+        let run_log = RunLog::create(path.to_str().unwrap()).unwrap();
+        load_players(input.as_bytes(), &ParseOptions::default(), None, &run_log, &RowFiles::default()).unwrap();
+        let events: Vec<serde_json::Value> = fs::read_to_string(&path).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(events.iter().map(|event| event["event"].as_str().unwrap()).collect::<Vec<_>>(), ["rows", "normalized"]);
+        assert_eq!((&events[0]["processed"], &events[0]["skipped"]), (&json!(1), &json!(4)));
+        assert_eq!(events[0]["skip_reasons"], json!({ "insufficient_fields": 2, "invalid_position": 1, "missing_name_or_position": 1 }));
+        assert_eq!(events[1]["positions"]["Defense"]["Hits"]["max"], json!(80.0));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_players_keep_file_order() {
+        let stats = "200,4000,60,150,90,60,210,60.5,500,12,40,900,700,400,100,40,80,50,25,25,0,0,60,150,120,80,60,190,0,0,0";
+        let names = ["Zach Werenski", "Cale Makar", "Quinn Hughes", "Adam Fox"];
+        let input: String = names.iter().enumerate().map(|(i, name)| format!("{},{},D,{}\n", i + 1, name, stats)).collect();
+        let players = load_players(format!(",Player,Position\n{}", input).as_bytes(), &ParseOptions::default(), None, &RunLog::default(), &RowFiles::default()).unwrap();
+
+        assert_eq!(players.keys().map(|name| &**name).collect::<Vec<_>>(), names);
+    }
+
+    #[test]
+    fn test_duplicate_policies() {
+        let row = |team: &str, goals: u32| {
+            format!("1,Sebastian Aho,C,41,800,{},10,6,4,20,60.0,100,10.0,8,150,120,60,20,5,4,10,5,5,0,0,8,15,20,10,10,10,300,250,54.5,{}\n", goals, team)
+        };
+        let input = format!("{},Team\n{}{}", COLUMNS.join(","), row("CAR", 10), row("NYI", 4));
+        let load = |policy: &str| {
+            let options = ParseOptions { duplicates: policy.parse().unwrap(), ..ParseOptions::default() };
+            load_players(input.as_bytes(), &options, None, &RunLog::default(), &RowFiles::default()).unwrap()
+        };
+
+        assert_eq!(load("first")["Sebastian Aho"].stats["Goals"], 10.0);
+        assert_eq!(load("last")["Sebastian Aho"].stats["Goals"], 4.0);
+
+        let summed = &load("merge")["Sebastian Aho"];
+        assert_eq!((summed.stats["Goals"], summed.stats["GP"], summed.stats["SH%"]), (14.0, 82.0, 10.0));
+        assert_eq!(summed.team.as_deref(), Some("CAR/NYI"));
+
+        let split = load("team");
+        assert_eq!(split.keys().map(|name| &**name).collect::<Vec<_>>(), ["Sebastian Aho", "Sebastian Aho (NYI)"]);
+        assert_eq!(split["Sebastian Aho (NYI)"].stats["Goals"], 4.0);
+        assert!("keep-both".parse::<DuplicatePolicy>().is_err());
+    }
+
+    #[test]
+    fn test_row_reader_handles_crlf_and_skipped_rows() {
+        let row = "1,Cale Makar,D,200,4000,60,150,90,60,210,60.5,500,12,40,900,700,400,100,40,80,50,25,25,0,0,60,150,120,80,60,190,0,0,0";
+        let input = format!(",Player,Position\r\n{}\r\n2,Bad Row,D\r\n{}", row, row.replace("Cale Makar", "Quinn Hughes"));
+        let mut rows = RowReader::new(input.as_bytes(), &ParseOptions::default()).unwrap();
+
+        let makar = rows.next_row().unwrap().unwrap().unwrap();
+        assert_eq!(&*makar.name, "Cale Makar");
+        assert_eq!(makar.stats["Faceoffs %"], 0.0);
+        assert!(rows.next_row().unwrap().unwrap().is_none());
+        assert_eq!(&*rows.next_row().unwrap().unwrap().unwrap().name, "Quinn Hughes");
+        assert!(rows.next_row().unwrap().is_none());
+
+        assert_eq!(split_fields("a|\"b|c\"|é", '|'), vec!["a", "b|c", "é"]);
+        assert_eq!(split_fields("a→\"b→c\"→é", '→'), vec!["a", "b→c", "é"]);
+    }
+
+    #[test]
+    fn test_process_reader_reads_age_columns_by_name() {
+        let row = "1,Cale Makar,D,200,4000,60,150,90,60,210,60.5,500,12,40,900,700,400,100,40,80,50,25,25,0,0,60,150,120,80,60,190,0,0,0";
+        let input = format!(",Player,Position{}\n{},25,1998-10-30\n", ",x".repeat(31) + ",Age,Date of Birth", row);
+
+        let players = process_reader(input.as_bytes(), &ParseOptions::default()).unwrap();
+        let makar = &players["Cale Makar"];
+
+        assert_eq!(makar.stats["Age"], 25.0);
+        assert_eq!(makar.stats["Birth Date"], finalproject::age::parse_date("1998-10-30").unwrap() as f64);
+    }
+
+    #[test]
+    fn test_process_file_gzip() {
+        let input = ",Player,Position\n1,Cale Makar,D,200,4000,60,150,90,60,210,60.5,500,12,40,900,700,400,100,40,80,50,25,25,0,0,60,150,120,80,60,190,0,0,0\n";
+        let file_path = std::env::temp_dir().join("finalproject_gzip_test.csv.gz");
+
+        let mut encoder = GzEncoder::new(File::create(&file_path).unwrap(), Compression::default());
+        encoder.write_all(input.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let players = process_file(file_path.to_str().unwrap()).unwrap();
+        assert!(players.contains_key("Cale Makar"));
+
+        std::fs::remove_file(file_path).unwrap();
+    }
+
+    #[test]
+    fn test_clean_fields_semicolon_delimiter() {
+        let input = "1;Cale Makar;D;200;4000;60;150;90;60;210;60,5;500;12;40;900;700;400;100;40;80;50;25;25;0;0;60;150;120;80;60;190;0;0;0";
+        let options = ParseOptions::with_delimiter(';');
+
+        let (name, positions, metrics) = clean_fields_with(input, &options).unwrap();
+        assert_eq!(&*name, "Cale Makar");
+        assert_eq!(positions, vec![Position::Defense]);
+        assert_eq!(metrics[&Position::Defense], vec![80.0, 190.0, 120.0, 210.0, 40.0]);
+        assert_eq!(parse_delimiter("tab"), Ok('\t'));
+    }
+
+    #[test]
+    fn test_parse_line_reads_decimal_commas_into_stats() {
+        let input = "1;Cale Makar;D;200;4000,5;60;150;90;60;210;60,5;500;12,25;40;900;700;400;100;40;80;50;25;25;0;0;60;150;120;80;60;190;0;0;0";
+        let makar = parse_line(input, &ParseOptions::with_delimiter(';')).unwrap();
+
+        assert_eq!(makar.metrics[&Position::Defense], vec![80.0, 190.0, 120.0, 210.0, 40.0]);
+        assert_eq!((makar.stats["TOI"], makar.stats["IPP"], makar.stats["SH%"]), (4000.5, 60.5, 12.25));
+        assert_eq!(makar.stats.len(), COLUMNS.len() - 3);
+    }
+
+    #[test]
+    fn test_clean_fields_quoted_positions() {
+        let input = r#"1,"Player Two","C, R",82,1400,30,40,25,15,70,70.0,200,15.0,,,,,,20,,,,,,,,,30,50,,10,400,380,51.3"#;
+        let (name, positions, metrics) = clean_fields(input).unwrap();
+        assert_eq!(&*name, "Player Two");
+        assert_eq!(positions, vec![Position::Center, Position::Wing]);
+        assert_eq!(metrics[&Position::Center], vec![51.3, 70.0, 30.0, 25.0, 70.0]);
+        assert_eq!(metrics[&Position::Wing], vec![30.0, 15.0, 20.0, 70.0, 50.0]);
+
+        let (_, positions, metrics) = clean_fields(&input.replace("C, R", "L, R")).unwrap();
+        assert_eq!(positions, vec![Position::Wing]);
+        assert_eq!(metrics[&Position::Wing].len(), 5);
+    }
+
+    #[test]
+    fn test_position_from_str() {
+        assert_eq!("defense".parse::<Position>(), Ok(Position::Defense));
+        assert_eq!("C".parse::<Position>(), Ok(Position::Center));
+        assert_eq!(" Wing ".parse::<Position>(), Ok(Position::Wing));
+        assert!("goalie".parse::<Position>().is_err());
+    }
+
+    #[test]
+    fn test_parse_line_keeps_every_stat_for_leaders() {
+        let options = ParseOptions::default();
+        let makar = parse_line("1,Cale Makar,D,200,4000,60,150,90,60,210,60.5,500,12,40,900,700,400,100,40,80,50,25,25,0,0,60,150,120,80,60,190,0,0,0", &options).unwrap();
+        let matthews = parse_line("2,Auston Matthews,C,228,4700,169,129,79,50,298,68,1044,16.19,122,1861,1471,1240,506,42,154,58,25,24,0,1,59,157,245,234,293,233,1946,1651,54.1", &options).unwrap();
+
+        assert_eq!(makar.stats["GP"], 200.0);
+        assert_eq!(matthews.stats["Faceoffs %"], 54.1);
+
+        let players = PlayerMap::from_iter([(makar.name.clone(), makar), (matthews.name.clone(), matthews)]);
+        let leaders = stat_leaders(&players, COLUMNS[column_index("hits").unwrap()]);
+        assert_eq!(&*leaders[0].0.name, "Auston Matthews");
+        assert_eq!(leaders[0].1, 234.0);
+    }
+
+    #[test]
+    fn test_normalize_metrics_retains_positional_means() {
+        let mut players = PlayerMap::default();
+        for (name, hits) in [("A", 100.0), ("B", 300.0)] {
+            let metrics = HashMap::from([(Position::Defense, vec![hits, 10.0, 0.0, 0.0, 0.0])]);
+            players.insert(name.into(), Player::new(name, vec![Position::Defense], metrics));
+        }
+
+        let stats = normalize_metrics(&mut players);
+        assert_eq!(stats[&Position::Defense].max[0], 300.0);
+        assert_eq!(stats[&Position::Defense].mean[0], 200.0);
+        assert_eq!(raw_position_stats(&players), stats);
+    }
+
+    #[test]
+    fn test_min_gp_drops_players_and_renormalizes() {
+        let mut players = PlayerMap::default();
+        for (name, goals, games) in [("Regular", 30.0, 80.0), ("Call-up", 60.0, 5.0)] {
+            let mut player = Player::new(name.to_string(), vec![Position::Wing], HashMap::from([(Position::Wing, vec![goals, 10.0, 0.0, 0.0, 0.0])]));
+            player.stats.insert("GP".to_string(), games);
+            players.insert(name.into(), player);
+        }
+        normalize_metrics(&mut players);
+
+        let supplements = Supplements { cap_hits: None, rookies: None, usage: None, bios: None, situations: Vec::new(), baselines: Vec::new(), rates: Rate::Total, min_gp: Some(20.0), scaling: Scaling::default() };
+        supplements.apply(&mut players, false);
+        assert_eq!(players.len(), 1);
+        assert_eq!(players["Regular"].metrics[&Position::Wing][0], 1.0);
+    }
+
+    #[test]
+    fn test_direction_table_ranks_lower_is_better_metrics_in_reverse() {
+        let mut players = PlayerMap::default();
+        for (name, hits) in [("Grinder", 200.0), ("Middle", 100.0), ("Finesse", 20.0)] {
+            let player = Player::new(name.to_string(), vec![Position::Wing], HashMap::from([(Position::Wing, vec![10.0, 10.0, 10.0, 10.0, hits])]));
+            players.insert(name.into(), player);
+        }
+        let ranked = |players: &PlayerMap| finalproject::scoring::rank_players_with(players, &finalproject::model::Linear::default())[&Position::Wing].iter().map(|p| p.name.to_string()).collect::<Vec<_>>();
+
+        apply_rates(&mut players, Rate::Total, &Scaling::default());
+        assert_eq!(ranked(&players), ["Grinder", "Middle", "Finesse"]);
+
+        let scaling = Config::parse("[normalization]\nhits = \"min-max\"\n[direction]\nhits = \"lower\"").unwrap().scaling(None).unwrap();
+        apply_rates(&mut players, Rate::Total, &scaling);
+        assert_eq!(players["Finesse"].metrics[&Position::Wing][4], 1.0);
+        assert_eq!(players["Grinder"].metrics[&Position::Wing][4], 0.0);
+        assert_eq!(ranked(&players), ["Finesse", "Middle", "Grinder"]);
+    }
+
+    #[test]
+    fn test_apply_rates_prorates_counting_metrics_by_games() {
+        let mut players = PlayerMap::default();
+        for (name, goals, sh_pct, games) in [("Injured", 20.0, 15.0, Some(40.0)), ("Healthy", 30.0, 10.0, Some(80.0)), ("Unknown", 90.0, 10.0, None)] {
+            let metrics = HashMap::from([(Position::Wing, vec![goals, sh_pct, 0.0, 0.0, 0.0])]);
+            let mut player = Player::new(name.to_string(), vec![Position::Wing], metrics);
+            player.stats.extend(games.map(|games| ("GP".to_string(), games)));
+            players.insert(name.into(), player);
+        }
+        normalize_metrics(&mut players);
+
+        // Without GP the 90 goals would stay a total and set the bar for everyone's per-game rate.
+        apply_rates(&mut players, Rate::PerGame, &Scaling::default());
+        assert!(!players.contains_key("Unknown"));
+        assert_eq!(players["Injured"].raw_metrics[&Position::Wing][..2], [0.5, 15.0]);
+        assert_eq!(players["Healthy"].raw_metrics[&Position::Wing][..2], [0.375, 10.0]);
+        assert_eq!(players["Injured"].metrics[&Position::Wing][0], 1.0);
+        assert_eq!(players["Healthy"].metrics[&Position::Wing][0], 0.75);
+        assert_eq!("per-game".parse::<Rate>(), Ok(Rate::PerGame));
+    }
+
+    #[test]
+    fn test_flags_win_over_the_environment_and_both_over_the_config() {
+        let config = Config::parse("min_gp = 30\ntop = 3\n[normalization]\nhits = \"min-max\"").unwrap();
+        let settings = |cli: &mut Cli| {
+            layer_settings(cli, &config);
+            let Some(Command::Rank(args)) = &cli.command else { unreachable!() };
+            (cli.min_gp, args.top(), config.scaling(cli.normalization).unwrap().normalization(Metric::Hits))
+        };
+        assert_eq!(settings(&mut Cli::parse_from(["nhl_scores", "rank"])), (Some(30.0), 3, Normalization::MinMax));
+
+        std::env::set_var("NHL_SCORES_MIN_GP", "20");
+        std::env::set_var("NHL_SCORES_TOP", "5");
+        std::env::set_var("NHL_SCORES_NORMALIZATION", "z-score");
+        let from_env = settings(&mut Cli::parse_from(["nhl_scores", "rank"]));
+        let from_flags = settings(&mut Cli::parse_from(["nhl_scores", "--min-gp", "10", "--normalization", "max", "rank", "--top", "7"]));
+        for name in ["NHL_SCORES_MIN_GP", "NHL_SCORES_TOP", "NHL_SCORES_NORMALIZATION"] {
+            std::env::remove_var(name);
+        }
+        assert_eq!(from_env, (Some(20.0), 5, Normalization::ZScore));
+        assert_eq!(from_flags, (Some(10.0), 7, Normalization::Max));
+    }
+
+    #[test]
+    fn test_score_cache_key_follows_bio_birth_dates() {
+        let dir = std::env::temp_dir().join(format!("nhl_scores_cache_key_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (input, bio) = (dir.join("input.csv"), dir.join("bio.csv"));
+        fs::write(&input, "x").unwrap();
+        let (input, bio) = (input.to_str().unwrap(), bio.to_str().unwrap());
+        let cli = Cli::parse_from(["nhl_scores", "-i", input, "--bio", bio, "--age-adjust"]);
+
+        let key = |born: &str| {
+            fs::write(bio, format!("Player,Birth Date\nConnor McDavid,{}\n", born)).unwrap();
+            score_cache_key(&cli, &cli.input, &[Adjustment::Age], 0).unwrap()
+        };
+        assert!(key("1997-01-13").is_some());
+        assert_eq!(key("1997-01-13"), key("1997-01-13"));
+        assert_ne!(key("1997-01-13"), key("2005-07-17"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+use serde::Deserialize;
+use crate::cleaning::{column_index, metric_key, COLUMNS};
+
+// Natural Stat Trick labels its score-and-venue adjusted columns with these around the plain column name.
+const ADJUSTED_MARKERS: [&str; 6] = ["scoreandvenueadjusted", "scorevenueadjusted", "scoreadjusted", "scoreadj", "adjusted", "adj"];
+
+// `map` takes source column names to the export's column names, and wins over `score_adjusted`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColumnMapping {
+    pub score_adjusted: bool,
+    pub map: BTreeMap<String, String>,
+}
+
+impl ColumnMapping {
+    pub fn is_default(&self) -> bool {
+        *self == ColumnMapping::default()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (source, target) in &self.map {
+            if column_index(target).is_none() {
+                return Err(format!("column '{}' is mapped to unknown column '{}'", source, target));
+            }
+        }
+        Ok(())
+    }
+
+    fn mapped_column(&self, name: &str) -> Option<usize> {
+        let key = metric_key(name);
+        self.map.iter().find(|(source, _)| metric_key(source) == key).and_then(|(_, target)| column_index(target))
+    }
+
+    // Returns, for each column of the expected layout, the index of the header field to read it from.
+    pub fn resolve(&self, header: &[&str]) -> Vec<Option<usize>> {
+        let mut mapped = vec![None; COLUMNS.len()];
+        let mut adjusted = vec![None; COLUMNS.len()];
+        let mut plain = vec![None; COLUMNS.len()];
+
+        for (i, name) in header.iter().enumerate() {
+            if let Some(column) = self.mapped_column(name) {
+                mapped[column].get_or_insert(i);
+            } else if let Some(column) = column_index(name) {
+                plain[column].get_or_insert(i);
+            } else if let Some(column) = adjusted_column(name).filter(|_| self.score_adjusted) {
+                adjusted[column].get_or_insert(i);
+            }
+        }
+        plain[0] = Some(0);
+
+        mapped.into_iter().zip(adjusted).zip(plain).map(|((mapped, adjusted), plain)| mapped.or(adjusted).or(plain)).collect()
+    }
+}
+
+pub fn adjusted_column(name: &str) -> Option<usize> {
+    let key = metric_key(name);
+    ADJUSTED_MARKERS.iter().find_map(|marker| {
+        let base = key.strip_prefix(marker).or_else(|| key.strip_suffix(marker))?;
+        column_index(base)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_prefers_score_adjusted_columns() {
+        let header = ["", "Player", "Position", "Hits", "Hits (Score Adj.)", "Adj. Takeaways", "Takeaways"];
+        let plain = ColumnMapping::default().resolve(&header);
+        assert_eq!(plain[column_index("Hits").unwrap()], Some(3));
+        assert_eq!(plain[column_index("Takeaways").unwrap()], Some(6));
+        assert_eq!(plain[column_index("Goals").unwrap()], None);
+
+        let adjusted = ColumnMapping { score_adjusted: true, ..ColumnMapping::default() }.resolve(&header);
+        assert_eq!(adjusted[column_index("Hits").unwrap()], Some(4));
+        assert_eq!(adjusted[column_index("Takeaways").unwrap()], Some(5));
+        assert_eq!(adjusted[1], Some(1));
+    }
+
+    #[test]
+    fn test_map_overrides_columns() {
+        let mapping = ColumnMapping {
+            score_adjusted: true,
+            map: BTreeMap::from([("TkA".to_string(), "Takeaways".to_string())]),
+        };
+        let columns = mapping.resolve(&["", "Player", "Position", "Adj. Takeaways", "TkA"]);
+        assert_eq!(columns[column_index("Takeaways").unwrap()], Some(4));
+        assert!(mapping.validate().is_ok());
+
+        let unknown = ColumnMapping { map: BTreeMap::from([("W".to_string(), "Wins".to_string())]), ..mapping };
+        assert!(unknown.validate().is_err());
+    }
+}
@@ -0,0 +1,234 @@
+use std::{collections::HashMap, iter::repeat_n};
+use crate::cleaning::{PlayerMap, Position, PositionStats};
+use crate::scoring::metric_names;
+
+// One position's metric vectors for every player who has them, stored column by column so each
+// metric's values across the league are one contiguous slice. Every row is as wide as the position's
+// metric list: short rows are padded with NaN, which the column operations skip like any other
+// missing value, and anything past the last metric is left out.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricMatrix {
+    rows: usize,
+    width: usize,
+    values: Vec<f64>,
+}
+
+// Rows are copied in as they're read, each player's vector visited once, and only turned into
+// columns at the end.
+struct RowBuilder {
+    rows: usize,
+    width: usize,
+    values: Vec<f64>,
+}
+
+impl RowBuilder {
+    fn new(width: usize) -> Self {
+        RowBuilder { rows: 0, width, values: Vec::new() }
+    }
+
+    fn push(&mut self, row: &[f64]) {
+        if row.len() >= self.width {
+            self.values.extend_from_slice(&row[..self.width]);
+        } else {
+            self.values.extend_from_slice(row);
+            self.values.extend(repeat_n(f64::NAN, self.width - row.len()));
+        }
+        self.rows += 1;
+    }
+
+    fn build(self) -> MetricMatrix {
+        let mut values = Vec::with_capacity(self.values.len());
+        for i in 0..self.width {
+            values.extend(self.values.iter().skip(i).step_by(self.width).copied());
+        }
+        MetricMatrix { rows: self.rows, width: self.width, values }
+    }
+}
+
+impl MetricMatrix {
+    pub fn from_rows<'a>(width: usize, rows: impl Iterator<Item = &'a Vec<f64>>) -> Self {
+        let mut builder = RowBuilder::new(width);
+        rows.for_each(|row| builder.push(row));
+        builder.build()
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    // Empty past the last metric.
+    pub fn column(&self, i: usize) -> &[f64] {
+        if i < self.width { &self.values[i * self.rows..(i + 1) * self.rows] } else { &[] }
+    }
+
+    pub fn column_mut(&mut self, i: usize) -> &mut [f64] {
+        if i < self.width { &mut self.values[i * self.rows..(i + 1) * self.rows] } else { &mut [] }
+    }
+
+    // Copies a row into `buffer`, so reading every row in turn reuses one allocation.
+    pub fn read_row(&self, row: usize, buffer: &mut Vec<f64>) {
+        buffer.clear();
+        buffer.extend((0..self.width).map(|i| self.values[i * self.rows + row]));
+    }
+
+    // Writes a row back over a player's vector, leaving any values past the last metric alone.
+    pub fn write_row(&self, row: usize, values: &mut [f64]) {
+        for (i, value) in values.iter_mut().enumerate().take(self.width) {
+            *value = self.values[i * self.rows + row];
+        }
+    }
+
+    // The leader (floored at 0) and mean of each column's finite values.
+    pub fn stats(&self) -> PositionStats {
+        let (max, mean) = (0..self.width)
+            .map(|i| {
+                let (max, total, count) = self.column(i).iter().filter(|value| value.is_finite()).fold((0.0f64, 0.0, 0usize), |(max, total, count), &value| {
+                    (max.max(value), total + value, count + 1)
+                });
+                (max, if count > 0 { total / count as f64 } else { 0.0 })
+            })
+            .unzip();
+        PositionStats { max, mean }
+    }
+}
+
+// A position's players as rows of their normalized and raw metrics. `players[row]` is where the
+// row's player sits in the player map, so scoring a row never hashes a name, and `plays[row]` is
+// whether they still list the position (rankings leave out metrics kept for one they don't).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PositionMatrix {
+    pub players: Vec<usize>,
+    pub plays: Vec<bool>,
+    pub normalized: MetricMatrix,
+    pub raw: MetricMatrix,
+}
+
+// Only a handful of positions, so a linear scan beats hashing each row's key.
+fn group<'a, 'b, T>(groups: &'b mut Vec<(&'a Position, T)>, position: &'a Position, new: impl FnOnce() -> T) -> &'b mut T {
+    let i = match groups.iter().position(|(grouped, _)| *grouped == position) {
+        Some(i) => i,
+        None => {
+            groups.push((position, new()));
+            groups.len() - 1
+        }
+    };
+    &mut groups[i].1
+}
+
+// Groups the rows by position in a single pass over them.
+pub fn position_matrices<'a>(rows: impl Iterator<Item = (&'a Position, &'a Vec<f64>)>) -> HashMap<Position, MetricMatrix> {
+    let mut groups: Vec<(&Position, RowBuilder)> = Vec::new();
+    for (position, metrics) in rows {
+        group(&mut groups, position, || RowBuilder::new(metric_names(position).len())).push(metrics);
+    }
+    groups.into_iter().map(|(position, builder)| (position.clone(), builder.build())).collect()
+}
+
+// Every player's metrics at each of their positions, in map order. The raw row falls back to the
+// normalized one for players without raw metrics, as `MetricMap::for_player` does; a player only
+// has a position or two, so it's found by comparing rather than hashing.
+pub fn player_matrices(players: &PlayerMap) -> HashMap<Position, PositionMatrix> {
+    gather(players, true)
+}
+
+// The raw metrics alone, with `normalized` a copy of them for normalization to rescale.
+pub fn raw_matrices(players: &PlayerMap) -> HashMap<Position, PositionMatrix> {
+    gather(players, false)
+}
+
+fn gather(players: &PlayerMap, read_normalized: bool) -> HashMap<Position, PositionMatrix> {
+    struct Builder {
+        players: Vec<usize>,
+        plays: Vec<bool>,
+        normalized: RowBuilder,
+        raw: RowBuilder,
+    }
+
+    let mut groups: Vec<(&Position, Builder)> = Vec::new();
+    for (index, player) in players.values().enumerate() {
+        for (position, metrics) in &player.metrics {
+            let width = metric_names(position).len();
+            let builder = group(&mut groups, position, || Builder { players: Vec::new(), plays: Vec::new(), normalized: RowBuilder::new(width), raw: RowBuilder::new(width) });
+            builder.players.push(index);
+            builder.plays.push(player.positions.contains(position));
+            if read_normalized {
+                builder.normalized.push(metrics);
+            }
+            builder.raw.push(player.raw_metrics.iter().find(|(raw, _)| *raw == position).map_or(metrics, |(_, raw)| raw));
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(position, builder)| {
+            let raw = builder.raw.build();
+            let normalized = if read_normalized { builder.normalized.build() } else { raw.clone() };
+            (position.clone(), PositionMatrix { players: builder.players, plays: builder.plays, normalized, raw })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cleaning::{normalize_metrics, Player};
+    use crate::scoring::rank_players;
+
+    #[test]
+    fn test_matrix_columns_and_stats() {
+        let (center, wing) = (vec![3.0, 50.0, 1.0, 1.0, 1.0, 99.0], vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        let short = vec![-1.0];
+        let matrices = position_matrices([(&Position::Center, &short), (&Position::Wing, &wing), (&Position::Center, &center)].into_iter());
+
+        // Rows are padded or cut to the position's five metrics.
+        let centers = &matrices[&Position::Center];
+        assert_eq!((centers.rows(), centers.width()), (2, 5));
+        assert_eq!(centers.column(0), [-1.0, 3.0]);
+        assert!(centers.column(1)[0].is_nan());
+        assert!(centers.column(5).is_empty());
+        assert_eq!(centers.stats(), PositionStats { max: vec![3.0, 50.0, 1.0, 1.0, 1.0], mean: vec![1.0, 50.0, 1.0, 1.0, 1.0] });
+        assert_eq!(matrices[&Position::Wing].column(2), [3.0]);
+        assert_eq!(MetricMatrix::from_rows(0, std::iter::empty()).stats(), PositionStats { max: vec![], mean: vec![] });
+
+        let mut row = Vec::new();
+        centers.read_row(1, &mut row);
+        let mut written = center.clone();
+        centers.write_row(1, &mut written);
+        assert_eq!((&row[..], written), (&center[..5], center.clone()));
+    }
+
+    #[test]
+    fn test_player_matrices_index_the_map() {
+        let mut dual = Player::new("Dual", vec![Position::Center, Position::Wing], HashMap::from([(Position::Center, vec![0.5; 5]), (Position::Wing, vec![0.25; 5])]));
+        dual.raw_metrics.insert(Position::Center, vec![50.0; 5]);
+        let players: PlayerMap = [Player::new("Wing", vec![Position::Wing], HashMap::from([(Position::Wing, vec![1.0; 5])])), dual]
+            .into_iter()
+            .map(|player| (player.name.clone(), player))
+            .collect();
+
+        let matrices = player_matrices(&players);
+        assert_eq!(matrices[&Position::Wing].players, [0, 1]);
+        assert_eq!(matrices[&Position::Center].players, [1]);
+        assert_eq!(matrices[&Position::Wing].plays, [true, true]);
+        assert_eq!(matrices[&Position::Center].raw.column(0), [50.0]);
+        assert_eq!(matrices[&Position::Wing].raw.column(0), [1.0, 0.25]);
+    }
+
+    #[test]
+    fn test_matrices_are_kept_until_the_players_change() {
+        let mut players: PlayerMap = [("Low", 10.0), ("High", 40.0)]
+            .into_iter()
+            .map(|(name, goals)| (name.into(), Player::new(name, vec![Position::Wing], HashMap::from([(Position::Wing, vec![goals; 5])]))))
+            .collect();
+        normalize_metrics(&mut players);
+        assert_eq!(players.matrices()[&Position::Wing].normalized.column(0), [0.25, 1.0]);
+        assert_eq!(players["Low"].metrics[&Position::Wing][0], 0.25);
+
+        players.get_mut("Low").unwrap().metrics.insert(Position::Wing, vec![2.0; 5]);
+        assert_eq!(players.matrices()[&Position::Wing].normalized.column(0), [2.0, 1.0]);
+        assert_eq!(rank_players(&players)[&Position::Wing][0].name, "Low".into());
+    }
+}
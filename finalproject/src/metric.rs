@@ -0,0 +1,371 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+use serde::Deserialize;
+use crate::cleaning::{metric_key, Position, COLUMNS};
+use crate::locale::Locale;
+use crate::stats::summarize;
+use crate::positions;
+
+// The stats a positional score can be built from: every numeric column of the individual report.
+// Each one knows the column it's read from, whose header in the Natural Stat Trick export is also
+// its display name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Metric {
+    GamesPlayed,
+    TimeOnIce,
+    Goals,
+    TotalAssists,
+    FirstAssists,
+    SecondAssists,
+    TotalPoints,
+    Ipp,
+    Shots,
+    ShootingPct,
+    Ixg,
+    Icf,
+    Iff,
+    Iscf,
+    Ihdcf,
+    RushAttempts,
+    ReboundsCreated,
+    Pim,
+    TotalPenalties,
+    MinorPenalties,
+    MajorPenalties,
+    Misconducts,
+    PenaltiesDrawn,
+    Giveaways,
+    Takeaways,
+    Hits,
+    HitsTaken,
+    ShotsBlocked,
+    FaceoffsWon,
+    FaceoffsLost,
+    FaceoffPct,
+}
+
+// Percentages are bounded at 100 and don't grow with games played, so they're scaled and prorated differently from counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Count,
+    Percentage,
+}
+
+impl Unit {
+    pub fn format(self, value: f64, locale: &Locale) -> String {
+        match self {
+            Unit::Count if value.fract() == 0.0 => format!("{}", value),
+            Unit::Count => format!("{:.2}", locale.num(value)),
+            Unit::Percentage => format!("{:.1}%", locale.num(value)),
+        }
+    }
+}
+
+// Whether more of a metric is better. The `[direction]` config table can set it per metric, e.g.
+// `giveaways = "lower"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Direction {
+    #[serde(rename = "higher")]
+    HigherIsBetter,
+    #[serde(rename = "lower")]
+    LowerIsBetter,
+}
+
+// How a metric's raw values are rescaled before scoring. Counts default to the league leader and
+// percentages to their 0-100 scale; the `[normalization]` config table can override either.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Normalization {
+    Max,
+    #[serde(alias = "min-max")]
+    MinMax,
+    #[serde(alias = "z-score")]
+    ZScore,
+    Percent,
+    None,
+}
+
+impl FromStr for Normalization {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match metric_key(s).as_str() {
+            "max" => Ok(Normalization::Max),
+            "minmax" => Ok(Normalization::MinMax),
+            "zscore" => Ok(Normalization::ZScore),
+            "percent" => Ok(Normalization::Percent),
+            "none" => Ok(Normalization::None),
+            _ => Err(format!("invalid normalization '{}', expected max, min-max, z-score, percent or none", s)),
+        }
+    }
+}
+
+impl Normalization {
+    // The offset and divisor for the league's values, so a metric normalizes to (value - offset) / divisor.
+    pub fn fit(self, values: &[f64]) -> (f64, f64) {
+        let finite: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+        match self {
+            Normalization::Max => (0.0, finite.iter().copied().fold(0.0, f64::max)),
+            Normalization::MinMax => {
+                let min = finite.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = finite.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                (min, max - min)
+            }
+            Normalization::ZScore => {
+                let summary = summarize(&finite);
+                (summary.mean, summary.stddev)
+            }
+            Normalization::Percent => (0.0, 100.0),
+            Normalization::None => (0.0, 1.0),
+        }
+    }
+
+    // Flips a normalized lower-is-better value: strategies that land on a 0-1 scale mirror within
+    // it, while z-scores and untouched values are negated.
+    pub fn invert(self, value: f64) -> f64 {
+        match self {
+            Normalization::Max | Normalization::MinMax | Normalization::Percent => 1.0 - value,
+            Normalization::ZScore | Normalization::None => -value,
+        }
+    }
+}
+
+// The config's `[normalization]` and `[direction]` overrides. A metric neither names keeps its own
+// defaults.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Scaling {
+    pub normalization: HashMap<Metric, Normalization>,
+    pub direction: HashMap<Metric, Direction>,
+}
+
+impl Scaling {
+    pub fn is_empty(&self) -> bool {
+        self.normalization.is_empty() && self.direction.is_empty()
+    }
+
+    pub fn normalization(&self, metric: Metric) -> Normalization {
+        self.normalization.get(&metric).copied().unwrap_or(metric.normalization())
+    }
+
+    pub fn direction(&self, metric: Metric) -> Direction {
+        self.direction.get(&metric).copied().unwrap_or(metric.direction())
+    }
+
+    pub fn fit(&self, metric: Metric, values: &[f64]) -> Scale {
+        let normalization = self.normalization(metric);
+        let (offset, divisor) = normalization.fit(values);
+        Scale { metric, normalization, direction: self.direction(metric), offset, divisor }
+    }
+}
+
+// A metric's fitted normalization at one position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scale {
+    pub metric: Metric,
+    pub normalization: Normalization,
+    pub direction: Direction,
+    pub offset: f64,
+    pub divisor: f64,
+}
+
+impl Scale {
+
+    // Lower-is-better metrics are flipped after scaling so a higher value always scores better.
+    pub fn apply(&self, value: f64) -> f64 {
+        if !(self.divisor > 0.0 && value.is_finite()) {
+            return 0.0;
+        }
+        let scaled = (value - self.offset) / self.divisor;
+        match self.direction {
+            Direction::HigherIsBetter => scaled,
+            Direction::LowerIsBetter => self.normalization.invert(scaled),
+        }
+    }
+}
+
+// In column order.
+pub const METRICS: [Metric; 31] = [
+    Metric::GamesPlayed,
+    Metric::TimeOnIce,
+    Metric::Goals,
+    Metric::TotalAssists,
+    Metric::FirstAssists,
+    Metric::SecondAssists,
+    Metric::TotalPoints,
+    Metric::Ipp,
+    Metric::Shots,
+    Metric::ShootingPct,
+    Metric::Ixg,
+    Metric::Icf,
+    Metric::Iff,
+    Metric::Iscf,
+    Metric::Ihdcf,
+    Metric::RushAttempts,
+    Metric::ReboundsCreated,
+    Metric::Pim,
+    Metric::TotalPenalties,
+    Metric::MinorPenalties,
+    Metric::MajorPenalties,
+    Metric::Misconducts,
+    Metric::PenaltiesDrawn,
+    Metric::Giveaways,
+    Metric::Takeaways,
+    Metric::Hits,
+    Metric::HitsTaken,
+    Metric::ShotsBlocked,
+    Metric::FaceoffsWon,
+    Metric::FaceoffsLost,
+    Metric::FaceoffPct,
+];
+
+// The metrics each built-in position is scored on, in the order the weights apply to them.
+const CENTER: [Metric; 5] = [Metric::FaceoffPct, Metric::TotalPoints, Metric::Takeaways, Metric::FirstAssists, Metric::Ipp];
+const WING: [Metric; 5] = [Metric::Goals, Metric::ShootingPct, Metric::RushAttempts, Metric::TotalPoints, Metric::Hits];
+const DEFENSE: [Metric; 5] = [Metric::Hits, Metric::ShotsBlocked, Metric::Takeaways, Metric::TotalPoints, Metric::RushAttempts];
+
+pub const CENTER_NAMES: [&str; 5] = names(CENTER);
+pub const WING_NAMES: [&str; 5] = names(WING);
+pub const DEFENSE_NAMES: [&str; 5] = names(DEFENSE);
+
+const fn names<const N: usize>(metrics: [Metric; N]) -> [&'static str; N] {
+    let mut names = [""; N];
+    let mut i = 0;
+    while i < N {
+        names[i] = metrics[i].name();
+        i += 1;
+    }
+    names
+}
+
+impl Metric {
+    pub const fn name(self) -> &'static str {
+        COLUMNS[self.column()]
+    }
+
+    // Index into `COLUMNS`, the expected layout of the individual report; the stats start at GP.
+    pub const fn column(self) -> usize {
+        self as usize + 3
+    }
+
+    pub fn unit(self) -> Unit {
+        match self {
+            Metric::Ipp | Metric::ShootingPct | Metric::FaceoffPct => Unit::Percentage,
+            _ => Unit::Count,
+        }
+    }
+
+    pub fn normalization(self) -> Normalization {
+        match self.unit() {
+            Unit::Count => Normalization::Max,
+            Unit::Percentage => Normalization::Percent,
+        }
+    }
+
+    // Giving the puck away or taking penalties only ever hurts a team. Everything else defaults to
+    // rewarding more.
+    pub fn direction(self) -> Direction {
+        match self {
+            Metric::Giveaways
+            | Metric::Pim
+            | Metric::TotalPenalties
+            | Metric::MinorPenalties
+            | Metric::MajorPenalties
+            | Metric::Misconducts
+            | Metric::FaceoffsLost => Direction::LowerIsBetter,
+            _ => Direction::HigherIsBetter,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Metric> {
+        let key = metric_key(name);
+        METRICS.into_iter().find(|metric| metric_key(metric.name()) == key)
+    }
+
+    // The metrics a position is scored on, in the order the weights apply to them. An unregistered
+    // position has none, rather than passing for some other position.
+    pub fn for_position(position: &Position) -> &'static [Metric] {
+        match position {
+            Position::Center => &CENTER,
+            Position::Wing => &WING,
+            Position::Defense => &DEFENSE,
+            Position::Custom(id) => positions::definition(*id).map_or(&[], |def| &def.metrics),
+        }
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cleaning::{parse_line, ParseOptions};
+
+    #[test]
+    fn test_metric_columns_match_layout() {
+        assert_eq!(METRICS.len(), COLUMNS.len() - 3);
+        for metric in METRICS {
+            assert_eq!(Metric::from_name(&metric.name().to_lowercase()), Some(metric));
+        }
+        assert_eq!((Metric::Goals.name(), Metric::FaceoffPct.column()), ("Goals", 33));
+        assert_eq!(Metric::from_name("TOI"), Some(Metric::TimeOnIce));
+        assert_eq!(Metric::from_name("Player"), None);
+        assert_eq!(DEFENSE_NAMES, ["Hits", "Shots Blocked", "Takeaways", "Total Points", "Rush Attempts"]);
+    }
+
+    #[test]
+    fn test_defense_labels_name_the_columns_read() {
+        // Every stat column holds its own index, so each parsed value says which column it came from.
+        let fields: Vec<String> = (0..COLUMNS.len()).map(|i| match i {
+            1 => "Cale Makar".to_string(),
+            2 => "D".to_string(),
+            _ => i.to_string(),
+        }).collect();
+        let player = parse_line(&fields.join(","), &ParseOptions::default()).unwrap();
+        let labels: Vec<&str> = player.metrics[&Position::Defense].iter().map(|&column| COLUMNS[column as usize]).collect();
+        assert_eq!(labels, DEFENSE_NAMES);
+        assert_eq!(labels[3], "Total Points");
+    }
+
+    #[test]
+    fn test_unit_format() {
+        let locale = Locale::default();
+        assert_eq!(Metric::FaceoffPct.unit().format(52.333, &locale), "52.3%");
+        assert_eq!(Metric::Goals.unit().format(41.0, &locale), "41");
+        assert_eq!(Metric::Goals.unit().format(0.5125, &locale), "0.51");
+        assert_eq!(Metric::Goals.unit().format(0.5125, &Locale { decimal: ',', ..locale }), "0,51");
+    }
+
+    #[test]
+    fn test_normalization_fit() {
+        let values = [2.0, 4.0, 6.0, f64::NAN];
+        assert_eq!(Normalization::Max.fit(&values), (0.0, 6.0));
+        assert_eq!(Normalization::MinMax.fit(&values), (2.0, 4.0));
+        let (mean, stddev) = Normalization::ZScore.fit(&values);
+        assert_eq!(mean, 4.0);
+        assert!((stddev - (8.0_f64 / 3.0).sqrt()).abs() < 1e-9);
+        assert_eq!(Normalization::Percent.fit(&values), (0.0, 100.0));
+        assert_eq!(Metric::ShootingPct.normalization(), Normalization::Percent);
+    }
+
+    #[test]
+    fn test_invert_lower_is_better() {
+        assert_eq!(Normalization::MinMax.invert(0.25), 0.75);
+        assert_eq!(Normalization::Percent.invert(0.1), 0.9);
+        assert_eq!(Normalization::ZScore.invert(1.5), -1.5);
+        assert_eq!(Metric::Giveaways.direction(), Direction::LowerIsBetter);
+        assert_eq!(Metric::Takeaways.direction(), Direction::HigherIsBetter);
+
+        // Flipped after scaling, so a lower-is-better metric ranks the league in reverse.
+        let values = [2.0, 4.0, 8.0];
+        for normalization in [Normalization::Max, Normalization::MinMax, Normalization::ZScore] {
+            let scaling = Scaling { normalization: HashMap::from([(Metric::Hits, normalization)]), direction: HashMap::from([(Metric::Hits, Direction::LowerIsBetter)]) };
+            let scale = scaling.fit(Metric::Hits, &values);
+            let scaled: Vec<f64> = values.iter().map(|&value| scale.apply(value)).collect();
+            assert!(scaled[0] > scaled[1] && scaled[1] > scaled[2], "{:?}: {:?}", normalization, scaled);
+        }
+        assert_eq!(Scaling::default().fit(Metric::Giveaways, &values).apply(2.0), 0.75);
+    }
+}
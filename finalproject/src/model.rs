@@ -0,0 +1,525 @@
+use std::collections::HashMap;
+use serde::Deserialize;
+use crate::cleaning::{metric_key, Player, PlayerMap, Position};
+use crate::onice::Possession;
+use crate::fantasy::{parse_point_values, PointValue, DEFAULT_POINT_VALUES};
+use crate::scoring::{metric_names, rank_players_with, weighted_sum, weighted_sums, Sigmoid, Transform, WeightTable};
+use crate::positions;
+use crate::stat_line::StatLine;
+
+pub const DEFAULT_MODEL: &str = "sigmoid";
+
+#[derive(Debug, Clone, Copy)]
+pub struct MetricMap<'a> {
+    pub names: &'static [&'static str],
+    pub normalized: &'a [f64],
+    pub raw: &'a [f64],
+    pub stats: &'a StatLine,
+}
+
+impl<'a> MetricMap<'a> {
+    pub fn for_player(player: &'a Player, position: &Position) -> Option<MetricMap<'a>> {
+        let normalized = player.metrics.get(position)?;
+        let raw = player.raw_metrics.get(position).unwrap_or(normalized);
+        Some(MetricMap { names: metric_names(position), normalized, raw, stats: &player.stats })
+    }
+
+    pub fn get(&self, metric: &str) -> Option<f64> {
+        let key = metric_key(metric);
+        let index = self.names.iter().position(|name| metric_key(name) == key)?;
+        self.normalized.get(index).copied()
+    }
+
+    pub fn stat(&self, stat: &str) -> Option<f64> {
+        self.stats.get(stat).copied()
+    }
+}
+
+pub trait ScoringModel: Send {
+    fn name(&self) -> &str;
+
+    // Models that score relative to the league (percentiles, per-position maxima) look at it here first.
+    fn fit(&mut self, _players: &PlayerMap) {}
+
+    // Whether `fit` ties a player's score to everyone else's, so any change to the league can move it.
+    fn league_relative(&self) -> bool {
+        false
+    }
+
+    fn score(&self, position: &Position, metrics: &MetricMap) -> f64;
+
+    // One score per row of the position's matrix, in row order. Models that only need the normalized
+    // metrics can work down the matrix's columns instead of building each player's row.
+    fn score_position(&self, position: &Position, players: &PlayerMap) -> Vec<f64> {
+        let mut scores = Vec::new();
+        players.for_each_row(position, |metrics| scores.push(self.score(position, metrics)));
+        scores
+    }
+
+    fn unit(&self, _position: &Position) -> &'static str {
+        "%"
+    }
+
+    // The metric weights behind the score, for models whose score grows with the weighted sum of the
+    // normalized metrics. Score explanations use them to split the score between the metrics.
+    fn weights(&self, _position: &Position) -> Option<&[f64]> {
+        None
+    }
+}
+
+#[derive(Default)]
+pub struct WeightedSigmoid {
+    pub weights: WeightTable,
+    pub sigmoids: HashMap<Position, Sigmoid>,
+    pub transform: Transform,
+    sums: HashMap<Position, Vec<f64>>,
+}
+
+impl WeightedSigmoid {
+    pub fn new(weights: WeightTable, sigmoids: HashMap<Position, Sigmoid>, transform: Transform) -> Self {
+        WeightedSigmoid { weights, sigmoids, transform, sums: HashMap::new() }
+    }
+
+    fn transform_sum(&self, position: &Position, weights: &[f64], sum: Option<f64>) -> f64 {
+        let sigmoid = self.sigmoids.get(position).copied().unwrap_or_else(|| Sigmoid::for_position(position));
+        let Some(sum) = sum else { return 0.0 };
+        if self.transform == Transform::Logistic {
+            return sigmoid.apply(sum);
+        }
+
+        let total_weight: f64 = weights.iter().sum();
+        let share = if total_weight > 0.0 { sum / total_weight } else { 0.0 };
+        let percentile = self
+            .sums
+            .get(position)
+            .filter(|sums| !sums.is_empty())
+            .map_or(0.0, |sums| 100.0 * sums.partition_point(|&s| s <= sum) as f64 / sums.len() as f64);
+        self.transform.apply(sum, share, sigmoid, percentile)
+    }
+}
+
+impl ScoringModel for WeightedSigmoid {
+    fn name(&self) -> &str {
+        "sigmoid"
+    }
+
+    // Only the percentile transform needs the league's weighted sums.
+    fn fit(&mut self, players: &PlayerMap) {
+        self.sums.clear();
+        if self.transform != Transform::Percentile {
+            return;
+        }
+        for position in positions::all() {
+            let weights = self.weights.get(position);
+            let mut sums: Vec<f64> = players.matrices().get(position).map_or(Vec::new(), |matrix| weighted_sums(weights, &matrix.normalized).into_iter().flatten().collect());
+            sums.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            self.sums.insert(position.clone(), sums);
+        }
+    }
+
+    fn league_relative(&self) -> bool {
+        self.transform == Transform::Percentile
+    }
+
+    fn score(&self, position: &Position, metrics: &MetricMap) -> f64 {
+        let weights = self.weights.get(position);
+        self.transform_sum(position, weights, weighted_sum(weights, metrics.normalized))
+    }
+
+    // The weighted sums only need the normalized columns, so the whole position is summed at once.
+    fn score_position(&self, position: &Position, players: &PlayerMap) -> Vec<f64> {
+        let Some(matrix) = players.matrices().get(position) else { return Vec::new() };
+        let weights = self.weights.get(position);
+        weighted_sums(weights, &matrix.normalized).into_iter().map(|sum| self.transform_sum(position, weights, sum)).collect()
+    }
+
+    fn weights(&self, position: &Position) -> Option<&[f64]> {
+        Some(self.weights.get(position))
+    }
+}
+
+// `--model linear` is the weighted model under `--transform linear`, kept under its own name.
+pub struct Linear(WeightedSigmoid);
+
+impl Linear {
+    pub fn new(weights: WeightTable) -> Self {
+        Linear(WeightedSigmoid::new(weights, HashMap::new(), Transform::Linear))
+    }
+}
+
+impl Default for Linear {
+    fn default() -> Self {
+        Linear::new(WeightTable::default())
+    }
+}
+
+impl ScoringModel for Linear {
+    fn name(&self) -> &str {
+        "linear"
+    }
+
+    fn score(&self, position: &Position, metrics: &MetricMap) -> f64 {
+        self.0.score(position, metrics)
+    }
+
+    fn score_position(&self, position: &Position, players: &PlayerMap) -> Vec<f64> {
+        self.0.score_position(position, players)
+    }
+
+    fn weights(&self, position: &Position) -> Option<&[f64]> {
+        self.0.weights(position)
+    }
+}
+
+#[derive(Default)]
+pub struct PercentileSum {
+    sorted: HashMap<Position, Vec<Vec<f64>>>,
+}
+
+impl ScoringModel for PercentileSum {
+    fn name(&self) -> &str {
+        "percentile"
+    }
+
+    fn fit(&mut self, players: &PlayerMap) {
+        let matrices = players.matrices();
+        self.sorted = positions::all()
+            .iter()
+            .map(|position| {
+                let columns = (0..metric_names(position).len())
+                    .map(|i| {
+                        // Normalized metrics are never NaN, so only a short row's padding is left out.
+                        let column = matrices.get(position).map_or(&[][..], |matrix| matrix.normalized.column(i));
+                        let mut column: Vec<f64> = column.iter().copied().filter(|value| !value.is_nan()).collect();
+                        column.sort_by(|a, b| a.total_cmp(b));
+                        column
+                    })
+                    .collect();
+                (position.clone(), columns)
+            })
+            .collect();
+    }
+
+    fn league_relative(&self) -> bool {
+        true
+    }
+
+    fn score(&self, position: &Position, metrics: &MetricMap) -> f64 {
+        let Some(columns) = self.sorted.get(position) else { return 0.0 };
+        let percentiles: Vec<f64> = columns
+            .iter()
+            .zip(metrics.normalized)
+            .filter(|(column, _)| !column.is_empty())
+            .map(|(column, &value)| 100.0 * column.partition_point(|&v| v <= value) as f64 / column.len() as f64)
+            .collect();
+
+        if percentiles.is_empty() {
+            0.0
+        } else {
+            percentiles.iter().sum::<f64>() / percentiles.len() as f64
+        }
+    }
+}
+
+// Like `PercentileSum`, but ties share their percentile and the position weights still apply.
+#[derive(Default)]
+pub struct Ordinal {
+    pub weights: WeightTable,
+    sorted: HashMap<Position, Vec<Vec<f64>>>,
+}
+
+impl Ordinal {
+    pub fn new(weights: WeightTable) -> Self {
+        Ordinal { weights, sorted: HashMap::new() }
+    }
+}
+
+impl ScoringModel for Ordinal {
+    fn name(&self) -> &str {
+        "ordinal"
+    }
+
+    fn fit(&mut self, players: &PlayerMap) {
+        let mut percentile = PercentileSum::default();
+        percentile.fit(players);
+        self.sorted = percentile.sorted;
+    }
+
+    fn league_relative(&self) -> bool {
+        true
+    }
+
+    fn score(&self, position: &Position, metrics: &MetricMap) -> f64 {
+        let Some(columns) = self.sorted.get(position) else { return 0.0 };
+        let (sum, total_weight) = columns
+            .iter()
+            .zip(metrics.normalized)
+            .zip(self.weights.get(position))
+            .filter(|((column, _), _)| !column.is_empty())
+            .fold((0.0, 0.0), |(sum, total), ((column, &value), &weight)| {
+                let below = column.partition_point(|&v| v < value);
+                let equal = column.partition_point(|&v| v <= value) - below;
+                let percentile = 100.0 * (below as f64 + equal as f64 / 2.0) / column.len() as f64;
+                (sum + percentile * weight, total + weight)
+            });
+        if total_weight > 0.0 { sum / total_weight } else { 0.0 }
+    }
+}
+
+pub struct Fantasy {
+    pub values: Vec<PointValue>,
+}
+
+impl Default for Fantasy {
+    fn default() -> Self {
+        Fantasy { values: parse_point_values(DEFAULT_POINT_VALUES).expect("default point values are valid") }
+    }
+}
+
+impl ScoringModel for Fantasy {
+    fn name(&self) -> &str {
+        "fantasy"
+    }
+
+    fn score(&self, _position: &Position, metrics: &MetricMap) -> f64 {
+        self.values.iter().map(|value| metrics.stat(value.stat).unwrap_or(0.0) * value.points).sum()
+    }
+
+    fn unit(&self, _position: &Position) -> &'static str {
+        " pts"
+    }
+}
+
+// Roster spots per team at each position; the next player after the league's regulars is replacement level.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReplacementLevel {
+    pub teams: usize,
+    pub center: usize,
+    pub wing: usize,
+    pub defense: usize,
+}
+
+impl Default for ReplacementLevel {
+    fn default() -> Self {
+        ReplacementLevel { teams: 32, center: 4, wing: 8, defense: 6 }
+    }
+}
+
+impl ReplacementLevel {
+    pub fn rank(&self, position: &Position) -> usize {
+        let slots = match position {
+            Position::Center => self.center,
+            Position::Wing => self.wing,
+            Position::Defense => self.defense,
+            Position::Custom(id) => positions::definition(*id).map_or(0, |def| def.slots),
+        };
+        self.teams * slots + 1
+    }
+}
+
+pub struct ValueOverReplacement {
+    pub base: Box<dyn ScoringModel>,
+    pub level: ReplacementLevel,
+    replacement: HashMap<Position, f64>,
+}
+
+impl ValueOverReplacement {
+    pub fn new(base: Box<dyn ScoringModel>, level: ReplacementLevel) -> Self {
+        ValueOverReplacement { base, level, replacement: HashMap::new() }
+    }
+
+    pub fn replacement_score(&self, position: &Position) -> f64 {
+        self.replacement.get(position).copied().unwrap_or(0.0)
+    }
+}
+
+impl ScoringModel for ValueOverReplacement {
+    fn name(&self) -> &str {
+        "vor"
+    }
+
+    // Positions with fewer players than roster spots use their weakest player as replacement level.
+    fn fit(&mut self, players: &PlayerMap) {
+        self.base.fit(players);
+        self.replacement = rank_players_with(players, self.base.as_ref())
+            .into_iter()
+            .filter_map(|(position, ranked)| {
+                let index = self.level.rank(&position).min(ranked.len()).checked_sub(1)?;
+                Some((position, ranked[index].score))
+            })
+            .collect();
+    }
+
+    fn league_relative(&self) -> bool {
+        true
+    }
+
+    fn score(&self, position: &Position, metrics: &MetricMap) -> f64 {
+        self.base.score(position, metrics) - self.replacement_score(position)
+    }
+
+    fn unit(&self, position: &Position) -> &'static str {
+        match self.base.unit(position) {
+            "%" => "",
+            unit => unit,
+        }
+    }
+}
+
+// Adjustments layered over whichever model is chosen: `Age` (from `--age-adjust`) scales by the age
+// curve, `pdo` shrinks scores propped up by on-ice shooting and save percentages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjustment {
+    Age,
+    Pdo,
+}
+
+impl std::str::FromStr for Adjustment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "pdo" | "luck" => Ok(Adjustment::Pdo),
+            _ => Err(format!("invalid adjustment '{}', expected pdo (use --age-adjust for the age curve)", s)),
+        }
+    }
+}
+
+pub struct ModelRegistry {
+    models: Vec<Box<dyn ScoringModel>>,
+}
+
+impl Default for ModelRegistry {
+    fn default() -> Self {
+        let mut registry = ModelRegistry { models: Vec::new() };
+        registry.register(Box::new(WeightedSigmoid::default()));
+        registry.register(Box::new(Linear::default()));
+        registry.register(Box::new(PercentileSum::default()));
+        registry.register(Box::new(Ordinal::default()));
+        registry.register(Box::new(Fantasy::default()));
+        registry.register(Box::new(Possession));
+        registry
+    }
+}
+
+impl ModelRegistry {
+    // Registering a model under an existing name replaces it.
+    pub fn register(&mut self, model: Box<dyn ScoringModel>) {
+        self.models.retain(|existing| existing.name() != model.name());
+        self.models.push(model);
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.models.iter().map(|model| model.name()).collect()
+    }
+
+    pub fn take(mut self, name: &str) -> Result<Box<dyn ScoringModel>, String> {
+        match self.models.iter().position(|model| model.name().eq_ignore_ascii_case(name.trim())) {
+            Some(index) => Ok(self.models.swap_remove(index)),
+            None => Err(format!("unknown scoring model '{}', expected one of: {}", name, self.names().join(", "))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::{calculate_score, position_weights};
+
+    fn players() -> PlayerMap {
+        ["A", "B", "C", "D"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let metrics = vec![i as f64 / 3.0; 5];
+                ((*name).into(), Player::new(*name, vec![Position::Center], HashMap::from([(Position::Center, metrics)])))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_models_score_through_metric_map() {
+        let players = players();
+        let best = MetricMap::for_player(&players["D"], &Position::Center).unwrap();
+        assert_eq!(best.get("faceoffs %"), Some(1.0));
+
+        assert_eq!(WeightedSigmoid::default().score(&Position::Center, &best), calculate_score(&Position::Center, &[1.0; 5]));
+        assert!((Linear::default().score(&Position::Center, &best) - 100.0).abs() < 1e-9);
+
+        let mut percentile = PercentileSum::default();
+        percentile.fit(&players);
+        assert_eq!(percentile.score(&Position::Center, &best), 100.0);
+        let worst = MetricMap::for_player(&players["A"], &Position::Center).unwrap();
+        assert_eq!(percentile.score(&Position::Center, &worst), 25.0);
+
+        let mut ordinal = Ordinal::default();
+        ordinal.fit(&players);
+        assert!((ordinal.score(&Position::Center, &best) - 87.5).abs() < 1e-9);
+        assert!((ordinal.score(&Position::Center, &worst) - 12.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sigmoid_transforms() {
+        let players = players();
+        let best = MetricMap::for_player(&players["D"], &Position::Center).unwrap();
+        let worst = MetricMap::for_player(&players["A"], &Position::Center).unwrap();
+        let model = |transform| {
+            let mut model = WeightedSigmoid::new(WeightTable::default(), HashMap::new(), transform);
+            model.fit(&players);
+            model
+        };
+
+        assert!((model(Transform::Linear).score(&Position::Center, &best) - 100.0).abs() < 1e-9);
+        assert_eq!(model(Transform::Linear).score(&Position::Center, &worst), 0.0);
+        assert_eq!(Linear::default().score_position(&Position::Center, &players), model(Transform::Linear).score_position(&Position::Center, &players));
+        assert!((model(Transform::Tanh).score(&Position::Center, &best) - 100.0 * 2.0_f64.tanh()).abs() < 1e-9);
+        assert_eq!(model(Transform::Percentile).score(&Position::Center, &best), 100.0);
+        assert_eq!(model(Transform::Percentile).score(&Position::Center, &worst), 25.0);
+        assert_eq!(model(Transform::Logistic).score(&Position::Center, &best), WeightedSigmoid::default().score(&Position::Center, &best));
+        assert_eq!("TANH".parse::<Transform>(), Ok(Transform::Tanh));
+        assert!("cubic".parse::<Transform>().is_err());
+    }
+
+    #[test]
+    fn test_ordinal_weights_rank_percentiles() {
+        let mut players = players();
+        players.get_mut("D").unwrap().metrics.insert(Position::Center, vec![1.0, 1.0, 1.0, 1.0, 0.0]);
+        let mut ordinal = Ordinal::default();
+        ordinal.fit(&players);
+
+        let d = MetricMap::for_player(&players["D"], &Position::Center).unwrap();
+        let c = MetricMap::for_player(&players["C"], &Position::Center).unwrap();
+        let (weights, _) = position_weights(&Position::Center);
+        assert!((ordinal.score(&Position::Center, &d) - (87.5 * (1.0 - weights[4]) + 25.0 * weights[4])).abs() < 1e-9);
+        assert!((ordinal.score(&Position::Center, &c) - (62.5 * (1.0 - weights[4]) + 87.5 * weights[4])).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_value_over_replacement() {
+        let players = players();
+        let level = ReplacementLevel { teams: 1, center: 2, wing: 0, defense: 0 };
+        assert_eq!(level.rank(&Position::Center), 3);
+
+        let mut vor = ValueOverReplacement::new(Box::new(Linear::default()), level);
+        vor.fit(&players);
+        assert!((vor.replacement_score(&Position::Center) - 100.0 / 3.0).abs() < 1e-9);
+
+        let best = MetricMap::for_player(&players["D"], &Position::Center).unwrap();
+        let replacement = MetricMap::for_player(&players["B"], &Position::Center).unwrap();
+        assert!((vor.score(&Position::Center, &best) - 200.0 / 3.0).abs() < 1e-9);
+        assert!(vor.score(&Position::Center, &replacement).abs() < 1e-9);
+        assert_eq!(vor.unit(&Position::Center), "");
+    }
+
+    #[test]
+    fn test_registry_selects_by_name() {
+        let registry = ModelRegistry::default();
+        assert_eq!(registry.names(), vec!["sigmoid", "linear", "percentile", "ordinal", "fantasy", "possession"]);
+        assert_eq!(ModelRegistry::default().take("Linear").unwrap().name(), "linear");
+        assert!(ModelRegistry::default().take("elo").is_err());
+        // The age curve has its own flag, --age-adjust.
+        assert_eq!("luck".parse::<Adjustment>(), Ok(Adjustment::Pdo));
+        assert!("age".parse::<Adjustment>().is_err());
+    }
+}
@@ -0,0 +1,96 @@
+use std::str::FromStr;
+use crate::cleaning::{metric_key, PlayerMap, Position};
+use crate::model::{MetricMap, ScoringModel};
+
+// Share-of-events columns from Natural Stat Trick's on-ice report; raw for/against counts are left out.
+pub const ONICE_COLUMNS: [&str; 11] = [
+    "CF%", "FF%", "SF%", "GF%", "xGF%", "SCF%", "HDCF%", "On-Ice SH%", "On-Ice SV%", "PDO", "Off. Zone Start %",
+];
+
+const POSSESSION_WEIGHTS: [(&str, f64); 3] = [("xGF%", 0.4), ("CF%", 0.3), ("HDCF%", 0.3)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Report {
+    #[default]
+    Individual,
+    OnIce,
+}
+
+impl FromStr for Report {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match metric_key(s).as_str() {
+            "individual" | "ind" => Ok(Report::Individual),
+            "onice" | "oi" => Ok(Report::OnIce),
+            _ => Err(format!("invalid report '{}', expected individual or onice", s)),
+        }
+    }
+}
+
+// "CF" and "CF%" share a metric key, so the percent sign has to match too.
+pub fn onice_column(name: &str) -> Option<&'static str> {
+    let key = metric_key(name);
+    ONICE_COLUMNS.iter().find(|column| metric_key(column) == key && column.contains('%') == name.contains('%')).copied()
+}
+
+// Players with none of the on-ice shares the possession model scores, who would all get 0.
+pub fn players_without_shares(players: &PlayerMap) -> usize {
+    players.values().filter(|player| POSSESSION_WEIGHTS.iter().all(|(stat, _)| !player.stats.contains_key(stat))).count()
+}
+
+// Scores on-ice share of expected goals, shot attempts and high-danger chances, so 50 is break-even.
+pub struct Possession;
+
+impl ScoringModel for Possession {
+    fn name(&self) -> &str {
+        "possession"
+    }
+
+    fn fit(&mut self, players: &PlayerMap) {
+        let missing = players_without_shares(players);
+        if missing > 0 && missing == players.len() {
+            log::warn!("No player has the on-ice xGF%, CF% or HDCF% columns, so every possession score is 0; load the on-ice report with --report onice");
+        } else if missing > 0 {
+            log::warn!("{} players have no on-ice xGF%, CF% or HDCF% and score 0 under the possession model", missing);
+        }
+    }
+
+    fn score(&self, _position: &Position, metrics: &MetricMap) -> f64 {
+        let (sum, weight) = POSSESSION_WEIGHTS
+            .iter()
+            .filter_map(|&(stat, weight)| Some((metrics.stat(stat)? * weight, weight)))
+            .fold((0.0, 0.0), |(sum, total), (value, weight)| (sum + value, total + weight));
+        if weight > 0.0 { sum / weight } else { 0.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cleaning::{parse_line, ParseOptions};
+
+    #[test]
+    fn test_onice_report_maps_columns_by_name() {
+        let header = "Player,Team,Position,GP,TOI,CF,CA,CF%,xGF,xGA,xGF%,HDCF,HDCA,HDCF%,PDO";
+        let options = ParseOptions { report: Report::OnIce, ..ParseOptions::default() }.with_header(header);
+        let player = parse_line("Cale Makar,COL,D,77,1900.5,1500,1200,55.56,80,60,57.14,300,250,54.55,1.012", &options).unwrap();
+
+        assert_eq!(player.positions, vec![Position::Defense]);
+        assert_eq!(player.stats["GP"], 77.0);
+        assert_eq!(player.stats["xGF%"], 57.14);
+        assert_eq!(player.stats["PDO"], 1.012);
+        assert!(!player.stats.contains_key("CF"));
+
+        let metrics = MetricMap::for_player(&player, &Position::Defense).unwrap();
+        let expected = 0.4 * 57.14 + 0.3 * 55.56 + 0.3 * 54.55;
+        assert!((Possession.score(&Position::Defense, &metrics) - expected).abs() < 1e-9);
+
+        let individual = parse_line("1,Cale Makar,D,200,4000,60,150,90,60,210,60.5,500,12,40,900,700,400,100,40,80,50,25,25,0,0,60,150,120,80,60,190,0,0,0", &ParseOptions::default()).unwrap();
+        let players = PlayerMap::from_iter([(player.name.clone(), player), ("Individual".into(), individual)]);
+        assert_eq!(players_without_shares(&players), 1);
+        assert_eq!(onice_column("hdcf %"), Some("HDCF%"));
+        assert_eq!(onice_column("HDCF"), None);
+        assert_eq!("on-ice".parse::<Report>(), Ok(Report::OnIce));
+    }
+}
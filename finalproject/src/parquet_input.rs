@@ -0,0 +1,95 @@
+use std::{fs::File, io};
+use arrow_array::RecordBatch;
+use arrow_cast::display::{ArrayFormatter, FormatOptions};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use crate::cleaning::{insert_player, parse_record, DuplicatePolicy, PlayerMap, COLUMNS};
+
+pub fn process_parquet(file_path: &str) -> io::Result<PlayerMap> {
+    let file = File::open(file_path)?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .and_then(|builder| builder.build())
+        .map_err(io::Error::other)?;
+
+    let mut players = PlayerMap::default();
+    for batch in reader {
+        add_batch(&batch.map_err(io::Error::other)?, &mut players)?;
+    }
+
+    Ok(players)
+}
+
+fn add_batch(batch: &RecordBatch, players: &mut PlayerMap) -> io::Result<()> {
+    let schema = batch.schema();
+    for required in ["Player", "Position"] {
+        if schema.index_of(required).is_err() {
+            let message = format!("parquet file has no '{}' column", required);
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+    }
+
+    // Line the parquet columns up with the CSV layout so rows go through the same cleaning as CSV lines.
+    let options = FormatOptions::default();
+    let columns = COLUMNS
+        .iter()
+        .map(|name| match schema.index_of(name) {
+            Ok(i) => {
+                let array = batch.column(i).as_ref();
+                ArrayFormatter::try_new(array, &options).map(|formatter| Some((array, formatter)))
+            }
+            Err(_) => Ok(None),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(io::Error::other)?;
+
+    for row in 0..batch.num_rows() {
+        let values: Vec<String> = columns
+            .iter()
+            .map(|column| match column {
+                Some((array, formatter)) if !array.is_null(row) => formatter.value(row).to_string(),
+                _ => String::new(),
+            })
+            .collect();
+        let fields: Vec<&str> = values.iter().map(String::as_str).collect();
+
+        if let Some(player) = parse_record(&fields, &values.join(",")) {
+            insert_player(players, player, DuplicatePolicy::default());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use arrow_array::{ArrayRef, Float64Array, Int64Array, StringArray};
+    use parquet::arrow::ArrowWriter;
+    use crate::cleaning::Position;
+
+    #[test]
+    fn test_process_parquet_maps_columns_by_name() {
+        let file_path = std::env::temp_dir().join("finalproject_parquet_test.parquet");
+
+        let mut columns: Vec<(&str, ArrayRef)> = vec![
+            ("Player", Arc::new(StringArray::from(vec!["Cale Makar"]))),
+            ("Position", Arc::new(StringArray::from(vec!["D"]))),
+            ("Hits", Arc::new(Int64Array::from(vec![80]))),
+            ("Shots Blocked", Arc::new(Int64Array::from(vec![190]))),
+            ("Takeaways", Arc::new(Int64Array::from(vec![120]))),
+            ("Total Points", Arc::new(Int64Array::from(vec![210]))),
+            ("Rush Attempts", Arc::new(Int64Array::from(vec![40]))),
+        ];
+        columns.push(("SH%", Arc::new(Float64Array::from(vec![12.5]))));
+        let batch = RecordBatch::try_from_iter(columns).unwrap();
+
+        let mut writer = ArrowWriter::try_new(File::create(&file_path).unwrap(), batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let players = process_parquet(file_path.to_str().unwrap()).unwrap();
+        assert_eq!(players["Cale Makar"].raw_metrics[&Position::Defense], vec![80.0, 190.0, 120.0, 210.0, 40.0]);
+
+        std::fs::remove_file(file_path).unwrap();
+    }
+}
@@ -0,0 +1,186 @@
+use std::sync::OnceLock;
+use crate::cleaning::Position;
+use crate::metric::{Metric, METRICS};
+
+const BUILT_IN: [Position; 3] = [Position::Center, Position::Wing, Position::Defense];
+
+// A position group declared in the config's `[positions]` table, e.g. a generic forward or a
+// user-defined role, scored on its own list of metrics with one weight for each.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionDef {
+    pub id: String,
+    pub label: String,
+    pub aliases: Vec<String>,
+    pub metrics: Vec<Metric>,
+    pub names: Vec<&'static str>,
+    pub weights: Vec<f64>,
+    pub slots: usize,
+    pub file_name: String,
+    pub usage_stat: String,
+}
+
+impl PositionDef {
+    pub fn new(id: &str, label: &str, aliases: &[String], metrics: Vec<Metric>, weights: Vec<f64>, slots: usize) -> Result<Self, String> {
+        if metrics.is_empty() {
+            return Err("needs at least one metric".to_string());
+        }
+        if weights.len() != metrics.len() {
+            return Err(format!("has {} weights for {} metrics", weights.len(), metrics.len()));
+        }
+        Ok(PositionDef {
+            id: id.to_string(),
+            label: label.to_string(),
+            aliases: aliases.iter().map(|alias| alias.to_lowercase()).collect(),
+            names: metrics.iter().map(|metric| metric.name()).collect(),
+            metrics,
+            weights,
+            slots,
+            file_name: format!("{}.csv", label.to_lowercase().replace(' ', "_")),
+            usage_stat: format!("Usage {}", id),
+        })
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        let name = name.trim().to_lowercase();
+        name == self.id.to_lowercase() || name == self.label.to_lowercase() || self.aliases.contains(&name)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Registry {
+    defs: Vec<PositionDef>,
+    positions: Vec<Position>,
+}
+
+impl Registry {
+    pub fn new(defs: Vec<PositionDef>) -> Self {
+        let positions = BUILT_IN.into_iter().chain((0..defs.len()).map(|i| Position::Custom(i as u16))).collect();
+        Registry { defs, positions }
+    }
+
+    pub fn find(&self, name: &str) -> Option<Position> {
+        self.defs.iter().position(|def| def.matches(name)).map(|i| Position::Custom(i as u16))
+    }
+}
+
+// Where a set of `[positions]` is registered. It's set once, before any input is parsed, since the
+// ids it hands out are only meaningful for that set.
+#[derive(Debug, Default)]
+pub struct Positions {
+    registry: OnceLock<Registry>,
+}
+
+impl Positions {
+    pub const fn new() -> Self {
+        Positions { registry: OnceLock::new() }
+    }
+
+    // Positions are numbered in the order given. Registering the same definitions again is a no-op;
+    // different ones are an error, since players already parsed would point at the wrong positions.
+    pub fn register(&self, defs: Vec<PositionDef>) -> Result<(), String> {
+        let registry = Registry::new(defs);
+        if let Err(registry) = self.registry.set(registry) {
+            if self.registry.get() != Some(&registry) {
+                return Err("positions: a different [positions] table is already registered".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    // The built-in positions followed by the registered ones, in the order leaderboards list them.
+    pub fn all(&self) -> &[Position] {
+        self.registry.get().map_or(&BUILT_IN, |registry| &registry.positions)
+    }
+
+    // A registered position by its id, label or one of its aliases, ignoring case.
+    pub fn find(&self, name: &str) -> Option<Position> {
+        self.registry.get()?.find(name)
+    }
+
+    pub fn registered(&self) -> &[PositionDef] {
+        self.registry.get().map_or(&[], |registry| &registry.defs)
+    }
+
+    // Ids only come from the registry, so one it doesn't know is a bug in whatever made it up. It's
+    // reported, and the position is left without metrics or weights rather than scored as another.
+    pub fn definition(&self, id: u16) -> Option<&PositionDef> {
+        let def = self.registered().get(id as usize);
+        if def.is_none() {
+            log::warn!("Position id {} isn't registered, so it has no metrics to score", id);
+        }
+        def
+    }
+}
+
+// The config's positions, made parseable and scored everywhere the built-in ones are.
+static REGISTERED: Positions = Positions::new();
+
+pub fn register(defs: Vec<PositionDef>) -> Result<(), String> {
+    REGISTERED.register(defs)
+}
+
+pub fn all() -> &'static [Position] {
+    REGISTERED.all()
+}
+
+pub fn find(name: &str) -> Option<Position> {
+    REGISTERED.find(name)
+}
+
+pub fn registered() -> &'static [PositionDef] {
+    REGISTERED.registered()
+}
+
+pub fn definition(id: u16) -> Option<&'static PositionDef> {
+    REGISTERED.definition(id)
+}
+
+// Every metric some position is scored on, in column order.
+pub fn scored_metrics() -> Vec<Metric> {
+    METRICS.into_iter().filter(|metric| all().iter().any(|position| Metric::for_position(position).contains(metric))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forward() -> PositionDef {
+        PositionDef::new("F", "Generic Forward", &["FWD".to_string()], Metric::for_position(&Position::Wing).to_vec(), vec![0.2; 5], 12).unwrap()
+    }
+
+    #[test]
+    fn test_registry_finds_positions_by_id_label_or_alias() {
+        let forward = forward();
+        assert_eq!((forward.file_name.as_str(), forward.usage_stat.as_str()), ("generic_forward.csv", "Usage F"));
+
+        let registry = Registry::new(vec![forward]);
+        assert_eq!(registry.positions.len(), 4);
+        for name in ["F", "generic forward", " fwd"] {
+            assert_eq!(registry.find(name), Some(Position::Custom(0)));
+        }
+        assert_eq!(registry.find("G"), None);
+        assert_eq!(all()[..3], BUILT_IN);
+
+        // An unregistered id is named as such and scored on nothing, not as a wing.
+        assert_eq!(definition(7), None);
+        assert_eq!((Position::Custom(7).abbreviation(), Position::Custom(7).to_string()), ("?", "Unregistered position".to_string()));
+        assert!(Metric::for_position(&Position::Custom(7)).is_empty());
+        assert_eq!(scored_metrics().len(), 10);
+    }
+
+    #[test]
+    fn test_positions_take_any_number_of_metrics_and_register_once() {
+        let goalie = PositionDef::new("G", "Goalie", &[], vec![Metric::ShotsBlocked, Metric::Giveaways, Metric::TimeOnIce], vec![0.5, 0.25, 0.25], 2).unwrap();
+        assert_eq!(goalie.names, ["Shots Blocked", "Giveaways", "TOI"]);
+        assert!(PositionDef::new("G", "Goalie", &[], vec![Metric::Hits, Metric::Goals], vec![1.0], 2).is_err());
+        assert!(PositionDef::new("G", "Goalie", &[], Vec::new(), Vec::new(), 2).is_err());
+
+        let positions = Positions::new();
+        positions.register(vec![forward(), goalie.clone()]).unwrap();
+        positions.register(vec![forward(), goalie.clone()]).unwrap();
+        assert!(positions.register(vec![goalie]).is_err());
+        assert_eq!(positions.find("g"), Some(Position::Custom(1)));
+        assert_eq!(positions.all().len(), 5);
+        assert_eq!(positions.definition(1).unwrap().weights, [0.5, 0.25, 0.25]);
+    }
+}
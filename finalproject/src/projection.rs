@@ -0,0 +1,131 @@
+use std::sync::Arc;
+use serde::Serialize;
+use crate::age::{age_from_stats, AgeCurve};
+use crate::cleaning::{Player, PlayerMap, Position};
+use crate::model::{MetricMap, ScoringModel};
+use crate::scoring::{metric_names};
+use crate::stats::summarize;
+use crate::positions;
+
+// Games of league-average play blended into every player's line, so small samples regress the most.
+pub const REGRESSION_GAMES: f64 = 40.0;
+// The prior season counts for 2/3 as much as the current one when both are given.
+const PREVIOUS_SEASON_WEIGHT: f64 = 2.0 / 3.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Projection {
+    pub name: Arc<str>,
+    pub position: Position,
+    pub age: Option<f64>,
+    pub current: f64,
+    pub projected: f64,
+}
+
+fn games(player: &Player) -> f64 {
+    player.stats.get("GP").copied().unwrap_or(0.0)
+}
+
+// Pulls a value seen over `games` toward the league's `mean`, as if `REGRESSION_GAMES` of average play were added.
+pub fn regress(value: f64, games: f64, mean: f64) -> f64 {
+    (value * games + mean * REGRESSION_GAMES) / (games + REGRESSION_GAMES)
+}
+
+// How much a player `years` older is expected to keep of what they do at `age` along `curve`. Players
+// without an age, or at an age the curve zeroes out, are left unchanged.
+pub fn aging(curve: &AgeCurve, age: Option<f64>, years: f64) -> f64 {
+    match age {
+        Some(age) if curve.multiplier(age) > 0.0 => curve.multiplier(age + years) / curve.multiplier(age),
+        _ => 1.0,
+    }
+}
+
+// Normalized metrics with the previous season blended in, and the games they represent.
+fn combined_metrics(player: &Player, position: &Position, previous: Option<&PlayerMap>) -> Option<(Vec<f64>, f64)> {
+    let current = player.metrics.get(position)?;
+    let current_games = games(player);
+    let prior = previous.and_then(|players| players.get(&player.name)).and_then(|p| Some((p.metrics.get(position)?, games(p))));
+
+    match prior {
+        Some((metrics, prior_games)) if current_games + prior_games > 0.0 => {
+            let prior_games = prior_games * PREVIOUS_SEASON_WEIGHT;
+            let total = current_games + prior_games;
+            let blended = current.iter().zip(metrics).map(|(c, p)| (c * current_games + p * prior_games) / total).collect();
+            Some((blended, total))
+        }
+        _ => Some((current.clone(), current_games)),
+    }
+}
+
+// Regresses each player's metrics toward the position mean by `REGRESSION_GAMES`, scores them with `model`,
+// and ages the score one year along `curve`. Sorted best projection first within each position.
+pub fn project(
+    players: &PlayerMap,
+    model: &dyn ScoringModel,
+    curve: &AgeCurve,
+    reference_day: i64,
+    previous: Option<&PlayerMap>,
+) -> Vec<Projection> {
+    let mut projections = Vec::new();
+
+    for position in positions::all() {
+        let peers: Vec<&Vec<f64>> = players.values().filter_map(|p| p.metrics.get(position)).collect();
+        let means: Vec<f64> = (0..metric_names(position).len())
+            .map(|i| summarize(&peers.iter().filter_map(|m| m.get(i).copied()).collect::<Vec<f64>>()).mean)
+            .collect();
+        let mut position_projections = Vec::new();
+
+        for player in players.values() {
+            let Some(metric_map) = MetricMap::for_player(player, position) else { continue };
+            let Some((metrics, games)) = combined_metrics(player, position, previous) else { continue };
+            let regressed: Vec<f64> = metrics.iter().zip(&means).map(|(&m, &mean)| regress(m, games, mean)).collect();
+
+            let age = age_from_stats(&player.stats, reference_day);
+            let projected = model.score(position, &MetricMap { normalized: &regressed, ..metric_map }) * aging(curve, age, 1.0);
+
+            position_projections.push(Projection {
+                name: player.name.clone(),
+                position: position.clone(),
+                age,
+                current: model.score(position, &metric_map),
+                projected,
+            });
+        }
+
+        position_projections.sort_by(|a, b| b.projected.partial_cmp(&a.projected).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.name.cmp(&b.name)));
+        projections.extend(position_projections);
+    }
+
+    projections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::model::WeightedSigmoid;
+
+    fn wing(name: &str, value: f64, gp: f64, age: f64) -> (Arc<str>, Player) {
+        let mut player = Player::new(name, vec![Position::Wing], HashMap::from([(Position::Wing, vec![value; 5])]));
+        player.stats.insert("GP".to_string(), gp);
+        player.stats.insert("Age".to_string(), age);
+        (player.name.clone(), player)
+    }
+
+    #[test]
+    fn test_projection_regresses_small_samples_and_ages() {
+        let players = PlayerMap::from_iter([
+            wing("Veteran", 0.9, 82.0, 25.0),
+            wing("Call-up", 0.9, 5.0, 25.0),
+            wing("Average", 0.5, 82.0, 25.0),
+            wing("Old Timer", 0.9, 82.0, 34.0),
+        ]);
+        let projections = project(&players, &WeightedSigmoid::default(), &AgeCurve::default(), 0, None);
+        let find = |name: &str| projections.iter().find(|p| &*p.name == name).unwrap();
+
+        assert_eq!(&*projections[0].name, "Veteran");
+        assert!(find("Veteran").projected < find("Veteran").current);
+        assert!(find("Call-up").projected < find("Veteran").projected);
+        assert!(find("Old Timer").projected < find("Veteran").projected);
+        assert!(find("Average").projected > find("Average").current);
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt;
+use serde::Serialize;
+use crate::cleaning::{Player, PlayerMap};
+
+// The luck-driven percentages tracked against a player's own history, the stat their baseline is
+// stored under, and how many percentage points off it they have to be before they're flagged.
+pub const BASELINE_STATS: [(&str, &str, f64); 2] = [("SH%", "Baseline SH%", 4.0), ("IPP", "Baseline IPP", 12.0)];
+// Earlier seasons with fewer games than this in total are too noisy to call a baseline.
+pub const MIN_BASELINE_GAMES: f64 = 40.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Direction {
+    SellHigh,
+    BuyLow,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Direction::SellHigh => write!(f, "sell high"),
+            Direction::BuyLow => write!(f, "buy low"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RegressionFlag {
+    pub stat: &'static str,
+    pub current: f64,
+    pub baseline: f64,
+    pub direction: Direction,
+}
+
+impl fmt::Display for RegressionFlag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {} {:.1} vs {:.1} baseline", self.direction, self.stat, self.current, self.baseline)
+    }
+}
+
+// Stores each player's games-weighted SH% and IPP over the earlier seasons as baseline stats, so
+// rankings and lookups can flag them later. Returns how many players got a baseline.
+pub fn join_baselines(players: &mut PlayerMap, seasons: &[PlayerMap]) -> usize {
+    let mut matched = 0;
+    for player in players.values_mut() {
+        let mut joined = false;
+        for (stat, baseline_stat, _) in BASELINE_STATS {
+            let (mut total, mut games) = (0.0, 0.0);
+            for earlier in seasons.iter().filter_map(|season| season.get(&player.name)) {
+                let (Some(&value), Some(&gp)) = (earlier.stats.get(stat), earlier.stats.get("GP")) else { continue };
+                total += value * gp;
+                games += gp;
+            }
+            if games >= MIN_BASELINE_GAMES {
+                player.stats.insert(baseline_stat.to_string(), total / games);
+                joined = true;
+            }
+        }
+        matched += joined as usize;
+    }
+    matched
+}
+
+// The percentages this season that sit far enough from the player's baseline to expect them to regress.
+pub fn regression_flags(player: &Player) -> Vec<RegressionFlag> {
+    BASELINE_STATS
+        .iter()
+        .filter_map(|&(stat, baseline_stat, threshold)| {
+            let (current, baseline) = (*player.stats.get(stat)?, *player.stats.get(baseline_stat)?);
+            let direction = if current - baseline >= threshold {
+                Direction::SellHigh
+            } else if baseline - current >= threshold {
+                Direction::BuyLow
+            } else {
+                return None;
+            };
+            Some(RegressionFlag { stat, current, baseline, direction })
+        })
+        .collect()
+}
+
+// Every flag across the players, furthest from the baseline first.
+pub fn regression_candidates(players: &PlayerMap) -> Vec<(&Player, RegressionFlag)> {
+    let mut candidates: Vec<(&Player, RegressionFlag)> =
+        players.values().flat_map(|player| regression_flags(player).into_iter().map(move |flag| (player, flag))).collect();
+    candidates.sort_by(|a, b| {
+        let (a_gap, b_gap) = ((a.1.current - a.1.baseline).abs(), (b.1.current - b.1.baseline).abs());
+        b_gap.partial_cmp(&a_gap).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.0.name.cmp(&b.0.name))
+    });
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::HashMap, sync::Arc};
+    use crate::cleaning::Position;
+
+    fn season(rows: &[(&str, f64, f64, f64)]) -> PlayerMap {
+        rows.iter()
+            .map(|&(name, gp, sh, ipp)| {
+                let mut player = Player::new(name, vec![Position::Wing], HashMap::new());
+                player.stats = [("GP", gp), ("SH%", sh), ("IPP", ipp)].into();
+                (Arc::from(name), player)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_flags_players_far_from_their_baseline() {
+        let mut current = season(&[("Hot Shooter", 82.0, 19.0, 70.0), ("Cold Playmaker", 82.0, 10.0, 50.0), ("Steady", 82.0, 11.0, 68.0), ("Rookie", 82.0, 25.0, 90.0)]);
+        let earlier = [
+            season(&[("Hot Shooter", 60.0, 10.0, 68.0), ("Cold Playmaker", 82.0, 11.0, 72.0), ("Steady", 82.0, 12.0, 70.0)]),
+            season(&[("Hot Shooter", 20.0, 14.0, 68.0), ("Cold Playmaker", 10.0, 9.0, 68.0)]),
+        ];
+        assert_eq!(join_baselines(&mut current, &earlier), 3);
+        assert_eq!(current["Hot Shooter"].stats["Baseline SH%"], 11.0);
+        assert!(!current["Rookie"].stats.contains_key("Baseline SH%"));
+
+        let flags = regression_flags(&current["Hot Shooter"]);
+        assert_eq!(flags.len(), 1);
+        assert_eq!((flags[0].stat, flags[0].direction), ("SH%", Direction::SellHigh));
+        assert!(regression_flags(&current["Steady"]).is_empty());
+
+        let candidates = regression_candidates(&current);
+        assert_eq!(candidates.iter().map(|(player, flag)| (&*player.name, flag.direction)).collect::<Vec<_>>(), [
+            ("Cold Playmaker", Direction::BuyLow),
+            ("Hot Shooter", Direction::SellHigh)
+        ]);
+        assert_eq!(candidates[1].1.to_string(), "sell high: SH% 19.0 vs 11.0 baseline");
+    }
+}
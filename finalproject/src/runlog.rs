@@ -0,0 +1,73 @@
+use std::{cell::RefCell, fs::{File, OpenOptions}, io::{self, Write}, time::{Instant, SystemTime, UNIX_EPOCH}};
+use serde_json::{json, Map, Value};
+
+// A JSON-lines record of a run for pipelines that track data quality over time: one object per event,
+// stamped with the wall-clock time and the milliseconds since the run started. Lines are appended so
+// one file can hold many runs. A disabled log drops every event.
+pub struct RunLog {
+    file: RefCell<Option<File>>,
+    started: Instant,
+}
+
+impl Default for RunLog {
+    fn default() -> Self {
+        RunLog { file: RefCell::new(None), started: Instant::now() }
+    }
+}
+
+impl RunLog {
+    pub fn create(path: &str) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(RunLog { file: RefCell::new(Some(file)), ..RunLog::default() })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.file.borrow().is_some()
+    }
+
+    // Writes `fields` (an object) under the event's name. A failed write disables the log rather than
+    // failing the run.
+    pub fn event(&self, name: &str, fields: Value) {
+        let mut file = self.file.borrow_mut();
+        let Some(out) = file.as_mut() else { return };
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |since| since.as_secs_f64());
+        let mut event = Map::new();
+        event.insert("event".to_string(), json!(name));
+        event.insert("timestamp".to_string(), json!(timestamp));
+        event.insert("elapsed_ms".to_string(), json!(self.started.elapsed().as_secs_f64() * 1000.0));
+        if let Value::Object(fields) = fields {
+            event.extend(fields);
+        }
+
+        if let Err(e) = writeln!(out, "{}", Value::Object(event)) {
+            log::warn!("Could not write run log: {}", e);
+            *file = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_are_appended_as_json_lines() {
+        let path = std::env::temp_dir().join("finalproject_run_log_test.jsonl");
+        let _ = std::fs::remove_file(&path);
+        let path = path.to_str().unwrap();
+
+        for run in 0..2 {
+            let log = RunLog::create(path).unwrap();
+            assert!(log.is_enabled());
+            log.event("rows", json!({ "run": run, "skipped": 3 }));
+        }
+        RunLog::default().event("dropped", json!({}));
+
+        let events: Vec<Value> = std::fs::read_to_string(path).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!((&events[1]["event"], &events[1]["run"], &events[1]["skipped"]), (&json!("rows"), &json!(1), &json!(3)));
+        assert!(events[0]["timestamp"].as_f64().unwrap() > 0.0 && events[0]["elapsed_ms"].as_f64().unwrap() >= 0.0);
+        std::fs::remove_file(path).unwrap();
+    }
+}
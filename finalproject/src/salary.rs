@@ -0,0 +1,138 @@
+use std::{collections::HashMap, fs, io, sync::Arc};
+use serde::Serialize;
+use crate::cleaning::{PlayerMap, Position};
+use crate::model::ScoringModel;
+use crate::scoring::rank_players_with;
+use crate::stats::summarize;
+
+pub const CAP_HIT_STAT: &str = "Cap Hit";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValueLine {
+    pub name: Arc<str>,
+    pub position: Position,
+    pub score: f64,
+    pub cap_hit: f64,
+    pub score_per_million: f64,
+}
+
+// Accepts plain dollars ("950000"), formatted dollars ("$10,500,000") and millions ("10.5M").
+pub fn parse_cap_hit(value: &str) -> Option<f64> {
+    let cleaned: String = value.trim().trim_matches('"').chars().filter(|c| !matches!(c, '$' | ',' | ' ')).collect();
+    let (number, scale) = match cleaned.chars().last()? {
+        'm' | 'M' => (&cleaned[..cleaned.len() - 1], 1_000_000.0),
+        'k' | 'K' => (&cleaned[..cleaned.len() - 1], 1_000.0),
+        _ => (cleaned.as_str(), 1.0),
+    };
+    number.parse::<f64>().ok().map(|n| n * scale).filter(|n| n.is_finite() && *n > 0.0)
+}
+
+// `name,cap_hit` lines, where either field may be quoted; a header row is skipped.
+pub fn parse_cap_hits(text: &str) -> Result<HashMap<String, f64>, String> {
+    let mut cap_hits = HashMap::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let split = match line.strip_prefix('"') {
+            Some(rest) => rest.split_once('"').and_then(|(name, rest)| Some((name, rest.trim_start().strip_prefix(',')?))),
+            None => line.split_once(','),
+        };
+        let (name, cap_hit) = split.ok_or_else(|| format!("line {}: expected `name,cap_hit`", i + 1))?;
+
+        match parse_cap_hit(cap_hit) {
+            Some(value) => {
+                cap_hits.insert(name.trim().to_lowercase(), value);
+            }
+            None if i == 0 => continue,
+            None => return Err(format!("line {}: invalid cap hit '{}'", i + 1, cap_hit.trim())),
+        }
+    }
+
+    Ok(cap_hits)
+}
+
+pub fn read_cap_hits(path: &str) -> io::Result<HashMap<String, f64>> {
+    parse_cap_hits(&fs::read_to_string(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, e)))
+}
+
+pub fn join_cap_hits(players: &mut PlayerMap, cap_hits: &HashMap<String, f64>) -> usize {
+    let mut matched = 0;
+    for player in players.values_mut() {
+        if let Some(&cap_hit) = cap_hits.get(&player.name.to_lowercase()) {
+            player.stats.insert(CAP_HIT_STAT.to_string(), cap_hit);
+            matched += 1;
+        }
+    }
+    matched
+}
+
+pub fn value_leaderboard(players: &PlayerMap, model: &dyn ScoringModel) -> HashMap<Position, Vec<ValueLine>> {
+    rank_players_with(players, model)
+        .into_iter()
+        .map(|(position, ranked)| {
+            let mut lines: Vec<ValueLine> = ranked
+                .into_iter()
+                .filter_map(|scored| {
+                    let cap_hit = *players.get(&scored.name)?.stats.get(CAP_HIT_STAT)?;
+                    Some(ValueLine {
+                        score_per_million: scored.score / (cap_hit / 1_000_000.0),
+                        name: scored.name,
+                        position: scored.position,
+                        score: scored.score,
+                        cap_hit,
+                    })
+                })
+                .collect();
+            lines.sort_by(|a, b| b.score_per_million.partial_cmp(&a.score_per_million).unwrap_or(std::cmp::Ordering::Equal));
+            (position, lines)
+        })
+        .collect()
+}
+
+pub fn median_value(leaderboard: &HashMap<Position, Vec<ValueLine>>) -> HashMap<Position, f64> {
+    leaderboard
+        .iter()
+        .filter(|(_, lines)| !lines.is_empty())
+        .map(|(position, lines)| {
+            let values: Vec<f64> = lines.iter().map(|line| line.score_per_million).collect();
+            (position.clone(), summarize(&values).median)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cleaning::Player;
+    use crate::model::WeightedSigmoid;
+
+    #[test]
+    fn test_parse_cap_hits() {
+        let cap_hits = parse_cap_hits("Player,Cap Hit\n\"Cale Makar\",\"$9,000,000\"\nAdam Fox,9.5M\nLuke Hughes,950000\n").unwrap();
+        assert_eq!(cap_hits["cale makar"], 9_000_000.0);
+        assert_eq!(cap_hits["adam fox"], 9_500_000.0);
+        assert_eq!(cap_hits["luke hughes"], 950_000.0);
+        assert!(parse_cap_hits("Cale Makar,9M\nAdam Fox,expensive").is_err());
+    }
+
+    #[test]
+    fn test_value_leaderboard_ranks_by_score_per_million() {
+        let player = |name: &str| {
+            (name.into(), Player::new(name, vec![Position::Defense], HashMap::from([(Position::Defense, vec![1.0; 5])])))
+        };
+        let mut players = PlayerMap::from_iter([player("Star"), player("Bargain"), player("Unsigned")]);
+        let cap_hits = HashMap::from([("star".to_string(), 10_000_000.0), ("bargain".to_string(), 1_000_000.0)]);
+        assert_eq!(join_cap_hits(&mut players, &cap_hits), 2);
+
+        let leaderboard = value_leaderboard(&players, &WeightedSigmoid::default());
+        let defense = &leaderboard[&Position::Defense];
+        assert_eq!(defense.len(), 2);
+        assert_eq!(&*defense[0].name, "Bargain");
+        assert!((defense[0].score_per_million - 10.0 * defense[1].score_per_million).abs() < 1e-9);
+        assert!((median_value(&leaderboard)[&Position::Defense] - 5.5 * defense[1].score_per_million).abs() < 1e-9);
+    }
+}
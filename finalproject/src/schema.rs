@@ -0,0 +1,118 @@
+use crate::cleaning::{metric_key, resolve_stat, COLUMNS};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaReport {
+    pub missing: Vec<&'static str>,
+    pub extra: Vec<String>,
+    pub renamed: Vec<(String, &'static str)>,
+    pub reordered: bool,
+}
+
+impl SchemaReport {
+    // `columns` is the resolved layout (see `ColumnMapping::resolve`); `known` are header fields read as optional stats.
+    pub fn check(names: &[&str], columns: &[Option<usize>], known: &[usize]) -> SchemaReport {
+        let mut missing: Vec<(usize, &'static str)> =
+            COLUMNS.iter().enumerate().skip(1).filter(|&(i, _)| columns[i].is_none()).map(|(i, &name)| (i, name)).collect();
+        let mut extra: Vec<(usize, String)> = names
+            .iter()
+            .enumerate()
+            .filter(|&(j, name)| !metric_key(name).is_empty() && !known.contains(&j) && !columns.contains(&Some(j)))
+            .map(|(j, name)| (j, name.to_string()))
+            .collect();
+
+        // A leftover header is taken as a rename when it's a known abbreviation, sits where the column belongs, or is spelled nearly the same.
+        let mut renamed = Vec::new();
+        missing.retain(|&(i, expected)| {
+            let found = extra
+                .iter()
+                .position(|(_, name)| resolve_stat(name) == Some(expected))
+                .or_else(|| extra.iter().position(|&(j, _)| j == i))
+                .or_else(|| extra.iter().position(|(_, name)| edit_distance(&metric_key(name), &metric_key(expected)) <= 2));
+            match found {
+                Some(index) => {
+                    renamed.push((extra.remove(index).1, expected));
+                    false
+                }
+                None => true,
+            }
+        });
+
+        let reordered = columns.iter().enumerate().skip(1).any(|(i, column)| column.is_some_and(|j| j != i));
+        SchemaReport {
+            missing: missing.into_iter().map(|(_, name)| name).collect(),
+            extra: extra.into_iter().map(|(_, name)| name).collect(),
+            renamed,
+            reordered,
+        }
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.renamed.is_empty() && !self.reordered
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if !self.missing.is_empty() {
+            lines.push(format!("Missing columns: {}", self.missing.join(", ")));
+        }
+        for (source, expected) in &self.renamed {
+            lines.push(format!(
+                "Column '{}' looks like '{}'; add `\"{}\" = \"{}\"` to [columns.map] in the config to use it",
+                source, expected, source, expected
+            ));
+        }
+        if !self.extra.is_empty() {
+            lines.push(format!("Unrecognized columns (ignored): {}", self.extra.join(", ")));
+        }
+        if self.reordered {
+            lines.push("Columns are not in the expected order; reading them by header name".to_string());
+        }
+        lines
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::ColumnMapping;
+
+    #[test]
+    fn test_check_reports_missing_renamed_and_extra_columns() {
+        let mut names: Vec<&str> = COLUMNS.to_vec();
+        names[27] = "TkA";
+        names[28] = "Hitz";
+        names.retain(|name| *name != "Misconduct");
+        names.push("Team");
+
+        let columns = ColumnMapping::default().resolve(&names);
+        let report = SchemaReport::check(&names, &columns, &[]);
+        assert_eq!(report.missing, vec!["Misconduct"]);
+        assert_eq!(report.renamed, vec![("TkA".to_string(), "Takeaways"), ("Hitz".to_string(), "Hits")]);
+        assert_eq!(report.extra, vec!["Team"]);
+        assert!(report.reordered);
+        assert!(!report.is_clean());
+    }
+
+    #[test]
+    fn test_check_accepts_expected_header() {
+        let columns = ColumnMapping::default().resolve(&COLUMNS);
+        let report = SchemaReport::check(&COLUMNS, &columns, &[]);
+        assert!(report.is_clean());
+        assert!(report.lines().is_empty());
+        assert_eq!(edit_distance("hits", "hitz"), 1);
+    }
+}
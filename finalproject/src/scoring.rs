@@ -0,0 +1,566 @@
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+use serde::{Deserialize, Serialize};
+use crate::cleaning::{metric_key, Player, PlayerMap, Position};
+use crate::matrix::MetricMatrix;
+use crate::metric::{Metric, CENTER_NAMES, DEFENSE_NAMES, WING_NAMES};
+use crate::model::{MetricMap, ScoringModel, WeightedSigmoid};
+use crate::tiers::Grades;
+use crate::usage::usage_shares;
+use crate::positions;
+use crate::stats::{summarize, Summary};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoredPlayer {
+    pub name: Arc<str>,
+    pub position: Position,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricReport {
+    pub metric: &'static str,
+    pub normalized: f64,
+    pub percentile: f64,
+    pub contribution: Option<Contribution>,
+}
+
+// One metric's part of a score: weight × normalized value, as a percentage of the weighted sum and
+// as that share of the score's points.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Contribution {
+    pub weighted: f64,
+    pub points: f64,
+    pub share: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionReport {
+    pub position: Position,
+    pub score: f64,
+    pub grade: String,
+    pub metrics: Vec<MetricReport>,
+}
+
+pub fn metric_names(position: &Position) -> &'static [&'static str] {
+    match position {
+        Position::Center => &CENTER_NAMES,
+        Position::Wing => &WING_NAMES,
+        Position::Defense => &DEFENSE_NAMES,
+        Position::Custom(id) => positions::definition(*id).map_or(&[], |def| &def.names),
+    }
+}
+
+pub fn metric_index(position: &Position, metric: &str) -> Option<usize> {
+    let key = metric_key(metric);
+    metric_names(position).iter().position(|name| metric_key(name) == key)
+}
+
+pub fn position_weights(position: &Position) -> (&'static [f64], f64) {
+    match position {
+        Position::Center => (&[0.25, 0.3, 0.15, 0.2, 0.1], 5.0),
+        Position::Wing => (&[0.35, 0.25, 0.15, 0.2, 0.05], 5.0),
+        Position::Defense => (&[0.15, 0.3, 0.2, 0.2, 0.15], 5.0),
+        Position::Custom(id) => (positions::definition(*id).map_or(&[], |def| &def.weights), 5.0),
+    }
+}
+
+// Per-position weights that replace the built-in ones, e.g. from the config or a PCA fit.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WeightTable {
+    pub overrides: HashMap<Position, Vec<f64>>,
+}
+
+impl WeightTable {
+    pub fn get(&self, position: &Position) -> &[f64] {
+        match self.overrides.get(position) {
+            Some(weights) => weights,
+            None => position_weights(position).0,
+        }
+    }
+
+    // Gives one metric a new weight and rescales the position's other weights to fill the rest, so
+    // they still sum to 1 and keep their proportions to each other.
+    pub fn set_weight(&mut self, position: &Position, metric: &str, weight: f64) -> Result<(), String> {
+        let Some(index) = metric_index(position, metric) else {
+            return Err(format!("{} is not scored on '{}'; its metrics are {}", position, metric.trim(), metric_names(position).join(", ")));
+        };
+        if !(weight.is_finite() && (0.0..=1.0).contains(&weight)) {
+            return Err(format!("invalid weight {}, expected a value from 0 to 1", weight));
+        }
+
+        let mut weights = self.get(position).to_vec();
+        let others: f64 = weights.iter().enumerate().filter(|&(i, _)| i != index).map(|(_, w)| w).sum();
+        if others <= 0.0 && weight < 1.0 {
+            return Err(format!("every other {} weight is 0, so {} has to stay at 1", position.to_string().to_lowercase(), metric_names(position)[index]));
+        }
+        for (i, value) in weights.iter_mut().enumerate() {
+            *value = if i == index { weight } else if others > 0.0 { *value * (1.0 - weight) / others } else { 0.0 };
+        }
+        self.overrides.insert(position.clone(), weights);
+        Ok(())
+    }
+}
+
+pub fn calculate_score(position: &Position, metrics: &[f64]) -> f64 {
+    let (weights, scaling_factor) = position_weights(position);
+    debug_assert_eq!(weights.len(), Metric::for_position(position).len());
+    score_with_weights(weights, scaling_factor, metrics)
+}
+
+pub fn score_with_weights(weights: &[f64], scaling_factor: f64, metrics: &[f64]) -> f64 {
+    score_with_sigmoid(weights, Sigmoid { scale: scaling_factor, midpoint: 0.0 }, metrics)
+}
+
+pub fn score_with_sigmoid(weights: &[f64], sigmoid: Sigmoid, metrics: &[f64]) -> f64 {
+    weighted_sum(weights, metrics).map_or(0.0, |sum| sigmoid.apply(sum))
+}
+
+pub fn weighted_sum(weights: &[f64], metrics: &[f64]) -> Option<f64> {
+    if metrics.len() != weights.len() || metrics.iter().any(|m| !m.is_finite()) {
+        log::warn!("Invalid metrics for scoring: {:?}", metrics);
+        return None;
+    }
+
+    Some(metrics
+        .iter()
+        .zip(weights.iter())
+        .map(|(metric, weight)| metric * weight)
+        .sum())
+}
+
+// `weighted_sum` of every row of a position's matrix, worked out a column at a time.
+pub fn weighted_sums(weights: &[f64], matrix: &MetricMatrix) -> Vec<Option<f64>> {
+    let mut sums = vec![0.0; matrix.rows()];
+    for (i, &weight) in weights.iter().enumerate() {
+        for (sum, &metric) in sums.iter_mut().zip(matrix.column(i)) {
+            *sum += metric * weight;
+        }
+    }
+    let mut row = Vec::new();
+    sums.into_iter()
+        .enumerate()
+        .map(|(i, sum)| {
+            if matrix.width() == weights.len() && sum.is_finite() {
+                return Some(sum);
+            }
+            matrix.read_row(i, &mut row);
+            weighted_sum(weights, &row)
+        })
+        .collect()
+}
+
+// How the weighted sum of normalized metrics becomes a 0-100 score.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Transform {
+    Linear,
+    #[default]
+    Logistic,
+    Tanh,
+    Percentile,
+}
+
+const TANH_STEEPNESS: f64 = 2.0;
+
+impl Transform {
+    // `share` is the weighted sum over the total weight (0-1); `percentile` is only used by `Percentile`.
+    pub fn apply(&self, weighted_sum: f64, share: f64, sigmoid: Sigmoid, percentile: f64) -> f64 {
+        match self {
+            Transform::Linear => (100.0 * share).clamp(0.0, 100.0),
+            Transform::Logistic => sigmoid.apply(weighted_sum),
+            Transform::Tanh => (100.0 * (TANH_STEEPNESS * share).tanh()).clamp(0.0, 100.0),
+            Transform::Percentile => percentile,
+        }
+    }
+}
+
+impl FromStr for Transform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "linear" => Ok(Transform::Linear),
+            "logistic" | "sigmoid" => Ok(Transform::Logistic),
+            "tanh" => Ok(Transform::Tanh),
+            "percentile" => Ok(Transform::Percentile),
+            _ => Err(format!("invalid transform '{}', expected linear, logistic, tanh or percentile", s)),
+        }
+    }
+}
+
+// The weighted sum that maps to a score of 50 is `midpoint`; a larger `scale` spreads players further toward 0 and 100.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Sigmoid {
+    pub scale: f64,
+    pub midpoint: f64,
+}
+
+impl Default for Sigmoid {
+    fn default() -> Self {
+        Sigmoid { scale: 5.0, midpoint: 0.0 }
+    }
+}
+
+impl Sigmoid {
+    pub fn for_position(position: &Position) -> Sigmoid {
+        Sigmoid { scale: position_weights(position).1, midpoint: 0.0 }
+    }
+
+    pub fn apply(&self, weighted_sum: f64) -> f64 {
+        let scaled_score = self.scale * (weighted_sum - self.midpoint);
+        (100.0 / (1.0 + (-scaled_score).exp())).clamp(0.0, 100.0)
+    }
+}
+
+pub fn rank_players(players: &PlayerMap) -> HashMap<Position, Vec<ScoredPlayer>> {
+    rank_players_with(players, &WeightedSigmoid::default())
+}
+
+// Scores each position from its matrix, leaving out players who have metrics at a position they no
+// longer play. The scores are sorted before the names are looked up, so the sort moves small pairs.
+pub fn rank_players_with(players: &PlayerMap, model: &dyn ScoringModel) -> HashMap<Position, Vec<ScoredPlayer>> {
+    let mut position_groups: HashMap<Position, Vec<ScoredPlayer>> = HashMap::new();
+
+    for (position, matrix) in players.matrices() {
+        let scores = model.score_position(position, players).into_iter().zip(&matrix.players).zip(&matrix.plays);
+        let mut scores: Vec<(usize, f64)> = scores.filter(|&(_, &plays)| plays).map(|((score, &index), _)| (index, score)).collect();
+        if scores.is_empty() {
+            continue;
+        }
+        scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        let players_in_position = scores
+            .into_iter()
+            .filter_map(|(index, score)| Some(ScoredPlayer { name: players.get_index(index)?.0.clone(), position: position.clone(), score }))
+            .collect();
+        position_groups.insert(position.clone(), players_in_position);
+    }
+
+    position_groups
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OverallPlayer {
+    pub name: Arc<str>,
+    pub positions: Vec<Position>,
+    pub score: f64,
+    pub bonus: f64,
+}
+
+// Players eligible at several positions get the mean of their position scores, so the
+// overall rating stays on the same scale as a single-position one. With usage joined, the mean is
+// weighted by each position's share of it, so a handful of games at wing barely counts.
+pub fn overall_score(player: &Player, model: &dyn ScoringModel) -> Option<f64> {
+    position_mean(player, |position| Some(model.score(position, &MetricMap::for_player(player, position)?)))
+}
+
+fn position_mean(player: &Player, value: impl Fn(&Position) -> Option<f64>) -> Option<f64> {
+    let shares = usage_shares(player);
+    let scores: Vec<(f64, f64)> = player
+        .positions
+        .iter()
+        .filter_map(|position| {
+            let score = value(position)?;
+            let weight = shares.as_ref().map_or(1.0, |shares| shares.get(position).copied().unwrap_or(0.0));
+            Some((score, weight))
+        })
+        .collect();
+
+    let total_weight: f64 = scores.iter().map(|(_, weight)| weight).sum();
+    if scores.is_empty() {
+        None
+    } else if total_weight > 0.0 {
+        Some(scores.iter().map(|(score, weight)| score * weight).sum::<f64>() / total_weight)
+    } else {
+        Some(scores.iter().map(|(score, _)| score).sum::<f64>() / scores.len() as f64)
+    }
+}
+
+// A flat bonus for players who can be dressed at more than one position.
+pub fn versatility_bonus(player: &Player, bonus: f64) -> f64 {
+    if player.positions.len() > 1 { bonus } else { 0.0 }
+}
+
+pub fn overall_rankings(players: &PlayerMap, model: &dyn ScoringModel, bonus: f64) -> Vec<OverallPlayer> {
+    let mut ranked: Vec<OverallPlayer> = players
+        .values()
+        .filter_map(|player| {
+            let bonus = versatility_bonus(player, bonus);
+            let score = overall_score(player, model)? + bonus;
+            Some(OverallPlayer { name: player.name.clone(), positions: player.positions.clone(), score, bonus })
+        })
+        .collect();
+
+    sort_overall(&mut ranked);
+    ranked
+}
+
+// Ranks players across positions by how many standard deviations they score above their position's
+// mean, rather than by raw score, so a position whose scores bunch up isn't outranked by one whose
+// sigmoid spreads them out. Multi-position players combine their z-scores like `overall_score`.
+// There's no versatility bonus, since it's in score points.
+pub fn scarcity_rankings(players: &PlayerMap, model: &dyn ScoringModel) -> Vec<OverallPlayer> {
+    let summaries: HashMap<Position, Summary> = rank_players_with(players, model)
+        .into_iter()
+        .map(|(position, scored)| (position, summarize(&scored.iter().map(|scored| scored.score).collect::<Vec<_>>())))
+        .collect();
+
+    let mut ranked: Vec<OverallPlayer> = players
+        .values()
+        .filter_map(|player| {
+            let score = position_mean(player, |position| {
+                let summary = summaries.get(position).filter(|summary| summary.stddev > 0.0)?;
+                Some((model.score(position, &MetricMap::for_player(player, position)?) - summary.mean) / summary.stddev)
+            })?;
+            Some(OverallPlayer { name: player.name.clone(), positions: player.positions.clone(), score, bonus: 0.0 })
+        })
+        .collect();
+
+    sort_overall(&mut ranked);
+    ranked
+}
+
+fn sort_overall(ranked: &mut [OverallPlayer]) {
+    ranked.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.name.cmp(&b.name)));
+}
+
+pub fn rank_by_metric(players: &PlayerMap, position: &Position, index: usize, raw: bool) -> Vec<(Arc<str>, f64)> {
+    let mut ranked: Vec<(Arc<str>, f64)> = players
+        .values()
+        .filter_map(|player| {
+            let metrics = if raw { player.raw_metrics.get(position) } else { player.metrics.get(position) };
+            Some((player.name.clone(), *metrics?.get(index)?))
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    ranked
+}
+
+pub fn stat_leaders<'a>(players: &'a PlayerMap, stat: &str) -> Vec<(&'a Player, f64)> {
+    let mut leaders: Vec<(&Player, f64)> = players
+        .values()
+        .filter_map(|player| Some((player, *player.stats.get(stat)?)))
+        .collect();
+
+    leaders.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.0.name.cmp(&b.0.name)));
+    leaders
+}
+
+pub fn metric_percentiles(players: &PlayerMap, position: &Position, metrics: &[f64]) -> Vec<f64> {
+    let peers: Vec<&Vec<f64>> = players.values().filter_map(|p| p.metrics.get(position)).collect();
+
+    metrics
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| {
+            let at_or_below = peers.iter().filter(|m| m.get(i).is_some_and(|&v| v <= value)).count();
+            if peers.is_empty() {
+                0.0
+            } else {
+                100.0 * at_or_below as f64 / peers.len() as f64
+            }
+        })
+        .collect()
+}
+
+// None when the model isn't built on a weighted sum of the metrics (formulas, possession, value over replacement).
+pub fn score_contributions(model: &dyn ScoringModel, position: &Position, metrics: &[f64], score: f64) -> Option<Vec<Contribution>> {
+    let weights = model.weights(position)?;
+    let weighted: Vec<f64> = metrics.iter().zip(weights).map(|(metric, weight)| metric * weight).collect();
+    let total: f64 = weighted.iter().sum();
+    Some(
+        weighted
+            .into_iter()
+            .map(|weighted| {
+                let share = if total > 0.0 { weighted / total } else { 0.0 };
+                Contribution { weighted, points: score * share, share: 100.0 * share }
+            })
+            .collect(),
+    )
+}
+
+pub fn position_reports(
+    players: &PlayerMap,
+    player: &Player,
+    model: &dyn ScoringModel,
+    grades: &Grades,
+) -> Vec<PositionReport> {
+    player
+        .positions
+        .iter()
+        .filter_map(|position| {
+            let metrics = player.metrics.get(position)?;
+            let percentiles = metric_percentiles(players, position, metrics);
+            let score = model.score(position, &MetricMap::for_player(player, position)?);
+            let contributions = score_contributions(model, position, metrics, score).unwrap_or_default();
+            let metric_reports = metric_names(position)
+                .iter()
+                .zip(metrics)
+                .zip(percentiles)
+                .enumerate()
+                .map(|(i, ((&metric, &normalized), percentile))| MetricReport { metric, normalized, percentile, contribution: contributions.get(i).copied() })
+                .collect();
+
+            let grade = grades.grade(position, score).to_string();
+            Some(PositionReport { position: position.clone(), score, grade, metrics: metric_reports })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(name: &str, position: Position, metrics: Vec<f64>) -> (Arc<str>, Player) {
+        (name.into(), Player::new(name, vec![position.clone()], HashMap::from([(position, metrics)])))
+    }
+
+    #[test]
+    fn test_rank_players_sorted_descending() {
+        let players = PlayerMap::from_iter([
+            player("Low", Position::Defense, vec![0.1, 0.1, 0.1, 0.1, 0.1]),
+            player("High", Position::Defense, vec![0.9, 0.9, 0.9, 0.9, 0.9]),
+        ]);
+
+        let ranked = rank_players(&players);
+        let defense = &ranked[&Position::Defense];
+        assert_eq!(&*defense[0].name, "High");
+        assert_eq!(&*defense[1].name, "Low");
+    }
+
+    #[test]
+    fn test_ranked_names_share_the_player_allocation() {
+        let (_, solo) = player("Solo", Position::Wing, vec![0.5; 5]);
+        let players = PlayerMap::from_iter([(solo.name.clone(), solo)]);
+        let (key, solo) = players.get_key_value("Solo").unwrap();
+        let ranked = rank_players(&players);
+
+        assert!(Arc::ptr_eq(key, &solo.name));
+        assert!(Arc::ptr_eq(&ranked[&Position::Wing][0].name, &solo.name));
+    }
+
+    #[test]
+    fn test_scored_player_serializes_position_as_string() {
+        let scored = ScoredPlayer { name: "Cale Makar".into(), position: Position::Defense, score: 91.5 };
+        let json = serde_json::to_string(&scored).unwrap();
+        assert_eq!(json, r#"{"name":"Cale Makar","position":"Defense","score":91.5}"#);
+
+        let parsed: ScoredPlayer = serde_json::from_str(r#"{"name":"Cale Makar","position":"d","score":91.5}"#).unwrap();
+        assert_eq!(parsed, scored);
+    }
+
+    #[test]
+    fn test_metric_index_ignores_case_and_punctuation() {
+        assert_eq!(metric_index(&Position::Center, "faceoffs"), Some(0));
+        assert_eq!(metric_index(&Position::Wing, "sh%"), Some(1));
+        assert_eq!(metric_index(&Position::Defense, "shots_blocked"), Some(1));
+        assert_eq!(metric_index(&Position::Defense, "faceoffs"), None);
+    }
+
+    #[test]
+    fn test_set_weight_rescales_the_rest() {
+        let mut weights = WeightTable::default();
+        weights.set_weight(&Position::Center, "faceoffs", 0.4).unwrap();
+        let center = weights.get(&Position::Center);
+        assert_eq!(center[0], 0.4);
+        assert!((center.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!((center[1] / center[2] - 2.0).abs() < 1e-9);
+        assert_eq!(weights.get(&Position::Wing), position_weights(&Position::Wing).0);
+
+        assert!(weights.set_weight(&Position::Center, "hits", 0.2).is_err());
+        assert!(weights.set_weight(&Position::Center, "faceoffs", 1.5).is_err());
+        weights.set_weight(&Position::Center, "faceoffs", 1.0).unwrap();
+        assert!(weights.set_weight(&Position::Center, "faceoffs", 0.5).is_err());
+    }
+
+    #[test]
+    fn test_rank_by_metric_uses_raw_values() {
+        let mut players = PlayerMap::from_iter([
+            player("A", Position::Defense, vec![100.0, 0.0, 0.0, 0.0, 0.0]),
+            player("B", Position::Defense, vec![150.0, 0.0, 0.0, 0.0, 0.0]),
+        ]);
+        players.get_mut("A").unwrap().metrics.insert(Position::Defense, vec![1.0; 5]);
+        players.get_mut("B").unwrap().metrics.insert(Position::Defense, vec![0.5; 5]);
+
+        let by_raw = rank_by_metric(&players, &Position::Defense, 0, true);
+        assert_eq!(by_raw, vec![("B".into(), 150.0), ("A".into(), 100.0)]);
+        assert_eq!(&*rank_by_metric(&players, &Position::Defense, 0, false)[0].0, "A");
+    }
+
+    #[test]
+    fn test_overall_score_averages_positions() {
+        let mut dual = Player::new(
+            "Dual".to_string(),
+            vec![Position::Center, Position::Wing],
+            HashMap::from([(Position::Center, vec![1.0; 5]), (Position::Wing, vec![0.0; 5])]),
+        );
+        let expected = (calculate_score(&Position::Center, &[1.0; 5]) + 50.0) / 2.0;
+        assert!((overall_score(&dual, &WeightedSigmoid::default()).unwrap() - expected).abs() < 1e-9);
+
+        dual.stats = [("Usage C", 75.0), ("Usage W", 25.0)].into();
+        let weighted = 0.75 * calculate_score(&Position::Center, &[1.0; 5]) + 0.25 * 50.0;
+        assert!((overall_score(&dual, &WeightedSigmoid::default()).unwrap() - weighted).abs() < 1e-9);
+
+        dual.positions.clear();
+        assert_eq!(overall_score(&dual, &WeightedSigmoid::default()), None);
+
+        let players = PlayerMap::from_iter([
+            player("Low", Position::Defense, vec![0.1; 5]),
+            player("High", Position::Center, vec![0.9; 5]),
+        ]);
+        let overall = overall_rankings(&players, &WeightedSigmoid::default(), 2.0);
+        assert_eq!(&*overall[0].name, "High");
+        assert_eq!(overall[1].positions, vec![Position::Defense]);
+        assert_eq!(overall[1].bonus, 0.0);
+    }
+
+    #[test]
+    fn test_scarcity_rankings_compare_z_scores_across_positions() {
+        // Three tied defensemen put the fourth sqrt(3) deviations above the mean, further than
+        // the best of three spread-out centers can be, though the center has the higher score.
+        let players = PlayerMap::from_iter([
+            player("C1", Position::Center, vec![0.1; 5]),
+            player("C2", Position::Center, vec![0.5; 5]),
+            player("C3", Position::Center, vec![0.9; 5]),
+            player("D1", Position::Defense, vec![0.3; 5]),
+            player("D2", Position::Defense, vec![0.3; 5]),
+            player("D3", Position::Defense, vec![0.3; 5]),
+            player("D4", Position::Defense, vec![0.5; 5]),
+        ]);
+        let model = WeightedSigmoid::default();
+        assert_eq!(&*overall_rankings(&players, &model, 0.0)[0].name, "C3");
+
+        let scarcity = scarcity_rankings(&players, &model);
+        assert_eq!((&*scarcity[0].name, &*scarcity[1].name), ("D4", "C3"));
+        assert!((scarcity[0].score - 3f64.sqrt()).abs() < 1e-9);
+        assert_eq!(scarcity.len(), players.len());
+    }
+
+    #[test]
+    fn test_versatility_bonus_only_for_multi_position_players() {
+        let (_, single) = player("Single", Position::Wing, vec![0.5; 5]);
+        let mut dual = single.clone();
+        dual.positions.push(Position::Center);
+        dual.metrics.insert(Position::Center, vec![0.5; 5]);
+
+        assert_eq!(versatility_bonus(&single, 2.0), 0.0);
+        assert_eq!(versatility_bonus(&dual, 2.0), 2.0);
+
+        let players = PlayerMap::from_iter([("Dual".into(), dual)]);
+        let overall = &overall_rankings(&players, &WeightedSigmoid::default(), 2.0)[0];
+        assert_eq!(overall.bonus, 2.0);
+        assert!((overall.score - overall_score(&players["Dual"], &WeightedSigmoid::default()).unwrap() - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_metric_percentiles() {
+        let players = PlayerMap::from_iter([
+            player("A", Position::Center, vec![0.2, 0.4, 0.0, 0.0, 0.0]),
+            player("B", Position::Center, vec![0.8, 0.2, 0.0, 0.0, 0.0]),
+        ]);
+
+        let percentiles = metric_percentiles(&players, &Position::Center, &[0.8, 0.2, 0.0, 0.0, 0.0]);
+        assert_eq!(percentiles[0], 100.0);
+        assert_eq!(percentiles[1], 50.0);
+    }
+}
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+use serde::Serialize;
+use crate::cleaning::{PlayerMap, Position};
+use crate::scoring::{metric_names, position_weights, score_with_weights};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricSensitivity {
+    pub metric: &'static str,
+    pub mean_shift_up: f64,
+    pub mean_shift_down: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlayerSensitivity {
+    pub name: Arc<str>,
+    pub rank: usize,
+    pub max_shift: i64,
+    pub metric: &'static str,
+    pub weight_change: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SensitivityReport {
+    pub position: Position,
+    pub metrics: Vec<MetricSensitivity>,
+    pub players: Vec<PlayerSensitivity>,
+}
+
+// Rank 1 is the best score; ties keep name order so the baseline is stable.
+fn ranks(names: &[&str], metrics: &[&[f64]], weights: &[f64], scaling_factor: f64) -> Vec<usize> {
+    let scores: Vec<f64> = metrics.iter().map(|m| score_with_weights(weights, scaling_factor, m)).collect();
+    let mut order: Vec<usize> = (0..names.len()).collect();
+    order.sort_by(|&a, &b| scores[b].partial_cmp(&scores[a]).unwrap_or(std::cmp::Ordering::Equal).then_with(|| names[a].cmp(names[b])));
+
+    let mut ranks = vec![0; names.len()];
+    for (rank, &i) in order.iter().enumerate() {
+        ranks[i] = rank + 1;
+    }
+    ranks
+}
+
+// A positive shift means the player moves up the leaderboard when that weight changes.
+pub fn weight_sensitivity(players: &PlayerMap, position: &Position, delta: f64) -> SensitivityReport {
+    let mut peers: Vec<(&str, &[f64])> =
+        players.values().filter_map(|p| Some((&*p.name, p.metrics.get(position)?.as_slice()))).collect();
+    peers.sort_by(|a, b| a.0.cmp(b.0));
+    let names: Vec<&str> = peers.iter().map(|(name, _)| *name).collect();
+    let metrics: Vec<&[f64]> = peers.iter().map(|(_, metrics)| *metrics).collect();
+
+    let (weights, scaling_factor) = position_weights(position);
+    let baseline = ranks(&names, &metrics, weights, scaling_factor);
+    let mut worst: Vec<(i64, &'static str, f64)> = vec![(0, "", 0.0); names.len()];
+    let mut metric_reports = Vec::new();
+
+    for (i, &metric) in metric_names(position).iter().enumerate() {
+        let mut mean_shifts = [0.0; 2];
+
+        for (slot, change) in [delta, -delta].into_iter().enumerate() {
+            let mut perturbed = weights.to_vec();
+            perturbed[i] *= 1.0 + change;
+            let shifted = ranks(&names, &metrics, &perturbed, scaling_factor);
+
+            for (p, (&before, &after)) in baseline.iter().zip(&shifted).enumerate() {
+                let shift = before as i64 - after as i64;
+                mean_shifts[slot] += shift.unsigned_abs() as f64;
+                if shift.abs() > worst[p].0.abs() {
+                    worst[p] = (shift, metric, change);
+                }
+            }
+        }
+
+        let count = names.len().max(1) as f64;
+        metric_reports.push(MetricSensitivity { metric, mean_shift_up: mean_shifts[0] / count, mean_shift_down: mean_shifts[1] / count });
+    }
+
+    let mut player_reports: Vec<PlayerSensitivity> = names
+        .iter()
+        .zip(&baseline)
+        .zip(worst)
+        .map(|((name, &rank), (max_shift, metric, weight_change))| PlayerSensitivity {
+            name: Arc::from(*name),
+            rank,
+            max_shift,
+            metric,
+            weight_change,
+        })
+        .collect();
+    player_reports.sort_by(|a, b| b.max_shift.abs().cmp(&a.max_shift.abs()).then_with(|| a.rank.cmp(&b.rank)));
+
+    SensitivityReport { position: position.clone(), metrics: metric_reports, players: player_reports }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::cleaning::Player;
+
+    #[test]
+    fn test_weight_sensitivity_flags_single_metric_players() {
+        let player = |name: &str, metrics: Vec<f64>| {
+            (name.into(), Player::new(name, vec![Position::Defense], HashMap::from([(Position::Defense, metrics)])))
+        };
+        // Hitter lives on the hits weight; Balanced is just behind it and overtakes it when hits count for less.
+        let players = PlayerMap::from_iter([
+            player("Hitter", vec![1.0, 0.0, 0.0, 0.0, 0.0]),
+            player("Balanced", vec![0.0, 0.0, 0.7, 0.0, 0.0]),
+            player("Depth", vec![0.0, 0.0, 0.0, 0.0, 0.1]),
+        ]);
+
+        let report = weight_sensitivity(&players, &Position::Defense, 0.2);
+        assert_eq!(report.metrics.len(), 5);
+        let find = |name: &str| report.players.iter().find(|p| &*p.name == name).unwrap();
+        assert_eq!(&*report.players[2].name, "Depth");
+        assert_eq!(find("Depth").max_shift, 0);
+        assert_eq!(find("Hitter").max_shift, -1);
+
+        let balanced = find("Balanced");
+        assert_eq!((balanced.rank, balanced.max_shift), (2, 1));
+        assert_eq!((balanced.metric, balanced.weight_change), ("Hits", -0.2));
+    }
+}
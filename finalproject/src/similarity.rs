@@ -0,0 +1,76 @@
+use std::{collections::HashMap, sync::Arc};
+use serde::Serialize;
+use crate::cleaning::{PlayerMap, Position};
+use crate::graph::Graph;
+use crate::kdtree::KdTree;
+use crate::positions;
+
+pub const DEFAULT_NEIGHBORS: usize = 5;
+
+pub fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f64>().sqrt()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Neighbor {
+    pub name: Arc<str>,
+    pub distance: f64,
+}
+
+// A position's players in a k-d tree over their normalized metrics, in load order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimilarityIndex {
+    names: Vec<Arc<str>>,
+    tree: KdTree,
+}
+
+impl SimilarityIndex {
+    pub fn new(players: &PlayerMap, position: &Position) -> Self {
+        let (names, points) = players.values().filter_map(|player| Some((player.name.clone(), player.metrics.get(position)?.clone()))).unzip();
+        SimilarityIndex { names, tree: KdTree::new(points) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn name(&self, index: usize) -> &Arc<str> {
+        &self.names[index]
+    }
+
+    // The `k` players closest to this one, nearest first, or nothing if they aren't at the position.
+    pub fn similar_to(&self, name: &str, k: usize) -> Vec<Neighbor> {
+        let Some(index) = self.names.iter().position(|other| other.eq_ignore_ascii_case(name.trim())) else { return Vec::new() };
+        self.nearest_to(index, k).into_iter().map(|(index, distance)| Neighbor { name: self.names[index].clone(), distance }).collect()
+    }
+
+    fn nearest_to(&self, index: usize, k: usize) -> Vec<(usize, f64)> {
+        self.tree.nearest(self.tree.point(index), k, Some(index))
+    }
+}
+
+pub fn similarity_indexes(players: &PlayerMap) -> HashMap<Position, SimilarityIndex> {
+    positions::all().iter().map(|position| (position.clone(), SimilarityIndex::new(players, position))).collect()
+}
+
+// Links each player to their `neighbors` closest peers at the position by normalized metrics, weighted
+// 1 / (1 + distance) so near-identical players are linked most strongly. The links are undirected,
+// so a player can end up with more than `neighbors` of them.
+pub fn similarity_graph(players: &PlayerMap, position: &Position, neighbors: usize) -> Graph {
+    let index = SimilarityIndex::new(players, position);
+    let mut graph = Graph::new();
+    let ids: Vec<usize> = (0..index.len()).map(|i| graph.node(index.name(i))).collect();
+
+    for i in 0..index.len() {
+        for (j, distance) in index.nearest_to(i, neighbors) {
+            if graph.weight(ids[i], ids[j]).is_none() {
+                graph.add_undirected(ids[i], ids[j], 1.0 / (1.0 + distance));
+            }
+        }
+    }
+    graph
+}
@@ -0,0 +1,162 @@
+use std::{collections::HashMap, fmt, io, sync::Arc};
+use serde::Serialize;
+use crate::cleaning::{open_input, process_reader, ParseOptions, Player, PlayerMap, Position};
+use crate::stat_line::StatLine;
+
+const POWER_PLAY_WEIGHTS: [(&str, f64); 4] = [("Total Points", 0.4), ("Goals", 0.3), ("First Assists", 0.2), ("ixG", 0.1)];
+const PENALTY_KILL_WEIGHTS: [(&str, f64); 3] = [("Shots Blocked", 0.5), ("Takeaways", 0.3), ("Hits", 0.2)];
+
+// Per-player situational stats, keyed by lowercased name.
+pub type SituationStats = HashMap<String, StatLine>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Situation {
+    PowerPlay,
+    PenaltyKill,
+}
+
+impl Situation {
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Situation::PowerPlay => "PP",
+            Situation::PenaltyKill => "PK",
+        }
+    }
+
+    pub fn stat(&self, stat: &str) -> String {
+        format!("{} {}", self.prefix(), stat)
+    }
+
+    fn weights(&self) -> &'static [(&'static str, f64)] {
+        match self {
+            Situation::PowerPlay => &POWER_PLAY_WEIGHTS,
+            Situation::PenaltyKill => &PENALTY_KILL_WEIGHTS,
+        }
+    }
+
+    // Power-play scores are for forwards and penalty-kill scores for defensemen.
+    pub fn eligible(&self, player: &Player) -> bool {
+        match self {
+            Situation::PowerPlay => player.positions.iter().any(|p| *p != Position::Defense),
+            Situation::PenaltyKill => player.positions.contains(&Position::Defense),
+        }
+    }
+}
+
+impl fmt::Display for Situation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Situation::PowerPlay => write!(f, "Power Play"),
+            Situation::PenaltyKill => write!(f, "Penalty Kill"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpecialTeamsLine {
+    pub name: Arc<str>,
+    pub score: f64,
+}
+
+pub fn read_situation(path: &str, options: &ParseOptions) -> io::Result<SituationStats> {
+    let players = process_reader(open_input(path)?, options)?;
+    Ok(players.into_iter().map(|(_, player)| (player.name.to_lowercase(), player.stats)).collect())
+}
+
+// Situation stats are stored next to the all-situations ones under a "PP "/"PK " prefix.
+pub fn merge_situation(players: &mut PlayerMap, situation: Situation, stats: &SituationStats) -> usize {
+    let mut matched = 0;
+    for player in players.values_mut() {
+        if let Some(situation_stats) = stats.get(&player.name.to_lowercase()) {
+            for (stat, &value) in situation_stats {
+                player.stats.insert(situation.stat(stat), value);
+            }
+            matched += 1;
+        }
+    }
+    matched
+}
+
+fn has_situation(player: &Player, situation: Situation) -> bool {
+    situation.weights().iter().any(|(stat, _)| player.stats.contains_key(&situation.stat(stat)))
+}
+
+fn situation_maxes(players: &PlayerMap, situation: Situation) -> Vec<f64> {
+    situation
+        .weights()
+        .iter()
+        .map(|(stat, _)| {
+            let stat = situation.stat(stat);
+            players
+                .values()
+                .filter(|p| situation.eligible(p))
+                .filter_map(|p| p.stats.get(&stat).copied())
+                .fold(0.0, f64::max)
+        })
+        .collect()
+}
+
+fn score_with_maxes(player: &Player, situation: Situation, maxes: &[f64]) -> f64 {
+    let weights = situation.weights();
+    let total_weight: f64 = weights.iter().map(|(_, weight)| weight).sum();
+    let weighted_sum: f64 = weights
+        .iter()
+        .zip(maxes)
+        .filter(|(_, &max)| max > 0.0)
+        .map(|((stat, weight), max)| player.stats.get(&situation.stat(stat)).copied().unwrap_or(0.0) / max * weight)
+        .sum();
+    100.0 * weighted_sum / total_weight
+}
+
+// Weighted share of the best eligible player's output in each situational stat, out of 100.
+pub fn special_teams_score(players: &PlayerMap, player: &Player, situation: Situation) -> Option<f64> {
+    if !situation.eligible(player) || !has_situation(player, situation) {
+        return None;
+    }
+    Some(score_with_maxes(player, situation, &situation_maxes(players, situation)))
+}
+
+pub fn special_teams_leaderboard(players: &PlayerMap, situation: Situation) -> Vec<SpecialTeamsLine> {
+    let maxes = situation_maxes(players, situation);
+    let mut lines: Vec<SpecialTeamsLine> = players
+        .values()
+        .filter(|p| situation.eligible(p) && has_situation(p, situation))
+        .map(|p| SpecialTeamsLine { name: p.name.clone(), score: score_with_maxes(p, situation, &maxes) })
+        .collect();
+    lines.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.name.cmp(&b.name)));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(name: &str, position: Position) -> Player {
+        Player::new(name, vec![position.clone()], HashMap::from([(position, vec![0.0; 5])]))
+    }
+
+    #[test]
+    fn test_merge_and_score_power_play() {
+        let mut players = PlayerMap::from_iter([
+            ("A".into(), player("A", Position::Center)),
+            ("B".into(), player("B", Position::Wing)),
+            ("D".into(), player("D", Position::Defense)),
+        ]);
+        let stats = HashMap::from([
+            ("a".to_string(), StatLine::from([("Total Points", 40.0), ("Goals", 10.0)])),
+            ("b".to_string(), StatLine::from([("Total Points", 20.0), ("Goals", 20.0)])),
+            ("d".to_string(), StatLine::from([("Total Points", 80.0)])),
+        ]);
+
+        assert_eq!(merge_situation(&mut players, Situation::PowerPlay, &stats), 3);
+        assert_eq!(players["A"].stats["PP Total Points"], 40.0);
+
+        let leaderboard = special_teams_leaderboard(&players, Situation::PowerPlay);
+        assert_eq!(leaderboard.len(), 2);
+        assert_eq!(&*leaderboard[0].name, "A");
+        assert!((leaderboard[0].score - 55.0).abs() < 1e-9);
+        assert!((leaderboard[1].score - 50.0).abs() < 1e-9);
+        assert_eq!(special_teams_score(&players, &players["D"], Situation::PowerPlay), None);
+        assert_eq!(special_teams_score(&players, &players["D"], Situation::PenaltyKill), None);
+    }
+}
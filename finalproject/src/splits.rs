@@ -0,0 +1,124 @@
+use std::{collections::HashMap, fmt, str::FromStr, sync::Arc};
+use crate::cleaning::{PlayerMap, Position};
+use crate::scoring::ScoredPlayer;
+use crate::stat_line::StatLine;
+
+// Which split a row's stats cover, stored as a stat (1 for home, 0 for road) the way rookie flags
+// are, so a Split or Venue column survives the cache like any other extra column.
+pub const SPLIT_STAT: &str = "Split";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Venue {
+    Home,
+    Road,
+}
+
+impl Venue {
+    fn value(self) -> f64 {
+        match self {
+            Venue::Home => 1.0,
+            Venue::Road => 0.0,
+        }
+    }
+}
+
+impl fmt::Display for Venue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Venue::Home => "home",
+            Venue::Road => "road",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Venue {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "home" | "h" => Ok(Venue::Home),
+            "road" | "away" | "r" | "a" => Ok(Venue::Road),
+            _ => Err(format!("invalid split '{}', expected home or road", s)),
+        }
+    }
+}
+
+pub fn parse_split(value: &str) -> Option<f64> {
+    value.parse::<Venue>().ok().map(Venue::value)
+}
+
+pub fn venue_of(stats: &StatLine) -> Option<Venue> {
+    stats.get(SPLIT_STAT).map(|&value| if value > 0.5 { Venue::Home } else { Venue::Road })
+}
+
+// Marks every player as coming from `venue`'s export. Returns how many rows a Split column had
+// already tagged as the other venue, which usually means the files were passed the wrong way round.
+pub fn tag_venue(players: &mut PlayerMap, venue: Venue) -> usize {
+    let mut conflicts = 0;
+    for player in players.values_mut() {
+        conflicts += venue_of(&player.stats).is_some_and(|tagged| tagged != venue) as usize;
+        player.stats.insert(SPLIT_STAT.to_string(), venue.value());
+    }
+    conflicts
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VenueGap {
+    pub name: Arc<str>,
+    pub position: Position,
+    pub home: f64,
+    pub road: f64,
+}
+
+impl VenueGap {
+    pub fn gap(&self) -> f64 {
+        self.home - self.road
+    }
+}
+
+// Players ranked at the same position in both splits, largest gap first either way.
+pub fn venue_gaps(home: &HashMap<Position, Vec<ScoredPlayer>>, road: &HashMap<Position, Vec<ScoredPlayer>>) -> Vec<VenueGap> {
+    let road: HashMap<(&str, &Position), f64> = road.values().flatten().map(|scored| ((&*scored.name, &scored.position), scored.score)).collect();
+    let mut gaps: Vec<VenueGap> = home
+        .values()
+        .flatten()
+        .filter_map(|scored| {
+            let &road = road.get(&(&*scored.name, &scored.position))?;
+            Some(VenueGap { name: scored.name.clone(), position: scored.position.clone(), home: scored.score, road })
+        })
+        .collect();
+    gaps.sort_by(|a, b| b.gap().abs().total_cmp(&a.gap().abs()).then_with(|| a.name.cmp(&b.name)));
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cleaning::Player;
+
+    fn scored(name: &str, position: Position, score: f64) -> ScoredPlayer {
+        ScoredPlayer { name: name.into(), position, score }
+    }
+
+    #[test]
+    fn test_venue_gaps_and_tagging() {
+        assert_eq!(parse_split(" Away "), Some(0.0));
+        assert_eq!(parse_split("H"), Some(1.0));
+        assert_eq!(parse_split("neutral"), None);
+
+        let player = |name: &str, split: Option<f64>| {
+            let mut player = Player::new(name, vec![Position::Center], HashMap::new());
+            player.stats.extend(split.map(|value| (SPLIT_STAT.to_string(), value)));
+            (player.name.clone(), player)
+        };
+        let mut players = PlayerMap::from_iter([player("Tagged Road", Some(0.0)), player("Untagged", None)]);
+        assert_eq!(tag_venue(&mut players, Venue::Home), 1);
+        assert!(players.values().all(|player| venue_of(&player.stats) == Some(Venue::Home)));
+
+        let home = HashMap::from([(Position::Center, vec![scored("Homebody", Position::Center, 90.0), scored("Even", Position::Center, 70.0), scored("Home Only", Position::Center, 60.0)])]);
+        let road = HashMap::from([(Position::Center, vec![scored("Traveler", Position::Center, 85.0), scored("Homebody", Position::Center, 75.0), scored("Even", Position::Center, 71.0)])]);
+        let gaps = venue_gaps(&home, &road);
+        assert_eq!(gaps.iter().map(|gap| (&*gap.name, gap.gap())).collect::<Vec<_>>(), [("Homebody", 15.0), ("Even", -1.0)]);
+    }
+}
@@ -0,0 +1,189 @@
+use std::{collections::{hash_map, HashMap}, fmt, iter::Zip, ops::Index, slice};
+use serde::{de::{MapAccess, Visitor}, ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
+use crate::metric::{Metric, METRICS};
+
+// A player's stats by name. The individual report's own columns sit in one slot each, in column
+// order, so reading a row parses its fields straight into place without allocating a key per stat;
+// anything joined on afterwards (cap hits, bios, special teams) is kept by name beside them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatLine {
+    report: [Option<f64>; METRICS.len()],
+    joined: HashMap<String, f64>,
+}
+
+fn slot(stat: &str) -> Option<usize> {
+    METRICS.iter().position(|metric| metric.name() == stat)
+}
+
+impl StatLine {
+    pub fn new() -> Self {
+        StatLine::default()
+    }
+
+    pub fn metric(&self, metric: Metric) -> Option<f64> {
+        self.report[metric as usize]
+    }
+
+    pub fn set_metric(&mut self, metric: Metric, value: f64) {
+        self.report[metric as usize] = Some(value);
+    }
+
+    pub fn get(&self, stat: &str) -> Option<&f64> {
+        match slot(stat) {
+            Some(i) => self.report[i].as_ref(),
+            None => self.joined.get(stat),
+        }
+    }
+
+    pub fn get_mut(&mut self, stat: &str) -> Option<&mut f64> {
+        match slot(stat) {
+            Some(i) => self.report[i].as_mut(),
+            None => self.joined.get_mut(stat),
+        }
+    }
+
+    pub fn contains_key(&self, stat: &str) -> bool {
+        self.get(stat).is_some()
+    }
+
+    // A report column's name is only turned into an owned key when it isn't one of the slots.
+    pub fn insert(&mut self, stat: impl AsRef<str> + Into<String>, value: f64) -> Option<f64> {
+        match slot(stat.as_ref()) {
+            Some(i) => self.report[i].replace(value),
+            None => self.joined.insert(stat.into(), value),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.report.iter().flatten().count() + self.joined.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // The report's columns in order, then the joined stats in no particular order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { report: METRICS.iter().zip(self.report.iter()), joined: self.joined.iter() }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.iter().map(|(stat, _)| stat)
+    }
+}
+
+// Panics when the player has no such stat, like indexing a map.
+impl Index<&str> for StatLine {
+    type Output = f64;
+
+    fn index(&self, stat: &str) -> &f64 {
+        self.get(stat).unwrap_or_else(|| panic!("no stat '{}'", stat))
+    }
+}
+
+pub struct Iter<'a> {
+    report: Zip<slice::Iter<'a, Metric>, slice::Iter<'a, Option<f64>>>,
+    joined: hash_map::Iter<'a, String, f64>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a str, &'a f64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (metric, value) in self.report.by_ref() {
+            if let Some(value) = value {
+                return Some((metric.name(), value));
+            }
+        }
+        self.joined.next().map(|(stat, value)| (stat.as_str(), value))
+    }
+}
+
+impl<'a> IntoIterator for &'a StatLine {
+    type Item = (&'a str, &'a f64);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<S: AsRef<str> + Into<String>> Extend<(S, f64)> for StatLine {
+    fn extend<I: IntoIterator<Item = (S, f64)>>(&mut self, stats: I) {
+        for (stat, value) in stats {
+            self.insert(stat, value);
+        }
+    }
+}
+
+impl<S: AsRef<str> + Into<String>> FromIterator<(S, f64)> for StatLine {
+    fn from_iter<I: IntoIterator<Item = (S, f64)>>(stats: I) -> Self {
+        let mut line = StatLine::new();
+        line.extend(stats);
+        line
+    }
+}
+
+impl<S: AsRef<str> + Into<String>, const N: usize> From<[(S, f64); N]> for StatLine {
+    fn from(stats: [(S, f64); N]) -> Self {
+        stats.into_iter().collect()
+    }
+}
+
+// Written as a plain map of stat names, as the players' stats always have been, so caches and
+// exports from before the slots still read.
+impl Serialize for StatLine {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (stat, value) in self {
+            map.serialize_entry(stat, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for StatLine {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct StatVisitor;
+
+        impl<'de> Visitor<'de> for StatVisitor {
+            type Value = StatLine;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of stat names to values")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<StatLine, A::Error> {
+                let mut line = StatLine::new();
+                while let Some((stat, value)) = map.next_entry::<String, f64>()? {
+                    line.insert(stat, value);
+                }
+                Ok(line)
+            }
+        }
+
+        deserializer.deserialize_map(StatVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_columns_and_joined_stats_read_alike() {
+        let mut line: StatLine = [("GP", 82.0), ("Cap Hit", 9.5)].into_iter().collect();
+        line.set_metric(Metric::Hits, 120.0);
+        assert_eq!((line.get("GP"), line.metric(Metric::GamesPlayed)), (Some(&82.0), Some(82.0)));
+        assert_eq!((line.get("Hits"), line.get("Cap Hit"), line.get("Saves")), (Some(&120.0), Some(&9.5), None));
+        assert_eq!(line.insert("GP".to_string(), 80.0), Some(82.0));
+        *line.get_mut("Cap Hit").unwrap() += 0.5;
+
+        assert_eq!(line.len(), 3);
+        assert_eq!(line.iter().collect::<Vec<_>>(), [("GP", &80.0), ("Hits", &120.0), ("Cap Hit", &10.0)]);
+
+        let json = serde_json::to_string(&line).unwrap();
+        assert_eq!(serde_json::from_str::<StatLine>(&json).unwrap(), line);
+        assert_eq!(serde_json::from_str::<StatLine>("{\"GP\": 1.0}").unwrap().metric(Metric::GamesPlayed), Some(1.0));
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use serde::Serialize;
+use crate::cleaning::{Player, PlayerMap, Position};
+use crate::scoring::{calculate_score, metric_names};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+pub struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub stddev: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricSummary {
+    pub metric: &'static str,
+    pub raw: Summary,
+    pub normalized: Summary,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionSummary {
+    pub position: Position,
+    pub metrics: Vec<MetricSummary>,
+    pub score: Summary,
+}
+
+// Population statistics: the dataset is the whole league rather than a sample of it.
+pub fn summarize(values: &[f64]) -> Summary {
+    if values.is_empty() {
+        return Summary::default();
+    }
+
+    let count = values.len();
+    let mean = values.iter().sum::<f64>() / count as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count as f64;
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let median = if count.is_multiple_of(2) {
+        (sorted[count / 2 - 1] + sorted[count / 2]) / 2.0
+    } else {
+        sorted[count / 2]
+    };
+
+    Summary { count, mean, median, stddev: variance.sqrt() }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Regression {
+    pub intercept: f64,
+    pub coefficients: Vec<f64>,
+    pub r_squared: f64,
+}
+
+// Ordinary least squares with an intercept, solved from the normal equations.
+pub fn least_squares(rows: &[Vec<f64>], targets: &[f64]) -> Option<Regression> {
+    let width = rows.first()?.len() + 1;
+    if rows.len() < width || rows.len() != targets.len() {
+        return None;
+    }
+
+    let mut system = vec![vec![0.0; width + 1]; width];
+    for (row, &target) in rows.iter().zip(targets) {
+        let x: Vec<f64> = std::iter::once(1.0).chain(row.iter().copied()).collect();
+        for i in 0..width {
+            for j in 0..width {
+                system[i][j] += x[i] * x[j];
+            }
+            system[i][width] += x[i] * target;
+        }
+    }
+
+    for col in 0..width {
+        let pivot = (col..width).max_by(|&a, &b| system[a][col].abs().partial_cmp(&system[b][col].abs()).unwrap_or(std::cmp::Ordering::Equal))?;
+        if system[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        system.swap(col, pivot);
+
+        let pivot_row = system[col].clone();
+        for (r, row) in system.iter_mut().enumerate() {
+            if r != col {
+                let factor = row[col] / pivot_row[col];
+                for (value, pivot_value) in row.iter_mut().zip(&pivot_row).skip(col) {
+                    *value -= factor * pivot_value;
+                }
+            }
+        }
+    }
+
+    let solution: Vec<f64> = (0..width).map(|i| system[i][width] / system[i][i]).collect();
+    let mean = targets.iter().sum::<f64>() / targets.len() as f64;
+    let (mut residual, mut total) = (0.0, 0.0);
+    for (row, &target) in rows.iter().zip(targets) {
+        let predicted = solution[0] + row.iter().zip(&solution[1..]).map(|(x, b)| x * b).sum::<f64>();
+        residual += (target - predicted).powi(2);
+        total += (target - mean).powi(2);
+    }
+
+    Some(Regression {
+        intercept: solution[0],
+        coefficients: solution[1..].to_vec(),
+        r_squared: if total > 0.0 { 1.0 - residual / total } else { 0.0 },
+    })
+}
+
+// Leading eigenvector of the covariance matrix by power iteration, signed so its loadings mostly point up.
+pub fn first_principal_component(rows: &[Vec<f64>]) -> Option<Vec<f64>> {
+    let width = rows.first()?.len();
+    if rows.len() < 2 || width == 0 {
+        return None;
+    }
+
+    let means: Vec<f64> = (0..width).map(|j| rows.iter().map(|row| row[j]).sum::<f64>() / rows.len() as f64).collect();
+    let mut covariance = vec![vec![0.0; width]; width];
+    for row in rows {
+        for i in 0..width {
+            for j in 0..width {
+                covariance[i][j] += (row[i] - means[i]) * (row[j] - means[j]) / rows.len() as f64;
+            }
+        }
+    }
+
+    let mut vector = vec![1.0 / (width as f64).sqrt(); width];
+    for _ in 0..500 {
+        let next: Vec<f64> = covariance.iter().map(|row| row.iter().zip(&vector).map(|(c, v)| c * v).sum()).collect();
+        let norm = next.iter().map(|v| v * v).sum::<f64>().sqrt();
+        if norm < 1e-12 {
+            return None;
+        }
+        vector = next.iter().map(|v| v / norm).collect();
+    }
+
+    if vector.iter().sum::<f64>() < 0.0 {
+        vector.iter_mut().for_each(|v| *v = -*v);
+    }
+    Some(vector)
+}
+
+// Tied values share the average of the ranks they span.
+pub fn average_ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].partial_cmp(&values[b]).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &i in &order[start..end] {
+            ranks[i] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+pub fn pearson(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    if xs.len() != ys.len() || xs.len() < 2 {
+        return None;
+    }
+
+    let (x, y) = (summarize(xs), summarize(ys));
+    if x.stddev == 0.0 || y.stddev == 0.0 {
+        return None;
+    }
+
+    let covariance = xs.iter().zip(ys).map(|(a, b)| (a - x.mean) * (b - y.mean)).sum::<f64>() / xs.len() as f64;
+    Some(covariance / (x.stddev * y.stddev))
+}
+
+pub fn spearman(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    pearson(&average_ranks(xs), &average_ranks(ys))
+}
+
+pub fn summarize_position(players: &PlayerMap, position: &Position) -> PositionSummary {
+    let peers: Vec<&Player> = players.values().filter(|p| p.metrics.contains_key(position)).collect();
+    let column = |metrics: &HashMap<Position, Vec<f64>>, i: usize| metrics.get(position).and_then(|m| m.get(i)).copied();
+
+    let metrics = metric_names(position)
+        .iter()
+        .enumerate()
+        .map(|(i, &metric)| {
+            let raw: Vec<f64> = peers.iter().filter_map(|p| column(&p.raw_metrics, i)).collect();
+            let normalized: Vec<f64> = peers.iter().filter_map(|p| column(&p.metrics, i)).collect();
+            MetricSummary { metric, raw: summarize(&raw), normalized: summarize(&normalized) }
+        })
+        .collect();
+
+    let scores: Vec<f64> = peers.iter().map(|p| calculate_score(position, &p.metrics[position])).collect();
+
+    PositionSummary { position: position.clone(), metrics, score: summarize(&scores) }
+}
+
+// Pearson correlations between stat columns among the players at a position; None where a column is constant.
+pub fn correlation_matrix(players: &PlayerMap, position: &Position, stats: &[&str]) -> Vec<Vec<Option<f64>>> {
+    let peers: Vec<&Player> = players.values().filter(|p| p.positions.contains(position)).collect();
+    let columns: Vec<Vec<f64>> = stats
+        .iter()
+        .map(|stat| peers.iter().map(|p| p.stats.get(stat).copied().unwrap_or(0.0)).collect())
+        .collect();
+
+    columns.iter().map(|xs| columns.iter().map(|ys| pearson(xs, ys)).collect()).collect()
+}
+
+// Counts of scores in each tenth of the 0-100 scale; anything outside it lands in the first or last bucket.
+pub fn decile_counts(scores: &[f64]) -> [usize; 10] {
+    let mut counts = [0; 10];
+    for &score in scores {
+        counts[((score / 10.0).floor().max(0.0) as usize).min(9)] += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize() {
+        let summary = summarize(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+        assert_eq!(summary.count, 8);
+        assert_eq!(summary.mean, 5.0);
+        assert_eq!(summary.median, 4.5);
+        assert_eq!(summary.stddev, 2.0);
+
+        assert_eq!(summarize(&[3.0, 1.0, 2.0]).median, 2.0);
+        assert_eq!(summarize(&[]), Summary::default());
+    }
+
+    #[test]
+    fn test_least_squares_recovers_exact_fit() {
+        let rows = vec![vec![0.0, 1.0], vec![1.0, 0.0], vec![1.0, 1.0], vec![2.0, 3.0]];
+        let targets: Vec<f64> = rows.iter().map(|r| 1.0 + 2.0 * r[0] - 0.5 * r[1]).collect();
+
+        let fit = least_squares(&rows, &targets).unwrap();
+        assert!((fit.intercept - 1.0).abs() < 1e-9);
+        assert!((fit.coefficients[0] - 2.0).abs() < 1e-9);
+        assert!((fit.coefficients[1] + 0.5).abs() < 1e-9);
+        assert!((fit.r_squared - 1.0).abs() < 1e-9);
+
+        assert!(least_squares(&rows[..2], &targets[..2]).is_none());
+        assert!(least_squares(&[vec![1.0], vec![1.0], vec![1.0]], &[1.0, 2.0, 3.0]).is_none());
+    }
+
+    #[test]
+    fn test_correlation_matrix() {
+        let players: PlayerMap = [(1.0, 2.0, 5.0), (2.0, 4.0, 3.0), (3.0, 6.0, 4.0)]
+            .iter()
+            .enumerate()
+            .map(|(i, &(goals, points, hits))| {
+                let name = format!("C{}", i);
+                let mut player = Player::new(name, vec![Position::Center], HashMap::new());
+                player.stats = [("Goals", goals), ("Total Points", points), ("Hits", hits)].into();
+                (player.name.clone(), player)
+            })
+            .collect();
+
+        let matrix = correlation_matrix(&players, &Position::Center, &["Goals", "Total Points", "Hits", "PIM"]);
+        assert!((matrix[0][1].unwrap() - 1.0).abs() < 1e-12);
+        assert!((matrix[1][2].unwrap() + 0.5).abs() < 1e-12);
+        assert_eq!(matrix[2][1], matrix[1][2]);
+        assert_eq!(matrix[3][0], None);
+    }
+
+    #[test]
+    fn test_first_principal_component() {
+        let rows: Vec<Vec<f64>> = (0..20).map(|i| vec![i as f64, -2.0 * i as f64, (i % 2) as f64 * 0.01]).collect();
+        let component = first_principal_component(&rows).unwrap();
+        assert!((component[0] + 1.0 / 5.0_f64.sqrt()).abs() < 1e-3);
+        assert!((component[1] - 2.0 / 5.0_f64.sqrt()).abs() < 1e-3);
+        assert!(component[2].abs() < 1e-3);
+        assert!(first_principal_component(&[vec![1.0, 1.0], vec![1.0, 1.0]]).is_none());
+    }
+
+    #[test]
+    fn test_decile_counts() {
+        let counts = decile_counts(&[0.0, 9.9, 10.0, 49.0, 51.0, 100.0, 120.0, -3.0]);
+        assert_eq!(counts, [3, 1, 0, 0, 1, 1, 0, 0, 0, 2]);
+    }
+
+    #[test]
+    fn test_spearman() {
+        assert_eq!(average_ranks(&[10.0, 30.0, 20.0, 20.0]), vec![1.0, 4.0, 2.5, 2.5]);
+        assert!((spearman(&[1.0, 2.0, 3.0, 4.0], &[10.0, 40.0, 90.0, 160.0]).unwrap() - 1.0).abs() < 1e-12);
+        assert!((spearman(&[1.0, 2.0, 3.0], &[3.0, 2.0, 1.0]).unwrap() + 1.0).abs() < 1e-12);
+        assert_eq!(spearman(&[1.0, 1.0], &[1.0, 2.0]), None);
+    }
+
+    #[test]
+    fn test_summarize_position_only_counts_players_at_position() {
+        let mut players = PlayerMap::default();
+        for (name, position, value) in [("A", Position::Wing, 10.0), ("B", Position::Wing, 20.0), ("C", Position::Center, 99.0)] {
+            players.insert(name.into(), Player::new(name, vec![position.clone()], HashMap::from([(position, vec![value; 5])])));
+        }
+
+        let summary = summarize_position(&players, &Position::Wing);
+        assert_eq!(summary.score.count, 2);
+        assert_eq!(summary.metrics[0].metric, "Goals");
+        assert_eq!(summary.metrics[0].raw.mean, 15.0);
+    }
+}
@@ -0,0 +1,261 @@
+use std::{collections::BTreeMap, sync::Arc};
+use serde::Serialize;
+use crate::cleaning::{metric_key, Player, PlayerMap, Position, Side};
+use crate::model::{MetricMap, ScoringModel};
+use crate::scoring::{overall_score, versatility_bonus};
+
+// Player names by team abbreviation. A traded player's team reads like "CAR, NYI" (or "CAR/NYI" once
+// rows are merged with `--duplicates sum`), and they're listed under each of those teams.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TeamIndex {
+    teams: BTreeMap<String, Vec<Arc<str>>>,
+}
+
+impl TeamIndex {
+    pub fn new(players: &PlayerMap) -> Self {
+        let mut teams: BTreeMap<String, Vec<Arc<str>>> = BTreeMap::new();
+        for player in players.values() {
+            for team in player_teams(player) {
+                teams.entry(team).or_default().push(player.name.clone());
+            }
+        }
+        TeamIndex { teams }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.teams.is_empty()
+    }
+
+    pub fn teams(&self) -> impl Iterator<Item = &str> {
+        self.teams.keys().map(String::as_str)
+    }
+
+    // Matches the abbreviation ignoring case and punctuation, so "la" finds Natural Stat Trick's "L.A".
+    // Players keep the order they were loaded in.
+    pub fn players<'a>(&self, players: &'a PlayerMap, team: &str) -> Vec<&'a Player> {
+        let key = metric_key(team);
+        self.teams
+            .iter()
+            .find(|(team, _)| metric_key(team) == key)
+            .map(|(_, names)| names.iter().filter_map(|name| players.get(name)).collect())
+            .unwrap_or_default()
+    }
+}
+
+pub fn player_teams(player: &Player) -> Vec<String> {
+    let Some(team) = &player.team else { return Vec::new() };
+    team.split([',', '/']).map(|team| team.trim().to_uppercase()).filter(|team| !team.is_empty()).collect()
+}
+
+// A team's players by overall rating (versatility bonus included), best first.
+pub fn team_ratings<'a>(players: &'a PlayerMap, index: &TeamIndex, team: &str, model: &dyn ScoringModel, bonus: f64) -> Vec<(&'a Player, f64)> {
+    let mut ratings: Vec<(&Player, f64)> = index
+        .players(players, team)
+        .into_iter()
+        .filter_map(|player| Some((player, overall_score(player, model)? + versatility_bonus(player, bonus))))
+        .collect();
+    ratings.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    ratings
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    LeftWing,
+    Center,
+    RightWing,
+    Defense,
+}
+
+impl Slot {
+    pub const ALL: [Slot; 4] = [Slot::LeftWing, Slot::Center, Slot::RightWing, Slot::Defense];
+
+    pub fn abbreviation(self) -> &'static str {
+        match self {
+            Slot::LeftWing => "LW",
+            Slot::Center => "C",
+            Slot::RightWing => "RW",
+            Slot::Defense => "D",
+        }
+    }
+
+    // Four forward lines and three defense pairs.
+    pub fn depth(self) -> usize {
+        match self {
+            Slot::Defense => 6,
+            _ => 4,
+        }
+    }
+
+    fn for_position(position: &Position, sides: &[Side]) -> Vec<Slot> {
+        let side = |side: &Side| match side {
+            Side::Left => Slot::LeftWing,
+            Side::Right => Slot::RightWing,
+        };
+        match position {
+            Position::Center => vec![Slot::Center],
+            Position::Defense => vec![Slot::Defense],
+            Position::Wing if sides.is_empty() => vec![Slot::LeftWing, Slot::RightWing],
+            Position::Wing => sides.iter().map(side).collect(),
+            // Depth charts only have forward lines and defense pairs.
+            Position::Custom(_) => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DepthSlot {
+    pub name: Arc<str>,
+    pub score: f64,
+}
+
+// Each column is ordered by score; a column shorter than its slot's depth has holes at the bottom.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DepthChart {
+    columns: [Vec<DepthSlot>; 4],
+}
+
+impl DepthChart {
+    pub fn column(&self, slot: Slot) -> &[DepthSlot] {
+        &self.columns[slot as usize]
+    }
+
+    pub fn get(&self, slot: Slot, depth: usize) -> Option<&DepthSlot> {
+        self.column(slot).get(depth)
+    }
+
+    // Mean score of the dressed lineup at each position and overall. Holes count as zero, so a team
+    // that can't fill its lineup is rated down for it.
+    pub fn strength(&self) -> TeamStrength {
+        let mean = |slots: &[Slot]| {
+            let total: f64 = slots.iter().flat_map(|&slot| self.column(slot)).map(|s| s.score).sum();
+            total / slots.iter().map(|slot| slot.depth()).sum::<usize>() as f64
+        };
+        TeamStrength {
+            positions: vec![
+                (Position::Center, mean(&[Slot::Center])),
+                (Position::Wing, mean(&[Slot::LeftWing, Slot::RightWing])),
+                (Position::Defense, mean(&[Slot::Defense])),
+            ],
+            overall: mean(&Slot::ALL),
+        }
+    }
+
+    // Empty slots like "RW4" or "D6", in chart order.
+    pub fn holes(&self) -> Vec<String> {
+        Slot::ALL
+            .iter()
+            .flat_map(|&slot| (self.column(slot).len()..slot.depth()).map(move |depth| format!("{}{}", slot.abbreviation(), depth + 1)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TeamStrength {
+    pub positions: Vec<(Position, f64)>,
+    pub overall: f64,
+}
+
+// Fills the chart best score first: each player takes the open slot they score highest at, so a
+// center who also plays wing only moves to the wing once the top four centers are set.
+pub fn depth_chart(players: &PlayerMap, index: &TeamIndex, team: &str, model: &dyn ScoringModel) -> DepthChart {
+    let roster = index.players(players, team);
+    let mut candidates: Vec<(f64, usize, Slot)> = Vec::new();
+    for (i, player) in roster.iter().enumerate() {
+        for position in &player.positions {
+            let Some(metrics) = MetricMap::for_player(player, position) else { continue };
+            let score = model.score(position, &metrics);
+            candidates.extend(Slot::for_position(position, &player.sides).into_iter().map(|slot| (score, i, slot)));
+        }
+    }
+    candidates.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut chart = DepthChart::default();
+    let mut placed = vec![false; roster.len()];
+    for (score, i, slot) in candidates {
+        let column = &mut chart.columns[slot as usize];
+        if placed[i] || column.len() >= slot.depth() {
+            continue;
+        }
+        column.push(DepthSlot { name: roster[i].name.clone(), score });
+        placed[i] = true;
+    }
+    chart
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::cleaning::Position;
+    use crate::model::WeightedSigmoid;
+
+    fn player(name: &str, team: &str, value: f64) -> (Arc<str>, Player) {
+        let mut player = Player::new(name, vec![Position::Wing], HashMap::from([(Position::Wing, vec![value; 5])]));
+        player.team = Some(team.to_string());
+        (name.into(), player)
+    }
+
+    fn skater(name: &str, positions: &[Position], sides: &[Side], value: f64) -> (Arc<str>, Player) {
+        let metrics = positions.iter().map(|position| (position.clone(), vec![value; 5])).collect();
+        let mut player = Player::new(name, positions.to_vec(), metrics);
+        player.team = Some("BOS".to_string());
+        player.sides = sides.to_vec();
+        (name.into(), player)
+    }
+
+    #[test]
+    fn test_team_ratings() {
+        let players = PlayerMap::from_iter([
+            player("Mitch Marner", "TOR", 0.6),
+            player("William Nylander", "tor", 0.9),
+            player("Tyler Bertuzzi", "TOR, CHI", 0.3),
+            player("David Pastrnak", "BOS", 1.0),
+        ]);
+        let index = TeamIndex::new(&players);
+        assert_eq!(index.teams().collect::<Vec<_>>(), ["BOS", "CHI", "TOR"]);
+
+        let ratings = team_ratings(&players, &index, "Tor", &WeightedSigmoid::default(), 0.0);
+        let names: Vec<&str> = ratings.iter().map(|(player, _)| &*player.name).collect();
+        assert_eq!(names, ["William Nylander", "Mitch Marner", "Tyler Bertuzzi"]);
+        assert!(ratings[0].1 > ratings[1].1);
+        assert_eq!(index.players(&players, "CHI").len(), 1);
+        assert_eq!(index.players(&players, "t.o.r.").len(), 3);
+        assert!(index.players(&players, "MTL").is_empty());
+    }
+
+    #[test]
+    fn test_depth_chart_fills_best_slots_and_flags_holes() {
+        let players = PlayerMap::from_iter([
+            skater("Brad Marchand", &[Position::Wing], &[Side::Left], 0.9),
+            skater("David Pastrnak", &[Position::Wing], &[Side::Right], 1.0),
+            skater("Charlie Coyle", &[Position::Center, Position::Wing], &[Side::Right], 0.5),
+            skater("Pavel Zacha", &[Position::Center], &[], 0.7),
+            skater("Trent Frederic", &[Position::Wing], &[], 0.4),
+            skater("Charlie McAvoy", &[Position::Defense], &[], 0.8),
+        ]);
+        let index = TeamIndex::new(&players);
+        let chart = depth_chart(&players, &index, "bos", &WeightedSigmoid::default());
+
+        let names = |slot| chart.column(slot).iter().map(|s: &DepthSlot| &*s.name).collect::<Vec<_>>();
+        assert_eq!(names(Slot::Center), ["Pavel Zacha", "Charlie Coyle"]);
+        assert_eq!(names(Slot::LeftWing), ["Brad Marchand", "Trent Frederic"]);
+        assert_eq!(names(Slot::RightWing), ["David Pastrnak"]);
+        assert_eq!(names(Slot::Defense), ["Charlie McAvoy"]);
+        assert_eq!(chart.holes(), ["LW3", "LW4", "C3", "C4", "RW2", "RW3", "RW4", "D2", "D3", "D4", "D5", "D6"]);
+    }
+
+    #[test]
+    fn test_strength_counts_holes_as_zero() {
+        let players = PlayerMap::from_iter([skater("Pavel Zacha", &[Position::Center], &[], 0.7), skater("Charlie McAvoy", &[Position::Defense], &[], 0.8)]);
+        let index = TeamIndex::new(&players);
+        let chart = depth_chart(&players, &index, "BOS", &WeightedSigmoid::default());
+        let strength = chart.strength();
+
+        let center = chart.column(Slot::Center)[0].score;
+        let defense = chart.column(Slot::Defense)[0].score;
+        assert_eq!(strength.positions[0], (Position::Center, center / 4.0));
+        assert_eq!(strength.positions[1], (Position::Wing, 0.0));
+        assert_eq!(strength.positions[2], (Position::Defense, defense / 6.0));
+        assert!((strength.overall - (center + defense) / 18.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,88 @@
+use std::{collections::HashMap, sync::Arc};
+use crate::cleaning::PlayerMap;
+use crate::graph::Graph;
+use crate::team::TeamIndex;
+
+// Players linked when they shared a team in a season, remembering the first team-season that linked
+// each pair so a path can say how every step connects.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TeammateGraph {
+    pub graph: Graph,
+    links: HashMap<(usize, usize), String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathStep {
+    pub name: Arc<str>,
+    // The team-season shared with the previous player on the path, like "EDM 2023-24".
+    pub via: Option<String>,
+}
+
+fn link_key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+impl TeammateGraph {
+    pub fn new() -> Self {
+        TeammateGraph::default()
+    }
+
+    // Links every pair of teammates in one season's export; returns the number of teams seen.
+    pub fn add_season(&mut self, season: &str, players: &PlayerMap) -> usize {
+        let index = TeamIndex::new(players);
+        for team in index.teams() {
+            let ids: Vec<usize> = index.players(players, team).iter().map(|player| self.graph.node(&player.name)).collect();
+            for (i, &a) in ids.iter().enumerate() {
+                for &b in &ids[i + 1..] {
+                    if self.graph.weight(a, b).is_none() {
+                        self.links.insert(link_key(a, b), format!("{} {}", team, season));
+                    }
+                    self.graph.add_undirected(a, b, 1.0);
+                }
+            }
+        }
+        index.teams().count()
+    }
+
+    pub fn path(&self, from: &str, to: &str) -> Option<Vec<PathStep>> {
+        let ids = self.graph.shortest_path(self.graph.id(from)?, self.graph.id(to)?)?;
+        Some(
+            ids.iter()
+                .enumerate()
+                .map(|(i, &id)| PathStep {
+                    name: self.graph.name(id).clone(),
+                    via: i.checked_sub(1).and_then(|previous| self.links.get(&link_key(ids[previous], id)).cloned()),
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::cleaning::{Player, Position};
+
+    fn season(rosters: &[(&str, &str)]) -> PlayerMap {
+        PlayerMap::from_iter(rosters.iter().map(|&(name, team)| {
+            let mut player = Player::new(name, vec![Position::Wing], HashMap::new());
+            player.team = Some(team.to_string());
+            (name.into(), player)
+        }))
+    }
+
+    #[test]
+    fn test_path_through_shared_seasons() {
+        let mut teammates = TeammateGraph::new();
+        assert_eq!(teammates.add_season("2018-19", &season(&[("Connor McDavid", "EDM"), ("Milan Lucic", "EDM"), ("Zach Hyman", "TOR")])), 2);
+        teammates.add_season("2019-20", &season(&[("Zach Hyman", "TOR"), ("Auston Matthews", "TOR"), ("Milan Lucic", "CGY")]));
+        teammates.add_season("2021-22", &season(&[("Connor McDavid", "EDM"), ("Zach Hyman", "EDM"), ("Sidney Crosby", "PIT")]));
+
+        let path = teammates.path("connor mcdavid", "Auston Matthews").unwrap();
+        let steps: Vec<(&str, Option<&str>)> = path.iter().map(|step| (&*step.name, step.via.as_deref())).collect();
+        assert_eq!(steps, [("Connor McDavid", None), ("Zach Hyman", Some("EDM 2021-22")), ("Auston Matthews", Some("TOR 2019-20"))]);
+        assert!(teammates.path("Connor McDavid", "Sidney Crosby").is_none());
+        assert!(teammates.path("Connor McDavid", "Nobody").is_none());
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::{BTreeMap, HashMap};
+use serde::Deserialize;
+use crate::cleaning::{PlayerMap, Position};
+use crate::model::ScoringModel;
+use crate::scoring::{rank_players_with, ScoredPlayer};
+
+const DEFAULT_TIERS: [(&str, f64); 11] = [
+    ("A+", 97.0), ("A", 90.0), ("A-", 85.0), ("B+", 78.0), ("B", 70.0), ("B-", 62.0),
+    ("C+", 54.0), ("C", 46.0), ("C-", 38.0), ("D", 20.0), ("F", 0.0),
+];
+
+// Letter grades with the minimum percentile within the position needed for each, best first.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "BTreeMap<String, f64>")]
+pub struct Tiers {
+    cutoffs: Vec<(String, f64)>,
+}
+
+impl Default for Tiers {
+    fn default() -> Self {
+        Tiers { cutoffs: DEFAULT_TIERS.iter().map(|&(grade, cutoff)| (grade.to_string(), cutoff)).collect() }
+    }
+}
+
+impl TryFrom<BTreeMap<String, f64>> for Tiers {
+    type Error = String;
+
+    fn try_from(map: BTreeMap<String, f64>) -> Result<Self, Self::Error> {
+        if let Some((grade, cutoff)) = map.iter().find(|(_, cutoff)| !(0.0..=100.0).contains(*cutoff)) {
+            return Err(format!("tier '{}' has cutoff {}, expected a percentile from 0 to 100", grade, cutoff));
+        }
+        if !map.values().any(|&cutoff| cutoff == 0.0) {
+            return Err("tiers need a grade with cutoff 0 so every player gets one".to_string());
+        }
+
+        let mut cutoffs: Vec<(String, f64)> = map.into_iter().collect();
+        cutoffs.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(Tiers { cutoffs })
+    }
+}
+
+impl Tiers {
+    pub fn grade(&self, percentile: f64) -> &str {
+        self.cutoffs
+            .iter()
+            .find(|(_, cutoff)| percentile >= *cutoff)
+            .or(self.cutoffs.last())
+            .map_or("", |(grade, _)| grade)
+    }
+}
+
+// Every player's score at each position, so a score can be graded against its position peers.
+pub struct Grades<'a> {
+    tiers: &'a Tiers,
+    scores: HashMap<Position, Vec<f64>>,
+}
+
+impl<'a> Grades<'a> {
+    pub fn new(players: &PlayerMap, model: &dyn ScoringModel, tiers: &'a Tiers) -> Self {
+        Grades::from_rankings(&rank_players_with(players, model), tiers)
+    }
+
+    pub fn from_rankings(rankings: &HashMap<Position, Vec<ScoredPlayer>>, tiers: &'a Tiers) -> Self {
+        let scores = rankings.iter().map(|(position, ranked)| (position.clone(), ranked.iter().map(|scored| scored.score).collect())).collect();
+        Grades { tiers, scores }
+    }
+
+    pub fn grade(&self, position: &Position, score: f64) -> &str {
+        let scores = self.scores.get(position).map_or(&[][..], Vec::as_slice);
+        self.tiers.grade(score_percentile(scores, score))
+    }
+}
+
+// Share of the other players with a lower score, so the best player is at 100 and the worst at 0.
+pub fn score_percentile(scores: &[f64], score: f64) -> f64 {
+    if scores.len() < 2 {
+        return 100.0;
+    }
+    let below = scores.iter().filter(|&&s| s < score).count();
+    100.0 * below as f64 / (scores.len() - 1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cleaning::Player;
+    use crate::model::WeightedSigmoid;
+
+    #[test]
+    fn test_grades_use_percentile_within_position() {
+        let players: PlayerMap = (0..21)
+            .map(|i| {
+                let name = format!("Wing {}", i);
+                let metrics = vec![i as f64 / 20.0; 5];
+                (name.clone().into(), Player::new(name, vec![Position::Wing], HashMap::from([(Position::Wing, metrics)])))
+            })
+            .collect();
+        let model = WeightedSigmoid::default();
+        let tiers = Tiers::default();
+        let grades = Grades::new(&players, &model, &tiers);
+        let scores = &grades.scores[&Position::Wing];
+        let best = scores.iter().cloned().fold(f64::MIN, f64::max);
+        let worst = scores.iter().cloned().fold(f64::MAX, f64::min);
+
+        assert_eq!(grades.grade(&Position::Wing, best), "A+");
+        assert_eq!(grades.grade(&Position::Wing, worst), "F");
+        assert_eq!(tiers.grade(50.0), "C");
+        assert_eq!(tiers.grade(90.0), "A");
+    }
+
+    #[test]
+    fn test_configured_tiers() {
+        let map = BTreeMap::from([("Elite".to_string(), 90.0), ("Regular".to_string(), 0.0)]);
+        let tiers = Tiers::try_from(map).unwrap();
+        assert_eq!(tiers.grade(95.0), "Elite");
+        assert_eq!(tiers.grade(50.0), "Regular");
+
+        assert!(Tiers::try_from(BTreeMap::from([("A".to_string(), 50.0)])).is_err());
+        assert!(Tiers::try_from(BTreeMap::from([("A".to_string(), 150.0), ("F".to_string(), 0.0)])).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+use std::{collections::HashMap, io};
+use finalproject::cleaning::{Player, PlayerMap, Position};
+use finalproject::locale::Locale;
+use finalproject::model::{MetricMap, ScoringModel};
+use finalproject::scoring::{metric_names, metric_percentiles, rank_players_with, ScoredPlayer};
+use finalproject::positions;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, List, ListItem, ListState, Paragraph, Row, Table, Tabs},
+    DefaultTerminal, Frame,
+};
+
+pub fn run(players: &PlayerMap, model: &dyn ScoringModel, locale: &Locale) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = App::new(players, model, locale).run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+struct App<'a> {
+    players: &'a PlayerMap,
+    model: &'a dyn ScoringModel,
+    locale: &'a Locale,
+    names: Vec<&'a str>,
+    leaderboards: HashMap<Position, Vec<ScoredPlayer>>,
+    query: String,
+    matches: Vec<&'a str>,
+    list_state: ListState,
+    tab: usize,
+}
+
+impl<'a> App<'a> {
+    fn new(players: &'a PlayerMap, model: &'a dyn ScoringModel, locale: &'a Locale) -> Self {
+        let mut names: Vec<&str> = players.keys().map(|name| &**name).collect();
+        names.sort_unstable();
+
+        let mut app = App {
+            players,
+            model,
+            locale,
+            names,
+            leaderboards: rank_players_with(players, model),
+            query: String::new(),
+            matches: Vec::new(),
+            list_state: ListState::default(),
+            tab: 0,
+        };
+        app.apply_filter();
+        app
+    }
+
+    fn apply_filter(&mut self) {
+        let query = self.query.to_lowercase();
+        self.matches = self
+            .names
+            .iter()
+            .copied()
+            .filter(|name| name.to_lowercase().contains(&query))
+            .collect();
+        self.list_state.select(if self.matches.is_empty() { None } else { Some(0) });
+    }
+
+    fn selected_player(&self) -> Option<&'a Player> {
+        let name = self.matches.get(self.list_state.selected()?)?;
+        self.players.get(*name)
+    }
+
+    fn run(mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let Event::Key(key) = event::read()? else { continue };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Esc => return Ok(()),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+                KeyCode::Char(c) => {
+                    self.query.push(c);
+                    self.apply_filter();
+                }
+                KeyCode::Backspace => {
+                    self.query.pop();
+                    self.apply_filter();
+                }
+                KeyCode::Down => self.list_state.select_next(),
+                KeyCode::Up => self.list_state.select_previous(),
+                KeyCode::Tab | KeyCode::Right => self.tab = (self.tab + 1) % positions::all().len(),
+                KeyCode::BackTab | KeyCode::Left => self.tab = (self.tab + positions::all().len() - 1) % positions::all().len(),
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [search_area, body, help_area] =
+            Layout::vertical([Constraint::Length(3), Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [list_area, detail_area, board_area] = Layout::horizontal([
+            Constraint::Percentage(30),
+            Constraint::Percentage(35),
+            Constraint::Percentage(35),
+        ])
+        .areas(body);
+
+        frame.render_widget(
+            Paragraph::new(self.query.as_str()).block(Block::bordered().title("Search")),
+            search_area,
+        );
+        frame.render_widget(
+            Paragraph::new("Type to search | Up/Down select player | Left/Right/Tab switch position | Esc quit")
+                .style(Style::default().fg(Color::DarkGray)),
+            help_area,
+        );
+
+        self.draw_player_list(frame, list_area);
+        self.draw_detail(frame, detail_area);
+        self.draw_leaderboard(frame, board_area);
+    }
+
+    fn draw_player_list(&mut self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self.matches.iter().map(|name| ListItem::new(*name)).collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(format!("Players ({})", self.matches.len())))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        frame.render_stateful_widget(list, area, &mut self.list_state);
+    }
+
+    fn draw_detail(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered().title("Details");
+        let Some(player) = self.selected_player() else {
+            frame.render_widget(Paragraph::new("No player selected").block(block), area);
+            return;
+        };
+
+        let mut rows = Vec::new();
+        for position in &player.positions {
+            let Some(metric_map) = MetricMap::for_player(player, position) else { continue };
+            let metrics = metric_map.normalized;
+            let score = self.model.score(position, &metric_map);
+            let percentiles = metric_percentiles(self.players, position, metrics);
+
+            rows.push(
+                Row::new(vec![position.to_string(), format!("{:.2}{}", self.locale.num(score), self.model.unit(position)), String::new()])
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            );
+            for ((name, value), percentile) in metric_names(position).iter().zip(metrics).zip(percentiles) {
+                rows.push(Row::new(vec![
+                    format!("  {}", self.locale.label(name)),
+                    format!("{:.2}", self.locale.num(*value)),
+                    format!("{:.0}th pct", self.locale.num(percentile)),
+                ]));
+            }
+            rows.push(Row::new(vec![String::new(); 3]));
+        }
+
+        let table = Table::new(rows, [Constraint::Percentage(45), Constraint::Percentage(25), Constraint::Percentage(30)])
+            .header(Row::new(vec!["Metric", "Normalized", "Percentile"]).style(Style::default().fg(Color::Yellow)))
+            .block(block.title(&*player.name));
+
+        frame.render_widget(table, area);
+    }
+
+    fn draw_leaderboard(&self, frame: &mut Frame, area: Rect) {
+        let [tabs_area, board_area] = Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(area);
+
+        let titles = positions::all().iter().map(|position| position.to_string());
+        frame.render_widget(
+            Tabs::new(titles)
+                .select(self.tab)
+                .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+                .block(Block::bordered().title("Leaderboard")),
+            tabs_area,
+        );
+
+        let selected_name = self.selected_player().map(|player| &*player.name);
+        let items: Vec<ListItem> = self
+            .leaderboards
+            .get(&positions::all()[self.tab])
+            .map(|ranked| ranked.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .map(|(rank, scored)| {
+                let unit = self.model.unit(&scored.position);
+                let line = Line::from(format!("{:>4}. {} - {:.2}{}", rank + 1, scored.name, self.locale.num(scored.score), unit));
+                if Some(&*scored.name) == selected_name {
+                    ListItem::new(line).style(Style::default().fg(Color::Green))
+                } else {
+                    ListItem::new(line)
+                }
+            })
+            .collect();
+
+        frame.render_widget(List::new(items).block(Block::bordered()), board_area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use finalproject::model::WeightedSigmoid;
+
+    #[test]
+    fn test_search_filters_case_insensitively() {
+        let players: PlayerMap = ["Connor McDavid", "Connor Bedard", "Cale Makar"]
+            .iter()
+            .map(|name| {
+                let player = Player::new(*name, vec![Position::Center], HashMap::new());
+                (player.name.clone(), player)
+            })
+            .collect();
+
+        let (model, locale) = (WeightedSigmoid::default(), Locale::default());
+        let mut app = App::new(&players, &model, &locale);
+        assert_eq!(app.matches.len(), 3);
+
+        app.query = "CONNOR".to_string();
+        app.apply_filter();
+        assert_eq!(app.matches, vec!["Connor Bedard", "Connor McDavid"]);
+        assert_eq!(app.selected_player().map(|p| &*p.name), Some("Connor Bedard"));
+    }
+}
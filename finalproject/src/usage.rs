@@ -0,0 +1,92 @@
+use std::{collections::HashMap, fs, io, str::FromStr};
+use crate::cleaning::{Player, PlayerMap, Position};
+use crate::positions;
+
+// Games (or minutes) a player spent at a position, however the usage file measured it. Only the
+// proportions between a player's positions matter.
+pub fn usage_stat(position: &Position) -> &'static str {
+    match position {
+        Position::Center => "Usage C",
+        Position::Wing => "Usage W",
+        Position::Defense => "Usage D",
+        Position::Custom(id) => positions::definition(*id).map_or("Usage ?", |def| def.usage_stat.as_str()),
+    }
+}
+
+// `name,position,games` lines, one per position a player appeared at, e.g. `Sebastian Aho,LW,12`.
+// Repeated rows for a position add up, so LW and RW both count toward wing. A header row is skipped.
+pub fn parse_usage(text: &str) -> Result<HashMap<String, HashMap<Position, f64>>, String> {
+    let mut usage: HashMap<String, HashMap<Position, f64>> = HashMap::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.rsplitn(3, ',').map(|field| field.trim().trim_matches('"')).collect();
+        let [games, position, name] = fields[..] else { return Err(format!("line {}: expected `name,position,games`", i + 1)) };
+        let position = match position.to_lowercase().as_str() {
+            "lw" | "rw" => Ok(Position::Wing),
+            other => Position::from_str(other),
+        };
+        let games = games.parse::<f64>().ok().filter(|games| games.is_finite() && *games >= 0.0);
+
+        match (position, games) {
+            (Ok(position), Some(games)) => *usage.entry(name.to_lowercase()).or_default().entry(position).or_default() += games,
+            _ if i == 0 => continue,
+            (Err(e), _) => return Err(format!("line {}: {}", i + 1, e)),
+            (_, None) => return Err(format!("line {}: invalid games '{}'", i + 1, fields[0])),
+        }
+    }
+
+    Ok(usage)
+}
+
+pub fn read_usage(path: &str) -> io::Result<HashMap<String, HashMap<Position, f64>>> {
+    parse_usage(&fs::read_to_string(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, e)))
+}
+
+pub fn join_usage(players: &mut PlayerMap, usage: &HashMap<String, HashMap<Position, f64>>) -> usize {
+    let mut matched = 0;
+    for player in players.values_mut() {
+        if let Some(positions) = usage.get(&player.name.to_lowercase()) {
+            for (position, &games) in positions {
+                player.stats.insert(usage_stat(position).to_string(), games);
+            }
+            matched += 1;
+        }
+    }
+    matched
+}
+
+// Each of the player's positions' share of their usage, or None when nothing was recorded for them.
+// A position without a usage figure gets no share.
+pub fn usage_shares(player: &Player) -> Option<HashMap<Position, f64>> {
+    let usage: Vec<(Position, f64)> =
+        player.positions.iter().map(|position| (position.clone(), player.stats.get(usage_stat(position)).copied().unwrap_or(0.0))).collect();
+    let total: f64 = usage.iter().map(|(_, games)| games).sum();
+    (total > 0.0).then(|| usage.into_iter().map(|(position, games)| (position, games / total)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_file_joins_as_shares() {
+        let usage = parse_usage("player,position,games\nSebastian Aho,C,70\n\"Sebastian Aho\",LW,8\nSebastian Aho,RW,2\nCale Makar,D,77\n").unwrap();
+        assert_eq!(usage["sebastian aho"][&Position::Wing], 10.0);
+        assert!(parse_usage("Sebastian Aho,C,70\nSebastian Aho,G,3\n").is_err());
+        assert!(parse_usage("Sebastian Aho,C,70\nSebastian Aho,W,lots\n").is_err());
+
+        let mut players = PlayerMap::from_iter([
+            ("Sebastian Aho".into(), Player::new("Sebastian Aho", vec![Position::Center, Position::Wing], HashMap::new())),
+            ("Jack Hughes".into(), Player::new("Jack Hughes", vec![Position::Center, Position::Wing], HashMap::new())),
+        ]);
+        assert_eq!(join_usage(&mut players, &usage), 1);
+        let shares = usage_shares(&players["Sebastian Aho"]).unwrap();
+        assert_eq!((shares[&Position::Center], shares[&Position::Wing]), (0.875, 0.125));
+        assert_eq!(usage_shares(&players["Jack Hughes"]), None);
+    }
+}
@@ -0,0 +1,35 @@
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::wasm_bindgen;
+use crate::cleaning::{normalize_metrics, process_text};
+use crate::scoring::{rank_players};
+use crate::positions;
+
+#[wasm_bindgen]
+pub fn score_csv(text: &str) -> String {
+    let mut players = process_text(text);
+    normalize_metrics(&mut players);
+    let rankings = rank_players(&players);
+
+    let mut result = serde_json::Map::new();
+    for position in positions::all() {
+        let ranked = rankings.get(position).map(|ranked| ranked.as_slice()).unwrap_or_default();
+        result.insert(position.to_string(), json!(ranked));
+    }
+
+    Value::Object(result).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_csv_groups_by_position() {
+        let header = ",Player,Position,GP,TOI,Goals,Total Assists,First Assists,Second Assists,Total Points,IPP,Shots,SH%,ixG,iCF,iFF,iSCF,iHDCF,Rush Attempts,Rebounds Created,PIM,Total Penalties,Minor,Major,Misconduct,Penalties Drawn,Giveaways,Takeaways,Hits,Hits Taken,Shots Blocked,Faceoffs Won,Faceoffs Lost,Faceoffs %";
+        let row = "1,Cale Makar,D,200,4000,60,150,90,60,210,60.5,500,12,40,900,700,400,100,40,80,50,25,25,0,0,60,150,120,80,60,190,0,0,0";
+        let json: Value = serde_json::from_str(&score_csv(&format!("{}\n{}", header, row))).unwrap();
+
+        assert_eq!(json["Defense"][0]["name"], "Cale Makar");
+        assert!(json["Center"].as_array().unwrap().is_empty());
+    }
+}
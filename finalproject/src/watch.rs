@@ -0,0 +1,91 @@
+use std::{io, path::{Path, PathBuf}, sync::mpsc, time::Duration};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+// Calls `on_change` for each of the files that changed, once their burst of events has settled.
+pub fn watch_files<F>(file_paths: &[&str], mut on_change: F) -> io::Result<()>
+where
+    F: FnMut(&Path) -> io::Result<()>,
+{
+    let paths: Vec<PathBuf> = file_paths.iter().map(|path| Path::new(path).canonicalize()).collect::<io::Result<_>>()?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(io::Error::other)?;
+    let mut dirs: Vec<&Path> = Vec::new();
+    for path in &paths {
+        // Watch the parent directory so files replaced by rename (as most exports do) keep being tracked.
+        let dir = path.parent().unwrap_or(Path::new("."));
+        if !dirs.contains(&dir) {
+            watcher.watch(dir, RecursiveMode::NonRecursive).map_err(io::Error::other)?;
+            dirs.push(dir);
+        }
+    }
+
+    let names: Vec<String> = paths.iter().map(|path| path.display().to_string()).collect();
+    println!("\nWatching {} for changes (Ctrl-C to stop)...", names.join(" and "));
+
+    while let Ok(event) = rx.recv() {
+        let mut changed = Vec::new();
+        add_change(&paths, event.map_err(io::Error::other)?, &mut changed);
+        if changed.is_empty() {
+            continue;
+        }
+
+        // A single save usually fires several events; wait for them to settle before reloading, keeping
+        // track of every watched file that changed meanwhile so a config saved during a CSV's burst isn't lost.
+        while let Ok(event) = rx.recv_timeout(DEBOUNCE) {
+            add_change(&paths, event.map_err(io::Error::other)?, &mut changed);
+        }
+
+        for path in changed {
+            println!("\n{} changed, reloading...", path.display());
+            if let Err(e) = on_change(path) {
+                eprintln!("Failed to reload {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Whether `changed`, as `watch_files` reports it, is the file at `path`.
+pub fn is_file(path: &str, changed: &Path) -> bool {
+    Path::new(path).canonicalize().is_ok_and(|path| path == changed)
+}
+
+// Adds the watched file an event is about to `changed`, once, in the order the files first changed.
+fn add_change<'a>(paths: &'a [PathBuf], event: Event, changed: &mut Vec<&'a PathBuf>) {
+    if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+        return;
+    }
+    let touched = paths.iter().filter(|path| event.paths.iter().any(|p| p.file_name() == path.file_name() && p.parent() == path.parent()));
+    for path in touched {
+        if !changed.contains(&path) {
+            changed.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, ModifyKind, RemoveKind};
+
+    #[test]
+    fn test_every_file_changed_during_the_debounce_is_kept() {
+        let (csv, config) = (PathBuf::from("/data/NHL.csv"), PathBuf::from("/data/nhl_scores.toml"));
+        let paths = [csv.clone(), config.clone()];
+        let event = |kind: EventKind, path: &str| Event::new(kind).add_path(PathBuf::from(path));
+
+        let mut changed = Vec::new();
+        add_change(&paths, event(EventKind::Modify(ModifyKind::Any), "/data/NHL.csv"), &mut changed);
+        add_change(&paths, event(EventKind::Create(CreateKind::File), "/data/NHL.csv"), &mut changed);
+        add_change(&paths, event(EventKind::Remove(RemoveKind::File), "/data/nhl_scores.toml"), &mut changed);
+        add_change(&paths, event(EventKind::Modify(ModifyKind::Any), "/other/nhl_scores.toml"), &mut changed);
+        assert_eq!(changed, [&csv]);
+
+        add_change(&paths, event(EventKind::Modify(ModifyKind::Any), "/data/nhl_scores.toml"), &mut changed);
+        assert_eq!(changed, [&csv, &config]);
+    }
+}
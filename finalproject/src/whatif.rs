@@ -0,0 +1,114 @@
+use crate::cleaning::{find_player, position_scales, resolve_stat, Player, PlayerMap, Position};
+use crate::metric::{Metric, Scaling};
+use crate::model::{MetricMap, ScoringModel};
+use crate::scoring::{metric_names, rank_players_with};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhatIf {
+    pub position: Position,
+    pub metric: Metric,
+    pub raw: f64,
+    pub value: f64,
+    pub score: f64,
+    pub new_score: f64,
+    pub rank: usize,
+    pub new_rank: usize,
+    pub players: usize,
+}
+
+// Splits `<player> <metric> <value>`, where both the name and the metric may contain spaces, at the
+// first point where the front is a known player and the rest a scored metric. The value may end in `%`.
+pub fn parse_what_if<'a>(players: &'a PlayerMap, input: &str) -> Result<(&'a Player, Metric, f64), String> {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let usage = "usage: whatif <player> <metric> <value>".to_string();
+    let Some((value, rest)) = words.split_last().filter(|(_, rest)| rest.len() >= 2) else { return Err(usage) };
+    let value: f64 = value.trim_end_matches('%').parse().map_err(|_| format!("'{}' is not a number; {}", value, usage))?;
+
+    let mut player = None;
+    for split in 1..rest.len() {
+        let Some(found) = find_player(players, &rest[..split].join(" ")) else { continue };
+        player = Some(found);
+        let metric = rest[split..].join(" ");
+        if let Some(metric) = Metric::from_name(&metric).or_else(|| resolve_stat(&metric).and_then(Metric::from_name)) {
+            return Ok((found, metric, value));
+        }
+    }
+    match player {
+        Some(player) => Err(format!("no scored metric in '{}' for {}", input.trim(), player.name)),
+        None => Err(format!("no player found in '{}'", input.trim())),
+    }
+}
+
+// Rescores the player with one raw metric replaced, at every position scored on that metric. The new
+// value is normalized against the league as loaded, so it doesn't move anyone else's scale.
+pub fn what_if(players: &PlayerMap, model: &dyn ScoringModel, player: &Player, metric: Metric, value: f64, scaling: &Scaling) -> Vec<WhatIf> {
+    let ranked = rank_players_with(players, model);
+    player
+        .positions
+        .iter()
+        .filter_map(|position| {
+            let index = Metric::for_position(position).iter().position(|&m| m == metric)?;
+            let (normalized, raw) = (player.metrics.get(position)?, player.raw_metrics.get(position)?);
+            let scales = position_scales(position, players.values().filter_map(|p| p.raw_metrics.get(position)), scaling);
+
+            let mut new_normalized = normalized.clone();
+            let mut new_raw = raw.clone();
+            new_normalized[index] = scales.get(index)?.apply(value);
+            new_raw[index] = value;
+            let metrics = MetricMap { names: metric_names(position), normalized: &new_normalized, raw: &new_raw, stats: &player.stats };
+            let new_score = model.score(position, &metrics);
+
+            let others = ranked.get(position).map_or(&[][..], Vec::as_slice);
+            let rank = others.iter().position(|scored| scored.name == player.name)? + 1;
+            let new_rank = 1 + others.iter().filter(|scored| scored.name != player.name && scored.score > new_score).count();
+            Some(WhatIf {
+                position: position.clone(),
+                metric,
+                raw: raw[index],
+                value,
+                score: others[rank - 1].score,
+                new_score,
+                rank,
+                new_rank,
+                players: others.len(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::model::Linear;
+
+    fn wing(name: &str, goals: f64) -> (std::sync::Arc<str>, Player) {
+        let raw = vec![goals, 10.0, 100.0, 50.0, 80.0];
+        let mut player = Player::new(name, vec![Position::Wing], HashMap::from([(Position::Wing, raw.clone())]));
+        player.raw_metrics = HashMap::from([(Position::Wing, raw)]);
+        (player.name.clone(), player)
+    }
+
+    #[test]
+    fn test_what_if_rescores_and_reranks() {
+        let mut players = PlayerMap::from_iter([wing("Top Sniper", 50.0), wing("Middle Man", 30.0), wing("Depth Winger", 10.0)]);
+        crate::cleaning::normalize_metrics(&mut players);
+
+        let (player, metric, value) = parse_what_if(&players, "depth winger total points 60").unwrap();
+        assert_eq!((&*player.name, metric, value), ("Depth Winger", Metric::TotalPoints, 60.0));
+        let (player, metric, value) = parse_what_if(&players, "depth winger goals 40").unwrap();
+        assert_eq!((metric, value), (Metric::Goals, 40.0));
+        assert!(parse_what_if(&players, "depth winger faceoffs% 55").is_ok());
+        assert!(parse_what_if(&players, "nobody goals 40").is_err());
+        assert!(parse_what_if(&players, "depth winger goals lots").is_err());
+
+        let model = Linear::default();
+        let results = what_if(&players, &model, player, metric, value, &Scaling::default());
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!((result.raw, result.rank, result.new_rank, result.players), (10.0, 3, 2, 3));
+        assert!(result.new_score > result.score);
+        // Depth Winger only plays wing, so a center metric has nothing to rescore.
+        assert!(what_if(&players, &model, player, Metric::FaceoffPct, 55.0, &Scaling::default()).is_empty());
+    }
+}
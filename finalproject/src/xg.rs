@@ -0,0 +1,151 @@
+use std::collections::{BTreeMap, HashMap};
+use serde::Deserialize;
+use crate::cleaning::{resolve_stat, PlayerMap, Position};
+use crate::model::{MetricMap, ScoringModel};
+use crate::stat_line::StatLine;
+
+pub const FINISHING_STAT: &str = "Finishing";
+const EXPECTED_GOALS_STAT: &str = "ixG";
+
+// Each position's expected-goals stats and their weights.
+pub type XgWeights = HashMap<Position, Vec<(&'static str, f64)>>;
+
+// Goals scored above (or below) the individual expected goals of the shots taken.
+pub fn finishing(stats: &StatLine) -> Option<f64> {
+    Some(stats.get("Goals")? - stats.get(EXPECTED_GOALS_STAT)?)
+}
+
+// Stores finishing for every player whose export has expected goals. Returns how many had them.
+pub fn add_finishing(players: &mut PlayerMap) -> usize {
+    let mut added = 0;
+    for player in players.values_mut() {
+        if let Some(value) = finishing(&player.stats) {
+            player.stats.insert(FINISHING_STAT.to_string(), value);
+            added += 1;
+        }
+    }
+    added
+}
+
+// `[xg]` weights expected-goals stats into a position's score, e.g. `wing = { ixG = 0.2 }`; the
+// position's own metrics keep the rest of the weight.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct XgConfig {
+    pub center: BTreeMap<String, f64>,
+    pub wing: BTreeMap<String, f64>,
+    pub defense: BTreeMap<String, f64>,
+}
+
+impl XgConfig {
+    pub fn is_empty(&self) -> bool {
+        self.center.is_empty() && self.wing.is_empty() && self.defense.is_empty()
+    }
+
+    pub fn weights(&self) -> Result<XgWeights, String> {
+        let mut positions = HashMap::new();
+        for (position, table) in [(Position::Center, &self.center), (Position::Wing, &self.wing), (Position::Defense, &self.defense)] {
+            let name = position.to_string().to_lowercase();
+            let mut weights = Vec::new();
+            for (stat, &weight) in table {
+                let resolved = resolve_stat(stat).filter(|resolved| resolved.contains("xG"));
+                let Some(resolved) = resolved else { return Err(format!("xg.{}: '{}' is not an expected-goals stat, expected ixG or xGF%", name, stat)) };
+                if !(weight.is_finite() && weight >= 0.0) {
+                    return Err(format!("xg.{}: {} has a weight of {}, expected zero or more", name, stat, weight));
+                }
+                weights.push((resolved, weight));
+            }
+            let total: f64 = weights.iter().map(|(_, weight)| weight).sum();
+            if total >= 1.0 {
+                return Err(format!("xg.{} weights sum to {}, leaving nothing for the position's metrics", name, total));
+            }
+            if !weights.is_empty() {
+                positions.insert(position, weights);
+            }
+        }
+        Ok(positions)
+    }
+}
+
+// Blends expected-goals stats into another model's 0-100 score. Counts are scaled by the league
+// leader and percentages by 100, like the position metrics they sit beside.
+pub struct ExpectedGoals {
+    pub base: Box<dyn ScoringModel>,
+    weights: XgWeights,
+    maxima: HashMap<&'static str, f64>,
+}
+
+impl ExpectedGoals {
+    pub fn new(base: Box<dyn ScoringModel>, weights: XgWeights) -> Self {
+        ExpectedGoals { base, weights, maxima: HashMap::new() }
+    }
+
+    fn scaled(&self, stat: &str, value: f64) -> f64 {
+        let divisor = if stat.contains('%') { 100.0 } else { self.maxima.get(stat).copied().unwrap_or(0.0) };
+        if divisor > 0.0 { (value / divisor).clamp(0.0, 1.0) } else { 0.0 }
+    }
+}
+
+impl ScoringModel for ExpectedGoals {
+    fn name(&self) -> &str {
+        "xg"
+    }
+
+    fn fit(&mut self, players: &PlayerMap) {
+        self.base.fit(players);
+        self.maxima = self
+            .weights
+            .values()
+            .flatten()
+            .map(|&(stat, _)| (stat, players.values().filter_map(|player| player.stats.get(stat)).copied().fold(0.0, f64::max)))
+            .collect();
+    }
+
+    fn league_relative(&self) -> bool {
+        !self.weights.is_empty() || self.base.league_relative()
+    }
+
+    fn score(&self, position: &Position, metrics: &MetricMap) -> f64 {
+        let score = self.base.score(position, metrics);
+        let Some(weights) = self.weights.get(position) else { return score };
+        let total: f64 = weights.iter().map(|(_, weight)| weight).sum();
+        let expected: f64 = weights.iter().map(|&(stat, weight)| weight * 100.0 * self.scaled(stat, metrics.stat(stat).unwrap_or(0.0))).sum();
+        score * (1.0 - total) + expected
+    }
+
+    fn unit(&self, position: &Position) -> &'static str {
+        self.base.unit(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::cleaning::Player;
+    use crate::model::Linear;
+
+    fn wing(name: &str, value: f64, goals: f64, ixg: f64) -> (Arc<str>, Player) {
+        let mut player = Player::new(name, vec![Position::Wing], HashMap::from([(Position::Wing, vec![value; 5])]));
+        player.stats = [("Goals", goals), ("ixG", ixg)].into();
+        (player.name.clone(), player)
+    }
+
+    #[test]
+    fn test_finishing_and_expected_goals_weights() {
+        let mut players = PlayerMap::from_iter([wing("Sniper", 0.5, 30.0, 20.0), wing("Grinder", 0.5, 10.0, 10.0)]);
+        assert_eq!(add_finishing(&mut players), 2);
+        assert_eq!(players["Sniper"].stats[FINISHING_STAT], 10.0);
+
+        let config = XgConfig { wing: BTreeMap::from([("ixg".to_string(), 0.2)]), ..XgConfig::default() };
+        let mut model = ExpectedGoals::new(Box::new(Linear::default()), config.weights().unwrap());
+        model.fit(&players);
+        let score = |name: &str| model.score(&Position::Wing, &MetricMap::for_player(&players[name], &Position::Wing).unwrap());
+        let base = Linear::default().score(&Position::Wing, &MetricMap::for_player(&players["Sniper"], &Position::Wing).unwrap());
+        assert!((score("Sniper") - (base * 0.8 + 20.0)).abs() < 1e-9);
+        assert!((score("Grinder") - (base * 0.8 + 10.0)).abs() < 1e-9);
+
+        assert!(XgConfig { wing: BTreeMap::from([("hits".to_string(), 0.2)]), ..XgConfig::default() }.weights().is_err());
+        assert!(XgConfig { center: BTreeMap::from([("xGF%".to_string(), 1.0)]), ..XgConfig::default() }.weights().is_err());
+    }
+}